@@ -0,0 +1,84 @@
+// Decoding benchmarks
+//
+// Reads the fixtures into GeoJSON structs, which is the path behind `ewkb_to_geojson` and
+// `twkb_to_geojson`, and straight into GeoJSON strings with the visitor. The bounding box and
+// vertex count of EWKB are measured with the visitors and with `EwkbView`. With the `rayon`
+// feature the GeoJSON structs are also read with the members split across threads.
+extern crate criterion;
+extern crate wkbparse;
+
+mod fixtures;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use wkbparse::geojson::{read_binary, BinaryFormat};
+use wkbparse::limits::ParseLimits;
+#[cfg(feature = "rayon")]
+use wkbparse::parallel;
+use wkbparse::view::EwkbView;
+use wkbparse::visit::{Bbox, GeoJsonWriter, VertexCount};
+use wkbparse::{ewkb, twkb};
+
+fn decode(c: &mut Criterion) {
+    let limits = ParseLimits::default();
+    for fixture in fixtures::all() {
+        let mut group = fixtures::group(c, "decode", &fixture);
+        let ewkb = &fixture.ewkb[..];
+        group.bench_function("ewkb", |b| {
+            b.iter(|| read_binary(black_box(ewkb), BinaryFormat::Ewkb, &limits).unwrap())
+        });
+        #[cfg(feature = "rayon")]
+        group.bench_function("ewkb_parallel", |b| {
+            b.iter(|| parallel::read_binary(black_box(ewkb), BinaryFormat::Ewkb, &limits).unwrap())
+        });
+        group.bench_function("ewkb_geojson_str", |b| {
+            b.iter(|| {
+                let mut writer = GeoJsonWriter::with_capacity(ewkb.len() * 2);
+                ewkb::visit_ewkb(&mut black_box(ewkb), &mut writer, &limits).unwrap();
+                writer.finish()
+            })
+        });
+        group.bench_function("ewkb_bbox", |b| {
+            b.iter(|| {
+                let mut bbox = Bbox::default();
+                ewkb::visit_ewkb(&mut black_box(ewkb), &mut bbox, &limits).unwrap();
+                bbox
+            })
+        });
+        group.bench_function("ewkb_view_bbox", |b| {
+            b.iter(|| EwkbView::new(black_box(ewkb)).unwrap().bbox())
+        });
+        group.bench_function("ewkb_vertex_count", |b| {
+            b.iter(|| {
+                let mut count = VertexCount::default();
+                ewkb::visit_ewkb(&mut black_box(ewkb), &mut count, &limits).unwrap();
+                count
+            })
+        });
+        group.bench_function("ewkb_view_vertex_count", |b| {
+            b.iter(|| EwkbView::new(black_box(ewkb)).unwrap().num_vertices())
+        });
+        if let Some(twkb) = fixture.twkb.as_deref() {
+            group.bench_function("twkb", |b| {
+                b.iter(|| read_binary(black_box(twkb), BinaryFormat::Twkb, &limits).unwrap())
+            });
+            #[cfg(feature = "rayon")]
+            group.bench_function("twkb_parallel", |b| {
+                b.iter(|| {
+                    parallel::read_binary(black_box(twkb), BinaryFormat::Twkb, &limits).unwrap()
+                })
+            });
+            group.bench_function("twkb_geojson_str", |b| {
+                b.iter(|| {
+                    let mut writer = GeoJsonWriter::with_capacity(twkb.len() * 8);
+                    twkb::visit_twkb(&mut black_box(twkb), &mut writer, &limits).unwrap();
+                    writer.finish()
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+// Encoding benchmarks
+//
+// Writes the fixtures read into GeoJSON structs back as EWKB and as GeoJSON strings, which are
+// the paths behind `geojson_to_ewkb` and `ewkb_to_geojson_str`, and converts TWKB into EWKB like
+// `twkb_to_ewkb`.
+extern crate criterion;
+extern crate wkbparse;
+
+mod fixtures;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use wkbparse::ewkb::{AsEwkbMultiPolygon, AsEwkbPolygon, Endianness, EwkbWrite};
+use wkbparse::geojson::{read_binary, BinaryFormat, GeoJSONEncode, GeometryType};
+use wkbparse::limits::ParseLimits;
+use wkbparse::twkb::{self, TwkbGeom};
+
+fn twkb_to_ewkb(mut twkb: &[u8], capacity: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(capacity);
+    match twkb::get_geom_type(twkb) {
+        GeometryType::Polygon => twkb::Polygon::read_twkb(&mut twkb)
+            .unwrap()
+            .as_ewkb()
+            .write_ewkb(&mut out),
+        GeometryType::MultiPolygon => twkb::MultiPolygon::read_twkb(&mut twkb)
+            .unwrap()
+            .as_ewkb()
+            .write_ewkb(&mut out),
+        _ => panic!("fixtures are polygons or multipolygons"),
+    }
+    .unwrap();
+    out
+}
+
+fn encode(c: &mut Criterion) {
+    let limits = ParseLimits::default();
+    for fixture in fixtures::all() {
+        let mut group = fixtures::group(c, "encode", &fixture);
+        let geom = read_binary(&fixture.ewkb, BinaryFormat::Ewkb, &limits).unwrap();
+        group.bench_function("ewkb", |b| b.iter(|| black_box(&geom).to_ewkb().unwrap()));
+        group.bench_function("ewkb_big_endian", |b| {
+            b.iter(|| black_box(&geom).to_ewkb_with(Endianness::Big).unwrap())
+        });
+        group.bench_function("geojson_str", |b| b.iter(|| black_box(&geom).as_str()));
+        if let Some(twkb) = fixture.twkb.as_deref() {
+            let capacity = fixture.ewkb.len();
+            group.bench_function("twkb_to_ewkb", |b| {
+                b.iter(|| twkb_to_ewkb(black_box(twkb), capacity))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
@@ -0,0 +1,281 @@
+// Deterministic fixtures for the benchmarks
+//
+// Geometries are wavy rings around Helsinki in EPSG:4326, so that they resemble administrative
+// boundaries and can be reprojected with the built-in transforms. Coordinates are rounded to the
+// TWKB precision, so the EWKB and TWKB encodings of a fixture hold the same values. The samples in
+// `tests/data` are included too when they have been fetched from Git LFS.
+#![allow(dead_code)]
+
+use criterion::measurement::WallTime;
+use criterion::{BenchmarkGroup, Criterion, Throughput};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+
+pub const SRID: i32 = 4326;
+
+const PRECISION_XY: i32 = 7;
+const PRECISION_Z: i32 = 2;
+const PRECISION_M: i32 = 0;
+
+/// Number of polygons and vertices per polygon of a size class.
+#[derive(Clone, Copy, Debug)]
+pub enum Size {
+    /// One polygon of 100 vertices
+    Small,
+    /// One polygon of 10 000 vertices
+    Medium,
+    /// A multipolygon of 30 polygons of 10 000 vertices each
+    Large,
+}
+
+impl Size {
+    pub const ALL: [Size; 3] = [Size::Small, Size::Medium, Size::Large];
+
+    fn shape(self) -> (usize, usize) {
+        match self {
+            Size::Small => (1, 100),
+            Size::Medium => (1, 10_000),
+            Size::Large => (30, 10_000),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Size::Small => "small",
+            Size::Medium => "medium",
+            Size::Large => "large",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Dims {
+    pub z: bool,
+    pub m: bool,
+}
+
+impl Dims {
+    pub const ALL: [Dims; 4] = [
+        Dims { z: false, m: false },
+        Dims { z: true, m: false },
+        Dims { z: false, m: true },
+        Dims { z: true, m: true },
+    ];
+    pub const XY: Dims = Dims { z: false, m: false };
+
+    pub fn name(self) -> &'static str {
+        match (self.z, self.m) {
+            (false, false) => "xy",
+            (true, false) => "xyz",
+            (false, true) => "xym",
+            (true, true) => "xyzm",
+        }
+    }
+}
+
+pub struct Fixture {
+    pub name: String,
+    pub vertices: usize,
+    pub ewkb: Vec<u8>,
+    /// Missing for samples that only exist as EWKB.
+    pub twkb: Option<Vec<u8>>,
+}
+
+impl Fixture {
+    pub fn generate(size: Size, dims: Dims) -> Fixture {
+        let (parts, ring) = size.shape();
+        let polygons: Vec<Vec<[f64; 4]>> = (0..parts).map(|part| polygon(part, ring)).collect();
+        Fixture {
+            name: format!("{}_{}", size.name(), dims.name()),
+            vertices: parts * ring,
+            ewkb: write_ewkb(&polygons, dims),
+            twkb: Some(write_twkb(&polygons, dims)),
+        }
+    }
+
+    /// The samples in `tests/data`, skipping files that are Git LFS pointers.
+    pub fn samples() -> Vec<Fixture> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+        let mut samples = Vec::new();
+        for name in [
+            "medium-ewkb-polygon",
+            "large-ewkb-polygon",
+            "large-ewkb-multipolygon",
+        ] {
+            let ewkb = match fs::read(dir.join(format!("{}-example.ewkb", name))) {
+                Ok(data) if !data.starts_with(b"version https://git-lfs") => data,
+                _ => continue,
+            };
+            let twkb_file = format!("{}-example.twkb", name.replace("ewkb", "twkb"));
+            let twkb = fs::read(dir.join(twkb_file))
+                .ok()
+                .filter(|data| !data.starts_with(b"version https://git-lfs"));
+            samples.push(Fixture {
+                name: format!("sample_{}", name.replace("-ewkb", "").replace('-', "_")),
+                vertices: count_vertices(&ewkb),
+                ewkb,
+                twkb,
+            });
+        }
+        samples
+    }
+}
+
+/// All generated fixtures followed by the available samples.
+pub fn all() -> Vec<Fixture> {
+    let mut fixtures = Vec::new();
+    for size in Size::ALL {
+        for dims in Dims::ALL {
+            fixtures.push(Fixture::generate(size, dims));
+        }
+    }
+    fixtures.extend(Fixture::samples());
+    fixtures
+}
+
+fn count_vertices(ewkb: &[u8]) -> usize {
+    use wkbparse::ewkb;
+    use wkbparse::visit::VertexCount;
+
+    let mut count = VertexCount::default();
+    ewkb::visit_ewkb(&mut &ewkb[..], &mut count, &Default::default()).unwrap();
+    count.0
+}
+
+fn round(v: f64, precision: i32) -> f64 {
+    let scale = 10f64.powi(precision);
+    (v * scale).round() / scale
+}
+
+/// A closed ring of `n` vertices as x, y, z, m.
+fn polygon(part: usize, n: usize) -> Vec<[f64; 4]> {
+    let cx = 24.9 + 0.05 * part as f64;
+    let cy = 60.2 + 0.03 * (part % 5) as f64;
+    let mut ring: Vec<[f64; 4]> = (0..n - 1)
+        .map(|i| {
+            let a = 2.0 * PI * i as f64 / (n - 1) as f64;
+            let r = 0.01 * (1.0 + 0.1 * (17.0 * a).sin() + 0.05 * (131.0 * a).sin());
+            [
+                round(cx + 2.0 * r * a.cos(), PRECISION_XY),
+                round(cy + r * a.sin(), PRECISION_XY),
+                round(10.0 + 5.0 * (3.0 * a).sin(), PRECISION_Z),
+                i as f64,
+            ]
+        })
+        .collect();
+    ring.push(ring[0]);
+    ring
+}
+
+fn ewkb_header(out: &mut Vec<u8>, type_id: u32, dims: Dims, srid: Option<i32>) {
+    let mut type_id = type_id;
+    if dims.z {
+        type_id |= 0x8000_0000;
+    }
+    if dims.m {
+        type_id |= 0x4000_0000;
+    }
+    if srid.is_some() {
+        type_id |= 0x2000_0000;
+    }
+    out.push(1);
+    out.extend(type_id.to_le_bytes());
+    if let Some(srid) = srid {
+        out.extend(srid.to_le_bytes());
+    }
+}
+
+fn ewkb_polygon(out: &mut Vec<u8>, ring: &[[f64; 4]], dims: Dims) {
+    out.extend(1u32.to_le_bytes());
+    out.extend((ring.len() as u32).to_le_bytes());
+    for crd in ring {
+        out.extend(crd[0].to_le_bytes());
+        out.extend(crd[1].to_le_bytes());
+        if dims.z {
+            out.extend(crd[2].to_le_bytes());
+        }
+        if dims.m {
+            out.extend(crd[3].to_le_bytes());
+        }
+    }
+}
+
+fn write_ewkb(polygons: &[Vec<[f64; 4]>], dims: Dims) -> Vec<u8> {
+    let mut out = Vec::new();
+    if let [ring] = polygons {
+        ewkb_header(&mut out, 3, dims, Some(SRID));
+        ewkb_polygon(&mut out, ring, dims);
+    } else {
+        ewkb_header(&mut out, 6, dims, Some(SRID));
+        out.extend((polygons.len() as u32).to_le_bytes());
+        for ring in polygons {
+            ewkb_header(&mut out, 3, dims, None);
+            ewkb_polygon(&mut out, ring, dims);
+        }
+    }
+    out
+}
+
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn write_twkb(polygons: &[Vec<[f64; 4]>], dims: Dims) -> Vec<u8> {
+    let precisions = [PRECISION_XY, PRECISION_XY, PRECISION_Z, PRECISION_M];
+    let used = [true, true, dims.z, dims.m];
+    let type_id = if polygons.len() == 1 { 3 } else { 6 };
+    let mut out = vec![type_id | (zigzag(PRECISION_XY as i64) as u8) << 4];
+    if dims.z || dims.m {
+        out.push(0x08);
+        out.push(
+            dims.z as u8
+                | (dims.m as u8) << 1
+                | (PRECISION_Z as u8) << 2
+                | (PRECISION_M as u8) << 5,
+        );
+    } else {
+        out.push(0);
+    }
+    if type_id == 6 {
+        varint(&mut out, polygons.len() as u64);
+    }
+    let mut prev = [0i64; 4];
+    for ring in polygons {
+        varint(&mut out, 1);
+        varint(&mut out, ring.len() as u64);
+        for crd in ring {
+            for dim in 0..4 {
+                if !used[dim] {
+                    continue;
+                }
+                let v = (crd[dim] * 10f64.powi(precisions[dim])).round() as i64;
+                varint(&mut out, zigzag(v - prev[dim]));
+                prev[dim] = v;
+            }
+        }
+    }
+    out
+}
+
+/// Start a benchmark group for `fixture`, measuring vertices per second.
+pub fn group<'a>(
+    c: &'a mut Criterion,
+    kind: &str,
+    fixture: &Fixture,
+) -> BenchmarkGroup<'a, WallTime> {
+    let mut group = c.benchmark_group(format!("{}/{}", kind, fixture.name));
+    group.throughput(Throughput::Elements(fixture.vertices as u64));
+    if fixture.vertices > 100_000 {
+        group.sample_size(10);
+    }
+    group
+}
@@ -0,0 +1,59 @@
+// Reprojection benchmarks
+//
+// Reprojects the XY and XYZ fixtures as EWKB and as GeoJSON structs with the built-in
+// transforms, and with PROJ when the `proj` feature is enabled. The transforms are created before
+// measuring, as the cache does for repeated calls.
+extern crate criterion;
+extern crate wkbparse;
+
+mod fixtures;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use fixtures::{Dims, Fixture, Size};
+use std::hint::black_box;
+use wkbparse::geojson::{read_binary, BinaryFormat, GeoJSONEncode};
+use wkbparse::reproject::{get_transform, reproject_ewkb, warm_transforms, TransformOptions};
+
+/// Web Mercator and UTM zone 35N, which covers the fixtures.
+const BUILTIN: [i32; 2] = [3857, 32635];
+
+/// ETRS-TM35FIN, which is not built in.
+#[cfg(feature = "proj")]
+const PROJ: [i32; 1] = [3067];
+
+#[cfg(not(feature = "proj"))]
+const PROJ: [i32; 0] = [];
+
+fn reproject(c: &mut Criterion) {
+    let options = TransformOptions::default();
+    let srids: Vec<i32> = BUILTIN.iter().chain(PROJ.iter()).copied().collect();
+    let pairs: Vec<(i32, i32)> = srids.iter().map(|&srid| (fixtures::SRID, srid)).collect();
+    warm_transforms(&pairs).unwrap();
+    for size in Size::ALL {
+        for dims in [Dims::XY, Dims { z: true, m: false }] {
+            let fixture = Fixture::generate(size, dims);
+            let geojson =
+                read_binary(&fixture.ewkb, BinaryFormat::Ewkb, &Default::default()).unwrap();
+            let mut group = fixtures::group(c, "reproject", &fixture);
+            for &srid in &srids {
+                group.bench_function(format!("ewkb_{}", srid), |b| {
+                    b.iter(|| {
+                        reproject_ewkb(black_box(&fixture.ewkb), srid, None, &options).unwrap()
+                    })
+                });
+                let xform = get_transform(fixtures::SRID, srid).unwrap();
+                group.bench_function(format!("geojson_{}", srid), |b| {
+                    b.iter_batched(
+                        || geojson.clone(),
+                        |mut geom| geom.transform_with(&xform, &options).unwrap(),
+                        BatchSize::LargeInput,
+                    )
+                });
+            }
+            group.finish();
+        }
+    }
+}
+
+criterion_group!(benches, reproject);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wkbparse::ewkb;
+use wkbparse::flat::{Coord, CoordZM};
+use wkbparse::limits::ParseLimits;
+use wkbparse::view::{EwkbView, LittleEndian};
+use wkbparse::visit::VertexCount;
+use wkbparse::{MultiPolygon, Point, Polygon};
+
+// Views accept any input without panicking, and agree with the reader on what they accept.
+fuzz_target!(|data: &[u8]| {
+    let limits = ParseLimits::default();
+    let mut count = VertexCount::default();
+    let read = ewkb::visit_ewkb(&mut &data[..], &mut count, &limits);
+    let view = match EwkbView::new(data) {
+        Ok(view) => view,
+        Err(_) => return,
+    };
+    assert!(read.is_ok());
+    assert_eq!(view.num_vertices(), count.0);
+    let _ = view.bbox();
+    let _ = view.first_coord::<CoordZM>();
+    for member in view.members() {
+        let _ = member.first_coord::<Coord>();
+    }
+    if let Ok(polygon) = view.as_polygon::<Coord, LittleEndian>() {
+        for ring in polygon.rings() {
+            let _ = ring.point(ring.len().saturating_sub(1)).map(|p| p.x());
+        }
+    }
+    if let Ok(polygons) = view.as_multipolygon::<Coord, LittleEndian>() {
+        let _ = polygons.polygons().flat_map(|p| p.rings()).count();
+    }
+});
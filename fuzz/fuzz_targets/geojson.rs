@@ -0,0 +1,28 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use serde_json::Value;
+use wkbparse::ewkb::{self, EwkbRead};
+use wkbparse::geojson::{
+    GeoJSONEncode, LineString, MultiLineString, MultiPoint, MultiPolygon, Point, Polygon,
+};
+
+fn check<G: GeoJSONEncode + serde::de::DeserializeOwned>(json: &Value) {
+    if let Ok(geom) = G::deserialize(json) {
+        if let Ok(encoded) = geom.to_ewkb() {
+            ewkb::Geometry::read_ewkb(&mut encoded.as_slice()).unwrap();
+        }
+    }
+}
+
+// GeoJSON geometries parsed from any JSON either fail to encode or encode into valid EWKB.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json) = serde_json::from_slice::<Value>(data) {
+        check::<Point>(&json);
+        check::<LineString>(&json);
+        check::<Polygon>(&json);
+        check::<MultiPoint>(&json);
+        check::<MultiLineString>(&json);
+        check::<MultiPolygon>(&json);
+    }
+});
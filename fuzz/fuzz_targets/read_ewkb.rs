@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wkbparse::ewkb::{
+    EwkbRead, GeometryCollection, LineString, LineStringZM, MultiLineString, MultiPoint,
+    MultiPolygon, MultiPolygonZ, Point, PointM, Polygon, PolygonZM,
+};
+
+// Typed readers accept any input without panicking.
+fuzz_target!(|data: &[u8]| {
+    let _ = Point::read_ewkb(&mut &data[..]);
+    let _ = PointM::read_ewkb(&mut &data[..]);
+    let _ = LineString::read_ewkb(&mut &data[..]);
+    let _ = LineStringZM::read_ewkb(&mut &data[..]);
+    let _ = Polygon::read_ewkb(&mut &data[..]);
+    let _ = PolygonZM::read_ewkb(&mut &data[..]);
+    let _ = MultiPoint::read_ewkb(&mut &data[..]);
+    let _ = MultiLineString::read_ewkb(&mut &data[..]);
+    let _ = MultiPolygon::read_ewkb(&mut &data[..]);
+    let _ = MultiPolygonZ::read_ewkb(&mut &data[..]);
+    let _ = GeometryCollection::read_ewkb(&mut &data[..]);
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wkbparse::ewkb::{self, AsEwkbGeometry, EwkbRead, EwkbWrite, Geometry};
+use wkbparse::limits::ParseLimits;
+
+fn write(geom: &Geometry) -> Vec<u8> {
+    let mut out = Vec::new();
+    geom.as_ewkb().write_ewkb(&mut out).unwrap();
+    out
+}
+
+// Geometries read from any input are written back into EWKB which reads as the same geometry.
+fuzz_target!(|data: &[u8]| {
+    for geom in ewkb::read_many::<Geometry, _>(data, &ParseLimits::default()).flatten() {
+        let encoded = write(&geom);
+        let decoded = Geometry::read_ewkb(&mut encoded.as_slice()).unwrap();
+        assert_eq!(write(&decoded), encoded);
+    }
+});
@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wkbparse::ewkb::{
+    self, AsEwkbLineString, AsEwkbMultiLineString, AsEwkbMultiPoint, AsEwkbMultiPolygon,
+    AsEwkbPoint, AsEwkbPolygon, EwkbRead, EwkbWrite,
+};
+use wkbparse::limits::ParseLimits;
+use wkbparse::twkb::{self, Geometry, LineString, MultiPolygon, Point, TwkbGeom};
+
+fn to_ewkb(geom: &Geometry) -> Vec<u8> {
+    let mut out = Vec::new();
+    match geom {
+        Geometry::Point(g) => g.as_ewkb().write_ewkb(&mut out),
+        Geometry::LineString(g) => g.as_ewkb().write_ewkb(&mut out),
+        Geometry::Polygon(g) => g.as_ewkb().write_ewkb(&mut out),
+        Geometry::MultiPoint(g) => g.as_ewkb().write_ewkb(&mut out),
+        Geometry::MultiLineString(g) => g.as_ewkb().write_ewkb(&mut out),
+        Geometry::MultiPolygon(g) => g.as_ewkb().write_ewkb(&mut out),
+    }
+    .unwrap();
+    out
+}
+
+// TWKB read from any input converts into EWKB which reads back without errors.
+fuzz_target!(|data: &[u8]| {
+    let _ = Point::read_twkb(&mut &data[..]);
+    let _ = LineString::read_twkb(&mut &data[..]);
+    let _ = MultiPolygon::read_twkb(&mut &data[..]);
+    for geom in twkb::read_many::<Geometry, _>(data, &ParseLimits::default()).flatten() {
+        let encoded = to_ewkb(&geom);
+        ewkb::Geometry::read_ewkb(&mut encoded.as_slice()).unwrap();
+    }
+});
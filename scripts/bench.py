@@ -0,0 +1,200 @@
+"""Benchmark the Python API of the installed wkbparse module.
+
+Generates the same fixtures as the Rust benchmarks in `benches/`: wavy polygons around Helsinki in
+EPSG:4326 in small (100 vertices), medium (10 000 vertices) and large (300 000 vertices in 30
+polygons) size classes with and without Z and M values.
+
+Usage:
+    python scripts/bench.py                          # run everything
+    python scripts/bench.py -k large_xy              # only benchmarks containing "large_xy"
+    python scripts/bench.py --save baseline.json     # store results
+    python scripts/bench.py --compare baseline.json  # fail on regressions against stored results
+"""
+
+import argparse
+import json
+import math
+import struct
+import sys
+import time
+
+import wkbparse
+
+SRID = 4326
+PRECISION_XY = 7
+PRECISION_Z = 2
+PRECISION_M = 0
+
+SIZES = {"small": (1, 100), "medium": (1, 10_000), "large": (30, 10_000)}
+DIMS = {"xy": (False, False), "xyz": (True, False), "xym": (False, True), "xyzm": (True, True)}
+
+
+def polygon(part, n):
+    """A closed ring of `n` vertices as x, y, z, m"""
+    cx = 24.9 + 0.05 * part
+    cy = 60.2 + 0.03 * (part % 5)
+    ring = []
+    for i in range(n - 1):
+        a = 2.0 * math.pi * i / (n - 1)
+        r = 0.01 * (1.0 + 0.1 * math.sin(17.0 * a) + 0.05 * math.sin(131.0 * a))
+        ring.append(
+            (
+                round(cx + 2.0 * r * math.cos(a), PRECISION_XY),
+                round(cy + r * math.sin(a), PRECISION_XY),
+                round(10.0 + 5.0 * math.sin(3.0 * a), PRECISION_Z),
+                float(i),
+            )
+        )
+    ring.append(ring[0])
+    return ring
+
+
+def ewkb_header(type_id, has_z, has_m, srid=None):
+    type_id |= (0x80000000 if has_z else 0) | (0x40000000 if has_m else 0)
+    if srid is None:
+        return struct.pack("<BI", 1, type_id)
+    return struct.pack("<BIi", 1, type_id | 0x20000000, srid)
+
+
+def write_ewkb(polygons, has_z, has_m):
+    used = [0, 1] + ([2] if has_z else []) + ([3] if has_m else [])
+    fmt = "<" + "d" * len(used)
+
+    def body(ring):
+        crds = b"".join(struct.pack(fmt, *(crd[dim] for dim in used)) for crd in ring)
+        return struct.pack("<II", 1, len(ring)) + crds
+
+    if len(polygons) == 1:
+        return ewkb_header(3, has_z, has_m, SRID) + body(polygons[0])
+    parts = [ewkb_header(3, has_z, has_m) + body(ring) for ring in polygons]
+    return ewkb_header(6, has_z, has_m, SRID) + struct.pack("<I", len(polygons)) + b"".join(parts)
+
+
+def zigzag(v):
+    return (v << 1) ^ (v >> 63)
+
+
+def varint(out, v):
+    while v >= 0x80:
+        out.append((v & 0x7F) | 0x80)
+        v >>= 7
+    out.append(v)
+
+
+def write_twkb(polygons, has_z, has_m):
+    scales = [(0, 10**PRECISION_XY), (1, 10**PRECISION_XY)]
+    scales += [(2, 10**PRECISION_Z)] if has_z else []
+    scales += [(3, 10**PRECISION_M)] if has_m else []
+    type_id = 3 if len(polygons) == 1 else 6
+    out = bytearray([type_id | zigzag(PRECISION_XY) << 4])
+    if has_z or has_m:
+        out += bytes([0x08, has_z | has_m << 1 | PRECISION_Z << 2 | PRECISION_M << 5])
+    else:
+        out.append(0)
+    if type_id == 6:
+        varint(out, len(polygons))
+    prev = [0, 0, 0, 0]
+    for ring in polygons:
+        varint(out, 1)
+        varint(out, len(ring))
+        for crd in ring:
+            for dim, scale in scales:
+                v = round(crd[dim] * scale)
+                varint(out, zigzag(v - prev[dim]))
+                prev[dim] = v
+    return bytes(out)
+
+
+def fixtures():
+    """Yield the name, vertex count, EWKB and TWKB of every fixture"""
+    for size, (parts, n) in SIZES.items():
+        polygons = [polygon(part, n) for part in range(parts)]
+        for dims, (has_z, has_m) in DIMS.items():
+            ewkb = write_ewkb(polygons, has_z, has_m)
+            twkb = write_twkb(polygons, has_z, has_m)
+            yield f"{size}_{dims}", parts * n, ewkb, twkb
+
+
+def benchmarks(ewkb, twkb, has_m):
+    """The functions to measure for one fixture"""
+    geojson = wkbparse.ewkb_to_geojson(ewkb)
+    cases = {
+        "ewkb_to_geojson": lambda: wkbparse.ewkb_to_geojson(ewkb),
+        "twkb_to_geojson": lambda: wkbparse.twkb_to_geojson(twkb),
+        "ewkb_to_geojson_str": lambda: wkbparse.ewkb_to_geojson_str(ewkb),
+        "twkb_to_geojson_str": lambda: wkbparse.twkb_to_geojson_str(twkb),
+        "ewkb_to_geojson+json.dumps": lambda: json.dumps(wkbparse.ewkb_to_geojson(ewkb)),
+        "geojson_to_ewkb": lambda: wkbparse.geojson_to_ewkb(geojson),
+        "twkb_to_ewkb": lambda: wkbparse.twkb_to_ewkb(twkb),
+        "reproject_ewkb_3857": lambda: wkbparse.reproject_ewkb(ewkb, 3857),
+        "reproject_ewkb_32635": lambda: wkbparse.reproject_ewkb(ewkb, 32635),
+    }
+    if not has_m:
+        cases["ewkb_to_geoarrow"] = lambda: wkbparse.ewkb_to_geoarrow([ewkb])
+    return cases
+
+
+def measure(func, min_time):
+    """Best time per call in seconds over five rounds taking at least `min_time` each"""
+    start = time.perf_counter()
+    func()
+    elapsed = time.perf_counter() - start
+    loops = max(1, int(min_time / max(elapsed, 1e-9)))
+    best = math.inf
+    for _ in range(5):
+        start = time.perf_counter()
+        for _ in range(loops):
+            func()
+        best = min(best, (time.perf_counter() - start) / loops)
+    return best
+
+
+def main():
+    parser = argparse.ArgumentParser(
+        description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter
+    )
+    parser.add_argument("-k", dest="filter", default="", help="only run benchmarks containing this string")
+    parser.add_argument("--min-time", type=float, default=0.2, help="seconds per measurement round")
+    parser.add_argument("--save", help="write the results as JSON to this file")
+    parser.add_argument("--compare", help="compare against results saved with --save")
+    parser.add_argument("--threshold", type=float, default=10.0, help="allowed slowdown in percent")
+    args = parser.parse_args()
+
+    baseline = {}
+    if args.compare:
+        with open(args.compare, encoding="utf-8") as file:
+            baseline = json.load(file)
+
+    results = {}
+    regressions = []
+    for fixture, vertices, ewkb, twkb in fixtures():
+        has_m = fixture.endswith("m")
+        for case, func in benchmarks(ewkb, twkb, has_m).items():
+            name = f"{fixture}/{case}"
+            if args.filter not in name:
+                continue
+            try:
+                seconds = measure(func, args.min_time)
+            except (AttributeError, ImportError, ValueError) as exc:
+                print(f"{name:<50} skipped: {exc}")
+                continue
+            results[name] = seconds
+            line = f"{name:<50} {seconds * 1e3:>10.3f} ms {vertices / seconds / 1e6:>8.1f} Mvertices/s"
+            if name in baseline:
+                change = (seconds / baseline[name] - 1.0) * 100.0
+                line += f" {change:>+7.1f} %"
+                if change > args.threshold:
+                    regressions.append(name)
+                    line += " REGRESSION"
+            print(line, flush=True)
+
+    if args.save:
+        with open(args.save, "w", encoding="utf-8") as file:
+            json.dump(results, file, indent=2)
+    if regressions:
+        print(f"{len(regressions)} benchmarks slower than {args.threshold} % over the baseline")
+        sys.exit(1)
+
+
+if __name__ == "__main__":
+    main()
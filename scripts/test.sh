@@ -3,6 +3,9 @@ set -e
 if [ "$#" -eq 0 ]; then
     cargo test --no-default-features
     cargo test --no-default-features -F proj
+    cargo test --no-default-features -F arrow
+    cargo test --no-default-features -F postgres -F sqlx -F geo-types -F geozero
+    cargo test --no-default-features -F rayon
     tox
 else
   exec "$@"
@@ -0,0 +1,419 @@
+// Built-in coordinate reference systems
+//
+// Pure-Rust forward and inverse projections for the handful of coordinate systems that cover
+// most web mapping use: WGS84 geographic, Web Mercator, World Equidistant Cylindrical and the
+// WGS84 UTM zones. These are available without the `proj` feature.
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use crate::error::Error;
+
+// WGS84 ellipsoid
+const WGS84_A: f64 = 6378137.0;
+const WGS84_F: f64 = 1.0 / 298.257223563;
+
+// UTM projection parameters
+const UTM_K0: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500000.0;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10000000.0;
+
+const EPS: f64 = 1e-10;
+
+/// Geographic bounding box in WGS84 degrees within which a CRS or transform is valid.
+///
+/// `west` is greater than `east` for areas crossing the antimeridian.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct AreaOfUse {
+    pub west: f64,
+    pub south: f64,
+    pub east: f64,
+    pub north: f64,
+}
+
+impl AreaOfUse {
+    pub const WORLD: AreaOfUse = AreaOfUse {
+        west: -180.0,
+        south: -90.0,
+        east: 180.0,
+        north: 90.0,
+    };
+
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        let lon_ok = if self.west <= self.east {
+            lon >= self.west && lon <= self.east
+        } else {
+            lon >= self.west || lon <= self.east
+        };
+        lon_ok && lat >= self.south && lat <= self.north
+    }
+
+    /// Move a coordinate onto the nearest edge of the area if it lies outside.
+    pub fn clamp(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let lat = lat.clamp(self.south, self.north);
+        if self.contains(lon, lat) {
+            return (lon, lat);
+        }
+        // Pick whichever edge is closer around the globe
+        let dist = |a: f64, b: f64| {
+            let d = (a - b).rem_euclid(360.0);
+            d.min(360.0 - d)
+        };
+        if dist(lon, self.west) <= dist(lon, self.east) {
+            (self.west, lat)
+        } else {
+            (self.east, lat)
+        }
+    }
+
+    /// Area covered by both areas, or `None` if they do not overlap. Areas crossing the
+    /// antimeridian are not supported.
+    pub fn intersection(&self, other: &AreaOfUse) -> Option<AreaOfUse> {
+        let area = AreaOfUse {
+            west: self.west.max(other.west),
+            south: self.south.max(other.south),
+            east: self.east.min(other.east),
+            north: self.north.min(other.north),
+        };
+        if area.west < area.east && area.south < area.north {
+            Some(area)
+        } else {
+            None
+        }
+    }
+}
+
+/// Coordinate reference systems supported without PROJ.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BuiltinCrs {
+    /// EPSG:4326, WGS84 longitude/latitude in degrees
+    Wgs84,
+    /// EPSG:3857, WGS84 / Pseudo-Mercator
+    WebMercator,
+    /// EPSG:4087, WGS84 / World Equidistant Cylindrical
+    WorldEquidistantCylindrical,
+    /// EPSG:32601-32660 and EPSG:32701-32760, WGS84 / UTM zones
+    Utm { zone: u8, north: bool },
+}
+
+impl BuiltinCrs {
+    /// Look up a built-in CRS by its EPSG code.
+    pub fn from_srid(srid: i32) -> Option<BuiltinCrs> {
+        match srid {
+            4326 => Some(BuiltinCrs::Wgs84),
+            3857 => Some(BuiltinCrs::WebMercator),
+            4087 => Some(BuiltinCrs::WorldEquidistantCylindrical),
+            32601..=32660 => Some(BuiltinCrs::Utm {
+                zone: (srid - 32600) as u8,
+                north: true,
+            }),
+            32701..=32760 => Some(BuiltinCrs::Utm {
+                zone: (srid - 32700) as u8,
+                north: false,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn srid(&self) -> i32 {
+        match *self {
+            BuiltinCrs::Wgs84 => 4326,
+            BuiltinCrs::WebMercator => 3857,
+            BuiltinCrs::WorldEquidistantCylindrical => 4087,
+            BuiltinCrs::Utm { zone, north: true } => 32600 + zone as i32,
+            BuiltinCrs::Utm { zone, north: false } => 32700 + zone as i32,
+        }
+    }
+
+    /// Bounds within which the CRS is valid, as given in the EPSG registry.
+    pub fn area_of_use(&self) -> AreaOfUse {
+        match *self {
+            BuiltinCrs::Wgs84 | BuiltinCrs::WorldEquidistantCylindrical => AreaOfUse::WORLD,
+            BuiltinCrs::WebMercator => AreaOfUse {
+                south: -85.06,
+                north: 85.06,
+                ..AreaOfUse::WORLD
+            },
+            BuiltinCrs::Utm { zone, north } => {
+                let west = -180.0 + 6.0 * (zone as f64 - 1.0);
+                AreaOfUse {
+                    west,
+                    south: if north { 0.0 } else { -80.0 },
+                    east: west + 6.0,
+                    north: if north { 84.0 } else { 0.0 },
+                }
+            }
+        }
+    }
+
+    /// Project WGS84 longitude/latitude in degrees into this CRS.
+    pub fn forward(&self, lon: f64, lat: f64) -> Result<(f64, f64), Error> {
+        check_geographic(lon, lat)?;
+        match *self {
+            BuiltinCrs::Wgs84 => Ok((lon, lat)),
+            BuiltinCrs::WebMercator => {
+                let phi = lat.to_radians();
+                if (phi.abs() - FRAC_PI_2).abs() <= EPS {
+                    return Err(out_of_range(lon, lat));
+                }
+                Ok((
+                    WGS84_A * lon.to_radians(),
+                    WGS84_A * (FRAC_PI_4 + 0.5 * phi).tan().ln(),
+                ))
+            }
+            BuiltinCrs::WorldEquidistantCylindrical => {
+                Ok((WGS84_A * lon.to_radians(), WGS84_A * lat.to_radians()))
+            }
+            BuiltinCrs::Utm { zone, north } => {
+                let (x, y) = TransverseMercator::utm(zone).forward(lon, lat);
+                let false_northing = if north { 0.0 } else { UTM_FALSE_NORTHING_SOUTH };
+                Ok((x, y + false_northing))
+            }
+        }
+    }
+
+    /// Unproject coordinates of this CRS into WGS84 longitude/latitude in degrees.
+    pub fn inverse(&self, x: f64, y: f64) -> Result<(f64, f64), Error> {
+        if !x.is_finite() || !y.is_finite() {
+            return Err(out_of_range(x, y));
+        }
+        let (lon, lat) = match *self {
+            BuiltinCrs::Wgs84 => (x, y),
+            BuiltinCrs::WebMercator => (
+                (x / WGS84_A).to_degrees(),
+                (y / WGS84_A).sinh().atan().to_degrees(),
+            ),
+            BuiltinCrs::WorldEquidistantCylindrical => {
+                ((x / WGS84_A).to_degrees(), (y / WGS84_A).to_degrees())
+            }
+            BuiltinCrs::Utm { zone, north } => {
+                let false_northing = if north { 0.0 } else { UTM_FALSE_NORTHING_SOUTH };
+                TransverseMercator::utm(zone).inverse(x, y - false_northing)
+            }
+        };
+        check_geographic(lon, lat)?;
+        Ok((lon, lat))
+    }
+}
+
+fn out_of_range(x: f64, y: f64) -> Error {
+    Error::Other(format!("coordinate ({}, {}) out of range", x, y))
+}
+
+fn check_geographic(lon: f64, lat: f64) -> Result<(), Error> {
+    if !lon.is_finite() || !lat.is_finite() || lat.abs() > 90.0 + EPS {
+        return Err(out_of_range(lon, lat));
+    }
+    Ok(())
+}
+
+/// Ellipsoidal transverse Mercator using the Krüger series to fourth order in the third
+/// flattening, which is accurate to well below a millimetre within a UTM zone.
+struct TransverseMercator {
+    lon_0: f64,
+    k0: f64,
+    false_easting: f64,
+}
+
+impl TransverseMercator {
+    fn utm(zone: u8) -> Self {
+        TransverseMercator {
+            lon_0: (zone as f64 - 1.0) * 6.0 - 180.0 + 3.0,
+            k0: UTM_K0,
+            false_easting: UTM_FALSE_EASTING,
+        }
+    }
+
+    fn third_flattening() -> f64 {
+        WGS84_F / (2.0 - WGS84_F)
+    }
+
+    // Rectifying radius
+    fn rectifying_radius(n: f64) -> f64 {
+        let n2 = n * n;
+        WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0)
+    }
+
+    fn forward(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let n = Self::third_flattening();
+        let (n2, n3, n4) = (n * n, n * n * n, n * n * n * n);
+        let alpha = [
+            n / 2.0 - 2.0 / 3.0 * n2 + 5.0 / 16.0 * n3 + 41.0 / 180.0 * n4,
+            13.0 / 48.0 * n2 - 3.0 / 5.0 * n3 + 557.0 / 1440.0 * n4,
+            61.0 / 240.0 * n3 - 103.0 / 140.0 * n4,
+            49561.0 / 161280.0 * n4,
+        ];
+        let e = (WGS84_F * (2.0 - WGS84_F)).sqrt();
+
+        let phi = lat.to_radians();
+        let lam = (lon - self.lon_0).to_radians();
+        let t = (phi.sin().atanh() - e * (e * phi.sin()).atanh()).sinh();
+        let xi_p = t.atan2(lam.cos());
+        let eta_p = (lam.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        let mut xi = xi_p;
+        let mut eta = eta_p;
+        for (j, a) in alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += a * (k * xi_p).sin() * (k * eta_p).cosh();
+            eta += a * (k * xi_p).cos() * (k * eta_p).sinh();
+        }
+        let scale = self.k0 * Self::rectifying_radius(n);
+        (self.false_easting + scale * eta, scale * xi)
+    }
+
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        let n = Self::third_flattening();
+        let (n2, n3, n4) = (n * n, n * n * n, n * n * n * n);
+        let beta = [
+            n / 2.0 - 2.0 / 3.0 * n2 + 37.0 / 96.0 * n3 - 1.0 / 360.0 * n4,
+            1.0 / 48.0 * n2 + 1.0 / 15.0 * n3 - 437.0 / 1440.0 * n4,
+            17.0 / 480.0 * n3 - 37.0 / 840.0 * n4,
+            4397.0 / 161280.0 * n4,
+        ];
+        let delta = [
+            2.0 * n - 2.0 / 3.0 * n2 - 2.0 * n3 + 116.0 / 45.0 * n4,
+            7.0 / 3.0 * n2 - 8.0 / 5.0 * n3 - 227.0 / 45.0 * n4,
+            56.0 / 15.0 * n3 - 136.0 / 35.0 * n4,
+            4279.0 / 630.0 * n4,
+        ];
+
+        let scale = self.k0 * Self::rectifying_radius(n);
+        let xi = y / scale;
+        let eta = (x - self.false_easting) / scale;
+
+        let mut xi_p = xi;
+        let mut eta_p = eta;
+        for (j, b) in beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_p -= b * (k * xi).sin() * (k * eta).cosh();
+            eta_p -= b * (k * xi).cos() * (k * eta).sinh();
+        }
+        let chi = (xi_p.sin() / eta_p.cosh()).asin();
+        let mut phi = chi;
+        for (j, d) in delta.iter().enumerate() {
+            phi += d * (2.0 * (j + 1) as f64 * chi).sin();
+        }
+        let lam = eta_p.sinh().atan2(xi_p.cos());
+        (self.lon_0 + lam.to_degrees(), phi.to_degrees())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate approx;
+    use self::approx::assert_relative_eq;
+    use super::{AreaOfUse, BuiltinCrs};
+
+    #[test]
+    fn test_area_of_use() {
+        let zone35 = BuiltinCrs::from_srid(32635).unwrap().area_of_use();
+        assert_eq!((zone35.west, zone35.east), (24.0, 30.0));
+        assert!(zone35.contains(24.94, 60.17));
+        assert!(!zone35.contains(40.0, 60.17));
+        assert_eq!(zone35.clamp(40.0, 85.0), (30.0, 84.0));
+        assert_eq!(zone35.clamp(-10.0, 60.0), (24.0, 60.0));
+
+        // Fiji crosses the antimeridian
+        let fiji = AreaOfUse {
+            west: 176.0,
+            south: -20.0,
+            east: -178.0,
+            north: -12.0,
+        };
+        assert!(fiji.contains(179.0, -17.0));
+        assert!(fiji.contains(-179.0, -17.0));
+        assert!(!fiji.contains(0.0, -17.0));
+        assert_eq!(fiji.clamp(-170.0, -17.0), (-178.0, -17.0));
+        assert_eq!(fiji.clamp(170.0, -17.0), (176.0, -17.0));
+    }
+
+    #[test]
+    fn test_area_of_use_intersection() {
+        let area = |srid| BuiltinCrs::from_srid(srid).unwrap().area_of_use();
+        let mercator = area(4326).intersection(&area(3857)).unwrap();
+        assert_eq!((mercator.south, mercator.north), (-85.06, 85.06));
+        assert_eq!(area(32635).intersection(&area(3857)), Some(area(32635)));
+        // Neighbouring zones only share an edge, and northern and southern zones the equator
+        assert_eq!(area(32633).intersection(&area(32635)), None);
+        assert_eq!(area(32634).intersection(&area(32635)), None);
+        assert_eq!(area(32633).intersection(&area(32733)), None);
+    }
+
+    #[test]
+    fn test_from_srid() {
+        assert_eq!(BuiltinCrs::from_srid(4326), Some(BuiltinCrs::Wgs84));
+        assert_eq!(BuiltinCrs::from_srid(3857), Some(BuiltinCrs::WebMercator));
+        assert_eq!(
+            BuiltinCrs::from_srid(32635),
+            Some(BuiltinCrs::Utm {
+                zone: 35,
+                north: true
+            })
+        );
+        assert_eq!(
+            BuiltinCrs::from_srid(32760),
+            Some(BuiltinCrs::Utm {
+                zone: 60,
+                north: false
+            })
+        );
+        assert_eq!(BuiltinCrs::from_srid(32600), None);
+        assert_eq!(BuiltinCrs::from_srid(3067), None);
+        for srid in [4326, 3857, 4087, 32601, 32660, 32701, 32760] {
+            assert_eq!(BuiltinCrs::from_srid(srid).unwrap().srid(), srid);
+        }
+    }
+
+    #[test]
+    fn test_web_mercator() {
+        let (x, y) = BuiltinCrs::WebMercator.forward(1.0, 2.0).unwrap();
+        assert_relative_eq!(x, 111319.49079327357, epsilon = 1e-6);
+        assert_relative_eq!(y, 222684.20850554405, epsilon = 1e-6);
+        let (lon, lat) = BuiltinCrs::WebMercator.inverse(x, y).unwrap();
+        assert_relative_eq!(lon, 1.0, epsilon = 1e-12);
+        assert_relative_eq!(lat, 2.0, epsilon = 1e-12);
+        assert!(BuiltinCrs::WebMercator.forward(0.0, 90.0).is_err());
+        assert!(BuiltinCrs::WebMercator.forward(0.0, 91.0).is_err());
+    }
+
+    #[test]
+    fn test_world_equidistant_cylindrical() {
+        let (x, y) = BuiltinCrs::WorldEquidistantCylindrical
+            .forward(24.94, 60.17)
+            .unwrap();
+        assert_relative_eq!(x, 2776308.100, epsilon = 0.001);
+        assert_relative_eq!(y, 6698093.761, epsilon = 0.001);
+        let (lon, lat) = BuiltinCrs::WorldEquidistantCylindrical
+            .inverse(x, y)
+            .unwrap();
+        assert_relative_eq!(lon, 24.94, epsilon = 1e-12);
+        assert_relative_eq!(lat, 60.17, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_utm() {
+        // UTM zone 35N shares its parameters with ETRS-TM35FIN (EPSG:3067)
+        let utm35n = BuiltinCrs::from_srid(32635).unwrap();
+        let expected = [
+            ([24.97793, 60.33016], [388351.126, 6689893.389]),
+            ([24.94841, 60.31733], [386677.069, 6688515.295]),
+            ([24.92764, 60.30755], [385495.829, 6687462.308]),
+        ];
+        for (lonlat, xy) in expected.iter() {
+            let (x, y) = utm35n.forward(lonlat[0], lonlat[1]).unwrap();
+            assert_relative_eq!(x, xy[0], epsilon = 0.001);
+            assert_relative_eq!(y, xy[1], epsilon = 0.001);
+            let (lon, lat) = utm35n.inverse(x, y).unwrap();
+            assert_relative_eq!(lon, lonlat[0], epsilon = 1e-9);
+            assert_relative_eq!(lat, lonlat[1], epsilon = 1e-9);
+        }
+
+        // Southern hemisphere uses a false northing
+        let utm56s = BuiltinCrs::from_srid(32756).unwrap();
+        let (x, y) = utm56s.forward(151.2093, -33.8688).unwrap();
+        assert_relative_eq!(x, 334368.6, epsilon = 0.1);
+        assert_relative_eq!(y, 6250948.3, epsilon = 0.1);
+        let (lon, lat) = utm56s.inverse(x, y).unwrap();
+        assert_relative_eq!(lon, 151.2093, epsilon = 1e-9);
+        assert_relative_eq!(lat, -33.8688, epsilon = 1e-9);
+    }
+}
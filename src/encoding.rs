@@ -0,0 +1,166 @@
+// Text encodings of binary geometries
+//
+// PostGIS returns EWKB as upper case hex in text mode and prefixes it with `\x` when a bytea is
+// printed. Base64 is common when geometries travel in JSON payloads.
+extern crate base64;
+
+use self::base64::alphabet;
+use self::base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use self::base64::Engine;
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use crate::error::Error;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Value of each ASCII hex digit, `INVALID` for other bytes.
+const HEX_VALUES: [u8; 256] = hex_values();
+const INVALID: u8 = 0xff;
+
+const fn hex_values() -> [u8; 256] {
+    let mut values = [INVALID; 256];
+    let mut i = 0;
+    while i < 10 {
+        values[b'0' as usize + i] = i as u8;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 6 {
+        values[b'a' as usize + i] = 10 + i as u8;
+        values[b'A' as usize + i] = 10 + i as u8;
+        i += 1;
+    }
+    values
+}
+
+/// Standard base64 alphabet, accepting input with or without padding.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Encoding of binary geometries.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Encoding {
+    /// Raw bytes.
+    #[default]
+    Bytes,
+    /// Hex digits, written in upper case.
+    Hex,
+    /// Base64 with the standard alphabet.
+    Base64,
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Encoding, Error> {
+        match s {
+            "bytes" => Ok(Encoding::Bytes),
+            "hex" => Ok(Encoding::Hex),
+            "base64" => Ok(Encoding::Base64),
+            _ => Err(Error::Other(format!(
+                "invalid encoding '{}' - expected one of bytes, hex, base64",
+                s
+            ))),
+        }
+    }
+}
+
+/// Encode `data` as upper case hex.
+pub fn encode_hex(data: &[u8]) -> String {
+    let mut hex = Vec::with_capacity(data.len() * 2);
+    for byte in data {
+        hex.push(HEX_DIGITS[(byte >> 4) as usize]);
+        hex.push(HEX_DIGITS[(byte & 0x0f) as usize]);
+    }
+    String::from_utf8(hex).unwrap()
+}
+
+/// Decode hex digits of either case, optionally prefixed with `\x` as in PostgreSQL bytea
+/// output.
+pub fn decode_hex(hex: &[u8]) -> Result<Vec<u8>, Error> {
+    let digits = hex.strip_prefix(b"\\x").unwrap_or(hex);
+    // usize::is_multiple_of needs Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    if digits.len() % 2 != 0 {
+        return Err(Error::Read(format!(
+            "hex input has an odd number of digits ({})",
+            digits.len()
+        )));
+    }
+    let mut data = Vec::with_capacity(digits.len() / 2);
+    for (i, pair) in digits.chunks_exact(2).enumerate() {
+        let hi = HEX_VALUES[pair[0] as usize];
+        let lo = HEX_VALUES[pair[1] as usize];
+        if hi == INVALID || lo == INVALID {
+            let pos = if hi == INVALID { 2 * i } else { 2 * i + 1 };
+            return Err(Error::Read(format!(
+                "invalid hex digit {:?} at position {}",
+                digits[pos] as char,
+                pos + hex.len() - digits.len()
+            )));
+        }
+        data.push(hi << 4 | lo);
+    }
+    Ok(data)
+}
+
+/// Encode `data` as base64 with padding.
+pub fn encode_base64(data: &[u8]) -> String {
+    BASE64.encode(data)
+}
+
+/// Decode base64 with the standard alphabet, with or without padding.
+pub fn decode_base64(base64: &[u8]) -> Result<Vec<u8>, Error> {
+    BASE64
+        .decode(base64)
+        .map_err(|err| Error::Read(format!("invalid base64 input: {}", err)))
+}
+
+/// Decode `data` in the given encoding, borrowing raw bytes.
+pub fn decode(data: &[u8], encoding: Encoding) -> Result<Cow<'_, [u8]>, Error> {
+    match encoding {
+        Encoding::Bytes => Ok(Cow::Borrowed(data)),
+        Encoding::Hex => decode_hex(data).map(Cow::Owned),
+        Encoding::Base64 => decode_base64(data).map(Cow::Owned),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, decode_base64, decode_hex, encode_base64, encode_hex, Encoding};
+
+    #[test]
+    fn test_hex() {
+        let data: Vec<u8> = (0..=255).collect();
+        let hex = encode_hex(&data);
+        assert_eq!(&hex[..8], "00010203");
+        assert_eq!(&hex[hex.len() - 4..], "FEFF");
+        assert_eq!(decode_hex(hex.as_bytes()).unwrap(), data);
+        assert_eq!(decode_hex(hex.to_lowercase().as_bytes()).unwrap(), data);
+        assert_eq!(decode_hex(b"\\x0aFf").unwrap(), vec![0x0a, 0xff]);
+        assert_eq!(decode_hex(b"").unwrap(), Vec::<u8>::new());
+        assert_eq!(
+            decode_hex(b"0a0").unwrap_err().to_string(),
+            "Read(\"hex input has an odd number of digits (3)\")"
+        );
+        assert_eq!(
+            decode_hex(b"\\x0a0g").unwrap_err().to_string(),
+            "Read(\"invalid hex digit 'g' at position 5\")"
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        let data = decode_hex(b"610805d00fa01f50").unwrap();
+        assert_eq!(encode_base64(&data), "YQgF0A+gH1A=");
+        assert_eq!(decode_base64(b"YQgF0A+gH1A=").unwrap(), data);
+        assert_eq!(decode_base64(b"YQgF0A+gH1A").unwrap(), data);
+        assert!(decode_base64(b"YQgF0A-gH1A=").is_err());
+        assert_eq!(decode(b"YQgF0A+gH1A=", Encoding::Base64).unwrap(), data);
+        assert_eq!(decode(&data, Encoding::Bytes).unwrap(), data);
+        assert!("utf8".parse::<Encoding>().is_err());
+    }
+}
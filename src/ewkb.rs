@@ -10,7 +10,13 @@
     clippy::redundant_field_names
 )]
 
-use crate::geojson::GeometryType;
+use crate::encoding::encode_hex;
+use crate::geojson::{Dims, GeometryType};
+use crate::limits::{expect_end, read_limited, ParseBudget, ParseLimits};
+use crate::reproject::{
+    retain_ring_vertices, retain_vertices, xform_xy_with, DropVertices, Transform, TransformOptions,
+};
+use crate::visit::Visitor;
 use crate::{error::Error, types as postgis};
 extern crate byteorder;
 use self::byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
@@ -19,6 +25,7 @@ use std::fmt;
 use std::io::prelude::*;
 use std::iter::FromIterator;
 use std::slice::Iter;
+use std::str::FromStr;
 
 // --- Structs for reading PostGIS geometries into
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -65,21 +72,55 @@ pub enum PointType {
     PointZM,
 }
 
+/// Byte order of written EWKB: little-endian (NDR) or big-endian (XDR).
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl FromStr for Endianness {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Endianness, Error> {
+        match s {
+            "little" => Ok(Endianness::Little),
+            "big" => Ok(Endianness::Big),
+            _ => Err(Error::Other(format!(
+                "invalid byte order '{}' - expected one of little, big",
+                s
+            ))),
+        }
+    }
+}
+
 // --- Traits
 
 pub trait EwkbRead: fmt::Debug + Sized {
     fn point_type() -> PointType;
 
     fn read_ewkb<R: Read>(raw: &mut R) -> Result<Self, Error> {
-        let byte_order = raw.read_i8()?;
-        let is_be = byte_order == 0i8;
+        Self::read_ewkb_limited(raw, &ParseLimits::default())
+    }
 
-        let type_id = read_u32(raw, is_be)?;
-        let mut srid: Option<i32> = None;
-        if type_id & 0x20000000 == 0x20000000 {
-            srid = Some(read_i32(raw, is_be)?);
-        }
-        Self::read_ewkb_body(raw, is_be, type_id, srid)
+    /// Read a geometry, failing when the input exceeds `limits`.
+    fn read_ewkb_limited<R: Read>(raw: &mut R, limits: &ParseLimits) -> Result<Self, Error> {
+        read_limited(raw, limits, |raw, budget| Self::read_ewkb_with(raw, budget))
+    }
+
+    /// Read a geometry like [`EwkbRead::read_ewkb_limited`], failing if `raw` has bytes left
+    /// after it.
+    fn read_ewkb_strict<R: Read>(raw: &mut R, limits: &ParseLimits) -> Result<Self, Error> {
+        let geom = Self::read_ewkb_limited(raw, limits)?;
+        expect_end(raw)?;
+        Ok(geom)
+    }
+
+    #[doc(hidden)]
+    fn read_ewkb_with<R: Read>(raw: &mut R, budget: &mut ParseBudget) -> Result<Self, Error> {
+        let (is_be, type_id, srid) = read_header(raw)?;
+        Self::read_ewkb_body(raw, is_be, type_id, srid, budget)
     }
 
     #[doc(hidden)]
@@ -88,6 +129,7 @@ pub trait EwkbRead: fmt::Debug + Sized {
         is_be: bool,
         type_id: u32,
         srid: Option<i32>,
+        budget: &mut ParseBudget,
     ) -> Result<Self, Error>;
 }
 
@@ -113,25 +155,36 @@ pub trait EwkbWrite: fmt::Debug + Sized {
     fn type_id(&self) -> u32;
 
     fn write_ewkb<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error> {
-        // use LE
-        w.write_u8(0x01)?;
-        let type_id = self.type_id();
-        w.write_u32::<LittleEndian>(type_id)?;
-        self.opt_srid()
-            .map(|srid| w.write_i32::<LittleEndian>(srid));
-        self.write_ewkb_body(w)?;
+        self.write_ewkb_with(w, Endianness::Little)
+    }
+    /// Write EWKB in the given byte order.
+    fn write_ewkb_with<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        w.write_u8(match byte_order {
+            Endianness::Little => 0x01,
+            Endianness::Big => 0x00,
+        })?;
+        write_u32(w, self.type_id(), byte_order)?;
+        if let Some(srid) = self.opt_srid() {
+            write_i32(w, srid, byte_order)?;
+        }
+        self.write_ewkb_body(w, byte_order)?;
         Ok(())
     }
     #[doc(hidden)]
-    fn write_ewkb_body<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error>;
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error>;
 
     fn to_hex_ewkb(&self) -> String {
         let mut buf: Vec<u8> = Vec::new();
         self.write_ewkb(&mut buf).unwrap();
-        let hex: String = buf
-            .iter()
-            .fold(String::new(), |s, &b| s + &format!("{:02X}", b));
-        hex
+        encode_hex(&buf)
     }
 }
 
@@ -167,12 +220,101 @@ fn read_f64<R: Read>(raw: &mut R, is_be: bool) -> Result<f64, Error> {
     })
 }
 
+pub(crate) fn write_u32<W: Write + ?Sized>(
+    w: &mut W,
+    v: u32,
+    byte_order: Endianness,
+) -> Result<(), Error> {
+    match byte_order {
+        Endianness::Little => w.write_u32::<LittleEndian>(v)?,
+        Endianness::Big => w.write_u32::<BigEndian>(v)?,
+    }
+    Ok(())
+}
+
+fn write_i32<W: Write + ?Sized>(w: &mut W, v: i32, byte_order: Endianness) -> Result<(), Error> {
+    match byte_order {
+        Endianness::Little => w.write_i32::<LittleEndian>(v)?,
+        Endianness::Big => w.write_i32::<BigEndian>(v)?,
+    }
+    Ok(())
+}
+
+pub(crate) fn write_f64<W: Write + ?Sized>(
+    w: &mut W,
+    v: f64,
+    byte_order: Endianness,
+) -> Result<(), Error> {
+    match byte_order {
+        Endianness::Little => w.write_f64::<LittleEndian>(v)?,
+        Endianness::Big => w.write_f64::<BigEndian>(v)?,
+    }
+    Ok(())
+}
+
 // --- Point
 
-fn has_z(type_id: u32) -> bool {
+fn read_header<R: Read>(raw: &mut R) -> Result<(bool, u32, Option<i32>), Error> {
+    let byte_order = raw.read_i8()?;
+    let is_be = byte_order == 0i8;
+
+    let type_id = read_u32(raw, is_be)?;
+    let mut srid: Option<i32> = None;
+    if type_id & 0x20000000 == 0x20000000 {
+        srid = Some(read_i32(raw, is_be)?);
+    }
+    Ok((is_be, type_id, srid))
+}
+
+/// Read a member of a multi-geometry or geometry collection with type id `type_id`, which must
+/// have the same dimensions.
+fn read_member<G: EwkbRead, R: Read>(
+    raw: &mut R,
+    type_id: u32,
+    budget: &mut ParseBudget,
+) -> Result<G, Error> {
+    let (is_be, member_type_id, srid) = read_member_header(raw, type_id)?;
+    G::read_ewkb_body(raw, is_be, member_type_id, srid, budget)
+}
+
+/// Read the header of a member of a geometry with type id `type_id`, failing if the dimensions
+/// differ.
+fn read_member_header<R: Read>(
+    raw: &mut R,
+    type_id: u32,
+) -> Result<(bool, u32, Option<i32>), Error> {
+    let (is_be, member_type_id, srid) = read_header(raw)?;
+    if has_z(member_type_id) != has_z(type_id) || has_m(member_type_id) != has_m(type_id) {
+        return Err(Error::Read(
+            "geometry has members with different dimensions".to_owned(),
+        ));
+    }
+    Ok((is_be, member_type_id, srid))
+}
+
+/// Point type matching the dimensions of `point`.
+fn dims_of<P: postgis::Point + ?Sized>(point: &P) -> PointType {
+    match (point.opt_z().is_some(), point.opt_m().is_some()) {
+        (false, false) => PointType::Point,
+        (true, false) => PointType::PointZ,
+        (false, true) => PointType::PointM,
+        (true, true) => PointType::PointZM,
+    }
+}
+
+/// Point type of the first of `points`, or of `P` if there are none.
+fn point_type_of<'a, P, I>(mut points: I) -> PointType
+where
+    P: 'a + postgis::Point + EwkbRead,
+    I: Iterator<Item = &'a P>,
+{
+    points.next().map_or_else(P::point_type, dims_of)
+}
+
+pub(crate) fn has_z(type_id: u32) -> bool {
     type_id & 0x80000000 == 0x80000000
 }
-fn has_m(type_id: u32) -> bool {
+pub(crate) fn has_m(type_id: u32) -> bool {
     type_id & 0x40000000 == 0x40000000
 }
 
@@ -237,17 +379,11 @@ impl postgis::Point for Point {
         self.m
     }
     fn crds(&self) -> Vec<f64> {
-        {
-            if let Some(z) = self.opt_z() {
-                if let Some(m) = self.opt_m() {
-                    [self.x(), self.y(), z, m].to_vec()
-                } else {
-                    [self.x(), self.y(), z].to_vec()
-                }
-            } else {
-                [self.x(), self.y()].to_vec()
-            }
-        }
+        let mut crds = Vec::with_capacity(4);
+        crds.extend([self.x(), self.y()]);
+        crds.extend(self.opt_z());
+        crds.extend(self.opt_m());
+        crds
     }
 }
 
@@ -286,17 +422,11 @@ impl postgis::Point for PointZ {
         self.m
     }
     fn crds(&self) -> Vec<f64> {
-        {
-            if let Some(z) = self.opt_z() {
-                if let Some(m) = self.opt_m() {
-                    [self.x(), self.y(), z, m].to_vec()
-                } else {
-                    [self.x(), self.y(), z].to_vec()
-                }
-            } else {
-                [self.x(), self.y()].to_vec()
-            }
-        }
+        let mut crds = Vec::with_capacity(4);
+        crds.extend([self.x(), self.y()]);
+        crds.extend(self.opt_z());
+        crds.extend(self.opt_m());
+        crds
     }
 }
 
@@ -335,17 +465,11 @@ impl postgis::Point for PointM {
         self.z
     }
     fn crds(&self) -> Vec<f64> {
-        {
-            if let Some(z) = self.opt_z() {
-                if let Some(m) = self.opt_m() {
-                    [self.x(), self.y(), z, m].to_vec()
-                } else {
-                    [self.x(), self.y(), z].to_vec()
-                }
-            } else {
-                [self.x(), self.y()].to_vec()
-            }
-        }
+        let mut crds = Vec::with_capacity(4);
+        crds.extend([self.x(), self.y()]);
+        crds.extend(self.opt_z());
+        crds.extend(self.opt_m());
+        crds
     }
 }
 
@@ -384,17 +508,11 @@ impl postgis::Point for PointZM {
         Some(self.m)
     }
     fn crds(&self) -> Vec<f64> {
-        {
-            if let Some(z) = self.opt_z() {
-                if let Some(m) = self.opt_m() {
-                    [self.x(), self.y(), z, m].to_vec()
-                } else {
-                    [self.x(), self.y(), z].to_vec()
-                }
-            } else {
-                [self.x(), self.y()].to_vec()
-            }
-        }
+        let mut crds = Vec::with_capacity(4);
+        crds.extend([self.x(), self.y()]);
+        crds.extend(self.opt_z());
+        crds.extend(self.opt_m());
+        crds
     }
 }
 
@@ -409,7 +527,18 @@ macro_rules! impl_point_read_traits {
                 is_be: bool,
                 type_id: u32,
                 srid: Option<i32>,
+                budget: &mut ParseBudget,
             ) -> Result<Self, Error> {
+                budget.add_vertices(1)?;
+                let point_type = Self::point_type();
+                let needs_z = point_type == PointType::PointZ || point_type == PointType::PointZM;
+                let needs_m = point_type == PointType::PointM || point_type == PointType::PointZM;
+                if needs_z && !has_z(type_id) || needs_m && !has_m(type_id) {
+                    return Err(Error::Read(format!(
+                        "geometry lacks the dimensions of {:?}",
+                        point_type
+                    )));
+                }
                 let x = read_f64(raw, is_be)?;
                 let y = read_f64(raw, is_be)?;
                 let z = if has_z(type_id) {
@@ -431,7 +560,7 @@ macro_rules! impl_point_read_traits {
                 EwkbPoint {
                     geom: self,
                     srid: self.srid,
-                    point_type: PointType::$ptype,
+                    point_type: dims_of(self),
                 }
             }
         }
@@ -467,15 +596,74 @@ impl EwkbWrite for EwkbPoint<'_> {
     fn opt_srid(&self) -> Option<i32> {
         self.srid
     }
-    fn write_ewkb_body<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error> {
-        w.write_f64::<LittleEndian>(self.geom.x())?;
-        w.write_f64::<LittleEndian>(self.geom.y())?;
-        self.geom.opt_z().map(|z| w.write_f64::<LittleEndian>(z));
-        self.geom.opt_m().map(|m| w.write_f64::<LittleEndian>(m));
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        write_f64(w, self.geom.x(), byte_order)?;
+        write_f64(w, self.geom.y(), byte_order)?;
+        if let Some(z) = self.geom.opt_z() {
+            write_f64(w, z, byte_order)?;
+        }
+        if let Some(m) = self.geom.opt_m() {
+            write_f64(w, m, byte_order)?;
+        }
         Ok(())
     }
 }
 
+pub fn get_byte_order(data: &[u8]) -> Endianness {
+    // Check byte order from the WKB binary
+    match data.first() {
+        Some(0) => Endianness::Big,
+        _ => Endianness::Little,
+    }
+}
+
+pub fn get_point_type(data: &[u8]) -> PointType {
+    // Check coordinate dimensions from the WKB binary
+    if data.len() < 5 {
+        return PointType::Point;
+    }
+
+    let is_be = data[0] as i8 == 0i8;
+    let type_id = if is_be {
+        BigEndian::read_u32(&data[1..5])
+    } else {
+        LittleEndian::read_u32(&data[1..5])
+    };
+
+    match (has_z(type_id), has_m(type_id)) {
+        (false, false) => PointType::Point,
+        (true, false) => PointType::PointZ,
+        (false, true) => PointType::PointM,
+        (true, true) => PointType::PointZM,
+    }
+}
+
+pub fn get_srid(data: &[u8]) -> Option<i32> {
+    // Check SRID from the EWKB binary
+    if data.len() < 9 {
+        return None;
+    }
+
+    let is_be = data[0] as i8 == 0i8;
+    let type_id = if is_be {
+        BigEndian::read_u32(&data[1..5])
+    } else {
+        LittleEndian::read_u32(&data[1..5])
+    };
+    if type_id & 0x20000000 != 0x20000000 {
+        return None;
+    }
+    Some(if is_be {
+        BigEndian::read_i32(&data[5..9])
+    } else {
+        LittleEndian::read_i32(&data[5..9])
+    })
+}
+
 macro_rules! point_container_type {
     // geometries containing points
     ($geotypetrait:ident for $geotype:ident) => {
@@ -590,11 +778,12 @@ macro_rules! impl_read_for_point_container_type {
                 is_be: bool,
                 type_id: u32,
                 srid: Option<i32>,
+                budget: &mut ParseBudget,
             ) -> Result<Self, Error> {
                 let mut points: Vec<P> = vec![];
                 let size = read_u32(raw, is_be)? as usize;
                 for _ in 0..size {
-                    points.push(P::read_ewkb_body(raw, is_be, type_id, srid)?);
+                    points.push(P::read_ewkb_body(raw, is_be, type_id, srid, budget)?);
                 }
                 Ok($geotype::<P> {
                     points: points,
@@ -614,13 +803,14 @@ macro_rules! impl_read_for_point_container_type {
             fn read_ewkb_body<R: Read>(
                 raw: &mut R,
                 is_be: bool,
-                _type_id: u32,
+                type_id: u32,
                 srid: Option<i32>,
+                budget: &mut ParseBudget,
             ) -> Result<Self, Error> {
                 let mut points: Vec<P> = vec![];
-                let size = read_u32(raw, is_be)? as usize;
+                let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
                 for _ in 0..size {
-                    points.push(P::read_ewkb(raw)?);
+                    points.push(read_member(raw, type_id, budget)?);
                 }
                 Ok($geotype::<P> {
                     points: points,
@@ -645,11 +835,14 @@ macro_rules! impl_read_for_geometry_container_type {
                 is_be: bool,
                 type_id: u32,
                 srid: Option<i32>,
+                budget: &mut ParseBudget,
             ) -> Result<Self, Error> {
                 let mut $itemname: Vec<$itemtype<P>> = vec![];
-                let size = read_u32(raw, is_be)? as usize;
+                let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
                 for _ in 0..size {
-                    $itemname.push($itemtype::read_ewkb_body(raw, is_be, type_id, srid)?);
+                    $itemname.push($itemtype::read_ewkb_body(
+                        raw, is_be, type_id, srid, budget,
+                    )?);
                 }
                 Ok($geotype::<P> {
                     $itemname: $itemname,
@@ -669,13 +862,14 @@ macro_rules! impl_read_for_geometry_container_type {
             fn read_ewkb_body<R: Read>(
                 raw: &mut R,
                 is_be: bool,
-                _type_id: u32,
+                type_id: u32,
                 srid: Option<i32>,
+                budget: &mut ParseBudget,
             ) -> Result<Self, Error> {
                 let mut $itemname: Vec<$itemtype<P>> = vec![];
-                let size = read_u32(raw, is_be)? as usize;
+                let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
                 for _ in 0..size {
-                    $itemname.push($itemtype::read_ewkb(raw)?);
+                    $itemname.push(read_member(raw, type_id, budget)?);
                 }
                 Ok($geotype::<P> {
                     $itemname: $itemname,
@@ -729,15 +923,19 @@ macro_rules! point_container_write {
                 $typecode | Self::wkb_type_id(&self.point_type, self.srid)
             }
 
-            fn write_ewkb_body<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error> {
-                w.write_u32::<LittleEndian>(self.geom.points().len() as u32)?;
+            fn write_ewkb_body<W: Write + ?Sized>(
+                &self,
+                w: &mut W,
+                byte_order: Endianness,
+            ) -> Result<(), Error> {
+                write_u32(w, self.geom.points().len() as u32, byte_order)?;
                 for geom in self.geom.points() {
                     let wkb = EwkbPoint {
                         geom: geom,
                         srid: None,
                         point_type: self.point_type.clone(),
                     };
-                    wkb.$writecmd(w)?;
+                    wkb.$writecmd(w, byte_order)?;
                 }
                 Ok(())
             }
@@ -753,7 +951,7 @@ macro_rules! point_container_write {
                 $ewkbtype {
                     geom: self,
                     srid: self.srid,
-                    point_type: Self::PointType::point_type(),
+                    point_type: point_type_of(self.points.iter()),
                 }
             }
         }
@@ -815,15 +1013,19 @@ macro_rules! geometry_container_write {
                 $typecode | Self::wkb_type_id(&self.point_type, self.srid)
             }
 
-            fn write_ewkb_body<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error> {
-                w.write_u32::<LittleEndian>(self.geom.$itemname().len() as u32)?;
+            fn write_ewkb_body<W: Write + ?Sized>(
+                &self,
+                w: &mut W,
+                byte_order: Endianness,
+            ) -> Result<(), Error> {
+                write_u32(w, self.geom.$itemname().len() as u32, byte_order)?;
                 for geom in self.geom.$itemname() {
                     let wkb = $ewkbitemtype {
                         geom: geom,
                         srid: None,
                         point_type: self.point_type.clone(),
                     };
-                    wkb.$writecmd(w)?;
+                    wkb.$writecmd(w, byte_order)?;
                 }
                 Ok(())
             }
@@ -843,7 +1045,9 @@ macro_rules! geometry_container_write {
                 $ewkbtype {
                     geom: self,
                     srid: self.srid,
-                    point_type: Self::PointType::point_type(),
+                    point_type: point_type_of(
+                        self.$itemname.iter().flat_map(|item| item.points.iter()),
+                    ),
                 }
             }
         }
@@ -920,15 +1124,19 @@ macro_rules! geometry_container_write {
                 $typecode | Self::wkb_type_id(&self.point_type, self.srid)
             }
 
-            fn write_ewkb_body<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error> {
-                w.write_u32::<LittleEndian>(self.geom.$itemname().len() as u32)?;
+            fn write_ewkb_body<W: Write + ?Sized>(
+                &self,
+                w: &mut W,
+                byte_order: Endianness,
+            ) -> Result<(), Error> {
+                write_u32(w, self.geom.$itemname().len() as u32, byte_order)?;
                 for geom in self.geom.$itemname() {
                     let wkb = $ewkbitemtype {
                         geom: geom,
                         srid: None,
                         point_type: self.point_type.clone(),
                     };
-                    wkb.$writecmd(w)?;
+                    wkb.$writecmd(w, byte_order)?;
                 }
                 Ok(())
             }
@@ -958,7 +1166,12 @@ macro_rules! geometry_container_write {
                 $ewkbtype {
                     geom: self,
                     srid: self.srid,
-                    point_type: Self::PointType::point_type(),
+                    point_type: point_type_of(
+                        self.$itemname
+                            .iter()
+                            .flat_map(|item| item.rings.iter())
+                            .flat_map(|ring| ring.points.iter()),
+                    ),
                 }
             }
         }
@@ -1000,7 +1213,7 @@ point_container_type!(MultiPoint for MultiPointT);
 impl_read_for_point_container_type!(multitype MultiPointT);
 point_container_write!(MultiPoint and AsEwkbMultiPoint for MultiPointT
                        to EwkbMultiPoint with type code 0x04,
-                       command write_ewkb);
+                       command write_ewkb_with);
 
 /// OGC MultiPoint type
 pub type MultiPoint = MultiPointT<Point>;
@@ -1016,7 +1229,7 @@ impl_read_for_geometry_container_type!(multitype MultiLineStringT contains LineS
 geometry_container_write!(MultiLineString and AsEwkbMultiLineString for MultiLineStringT
                           to EwkbMultiLineString with type code 0x05,
                           contains EwkbLineString,LineStringT as LineString named lines,
-                          command write_ewkb);
+                          command write_ewkb_with);
 
 /// OGC MultiLineString type
 pub type MultiLineString = MultiLineStringT<Point>;
@@ -1032,7 +1245,7 @@ impl_read_for_geometry_container_type!(multitype MultiPolygonT contains PolygonT
 geometry_container_write!(multipoly MultiPolygon and AsEwkbMultiPolygon for MultiPolygonT
                           to EwkbMultiPolygon with type code 0x06,
                           contains EwkbPolygon,PolygonT as Polygon named polygons,
-                          command write_ewkb);
+                          command write_ewkb_with);
 
 /// OGC MultiPolygon type
 pub type MultiPolygon = MultiPolygonT<Point>;
@@ -1099,31 +1312,32 @@ where
     fn point_type() -> PointType {
         P::point_type()
     }
-    fn read_ewkb<R: Read>(raw: &mut R) -> Result<Self, Error> {
-        let byte_order = raw.read_i8()?;
-        let is_be = byte_order == 0i8;
-
-        let type_id = read_u32(raw, is_be)?;
-        let mut srid: Option<i32> = None;
-        if type_id & 0x20000000 == 0x20000000 {
-            srid = Some(read_i32(raw, is_be)?);
-        }
-
+    fn read_ewkb_body<R: Read>(
+        raw: &mut R,
+        is_be: bool,
+        type_id: u32,
+        srid: Option<i32>,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
         let geom = match type_id & 0xff {
-            0x01 => GeometryT::Point(P::read_ewkb_body(raw, is_be, type_id, srid)?),
-            0x02 => {
-                GeometryT::LineString(LineStringT::<P>::read_ewkb_body(raw, is_be, type_id, srid)?)
+            0x01 => GeometryT::Point(P::read_ewkb_body(raw, is_be, type_id, srid, budget)?),
+            0x02 => GeometryT::LineString(LineStringT::<P>::read_ewkb_body(
+                raw, is_be, type_id, srid, budget,
+            )?),
+            0x03 => {
+                GeometryT::Polygon(PolygonT::read_ewkb_body(raw, is_be, type_id, srid, budget)?)
             }
-            0x03 => GeometryT::Polygon(PolygonT::read_ewkb_body(raw, is_be, type_id, srid)?),
-            0x04 => GeometryT::MultiPoint(MultiPointT::read_ewkb_body(raw, is_be, type_id, srid)?),
+            0x04 => GeometryT::MultiPoint(MultiPointT::read_ewkb_body(
+                raw, is_be, type_id, srid, budget,
+            )?),
             0x05 => GeometryT::MultiLineString(MultiLineStringT::read_ewkb_body(
-                raw, is_be, type_id, srid,
+                raw, is_be, type_id, srid, budget,
+            )?),
+            0x06 => GeometryT::MultiPolygon(MultiPolygonT::read_ewkb_body(
+                raw, is_be, type_id, srid, budget,
             )?),
-            0x06 => {
-                GeometryT::MultiPolygon(MultiPolygonT::read_ewkb_body(raw, is_be, type_id, srid)?)
-            }
             0x07 => GeometryT::GeometryCollection(GeometryCollectionT::read_ewkb_body(
-                raw, is_be, type_id, srid,
+                raw, is_be, type_id, srid, budget,
             )?),
             _ => {
                 return Err(Error::Read(format!(
@@ -1134,14 +1348,6 @@ where
         };
         Ok(geom)
     }
-    fn read_ewkb_body<R: Read>(
-        _raw: &mut R,
-        _is_be: bool,
-        _type_id: u32,
-        _srid: Option<i32>,
-    ) -> Result<Self, Error> {
-        panic!("Not used for generic geometry type")
-    }
 }
 
 pub enum EwkbGeometry<'a, P, PI, MP, L, LI, ML, Y, YI, MY, G, GI, GC>
@@ -1310,15 +1516,19 @@ where
         }
     }
 
-    fn write_ewkb_body<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error> {
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
         match *self {
-            EwkbGeometry::Point(ref ewkb) => ewkb.write_ewkb_body(w),
-            EwkbGeometry::LineString(ref ewkb) => ewkb.write_ewkb_body(w),
-            EwkbGeometry::Polygon(ref ewkb) => ewkb.write_ewkb_body(w),
-            EwkbGeometry::MultiPoint(ref ewkb) => ewkb.write_ewkb_body(w),
-            EwkbGeometry::MultiLineString(ref ewkb) => ewkb.write_ewkb_body(w),
-            EwkbGeometry::MultiPolygon(ref ewkb) => ewkb.write_ewkb_body(w),
-            EwkbGeometry::GeometryCollection(ref ewkb) => ewkb.write_ewkb_body(w),
+            EwkbGeometry::Point(ref ewkb) => ewkb.write_ewkb_body(w, byte_order),
+            EwkbGeometry::LineString(ref ewkb) => ewkb.write_ewkb_body(w, byte_order),
+            EwkbGeometry::Polygon(ref ewkb) => ewkb.write_ewkb_body(w, byte_order),
+            EwkbGeometry::MultiPoint(ref ewkb) => ewkb.write_ewkb_body(w, byte_order),
+            EwkbGeometry::MultiLineString(ref ewkb) => ewkb.write_ewkb_body(w, byte_order),
+            EwkbGeometry::MultiPolygon(ref ewkb) => ewkb.write_ewkb_body(w, byte_order),
+            EwkbGeometry::GeometryCollection(ref ewkb) => ewkb.write_ewkb_body(w, byte_order),
         }
     }
 }
@@ -1397,6 +1607,23 @@ where
     }
 }
 
+fn first_point<P: postgis::Point + EwkbRead>(geom: &GeometryT<P>) -> Option<&P> {
+    match geom {
+        GeometryT::Point(point) => Some(point),
+        GeometryT::LineString(g) => g.points.first(),
+        GeometryT::Polygon(g) => g.rings.iter().flat_map(|r| r.points.iter()).next(),
+        GeometryT::MultiPoint(g) => g.points.first(),
+        GeometryT::MultiLineString(g) => g.lines.iter().flat_map(|l| l.points.iter()).next(),
+        GeometryT::MultiPolygon(g) => g
+            .polygons
+            .iter()
+            .flat_map(|p| p.rings.iter())
+            .flat_map(|r| r.points.iter())
+            .next(),
+        GeometryT::GeometryCollection(g) => g.geometries.iter().find_map(first_point),
+    }
+}
+
 impl<'a, P> postgis::GeometryCollection<'a> for GeometryCollectionT<P>
 where
     P: 'a + postgis::Point + EwkbRead,
@@ -1419,46 +1646,17 @@ where
     fn read_ewkb_body<R: Read>(
         raw: &mut R,
         is_be: bool,
-        _type_id: u32,
+        type_id: u32,
         _srid: Option<i32>,
+        budget: &mut ParseBudget,
     ) -> Result<Self, Error> {
+        budget.enter()?;
         let mut ret = GeometryCollectionT::new();
-        let size = read_u32(raw, is_be)? as usize;
+        let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
         for _ in 0..size {
-            let is_be = raw.read_i8()? == 0i8;
-
-            let type_id = read_u32(raw, is_be)?;
-            let mut srid: Option<i32> = None;
-            if type_id & 0x20000000 == 0x20000000 {
-                srid = Some(read_i32(raw, is_be)?);
-            }
-            let geom = match type_id & 0xff {
-                0x01 => GeometryT::Point(P::read_ewkb_body(raw, is_be, type_id, srid)?),
-                0x02 => GeometryT::LineString(LineStringT::<P>::read_ewkb_body(
-                    raw, is_be, type_id, srid,
-                )?),
-                0x03 => GeometryT::Polygon(PolygonT::read_ewkb_body(raw, is_be, type_id, srid)?),
-                0x04 => {
-                    GeometryT::MultiPoint(MultiPointT::read_ewkb_body(raw, is_be, type_id, srid)?)
-                }
-                0x05 => GeometryT::MultiLineString(MultiLineStringT::read_ewkb_body(
-                    raw, is_be, type_id, srid,
-                )?),
-                0x06 => GeometryT::MultiPolygon(MultiPolygonT::read_ewkb_body(
-                    raw, is_be, type_id, srid,
-                )?),
-                0x07 => GeometryT::GeometryCollection(GeometryCollectionT::read_ewkb_body(
-                    raw, is_be, type_id, srid,
-                )?),
-                _ => {
-                    return Err(Error::Read(format!(
-                        "Error reading generic geometry type - unsupported type id {}.",
-                        type_id
-                    )))
-                }
-            };
-            ret.geometries.push(geom);
+            ret.geometries.push(read_member(raw, type_id, budget)?);
         }
+        budget.leave();
         Ok(ret)
     }
 }
@@ -1609,8 +1807,12 @@ where
         0x07 | Self::wkb_type_id(&self.point_type, self.srid)
     }
 
-    fn write_ewkb_body<W: Write + ?Sized>(&self, w: &mut W) -> Result<(), Error> {
-        w.write_u32::<LittleEndian>(self.geom.geometries().len() as u32)?;
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        write_u32(w, self.geom.geometries().len() as u32, byte_order)?;
 
         for geom in self.geom.geometries() {
             match geom.as_type() {
@@ -1620,7 +1822,7 @@ where
                         srid: None,
                         point_type: self.point_type,
                     };
-                    wkb.write_ewkb(w)?;
+                    wkb.write_ewkb_with(w, byte_order)?;
                 }
                 postgis::GeometryType::LineString(geom) => {
                     let wkb = EwkbLineString {
@@ -1628,7 +1830,7 @@ where
                         srid: None,
                         point_type: self.point_type,
                     };
-                    wkb.write_ewkb(w)?;
+                    wkb.write_ewkb_with(w, byte_order)?;
                 }
                 postgis::GeometryType::Polygon(geom) => {
                     let wkb = EwkbPolygon {
@@ -1636,7 +1838,7 @@ where
                         srid: None,
                         point_type: self.point_type,
                     };
-                    wkb.write_ewkb(w)?;
+                    wkb.write_ewkb_with(w, byte_order)?;
                 }
                 postgis::GeometryType::MultiPoint(geom) => {
                     let wkb = EwkbMultiPoint {
@@ -1644,7 +1846,7 @@ where
                         srid: None,
                         point_type: self.point_type,
                     };
-                    wkb.write_ewkb(w)?;
+                    wkb.write_ewkb_with(w, byte_order)?;
                 }
                 postgis::GeometryType::MultiLineString(geom) => {
                     let wkb = EwkbMultiLineString {
@@ -1652,7 +1854,7 @@ where
                         srid: None,
                         point_type: self.point_type,
                     };
-                    wkb.write_ewkb(w)?;
+                    wkb.write_ewkb_with(w, byte_order)?;
                 }
                 postgis::GeometryType::MultiPolygon(geom) => {
                     let wkb = EwkbMultiPolygon {
@@ -1660,7 +1862,7 @@ where
                         srid: None,
                         point_type: self.point_type,
                     };
-                    wkb.write_ewkb(w)?;
+                    wkb.write_ewkb_with(w, byte_order)?;
                 }
                 postgis::GeometryType::GeometryCollection(geom) => {
                     let wkb = EwkbGeometryCollection {
@@ -1668,7 +1870,7 @@ where
                         srid: None,
                         point_type: self.point_type,
                     };
-                    wkb.write_ewkb(w)?;
+                    wkb.write_ewkb_with(w, byte_order)?;
                 }
             }
         }
@@ -1712,7 +1914,7 @@ where
         EwkbGeometryCollection {
             geom: self,
             srid: self.srid,
-            point_type: P::point_type(),
+            point_type: point_type_of(self.geometries.iter().filter_map(first_point)),
         }
     }
 }
@@ -1726,6 +1928,479 @@ pub type GeometryCollectionM = GeometryCollectionT<PointM>;
 /// OGC GeometryCollectionZM type
 pub type GeometryCollectionZM = GeometryCollectionT<PointZM>;
 
+// --- Streams
+
+/// Iterator over geometries stored back to back, see [`read_many`].
+pub struct ReadMany<G, R> {
+    raw: R,
+    limits: ParseLimits,
+    done: bool,
+    geom: std::marker::PhantomData<G>,
+}
+
+/// Read successive geometries from `raw` until it ends, applying `limits` to each of them.
+///
+/// Reading stops after the first error, as the start of the next geometry is then unknown.
+///
+/// ```ignore
+/// for geom in ewkb::read_many::<ewkb::Geometry, _>(file, &ParseLimits::default()) {
+///     println!("{:?}", geom?);
+/// }
+/// ```
+pub fn read_many<G: EwkbRead, R: Read>(raw: R, limits: &ParseLimits) -> ReadMany<G, R> {
+    ReadMany {
+        raw,
+        limits: *limits,
+        done: false,
+        geom: std::marker::PhantomData,
+    }
+}
+
+impl<G: EwkbRead, R: Read> Iterator for ReadMany<G, R> {
+    type Item = Result<G, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut byte_order = [0u8];
+        match self.raw.read_exact(&mut byte_order) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err.into()));
+            }
+        }
+        let result =
+            G::read_ewkb_limited(&mut (&byte_order[..]).chain(&mut self.raw), &self.limits);
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+// --- Visitors
+
+/// Read a geometry, passing its parts to `visitor` while reading instead of building it.
+///
+/// The SRID is passed before the geometry. Empty points are passed with NaN coordinates.
+pub fn visit_ewkb<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    visitor: &mut V,
+    limits: &ParseLimits,
+) -> Result<(), Error> {
+    read_limited(raw, limits, |raw, budget| {
+        let (is_be, type_id, srid) = read_header(raw)?;
+        visitor.srid(srid)?;
+        visit_geometry(raw, is_be, type_id, visitor, budget)
+    })
+}
+
+fn visit_geometry<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    is_be: bool,
+    type_id: u32,
+    visitor: &mut V,
+    budget: &mut ParseBudget,
+) -> Result<(), Error> {
+    let dims = Dims::new(has_z(type_id), has_m(type_id));
+    match type_id & 0xff {
+        0x01 => {
+            budget.add_vertices(1)?;
+            visitor.begin_point(dims)?;
+            visit_coord(raw, is_be, type_id, visitor)?;
+            visitor.end_point()
+        }
+        0x02 => visit_line(raw, is_be, type_id, visitor, budget),
+        0x03 => visit_polygon(raw, is_be, type_id, visitor, budget),
+        0x04 => {
+            let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
+            budget.add_vertices(size as u64)?;
+            visitor.begin_multipoint(dims, size)?;
+            for _ in 0..size {
+                let (is_be, member_type_id, _) = read_member_header(raw, type_id)?;
+                visit_coord(raw, is_be, member_type_id, visitor)?;
+            }
+            visitor.end_multipoint()
+        }
+        0x05 => {
+            let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
+            visitor.begin_multilinestring(dims, size)?;
+            for _ in 0..size {
+                let (is_be, member_type_id, _) = read_member_header(raw, type_id)?;
+                visit_line(raw, is_be, member_type_id, visitor, budget)?;
+            }
+            visitor.end_multilinestring()
+        }
+        0x06 => {
+            let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
+            visitor.begin_multipolygon(dims, size)?;
+            for _ in 0..size {
+                let (is_be, member_type_id, _) = read_member_header(raw, type_id)?;
+                visit_polygon(raw, is_be, member_type_id, visitor, budget)?;
+            }
+            visitor.end_multipolygon()
+        }
+        0x07 => {
+            budget.enter()?;
+            let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
+            visitor.begin_collection(dims, size)?;
+            for _ in 0..size {
+                let (is_be, member_type_id, _) = read_member_header(raw, type_id)?;
+                visit_geometry(raw, is_be, member_type_id, visitor, budget)?;
+            }
+            budget.leave();
+            visitor.end_collection()
+        }
+        _ => Err(Error::Read(format!(
+            "Error reading generic geometry type - unsupported type id {}.",
+            type_id
+        ))),
+    }
+}
+
+fn visit_line<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    is_be: bool,
+    type_id: u32,
+    visitor: &mut V,
+    budget: &mut ParseBudget,
+) -> Result<(), Error> {
+    let size = budget.add_vertices(read_u32(raw, is_be)?.into())?;
+    visitor.begin_linestring(Dims::new(has_z(type_id), has_m(type_id)), size)?;
+    for _ in 0..size {
+        visit_coord(raw, is_be, type_id, visitor)?;
+    }
+    visitor.end_linestring()
+}
+
+fn visit_polygon<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    is_be: bool,
+    type_id: u32,
+    visitor: &mut V,
+    budget: &mut ParseBudget,
+) -> Result<(), Error> {
+    let size = budget.add_parts(read_u32(raw, is_be)?.into())?;
+    visitor.begin_polygon(Dims::new(has_z(type_id), has_m(type_id)), size)?;
+    for _ in 0..size {
+        let npoints = budget.add_vertices(read_u32(raw, is_be)?.into())?;
+        visitor.begin_ring(npoints)?;
+        for _ in 0..npoints {
+            visit_coord(raw, is_be, type_id, visitor)?;
+        }
+        visitor.end_ring()?;
+    }
+    visitor.end_polygon()
+}
+
+fn visit_coord<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    is_be: bool,
+    type_id: u32,
+    visitor: &mut V,
+) -> Result<(), Error> {
+    let x = read_f64(raw, is_be)?;
+    let y = read_f64(raw, is_be)?;
+    let z = if has_z(type_id) {
+        Some(read_f64(raw, is_be)?)
+    } else {
+        None
+    };
+    let m = if has_m(type_id) {
+        Some(read_f64(raw, is_be)?)
+    } else {
+        None
+    };
+    visitor.coord(x, y, z, m)
+}
+
+// --- Reprojection
+
+/// Geometries whose coordinates can be reprojected in place.
+pub trait EwkbTransform {
+    /// Transform x and y of every point. Z and M values are left as they are.
+    fn transform(&mut self, xform: &Transform) -> Result<(), Error> {
+        self.transform_with(xform, &TransformOptions::default())
+    }
+    /// Transform x and y of every point, handling failing vertices as `options` says.
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error>;
+    /// Set the SRID of the geometry and all of its parts.
+    fn set_srid(&mut self, srid: Option<i32>);
+}
+
+#[doc(hidden)]
+pub trait PointXyMut {
+    fn xy_mut(&mut self) -> (&mut f64, &mut f64);
+}
+
+macro_rules! impl_point_transform {
+    ($ptype:ident) => {
+        impl PointXyMut for $ptype {
+            fn xy_mut(&mut self) -> (&mut f64, &mut f64) {
+                (&mut self.x, &mut self.y)
+            }
+        }
+
+        impl EwkbTransform for $ptype {
+            fn transform_with(
+                &mut self,
+                xform: &Transform,
+                options: &TransformOptions,
+            ) -> Result<(), Error> {
+                let dropped = xform_xy_with(std::iter::once(self.xy_mut()), xform, options)?;
+                self.drop_vertices(&dropped);
+                Ok(())
+            }
+            fn set_srid(&mut self, srid: Option<i32>) {
+                self.srid = srid;
+            }
+        }
+
+        impl DropVertices for $ptype {
+            fn drop_vertices(&mut self, dropped: &[usize]) {
+                // A point cannot be removed from itself, so it becomes an empty point
+                if !dropped.is_empty() {
+                    self.x = f64::NAN;
+                    self.y = f64::NAN;
+                }
+            }
+        }
+    };
+}
+
+impl_point_transform!(Point);
+impl_point_transform!(PointZ);
+impl_point_transform!(PointM);
+impl_point_transform!(PointZM);
+
+impl<P> EwkbTransform for LineStringT<P>
+where
+    P: postgis::Point + EwkbRead + EwkbTransform + PointXyMut,
+{
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        let dropped = xform_xy_with(self.points.iter_mut().map(P::xy_mut), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
+    }
+    fn set_srid(&mut self, srid: Option<i32>) {
+        self.srid = srid;
+        self.points.iter_mut().for_each(|p| p.set_srid(srid));
+    }
+}
+
+impl<P> DropVertices for LineStringT<P>
+where
+    P: postgis::Point + EwkbRead,
+{
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        retain_vertices(&mut self.points, &mut 0, dropped);
+    }
+}
+
+impl<P> EwkbTransform for PolygonT<P>
+where
+    P: postgis::Point + EwkbRead + EwkbTransform + PointXyMut + Clone + PartialEq,
+{
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        let points = self
+            .rings
+            .iter_mut()
+            .flat_map(|ring| ring.points.iter_mut());
+        let dropped = xform_xy_with(points.map(P::xy_mut), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
+    }
+    fn set_srid(&mut self, srid: Option<i32>) {
+        self.srid = srid;
+        self.rings.iter_mut().for_each(|ring| ring.set_srid(srid));
+    }
+}
+
+impl<P> DropVertices for PolygonT<P>
+where
+    P: postgis::Point + EwkbRead + Clone + PartialEq,
+{
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        retain_ring_vertices(&mut self.rings, |ring| &mut ring.points, &mut 0, dropped);
+    }
+}
+
+impl<P> EwkbTransform for MultiPointT<P>
+where
+    P: postgis::Point + EwkbRead + EwkbTransform + PointXyMut,
+{
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        let dropped = xform_xy_with(self.points.iter_mut().map(P::xy_mut), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
+    }
+    fn set_srid(&mut self, srid: Option<i32>) {
+        self.srid = srid;
+        self.points.iter_mut().for_each(|p| p.set_srid(srid));
+    }
+}
+
+impl<P> DropVertices for MultiPointT<P>
+where
+    P: postgis::Point + EwkbRead,
+{
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        retain_vertices(&mut self.points, &mut 0, dropped);
+    }
+}
+
+impl<P> EwkbTransform for MultiLineStringT<P>
+where
+    P: postgis::Point + EwkbRead + EwkbTransform + PointXyMut,
+{
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        let points = self
+            .lines
+            .iter_mut()
+            .flat_map(|line| line.points.iter_mut());
+        let dropped = xform_xy_with(points.map(P::xy_mut), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
+    }
+    fn set_srid(&mut self, srid: Option<i32>) {
+        self.srid = srid;
+        self.lines.iter_mut().for_each(|line| line.set_srid(srid));
+    }
+}
+
+impl<P> DropVertices for MultiLineStringT<P>
+where
+    P: postgis::Point + EwkbRead,
+{
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        let mut index = 0;
+        for line in self.lines.iter_mut() {
+            retain_vertices(&mut line.points, &mut index, dropped);
+        }
+    }
+}
+
+impl<P> EwkbTransform for MultiPolygonT<P>
+where
+    P: postgis::Point + EwkbRead + EwkbTransform + PointXyMut + Clone + PartialEq,
+{
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        let points = self
+            .polygons
+            .iter_mut()
+            .flat_map(|poly| poly.rings.iter_mut())
+            .flat_map(|ring| ring.points.iter_mut());
+        let dropped = xform_xy_with(points.map(P::xy_mut), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
+    }
+    fn set_srid(&mut self, srid: Option<i32>) {
+        self.srid = srid;
+        self.polygons
+            .iter_mut()
+            .for_each(|poly| poly.set_srid(srid));
+    }
+}
+
+impl<P> DropVertices for MultiPolygonT<P>
+where
+    P: postgis::Point + EwkbRead + Clone + PartialEq,
+{
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        let mut index = 0;
+        self.polygons.retain_mut(|poly| {
+            retain_ring_vertices(
+                &mut poly.rings,
+                |ring| &mut ring.points,
+                &mut index,
+                dropped,
+            )
+        });
+    }
+}
+
+impl<P> EwkbTransform for GeometryT<P>
+where
+    P: postgis::Point + EwkbRead + EwkbTransform + PointXyMut + Clone + PartialEq,
+{
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        match *self {
+            GeometryT::Point(ref mut geom) => geom.transform_with(xform, options),
+            GeometryT::LineString(ref mut geom) => geom.transform_with(xform, options),
+            GeometryT::Polygon(ref mut geom) => geom.transform_with(xform, options),
+            GeometryT::MultiPoint(ref mut geom) => geom.transform_with(xform, options),
+            GeometryT::MultiLineString(ref mut geom) => geom.transform_with(xform, options),
+            GeometryT::MultiPolygon(ref mut geom) => geom.transform_with(xform, options),
+            GeometryT::GeometryCollection(ref mut geom) => geom.transform_with(xform, options),
+        }
+    }
+    fn set_srid(&mut self, srid: Option<i32>) {
+        match *self {
+            GeometryT::Point(ref mut geom) => geom.set_srid(srid),
+            GeometryT::LineString(ref mut geom) => geom.set_srid(srid),
+            GeometryT::Polygon(ref mut geom) => geom.set_srid(srid),
+            GeometryT::MultiPoint(ref mut geom) => geom.set_srid(srid),
+            GeometryT::MultiLineString(ref mut geom) => geom.set_srid(srid),
+            GeometryT::MultiPolygon(ref mut geom) => geom.set_srid(srid),
+            GeometryT::GeometryCollection(ref mut geom) => geom.set_srid(srid),
+        }
+    }
+}
+
+impl<P> EwkbTransform for GeometryCollectionT<P>
+where
+    P: postgis::Point + EwkbRead + EwkbTransform + PointXyMut + Clone + PartialEq,
+{
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        for geom in self.geometries.iter_mut() {
+            geom.transform_with(xform, options)?;
+        }
+        Ok(())
+    }
+    fn set_srid(&mut self, srid: Option<i32>) {
+        self.srid = srid;
+        self.geometries
+            .iter_mut()
+            .for_each(|geom| geom.set_srid(srid));
+    }
+}
+
 #[test]
 #[rustfmt::skip]
 fn test_point_write() {
@@ -1824,6 +2499,32 @@ fn test_ewkb_adapters() {
     assert_eq!(point.as_ewkb().to_hex_ewkb(), "0101000020E6100000000000000000244000000000000034C0");
 }
 
+#[test]
+#[rustfmt::skip]
+fn test_write_big_endian() {
+    let to_hex = |geom: &dyn Fn(&mut Vec<u8>) -> Result<(), Error>| {
+        let mut buf = Vec::new();
+        geom(&mut buf).unwrap();
+        buf.iter().map(|b| format!("{:02X}", b)).collect::<String>()
+    };
+    // SELECT ST_AsEWKB('SRID=4326;POINT Z (10 -20 100)'::geometry, 'XDR')
+    let point = PointZ { x: 10.0, y: -20.0, z: 100.0, m: None, srid: Some(4326) };
+    assert_eq!(to_hex(&|w| point.as_ewkb().write_ewkb_with(w, Endianness::Big)), "00A0000001000010E64024000000000000C0340000000000004059000000000000");
+    // SELECT ST_AsEWKB('SRID=4326;MULTIPOINT ((10 -20), (0 -0.5))'::geometry, 'XDR')
+    let p = |x, y| Point { x: x, y: y, z: None, m: None, srid: None };
+    let points = MultiPointT::<Point> {srid: Some(4326), points: vec![p(10.0, -20.0), p(0., -0.5)]};
+    assert_eq!(to_hex(&|w| points.as_ewkb().write_ewkb_with(w, Endianness::Big)), "0020000004000010E60000000200000000014024000000000000C03400000000000000000000010000000000000000BFE0000000000000");
+
+    // SELECT 'GeometryCollection(POINT (10 10),POINT (30 30),LINESTRING (15 15, 20 20))'::geometry
+    let ewkb = "01070000000300000001010000000000000000002440000000000000244001010000000000000000003E400000000000003E400102000000020000000000000000002E400000000000002E4000000000000034400000000000003440";
+    let geom = GeometryT::<Point>::read_ewkb(&mut hex_to_vec(ewkb).as_slice()).unwrap();
+    let mut xdr = Vec::new();
+    geom.as_ewkb().write_ewkb_with(&mut xdr, Endianness::Big).unwrap();
+    assert_eq!(get_byte_order(&xdr), Endianness::Big);
+    let from_xdr = GeometryT::<Point>::read_ewkb(&mut xdr.as_slice()).unwrap();
+    assert_eq!(from_xdr.as_ewkb().to_hex_ewkb(), ewkb);
+}
+
 #[cfg(test)]
 #[rustfmt::skip]
 fn hex_to_vec(hexstr: &str) -> Vec<u8> {
@@ -1976,6 +2677,74 @@ fn test_read_error() {
     assert!(poly.is_err()); // UnexpectedEof "failed to fill whole buffer"
 }
 
+#[test]
+#[rustfmt::skip]
+fn test_read_write_dims() {
+    // Points with optional dimensions are written with the dimensions they were read with
+    // SELECT 'SRID=4326;LINESTRING (10 -20 100, 0 -0.5 101)'::geometry
+    let ewkb = "01020000A0E610000002000000000000000000244000000000000034C000000000000059400000000000000000000000000000E0BF0000000000405940";
+    let geom = GeometryT::<Point>::read_ewkb(&mut hex_to_vec(ewkb).as_slice()).unwrap();
+    assert_eq!(geom.as_ewkb().to_hex_ewkb(), ewkb);
+    // SELECT 'SRID=4326;MULTIPOINT ((10 -20 100), (0 -0.5 101))'::geometry
+    let ewkb = "01040000A0E6100000020000000101000080000000000000244000000000000034C0000000000000594001010000800000000000000000000000000000E0BF0000000000405940";
+    let geom = GeometryT::<Point>::read_ewkb(&mut hex_to_vec(ewkb).as_slice()).unwrap();
+    assert_eq!(geom.as_ewkb().to_hex_ewkb(), ewkb);
+
+    // Members must have the dimensions of the multi-geometry
+    let ewkb = hex_to_vec("01040000A0E610000001000000010100000000000000000024400000000000000000");
+    assert_eq!(GeometryT::<Point>::read_ewkb(&mut ewkb.as_slice()).unwrap_err().to_string(), "Read(\"geometry has members with different dimensions\")");
+    // Typed points require their dimensions
+    let ewkb = hex_to_vec("0101000000000000000000244000000000000034C0");
+    assert_eq!(PointZ::read_ewkb(&mut ewkb.as_slice()).unwrap_err().to_string(), "Read(\"geometry lacks the dimensions of PointZ\")");
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_read_limits() {
+    use crate::limits::ParseLimits;
+    let limits = |max_vertices, max_parts, max_bytes| ParseLimits { max_vertices, max_parts, max_bytes, ..Default::default() };
+    let read = |ewkb: &[u8], limits: &ParseLimits| GeometryT::<Point>::read_ewkb_limited(&mut &ewkb[..], limits).map_err(|err| err.to_string());
+    // SELECT 'GeometryCollection(POINT (10 10),POINT (30 30),LINESTRING (15 15, 20 20))'::geometry
+    let ewkb = hex_to_vec("01070000000300000001010000000000000000002440000000000000244001010000000000000000003E400000000000003E400102000000020000000000000000002E400000000000002E4000000000000034400000000000003440");
+    assert!(read(&ewkb, &limits(4, 3, ewkb.len())).is_ok());
+    assert!(read(&ewkb, &limits(3, 3, ewkb.len())).unwrap_err().contains("max_vertices limit of 3"));
+    assert!(read(&ewkb, &limits(4, 2, ewkb.len())).unwrap_err().contains("max_parts limit of 2"));
+    assert!(read(&ewkb, &limits(4, 3, ewkb.len() - 1)).unwrap_err().contains("max_bytes limit of"));
+
+    // Collections nested deeper than the default limit fail instead of overflowing the stack
+    let mut nested = hex_to_vec("010700000001000000").repeat(100_000);
+    nested.extend(hex_to_vec("010700000000000000"));
+    assert!(read(&nested, &ParseLimits::default()).unwrap_err().contains("max_depth limit of 64"));
+
+    // A huge count in a truncated input
+    let ewkb = hex_to_vec("0102000000FFFFFFFF");
+    assert!(read(&ewkb, &ParseLimits::default()).is_err());
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_read_many() {
+    use crate::limits::ParseLimits;
+    let limits = ParseLimits::default();
+    // SELECT 'POINT (10 -20)'::geometry || 'SRID=4326;LINESTRING (10 -20, 0 -0.5)'::geometry
+    let ewkb = hex_to_vec("0101000000000000000000244000000000000034C00102000020E610000002000000000000000000244000000000000034C00000000000000000000000000000E0BF");
+    let geoms = read_many::<Geometry, _>(ewkb.as_slice(), &limits).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(geoms.len(), 2);
+    assert!(matches!(geoms[0], GeometryT::Point(Point { x, y, .. }) if x == 10.0 && y == -20.0));
+    assert!(matches!(&geoms[1], GeometryT::LineString(line) if line.srid == Some(4326) && line.points.len() == 2));
+    assert!(read_many::<Geometry, _>([].as_slice(), &limits).next().is_none());
+
+    // Reading stops after an error
+    let mut results = read_many::<Geometry, _>(&ewkb[..30], &limits);
+    assert!(results.next().unwrap().is_ok());
+    assert!(results.next().unwrap().is_err());
+    assert!(results.next().is_none());
+
+    // Strict reads fail on trailing bytes
+    assert!(Geometry::read_ewkb_strict(&mut &ewkb[..21], &limits).is_ok());
+    assert_eq!(Geometry::read_ewkb_strict(&mut ewkb.as_slice(), &limits).unwrap_err().to_string(), "Read(\"45 trailing bytes after geometry\")");
+}
+
 #[test]
 #[rustfmt::skip]
 fn test_iterators() {
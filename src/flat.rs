@@ -0,0 +1,880 @@
+// Geometries with flat coordinate storage
+//
+// The geometries of `ewkb` and `twkb` store every vertex as its own struct with optional Z and M
+// values, and every ring and line in its own vector. The geometries here keep the coordinates of
+// a geometry in one vector of fixed-size coordinates, which is a contiguous buffer of `f64` with
+// the coordinate width as stride, and delimit rings and lines by offsets into it.
+//
+// Rings and lines are borrowed from the buffer as `Coords`, so that the geometries implement the
+// `types` traits. Multipolygons keep a flat polygon per part, as a part needs both coordinates
+// and ring offsets.
+#![allow(clippy::new_without_default)]
+
+use std::f64;
+use std::fmt;
+use std::io::prelude::*;
+use std::iter::FromIterator;
+use std::mem;
+use std::slice::{self, Iter, Windows};
+
+use crate::error::Error;
+use crate::ewkb::{self, Endianness, EwkbWrite, PointType};
+use crate::geojson::Dims;
+use crate::limits::{capacity, expect_end, ParseLimits};
+use crate::twkb;
+use crate::types as postgis;
+use crate::visit::Visitor;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Coordinate with a fixed layout, stored as an array of its values.
+///
+/// Implemented by [`Coord`], [`CoordZ`], [`CoordM`] and [`CoordZM`].
+pub trait CoordType:
+    private::Sealed + postgis::Point + Copy + PartialEq + fmt::Debug + 'static
+{
+    const DIMS: Dims;
+
+    /// Coordinate of the given values. Missing Z and M values are taken as NaN.
+    fn new(x: f64, y: f64, z: Option<f64>, m: Option<f64>) -> Self;
+
+    fn values(&self) -> &[f64];
+}
+
+macro_rules! coord_type {
+    ($(#[$attr:meta])* $coord:ident([f64; $width:expr]), $dims:ident, z: $z:expr, m: $m:expr) => {
+        $(#[$attr])*
+        #[derive(PartialEq, Clone, Copy, Debug)]
+        #[repr(transparent)]
+        pub struct $coord(pub [f64; $width]);
+
+        impl private::Sealed for $coord {}
+
+        impl CoordType for $coord {
+            const DIMS: Dims = Dims::$dims;
+
+            fn new(x: f64, y: f64, z: Option<f64>, m: Option<f64>) -> Self {
+                let mut values = [f64::NAN; $width];
+                values[0] = x;
+                values[1] = y;
+                let (z_index, m_index): (Option<usize>, Option<usize>) = ($z, $m);
+                if let Some(i) = z_index {
+                    values[i] = z.unwrap_or(f64::NAN);
+                }
+                if let Some(i) = m_index {
+                    values[i] = m.unwrap_or(f64::NAN);
+                }
+                $coord(values)
+            }
+
+            fn values(&self) -> &[f64] {
+                &self.0
+            }
+        }
+
+        impl postgis::Point for $coord {
+            fn x(&self) -> f64 {
+                self.0[0]
+            }
+            fn y(&self) -> f64 {
+                self.0[1]
+            }
+            fn opt_z(&self) -> Option<f64> {
+                let z_index: Option<usize> = $z;
+                z_index.map(|i| self.0[i])
+            }
+            fn opt_m(&self) -> Option<f64> {
+                let m_index: Option<usize> = $m;
+                m_index.map(|i| self.0[i])
+            }
+            fn crds(&self) -> Vec<f64> {
+                self.0.to_vec()
+            }
+        }
+    };
+}
+
+coord_type!(
+    /// X and Y
+    Coord([f64; 2]), Xy, z: None, m: None
+);
+coord_type!(
+    /// X, Y and Z
+    CoordZ([f64; 3]), Xyz, z: Some(2), m: None
+);
+coord_type!(
+    /// X, Y and M
+    CoordM([f64; 3]), Xym, z: None, m: Some(2)
+);
+coord_type!(
+    /// X, Y, Z and M
+    CoordZM([f64; 4]), Xyzm, z: Some(2), m: Some(3)
+);
+
+/// Values of `coords` as one slice.
+fn values<C: CoordType>(coords: &[C]) -> &[f64] {
+    let len = mem::size_of_val(coords) / mem::size_of::<f64>();
+    // Coordinate types are transparent wrappers of `f64` arrays, so the coordinates are `len`
+    // contiguous values.
+    unsafe { slice::from_raw_parts(coords.as_ptr() as *const f64, len) }
+}
+
+fn point_type<C: CoordType>() -> PointType {
+    match C::DIMS {
+        Dims::Xy => PointType::Point,
+        Dims::Xyz => PointType::PointZ,
+        Dims::Xym => PointType::PointM,
+        Dims::Xyzm => PointType::PointZM,
+    }
+}
+
+/// Coordinates of a ring or line, borrowed from the coordinates of a geometry.
+#[repr(transparent)]
+pub struct Coords<C: CoordType>([C]);
+
+impl<C: CoordType> Coords<C> {
+    fn new(coords: &[C]) -> &Coords<C> {
+        // `Coords` is a transparent wrapper of the slice.
+        unsafe { &*(coords as *const [C] as *const Coords<C>) }
+    }
+
+    pub fn as_slice(&self) -> &[C] {
+        &self.0
+    }
+
+    /// Interleaved values of the coordinates.
+    pub fn values(&self) -> &[f64] {
+        values(&self.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<C: CoordType> fmt::Debug for Coords<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.0.iter()).finish()
+    }
+}
+
+impl<'a, C: CoordType> postgis::LineString<'a> for Coords<C> {
+    type ItemType = C;
+    type Iter = Iter<'a, C>;
+    fn points(&'a self) -> Self::Iter {
+        self.0.iter()
+    }
+}
+
+/// Iterator over the rings of a polygon or the lines of a multilinestring.
+pub struct Parts<'a, C: 'a + CoordType> {
+    coords: &'a [C],
+    offsets: Windows<'a, usize>,
+}
+
+impl<'a, C: CoordType> Parts<'a, C> {
+    fn new(coords: &'a [C], offsets: &'a [usize]) -> Self {
+        Parts {
+            coords,
+            offsets: offsets.windows(2),
+        }
+    }
+}
+
+impl<'a, C: CoordType> Iterator for Parts<'a, C> {
+    type Item = &'a Coords<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let coords = self.coords;
+        self.offsets
+            .next()
+            .map(|part| Coords::new(&coords[part[0]..part[1]]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.offsets.size_hint()
+    }
+}
+
+impl<'a, C: CoordType> ExactSizeIterator for Parts<'a, C> {}
+
+// --- Geometries
+
+/// Line with its coordinates in one vector.
+#[derive(PartialEq, Clone, Debug)]
+pub struct LineStringT<C: CoordType> {
+    pub coords: Vec<C>,
+    pub srid: Option<i32>,
+}
+
+/// Points in one vector.
+#[derive(PartialEq, Clone, Debug)]
+pub struct MultiPointT<C: CoordType> {
+    pub coords: Vec<C>,
+    pub srid: Option<i32>,
+}
+
+/// Polygon with the coordinates of all rings in one vector.
+#[derive(PartialEq, Clone, Debug)]
+pub struct PolygonT<C: CoordType> {
+    coords: Vec<C>,
+    ring_offsets: Vec<usize>,
+    pub srid: Option<i32>,
+}
+
+/// Lines with the coordinates of all lines in one vector.
+#[derive(PartialEq, Clone, Debug)]
+pub struct MultiLineStringT<C: CoordType> {
+    coords: Vec<C>,
+    line_offsets: Vec<usize>,
+    pub srid: Option<i32>,
+}
+
+/// Polygons, each with its coordinates in one vector.
+#[derive(PartialEq, Clone, Debug)]
+pub struct MultiPolygonT<C: CoordType> {
+    pub polygons: Vec<PolygonT<C>>,
+    pub srid: Option<i32>,
+}
+
+macro_rules! point_container_type {
+    ($geotypetrait:ident for $geotype:ident) => {
+        impl<C: CoordType> $geotype<C> {
+            pub fn new() -> $geotype<C> {
+                $geotype {
+                    coords: Vec::new(),
+                    srid: None,
+                }
+            }
+
+            /// Interleaved values of the coordinates.
+            pub fn values(&self) -> &[f64] {
+                values(&self.coords)
+            }
+        }
+
+        impl<C: CoordType> FromIterator<C> for $geotype<C> {
+            fn from_iter<I: IntoIterator<Item = C>>(iterable: I) -> $geotype<C> {
+                $geotype {
+                    coords: iterable.into_iter().collect(),
+                    srid: None,
+                }
+            }
+        }
+
+        impl<'a, C: CoordType> postgis::$geotypetrait<'a> for $geotype<C> {
+            type ItemType = C;
+            type Iter = Iter<'a, C>;
+            fn points(&'a self) -> Self::Iter {
+                self.coords.iter()
+            }
+        }
+    };
+}
+
+point_container_type!(LineString for LineStringT);
+point_container_type!(MultiPoint for MultiPointT);
+
+macro_rules! part_container_type {
+    ($geotypetrait:ident for $geotype:ident, parts $partname:ident, offsets $offsets:ident, push $push:ident) => {
+        impl<C: CoordType> $geotype<C> {
+            pub fn new() -> $geotype<C> {
+                $geotype {
+                    coords: Vec::new(),
+                    $offsets: vec![0],
+                    srid: None,
+                }
+            }
+
+            /// Append a part with the given coordinates.
+            pub fn $push<I: IntoIterator<Item = C>>(&mut self, coords: I) {
+                self.coords.extend(coords);
+                self.$offsets.push(self.coords.len());
+            }
+
+            /// Coordinates of all parts.
+            pub fn coords(&self) -> &[C] {
+                &self.coords
+            }
+
+            /// Interleaved values of the coordinates of all parts.
+            pub fn values(&self) -> &[f64] {
+                values(&self.coords)
+            }
+
+            /// Start of each part in the coordinates, followed by the end of the last part.
+            pub fn $offsets(&self) -> &[usize] {
+                &self.$offsets
+            }
+        }
+
+        impl<'a, C: CoordType> postgis::$geotypetrait<'a> for $geotype<C> {
+            type ItemType = Coords<C>;
+            type Iter = Parts<'a, C>;
+            fn $partname(&'a self) -> Self::Iter {
+                Parts::new(&self.coords, &self.$offsets)
+            }
+        }
+    };
+}
+
+part_container_type!(Polygon for PolygonT, parts rings, offsets ring_offsets, push push_ring);
+part_container_type!(MultiLineString for MultiLineStringT, parts lines, offsets line_offsets, push push_line);
+
+impl<C: CoordType> MultiPolygonT<C> {
+    pub fn new() -> MultiPolygonT<C> {
+        MultiPolygonT {
+            polygons: Vec::new(),
+            srid: None,
+        }
+    }
+}
+
+impl<C: CoordType> FromIterator<PolygonT<C>> for MultiPolygonT<C> {
+    fn from_iter<I: IntoIterator<Item = PolygonT<C>>>(iterable: I) -> MultiPolygonT<C> {
+        MultiPolygonT {
+            polygons: iterable.into_iter().collect(),
+            srid: None,
+        }
+    }
+}
+
+impl<'a, C: CoordType> postgis::MultiPolygon<'a> for MultiPolygonT<C> {
+    type ItemType = PolygonT<C>;
+    type Iter = Iter<'a, PolygonT<C>>;
+    fn polygons(&'a self) -> Self::Iter {
+        self.polygons.iter()
+    }
+}
+
+pub type LineString = LineStringT<Coord>;
+pub type LineStringZ = LineStringT<CoordZ>;
+pub type LineStringM = LineStringT<CoordM>;
+pub type LineStringZM = LineStringT<CoordZM>;
+
+pub type Polygon = PolygonT<Coord>;
+pub type PolygonZ = PolygonT<CoordZ>;
+pub type PolygonM = PolygonT<CoordM>;
+pub type PolygonZM = PolygonT<CoordZM>;
+
+pub type MultiPoint = MultiPointT<Coord>;
+pub type MultiPointZ = MultiPointT<CoordZ>;
+pub type MultiPointM = MultiPointT<CoordM>;
+pub type MultiPointZM = MultiPointT<CoordZM>;
+
+pub type MultiLineString = MultiLineStringT<Coord>;
+pub type MultiLineStringZ = MultiLineStringT<CoordZ>;
+pub type MultiLineStringM = MultiLineStringT<CoordM>;
+pub type MultiLineStringZM = MultiLineStringT<CoordZM>;
+
+pub type MultiPolygon = MultiPolygonT<Coord>;
+pub type MultiPolygonZ = MultiPolygonT<CoordZ>;
+pub type MultiPolygonM = MultiPolygonT<CoordM>;
+pub type MultiPolygonZM = MultiPolygonT<CoordZM>;
+
+// --- Reading
+
+/// Visitor collecting a geometry into flat coordinates and offsets.
+struct Builder<C: CoordType> {
+    srid: Option<i32>,
+    kind: Option<&'static str>,
+    depth: usize,
+    coords: Vec<C>,
+    offsets: Vec<usize>,
+    polygons: Vec<PolygonT<C>>,
+}
+
+impl<C: CoordType> Builder<C> {
+    /// Visit a geometry with `visit`, failing unless it is a `kind`.
+    fn read<F>(kind: &'static str, visit: F) -> Result<Builder<C>, Error>
+    where
+        F: FnOnce(&mut Builder<C>) -> Result<(), Error>,
+    {
+        let mut builder = Builder {
+            srid: None,
+            kind: None,
+            depth: 0,
+            coords: Vec::new(),
+            offsets: vec![0],
+            polygons: Vec::new(),
+        };
+        visit(&mut builder)?;
+        match builder.kind {
+            Some(found) if found == kind => Ok(builder),
+            found => Err(Error::Read(format!(
+                "expected {}, found {}",
+                kind,
+                found.unwrap_or("nothing")
+            ))),
+        }
+    }
+
+    fn begin(&mut self, kind: &'static str, dims: Dims) -> Result<(), Error> {
+        if dims != C::DIMS {
+            return Err(Error::Read(format!(
+                "geometry has dimensions {}, expected {}",
+                dims,
+                C::DIMS
+            )));
+        }
+        if self.depth == 0 {
+            self.kind = Some(kind);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+impl<C: CoordType> Visitor for Builder<C> {
+    fn srid(&mut self, srid: Option<i32>) -> Result<(), Error> {
+        self.srid = srid;
+        Ok(())
+    }
+    fn coord(&mut self, x: f64, y: f64, z: Option<f64>, m: Option<f64>) -> Result<(), Error> {
+        self.coords.push(C::new(x, y, z, m));
+        Ok(())
+    }
+    fn begin_point(&mut self, dims: Dims) -> Result<(), Error> {
+        self.begin("Point", dims)
+    }
+    fn end_point(&mut self) -> Result<(), Error> {
+        self.depth -= 1;
+        Ok(())
+    }
+    fn begin_linestring(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.coords.reserve(capacity(size));
+        self.begin("LineString", dims)
+    }
+    fn end_linestring(&mut self) -> Result<(), Error> {
+        self.depth -= 1;
+        if self.depth > 0 {
+            self.offsets.push(self.coords.len());
+        }
+        Ok(())
+    }
+    fn begin_polygon(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.offsets.reserve(capacity(size));
+        self.begin("Polygon", dims)
+    }
+    fn begin_ring(&mut self, size: usize) -> Result<(), Error> {
+        self.coords.reserve(capacity(size));
+        Ok(())
+    }
+    fn end_ring(&mut self) -> Result<(), Error> {
+        self.offsets.push(self.coords.len());
+        Ok(())
+    }
+    fn end_polygon(&mut self) -> Result<(), Error> {
+        self.depth -= 1;
+        if self.depth > 0 {
+            self.polygons.push(PolygonT {
+                coords: mem::take(&mut self.coords),
+                ring_offsets: mem::replace(&mut self.offsets, vec![0]),
+                srid: None,
+            });
+        }
+        Ok(())
+    }
+    fn begin_multipoint(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.coords.reserve(capacity(size));
+        self.begin("MultiPoint", dims)
+    }
+    fn end_multipoint(&mut self) -> Result<(), Error> {
+        self.depth -= 1;
+        Ok(())
+    }
+    fn begin_multilinestring(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.offsets.reserve(capacity(size));
+        self.begin("MultiLineString", dims)
+    }
+    fn end_multilinestring(&mut self) -> Result<(), Error> {
+        self.depth -= 1;
+        Ok(())
+    }
+    fn begin_multipolygon(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.polygons.reserve(capacity(size));
+        self.begin("MultiPolygon", dims)
+    }
+    fn end_multipolygon(&mut self) -> Result<(), Error> {
+        self.depth -= 1;
+        Ok(())
+    }
+    fn begin_collection(&mut self, _dims: Dims, _size: usize) -> Result<(), Error> {
+        Err(Error::Other(
+            "not implemented for GeometryCollection".to_owned(),
+        ))
+    }
+}
+
+macro_rules! impl_read {
+    ($geotype:ident, $kind:expr, |$builder:ident| $build:expr) => {
+        impl<C: CoordType> $geotype<C> {
+            pub fn read_ewkb<R: Read>(raw: &mut R) -> Result<Self, Error> {
+                Self::read_ewkb_limited(raw, &ParseLimits::default())
+            }
+
+            /// Read EWKB, failing when the input exceeds `limits` or its dimensions differ from
+            /// those of `C`.
+            pub fn read_ewkb_limited<R: Read>(
+                raw: &mut R,
+                limits: &ParseLimits,
+            ) -> Result<Self, Error> {
+                let $builder = Builder::read($kind, |b| ewkb::visit_ewkb(raw, b, limits))?;
+                Ok($build)
+            }
+
+            /// Read EWKB like [`Self::read_ewkb_limited`], failing if `raw` has bytes left
+            /// after the geometry.
+            pub fn read_ewkb_strict<R: Read>(
+                raw: &mut R,
+                limits: &ParseLimits,
+            ) -> Result<Self, Error> {
+                let geom = Self::read_ewkb_limited(raw, limits)?;
+                expect_end(raw)?;
+                Ok(geom)
+            }
+
+            pub fn read_twkb<R: Read>(raw: &mut R) -> Result<Self, Error> {
+                Self::read_twkb_limited(raw, &ParseLimits::default())
+            }
+
+            /// Read TWKB, failing when the input exceeds `limits` or its dimensions differ from
+            /// those of `C`.
+            pub fn read_twkb_limited<R: Read>(
+                raw: &mut R,
+                limits: &ParseLimits,
+            ) -> Result<Self, Error> {
+                let $builder = Builder::read($kind, |b| twkb::visit_twkb(raw, b, limits))?;
+                Ok($build)
+            }
+
+            /// Read TWKB like [`Self::read_twkb_limited`], failing if `raw` has bytes left
+            /// after the geometry.
+            pub fn read_twkb_strict<R: Read>(
+                raw: &mut R,
+                limits: &ParseLimits,
+            ) -> Result<Self, Error> {
+                let geom = Self::read_twkb_limited(raw, limits)?;
+                expect_end(raw)?;
+                Ok(geom)
+            }
+        }
+    };
+}
+
+impl_read!(LineStringT, "LineString", |b| LineStringT {
+    coords: b.coords,
+    srid: b.srid,
+});
+impl_read!(MultiPointT, "MultiPoint", |b| MultiPointT {
+    coords: b.coords,
+    srid: b.srid,
+});
+impl_read!(PolygonT, "Polygon", |b| PolygonT {
+    coords: b.coords,
+    ring_offsets: b.offsets,
+    srid: b.srid,
+});
+impl_read!(MultiLineStringT, "MultiLineString", |b| MultiLineStringT {
+    coords: b.coords,
+    line_offsets: b.offsets,
+    srid: b.srid,
+});
+impl_read!(MultiPolygonT, "MultiPolygon", |b| MultiPolygonT {
+    polygons: b.polygons,
+    srid: b.srid,
+});
+
+// --- Writing
+
+fn write_header<W: Write + ?Sized>(
+    w: &mut W,
+    type_id: u32,
+    byte_order: Endianness,
+) -> Result<(), Error> {
+    w.write_all(&[match byte_order {
+        Endianness::Little => 0x01,
+        Endianness::Big => 0x00,
+    }])?;
+    ewkb::write_u32(w, type_id, byte_order)
+}
+
+fn write_values<W: Write + ?Sized>(
+    w: &mut W,
+    values: &[f64],
+    byte_order: Endianness,
+) -> Result<(), Error> {
+    for &v in values {
+        ewkb::write_f64(w, v, byte_order)?;
+    }
+    Ok(())
+}
+
+fn write_coords<W: Write + ?Sized, C: CoordType>(
+    w: &mut W,
+    coords: &[C],
+    byte_order: Endianness,
+) -> Result<(), Error> {
+    ewkb::write_u32(w, coords.len() as u32, byte_order)?;
+    write_values(w, values(coords), byte_order)
+}
+
+fn write_parts<W: Write + ?Sized, C: CoordType>(
+    w: &mut W,
+    parts: Parts<C>,
+    header: Option<u32>,
+    byte_order: Endianness,
+) -> Result<(), Error> {
+    ewkb::write_u32(w, parts.len() as u32, byte_order)?;
+    for part in parts {
+        if let Some(type_id) = header {
+            write_header(w, type_id, byte_order)?;
+        }
+        write_coords(w, part.as_slice(), byte_order)?;
+    }
+    Ok(())
+}
+
+impl<C: CoordType> EwkbWrite for LineStringT<C> {
+    fn opt_srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    fn type_id(&self) -> u32 {
+        0x02 | Self::wkb_type_id(&point_type::<C>(), self.srid)
+    }
+
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        write_coords(w, &self.coords, byte_order)
+    }
+}
+
+impl<C: CoordType> EwkbWrite for PolygonT<C> {
+    fn opt_srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    fn type_id(&self) -> u32 {
+        0x03 | Self::wkb_type_id(&point_type::<C>(), self.srid)
+    }
+
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        let rings = Parts::new(&self.coords, &self.ring_offsets);
+        write_parts(w, rings, None, byte_order)
+    }
+}
+
+impl<C: CoordType> EwkbWrite for MultiPointT<C> {
+    fn opt_srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    fn type_id(&self) -> u32 {
+        0x04 | Self::wkb_type_id(&point_type::<C>(), self.srid)
+    }
+
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        let type_id = 0x01 | Self::wkb_type_id(&point_type::<C>(), None);
+        ewkb::write_u32(w, self.coords.len() as u32, byte_order)?;
+        for coord in &self.coords {
+            write_header(w, type_id, byte_order)?;
+            write_values(w, coord.values(), byte_order)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: CoordType> EwkbWrite for MultiLineStringT<C> {
+    fn opt_srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    fn type_id(&self) -> u32 {
+        0x05 | Self::wkb_type_id(&point_type::<C>(), self.srid)
+    }
+
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        let type_id = 0x02 | Self::wkb_type_id(&point_type::<C>(), None);
+        let lines = Parts::new(&self.coords, &self.line_offsets);
+        write_parts(w, lines, Some(type_id), byte_order)
+    }
+}
+
+impl<C: CoordType> EwkbWrite for MultiPolygonT<C> {
+    fn opt_srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    fn type_id(&self) -> u32 {
+        0x06 | Self::wkb_type_id(&point_type::<C>(), self.srid)
+    }
+
+    fn write_ewkb_body<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        byte_order: Endianness,
+    ) -> Result<(), Error> {
+        let type_id = 0x03 | Self::wkb_type_id(&point_type::<C>(), None);
+        ewkb::write_u32(w, self.polygons.len() as u32, byte_order)?;
+        for polygon in &self.polygons {
+            write_header(w, type_id, byte_order)?;
+            polygon.write_ewkb_body(w, byte_order)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::decode_hex;
+    use crate::ewkb::EwkbRead;
+    use crate::geojson::{GeoJSONMultiLineString, GeoJSONMultiPolygon, GeoJSONPolygon};
+    use crate::twkb::TwkbGeom;
+    use crate::types::{LineString as _, Polygon as _};
+
+    fn to_ewkb<G: EwkbWrite>(geom: &G) -> Vec<u8> {
+        let mut data = Vec::new();
+        geom.write_ewkb(&mut data).unwrap();
+        data
+    }
+
+    // SRID=4326;MULTIPOLYGON(((0 0,2 0,2 2,0 2,0 0)),((10 10,-2 10,-2 -2,10 -2,10 10)))
+    const MULTIPOLYGON: &str = "0106000020E610000002000000010300000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000010300000001000000050000000000000000002440000000000000244000000000000000C0000000000000244000000000000000C000000000000000C0000000000000244000000000000000C000000000000024400000000000002440";
+
+    #[test]
+    fn test_ewkb_roundtrip() {
+        // SRID=4326;LINESTRING Z(10 -20 100,0 -0.5 101)
+        let ewkb = decode_hex(
+            b"01020000A0E610000002000000000000000000244000000000000034C000000000000059400000000000000000000000000000E0BF0000000000405940",
+        )
+        .unwrap();
+        let line = LineStringZ::read_ewkb(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(line.srid, Some(4326));
+        assert_eq!(line.values(), &[10.0, -20.0, 100.0, 0.0, -0.5, 101.0]);
+        assert_eq!(to_ewkb(&line), ewkb);
+
+        // SRID=4326;MULTIPOINT Z(10 -20 100,0 -0.5 101)
+        let ewkb = decode_hex(
+            b"01040000A0E6100000020000000101000080000000000000244000000000000034C0000000000000594001010000800000000000000000000000000000E0BF0000000000405940",
+        )
+        .unwrap();
+        let points = MultiPointZ::read_ewkb(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(points.values(), line.values());
+        assert_eq!(to_ewkb(&points), ewkb);
+
+        // SRID=4326;MULTILINESTRING((10 -20,0 -0.5),(0 0,2 0))
+        let ewkb = decode_hex(
+            b"0105000020E610000002000000010200000002000000000000000000244000000000000034C00000000000000000000000000000E0BF0102000000020000000000000000000000000000000000000000000000000000400000000000000000",
+        )
+        .unwrap();
+        let lines = MultiLineString::read_ewkb(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(lines.line_offsets(), &[0, 2, 4]);
+        assert_eq!(to_ewkb(&lines), ewkb);
+        let expected = ewkb::MultiLineString::read_ewkb(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(lines.to_geojson(), expected.to_geojson());
+
+        let ewkb = decode_hex(MULTIPOLYGON.as_bytes()).unwrap();
+        let polygons = MultiPolygon::read_ewkb(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(polygons.polygons.len(), 2);
+        assert_eq!(to_ewkb(&polygons), ewkb);
+        let expected = ewkb::MultiPolygon::read_ewkb(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(polygons.to_geojson(), expected.to_geojson());
+    }
+
+    #[test]
+    fn test_read_twkb() {
+        // POLYGON((0 0,2 0,2 2,0 2,0 0),(10 10,-2 10,-2 -2,10 -2,10 10))
+        let twkb = decode_hex(b"03000205000004000004030000030514141700001718000018").unwrap();
+        let polygon = Polygon::read_twkb(&mut twkb.as_slice()).unwrap();
+        assert_eq!(polygon.ring_offsets(), &[0, 5, 10]);
+        assert_eq!(polygon.coords().len(), 10);
+        assert_eq!(&polygon.values()[..6], &[0.0, 0.0, 2.0, 0.0, 2.0, 2.0]);
+        let expected = twkb::Polygon::read_twkb(&mut twkb.as_slice()).unwrap();
+        assert_eq!(polygon.to_geojson(), expected.to_geojson());
+
+        let rings: Vec<&[Coord]> = polygon.rings().map(Coords::as_slice).collect();
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[1][1], Coord([-2.0, 10.0]));
+        let xs: Vec<f64> = polygon
+            .rings()
+            .nth(1)
+            .unwrap()
+            .points()
+            .map(|p| p.0[0])
+            .collect();
+        assert_eq!(xs, vec![10.0, -2.0, -2.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn test_build() {
+        let mut polygon = Polygon::new();
+        polygon.srid = Some(4326);
+        polygon.push_ring(
+            [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0], [0.0, 0.0]]
+                .iter()
+                .map(|&crd| Coord(crd)),
+        );
+        let polygons: MultiPolygon = vec![polygon.clone(), polygon].into_iter().collect();
+        assert_eq!(polygons.polygons[1].ring_offsets(), &[0, 5]);
+
+        let mut line = LineStringZM::new();
+        line.coords.push(CoordZM::new(1.0, 2.0, None, Some(4.0)));
+        assert!(line.coords[0].0[2].is_nan());
+        assert_eq!(line.coords[0].0[3], 4.0);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        // SRID=4326;LINESTRING Z(10 -20 100,0 -0.5 101)
+        let ewkb = decode_hex(
+            b"01020000A0E610000002000000000000000000244000000000000034C000000000000059400000000000000000000000000000E0BF0000000000405940",
+        )
+        .unwrap();
+        let err = LineString::read_ewkb(&mut ewkb.as_slice()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Read(\"geometry has dimensions XYZ, expected XY\")"
+        );
+        let err = MultiPointZ::read_ewkb(&mut ewkb.as_slice()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Read(\"expected MultiPoint, found LineString\")"
+        );
+
+        let ewkb = decode_hex(MULTIPOLYGON.as_bytes()).unwrap();
+        assert!(MultiPolygon::read_ewkb_strict(&mut ewkb.as_slice(), &Default::default()).is_ok());
+        let mut trailing = ewkb.clone();
+        trailing.push(0);
+        assert!(
+            MultiPolygon::read_ewkb_strict(&mut trailing.as_slice(), &Default::default()).is_err()
+        );
+
+        // GEOMETRYCOLLECTION(POINT(10 10))
+        let ewkb =
+            decode_hex(b"0107000000010000000101000000000000000000244000000000000024C0").unwrap();
+        assert!(MultiPoint::read_ewkb(&mut ewkb.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,421 @@
+// geo-types conversions
+//
+// geo-types geometries are two-dimensional, so Z and M values are dropped when converting into
+// them. Geometries converted from geo-types have no SRID. `Line`, `Rect` and `Triangle` become
+// line strings and polygons.
+extern crate geo_types;
+
+use self::geo_types::{Coord, Geometry};
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::ewkb::{self, EwkbRead, GeometryCollectionT, GeometryT};
+use crate::geojson::{self, GeoJSONGeometry};
+use crate::twkb;
+use crate::types as postgis;
+
+fn coord<P: postgis::Point>(point: &P) -> Coord<f64> {
+    Coord {
+        x: point.x(),
+        y: point.y(),
+    }
+}
+
+fn line_string<'a, P, I>(points: I) -> geo_types::LineString<f64>
+where
+    P: 'a + postgis::Point,
+    I: IntoIterator<Item = &'a P>,
+{
+    geo_types::LineString(points.into_iter().map(coord).collect())
+}
+
+fn polygon<I>(mut rings: I) -> geo_types::Polygon<f64>
+where
+    I: Iterator<Item = geo_types::LineString<f64>>,
+{
+    let exterior = rings
+        .next()
+        .unwrap_or_else(|| geo_types::LineString(vec![]));
+    geo_types::Polygon::new(exterior, rings.collect())
+}
+
+// --- EWKB
+
+impl<P: postgis::Point + EwkbRead> From<&GeometryT<P>> for Geometry<f64> {
+    fn from(geom: &GeometryT<P>) -> Self {
+        match geom {
+            GeometryT::Point(p) => Geometry::Point(coord(p).into()),
+            GeometryT::LineString(l) => Geometry::LineString(line_string(&l.points)),
+            GeometryT::Polygon(y) => Geometry::Polygon(polygon(
+                y.rings.iter().map(|ring| line_string(&ring.points)),
+            )),
+            GeometryT::MultiPoint(mp) => Geometry::MultiPoint(geo_types::MultiPoint(
+                mp.points.iter().map(|p| coord(p).into()).collect(),
+            )),
+            GeometryT::MultiLineString(ml) => {
+                Geometry::MultiLineString(geo_types::MultiLineString(
+                    ml.lines.iter().map(|l| line_string(&l.points)).collect(),
+                ))
+            }
+            GeometryT::MultiPolygon(my) => Geometry::MultiPolygon(geo_types::MultiPolygon(
+                my.polygons
+                    .iter()
+                    .map(|y| polygon(y.rings.iter().map(|ring| line_string(&ring.points))))
+                    .collect(),
+            )),
+            GeometryT::GeometryCollection(gc) => {
+                Geometry::GeometryCollection(gc.geometries.iter().map(Geometry::from).collect())
+            }
+        }
+    }
+}
+
+fn ewkb_point(coord: &Coord<f64>) -> ewkb::Point {
+    ewkb::Point::new(coord.x, coord.y, None, None, None)
+}
+
+fn ewkb_line_string(line: &geo_types::LineString<f64>) -> ewkb::LineString {
+    line.0.iter().map(ewkb_point).collect()
+}
+
+/// Rings of `polygon`, none if it is empty. An empty exterior is kept when there are interiors,
+/// so that the first interior does not take its place.
+fn polygon_rings(
+    polygon: &geo_types::Polygon<f64>,
+) -> impl Iterator<Item = &geo_types::LineString<f64>> {
+    let exterior = match polygon.exterior().0.is_empty() && polygon.interiors().is_empty() {
+        true => None,
+        false => Some(polygon.exterior()),
+    };
+    exterior.into_iter().chain(polygon.interiors())
+}
+
+fn ewkb_polygon(polygon: &geo_types::Polygon<f64>) -> ewkb::Polygon {
+    polygon_rings(polygon).map(ewkb_line_string).collect()
+}
+
+impl From<&Geometry<f64>> for ewkb::Geometry {
+    fn from(geom: &Geometry<f64>) -> Self {
+        match geom {
+            Geometry::Point(p) => GeometryT::Point(ewkb_point(&p.0)),
+            Geometry::Line(l) => {
+                GeometryT::LineString([l.start, l.end].iter().map(ewkb_point).collect())
+            }
+            Geometry::LineString(l) => GeometryT::LineString(ewkb_line_string(l)),
+            Geometry::Polygon(y) => GeometryT::Polygon(ewkb_polygon(y)),
+            Geometry::MultiPoint(mp) => {
+                GeometryT::MultiPoint(mp.0.iter().map(|p| ewkb_point(&p.0)).collect())
+            }
+            Geometry::MultiLineString(ml) => {
+                GeometryT::MultiLineString(ml.0.iter().map(ewkb_line_string).collect())
+            }
+            Geometry::MultiPolygon(my) => {
+                GeometryT::MultiPolygon(my.0.iter().map(ewkb_polygon).collect())
+            }
+            Geometry::GeometryCollection(gc) => {
+                let mut collection = GeometryCollectionT::new();
+                collection.geometries = gc.0.iter().map(ewkb::Geometry::from).collect();
+                GeometryT::GeometryCollection(collection)
+            }
+            Geometry::Rect(r) => GeometryT::Polygon(ewkb_polygon(&r.to_polygon())),
+            Geometry::Triangle(t) => GeometryT::Polygon(ewkb_polygon(&t.to_polygon())),
+        }
+    }
+}
+
+// --- TWKB
+
+impl From<&twkb::Point> for geo_types::Point<f64> {
+    fn from(point: &twkb::Point) -> Self {
+        coord(point).into()
+    }
+}
+
+impl From<&twkb::LineString> for geo_types::LineString<f64> {
+    fn from(line: &twkb::LineString) -> Self {
+        line_string(&line.points)
+    }
+}
+
+impl From<&twkb::Polygon> for geo_types::Polygon<f64> {
+    fn from(polygon: &twkb::Polygon) -> Self {
+        self::polygon(polygon.rings.iter().map(geo_types::LineString::from))
+    }
+}
+
+impl From<&twkb::MultiPoint> for geo_types::MultiPoint<f64> {
+    fn from(points: &twkb::MultiPoint) -> Self {
+        geo_types::MultiPoint(points.points.iter().map(geo_types::Point::from).collect())
+    }
+}
+
+impl From<&twkb::MultiLineString> for geo_types::MultiLineString<f64> {
+    fn from(lines: &twkb::MultiLineString) -> Self {
+        geo_types::MultiLineString(
+            lines
+                .lines
+                .iter()
+                .map(geo_types::LineString::from)
+                .collect(),
+        )
+    }
+}
+
+impl From<&twkb::MultiPolygon> for geo_types::MultiPolygon<f64> {
+    fn from(polygons: &twkb::MultiPolygon) -> Self {
+        geo_types::MultiPolygon(
+            polygons
+                .polygons
+                .iter()
+                .map(geo_types::Polygon::from)
+                .collect(),
+        )
+    }
+}
+
+impl From<&twkb::Geometry> for Geometry<f64> {
+    fn from(geom: &twkb::Geometry) -> Self {
+        match geom {
+            twkb::Geometry::Point(g) => Geometry::Point(g.into()),
+            twkb::Geometry::LineString(g) => Geometry::LineString(g.into()),
+            twkb::Geometry::Polygon(g) => Geometry::Polygon(g.into()),
+            twkb::Geometry::MultiPoint(g) => Geometry::MultiPoint(g.into()),
+            twkb::Geometry::MultiLineString(g) => Geometry::MultiLineString(g.into()),
+            twkb::Geometry::MultiPolygon(g) => Geometry::MultiPolygon(g.into()),
+        }
+    }
+}
+
+// --- GeoJSON
+
+fn geojson_coord(crds: &[f64]) -> Result<Coord<f64>, Error> {
+    match *crds {
+        [x, y, ..] => Ok(Coord { x, y }),
+        _ => Err(Error::Read(format!(
+            "invalid coordinate {:?} - expected 2 values",
+            crds
+        ))),
+    }
+}
+
+fn geojson_line_string(crds: &[Vec<f64>]) -> Result<geo_types::LineString<f64>, Error> {
+    crds.iter()
+        .map(|crds| geojson_coord(crds))
+        .collect::<Result<_, _>>()
+        .map(geo_types::LineString)
+}
+
+fn geojson_polygon(crds: &[Vec<Vec<f64>>]) -> Result<geo_types::Polygon<f64>, Error> {
+    let rings = crds
+        .iter()
+        .map(|ring| geojson_line_string(ring))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(polygon(rings.into_iter()))
+}
+
+impl TryFrom<&GeoJSONGeometry> for Geometry<f64> {
+    type Error = Error;
+
+    fn try_from(geom: &GeoJSONGeometry) -> Result<Self, Error> {
+        Ok(match geom {
+            GeoJSONGeometry::Point(g) => Geometry::Point(geojson_coord(&g.coordinates)?.into()),
+            GeoJSONGeometry::LineString(g) => {
+                Geometry::LineString(geojson_line_string(&g.coordinates)?)
+            }
+            GeoJSONGeometry::Polygon(g) => Geometry::Polygon(geojson_polygon(&g.coordinates)?),
+            GeoJSONGeometry::MultiPoint(g) => Geometry::MultiPoint(
+                g.coordinates
+                    .iter()
+                    .map(|crds| geojson_coord(crds).map(geo_types::Point::from))
+                    .collect::<Result<_, _>>()?,
+            ),
+            GeoJSONGeometry::MultiLineString(g) => Geometry::MultiLineString(
+                g.coordinates
+                    .iter()
+                    .map(|crds| geojson_line_string(crds))
+                    .collect::<Result<_, _>>()?,
+            ),
+            GeoJSONGeometry::MultiPolygon(g) => Geometry::MultiPolygon(
+                g.coordinates
+                    .iter()
+                    .map(|crds| geojson_polygon(crds))
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+fn crds(coord: &Coord<f64>) -> Vec<f64> {
+    vec![coord.x, coord.y]
+}
+
+fn line_crds(line: &geo_types::LineString<f64>) -> Vec<Vec<f64>> {
+    line.0.iter().map(crds).collect()
+}
+
+fn polygon_crds(polygon: &geo_types::Polygon<f64>) -> Vec<Vec<Vec<f64>>> {
+    polygon_rings(polygon).map(line_crds).collect()
+}
+
+impl TryFrom<&Geometry<f64>> for GeoJSONGeometry {
+    type Error = Error;
+
+    fn try_from(geom: &Geometry<f64>) -> Result<Self, Error> {
+        let line = |coordinates| {
+            GeoJSONGeometry::LineString(geojson::LineString {
+                type_name: "LineString".to_owned(),
+                crs: None,
+                coordinates,
+                dims: Some(geojson::Dims::Xy),
+            })
+        };
+        let polygon = |coordinates| {
+            GeoJSONGeometry::Polygon(geojson::Polygon {
+                type_name: "Polygon".to_owned(),
+                crs: None,
+                coordinates,
+                dims: Some(geojson::Dims::Xy),
+            })
+        };
+        Ok(match geom {
+            Geometry::Point(p) => GeoJSONGeometry::Point(geojson::Point {
+                type_name: "Point".to_owned(),
+                crs: None,
+                coordinates: crds(&p.0),
+                dims: Some(geojson::Dims::Xy),
+            }),
+            Geometry::Line(l) => line(vec![crds(&l.start), crds(&l.end)]),
+            Geometry::LineString(l) => line(line_crds(l)),
+            Geometry::Polygon(y) => polygon(polygon_crds(y)),
+            Geometry::MultiPoint(mp) => GeoJSONGeometry::MultiPoint(geojson::MultiPoint {
+                type_name: "MultiPoint".to_owned(),
+                crs: None,
+                coordinates: mp.0.iter().map(|p| crds(&p.0)).collect(),
+                dims: Some(geojson::Dims::Xy),
+            }),
+            Geometry::MultiLineString(ml) => {
+                GeoJSONGeometry::MultiLineString(geojson::MultiLineString {
+                    type_name: "MultiLineString".to_owned(),
+                    crs: None,
+                    coordinates: ml.0.iter().map(line_crds).collect(),
+                    dims: Some(geojson::Dims::Xy),
+                })
+            }
+            Geometry::MultiPolygon(my) => GeoJSONGeometry::MultiPolygon(geojson::MultiPolygon {
+                type_name: "MultiPolygon".to_owned(),
+                crs: None,
+                coordinates: my.0.iter().map(polygon_crds).collect(),
+                dims: Some(geojson::Dims::Xy),
+            }),
+            Geometry::Rect(r) => polygon(polygon_crds(&r.to_polygon())),
+            Geometry::Triangle(t) => polygon(polygon_crds(&t.to_polygon())),
+            Geometry::GeometryCollection(_) => {
+                return Err(Error::Other(
+                    "not implemented for GeometryCollection".to_owned(),
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::geo_types::{coord, line_string, polygon, Geometry, LineString, Polygon, Rect};
+    use crate::encoding::decode_hex;
+    use crate::ewkb::{self, AsEwkbGeometry, EwkbRead, EwkbWrite};
+    use crate::geojson::{GeoJSONEncode, GeoJSONGeometry};
+    use crate::twkb::{self, TwkbGeom};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_ewkb_roundtrip() {
+        // SELECT 'SRID=4326;POLYGON((0 0, 2 0, 2 2, 0 2, 0 0))'::geometry
+        let ewkb = decode_hex(
+            b"0103000020E610000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000",
+        )
+        .unwrap();
+        let geom = ewkb::Geometry::read_ewkb(&mut ewkb.as_slice()).unwrap();
+        let polygon = Geometry::from(&geom);
+        assert_eq!(
+            polygon,
+            Geometry::Polygon(
+                polygon![(x: 0., y: 0.), (x: 2., y: 0.), (x: 2., y: 2.), (x: 0., y: 2.)]
+            )
+        );
+        let mut converted = ewkb::Geometry::from(&polygon);
+        if let ewkb::GeometryT::Polygon(ref mut polygon) = converted {
+            polygon.srid = Some(4326);
+        }
+        let mut written = Vec::new();
+        converted.as_ewkb().write_ewkb(&mut written).unwrap();
+        assert_eq!(written, ewkb);
+
+        let rect = Geometry::Rect(Rect::new(coord! {x: 0., y: 0.}, coord! {x: 2., y: 2.}));
+        match ewkb::Geometry::from(&rect) {
+            ewkb::GeometryT::Polygon(polygon) => assert_eq!(polygon.rings[0].points.len(), 5),
+            geom => panic!("unexpected geometry {:?}", geom),
+        }
+    }
+
+    #[test]
+    fn test_twkb_to_geo() {
+        // SELECT encode(ST_AsTWKB('POLYGON ((0 0, 2 0, 2 2, 0 2, 0 0),
+        //     (10 10, -2 10, -2 -2, 10 -2, 10 10))'::geometry), 'hex')
+        let twkb = decode_hex(b"03000205000004000004030000030514141700001718000018").unwrap();
+        let geom = twkb::Geometry::read_twkb(&mut twkb.as_slice()).unwrap();
+        match Geometry::from(&geom) {
+            Geometry::Polygon(polygon) => {
+                assert_eq!(polygon.exterior().0.len(), 5);
+                assert_eq!(polygon.interiors().len(), 1);
+                assert_eq!(polygon.interiors()[0].0[1], coord! {x: -2., y: 10.});
+            }
+            geom => panic!("unexpected geometry {:?}", geom),
+        }
+        let point =
+            twkb::Point::read_twkb(&mut decode_hex(b"610805d00fa01f50").unwrap().as_slice())
+                .unwrap();
+        assert_eq!(
+            Geometry::from(&twkb::Geometry::Point(point)),
+            Geometry::Point((1., 2.).into())
+        );
+    }
+
+    #[test]
+    fn test_geojson_roundtrip() {
+        let line = Geometry::LineString(line_string![(x: 10., y: -20.), (x: 0., y: -0.5)]);
+        let geojson = GeoJSONGeometry::try_from(&line).unwrap();
+        assert_eq!(
+            geojson.as_str(),
+            r#"{"type":"LineString","crs":null,"coordinates":[[10.0,-20.0],[0.0,-0.5]]}"#
+        );
+        assert_eq!(Geometry::try_from(&geojson).unwrap(), line);
+        let collection = Geometry::GeometryCollection(vec![line].into());
+        assert!(GeoJSONGeometry::try_from(&collection).is_err());
+    }
+
+    #[test]
+    fn test_empty_exterior() {
+        let hole = line_string![(x: 1., y: 1.), (x: 2., y: 1.), (x: 2., y: 2.), (x: 1., y: 1.)];
+        let polygon = Polygon::new(LineString(vec![]), vec![hole]);
+        match ewkb::Geometry::from(&Geometry::Polygon(polygon.clone())) {
+            ewkb::GeometryT::Polygon(polygon) => {
+                let sizes: Vec<_> = polygon.rings.iter().map(|ring| ring.points.len()).collect();
+                assert_eq!(sizes, [0, 4]);
+            }
+            geom => panic!("unexpected geometry {:?}", geom),
+        }
+        match GeoJSONGeometry::try_from(&Geometry::Polygon(polygon)).unwrap() {
+            GeoJSONGeometry::Polygon(polygon) => {
+                let sizes: Vec<_> = polygon.coordinates.iter().map(Vec::len).collect();
+                assert_eq!(sizes, [0, 4]);
+            }
+            geom => panic!("unexpected geometry {:?}", geom),
+        }
+
+        let empty = Geometry::Polygon(Polygon::new(LineString(vec![]), vec![]));
+        match ewkb::Geometry::from(&empty) {
+            ewkb::GeometryT::Polygon(polygon) => assert!(polygon.rings.is_empty()),
+            geom => panic!("unexpected geometry {:?}", geom),
+        }
+    }
+}
@@ -0,0 +1,506 @@
+// Conversion between Arrow binary arrays and GeoArrow native arrays
+//
+// GeoArrow stores geometries of one type as nested lists over interleaved coordinates, e.g. a
+// polygon array is `List<List<FixedSizeList<f64>>>` with one offset buffer per level. See
+// https://geoarrow.org/format.html.
+extern crate arrow_array;
+extern crate arrow_buffer;
+extern crate arrow_schema;
+extern crate serde_json;
+
+use self::arrow_array::builder::BinaryBuilder;
+use self::arrow_array::cast::AsArray;
+use self::arrow_array::types::Float64Type;
+use self::arrow_array::{
+    Array, ArrayRef, BinaryArray, FixedSizeListArray, Float64Array, GenericListArray, ListArray,
+    OffsetSizeTrait,
+};
+use self::arrow_buffer::{NullBuffer, OffsetBuffer, ScalarBuffer};
+use self::arrow_schema::{ArrowError, DataType, Field};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::ewkb::Endianness;
+pub use crate::geojson::BinaryFormat;
+use crate::geojson::{self, Dims, GeoJSONEncode, GeoJSONGeometry};
+use crate::limits::ParseLimits;
+use crate::ragged::{Kind, Ragged};
+
+const EXTENSION_NAME: &str = "ARROW:extension:name";
+const EXTENSION_METADATA: &str = "ARROW:extension:metadata";
+
+impl From<ArrowError> for Error {
+    fn from(e: ArrowError) -> Error {
+        Error::Other(e.to_string())
+    }
+}
+
+fn kind_from_extension_name(name: &str) -> Option<Kind> {
+    match name.strip_prefix("geoarrow.")? {
+        "point" => Some(Kind::Point),
+        "linestring" => Some(Kind::LineString),
+        "polygon" => Some(Kind::Polygon),
+        "multipoint" => Some(Kind::MultiPoint),
+        "multilinestring" => Some(Kind::MultiLineString),
+        "multipolygon" => Some(Kind::MultiPolygon),
+        _ => None,
+    }
+}
+
+/// Names of the list levels of a GeoArrow array from the outermost inwards.
+fn level_names(kind: Kind) -> &'static [&'static str] {
+    match kind {
+        Kind::Point => &[],
+        Kind::LineString => &["vertices"],
+        Kind::Polygon => &["rings", "vertices"],
+        Kind::MultiPoint => &["points"],
+        Kind::MultiLineString => &["linestrings", "vertices"],
+        Kind::MultiPolygon => &["polygons", "rings", "vertices"],
+    }
+}
+
+/// Arrow array of ragged geometries of one type.
+fn ragged_to_array(ragged: Ragged, dims: Dims, levels: &[&str]) -> Result<ArrayRef, Error> {
+    let nulls = NullBuffer::from(ragged.validity);
+    let nulls = Some(nulls).filter(|nulls| nulls.null_count() > 0);
+    let coords = Arc::new(Float64Array::from(ragged.coords));
+    let coord_field = Field::new(dims.as_str().to_lowercase(), DataType::Float64, false);
+    let mut array: ArrayRef = Arc::new(FixedSizeListArray::try_new(
+        Arc::new(coord_field),
+        ragged.width as i32,
+        coords,
+        if levels.is_empty() {
+            nulls.clone()
+        } else {
+            None
+        },
+    )?);
+    for (level, offsets) in ragged.offsets.into_iter().enumerate().rev() {
+        let offsets = offsets
+            .into_iter()
+            .map(i32::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Error::Other("GeoArrow array exceeds 32-bit offsets".to_owned()))?;
+        let field = Field::new(levels[level], array.data_type().clone(), false);
+        array = Arc::new(ListArray::try_new(
+            Arc::new(field),
+            OffsetBuffer::new(ScalarBuffer::from(offsets)),
+            array,
+            if level == 0 { nulls.clone() } else { None },
+        )?);
+    }
+    Ok(array)
+}
+
+/// Values of an Arrow binary array: `Binary`, `LargeBinary` or `BinaryView`.
+fn binary_values(array: &dyn Array) -> Result<Vec<Option<&[u8]>>, Error> {
+    Ok(match array.data_type() {
+        DataType::Binary => array.as_binary::<i32>().iter().collect(),
+        DataType::LargeBinary => array.as_binary::<i64>().iter().collect(),
+        DataType::BinaryView => array.as_binary_view().iter().collect(),
+        data_type => {
+            return Err(Error::Other(format!(
+                "expected a binary array, got {}",
+                data_type
+            )))
+        }
+    })
+}
+
+/// Convert an Arrow binary array of geometries into a GeoArrow native array.
+///
+/// All geometries must have the same coordinate layout and SRID and be of one type, except that
+/// single geometries are stored as multi geometries when mixed with them. Nulls stay null.
+/// Returns the GeoArrow field, with the extension name and the CRS if the geometries have an
+/// SRID, and the array.
+pub fn binary_to_geoarrow(
+    array: &dyn Array,
+    format: BinaryFormat,
+    limits: &ParseLimits,
+) -> Result<(Field, ArrayRef), Error> {
+    let geoms = binary_values(array)?
+        .into_iter()
+        .map(|data| {
+            data.map(|data| geojson::read_binary(data, format, limits))
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut kind = None;
+    let mut dims = None;
+    let mut srid = None;
+    for geom in geoms.iter().flatten() {
+        kind = Some(match kind {
+            Some(kind) => Kind::common(kind, Kind::of(geom)).ok_or_else(|| {
+                Error::Other(format!(
+                    "cannot store {} and {} geometries in one GeoArrow array",
+                    kind.name(),
+                    Kind::of(geom).name()
+                ))
+            })?,
+            None => Kind::of(geom),
+        });
+        match dims {
+            Some(dims) if dims != geom.dims() => {
+                return Err(Error::Other(format!(
+                    "cannot store {} and {} geometries in one GeoArrow array",
+                    dims,
+                    geom.dims()
+                )))
+            }
+            _ => dims = Some(geom.dims()),
+        }
+        match srid {
+            Some(srid) if srid != geom.srid() => {
+                let name = |srid: Option<i32>| match srid {
+                    Some(srid) => format!("SRID {}", srid),
+                    None => "no SRID".to_owned(),
+                };
+                return Err(Error::Other(format!(
+                    "cannot store geometries with {} and {} in one GeoArrow array",
+                    name(srid),
+                    name(geom.srid())
+                )));
+            }
+            _ => srid = Some(geom.srid()),
+        }
+    }
+    let kind = kind.unwrap_or(Kind::Point);
+    let dims = dims.unwrap_or(Dims::Xy);
+
+    let mut ragged = Ragged::new(kind, dims);
+    for geom in &geoms {
+        ragged.push(kind, geom.as_ref());
+    }
+    let array = ragged_to_array(ragged, dims, level_names(kind))?;
+
+    let crs = match srid.flatten() {
+        Some(srid) => format!(
+            "{{\"crs\":\"EPSG:{}\",\"crs_type\":\"authority_code\"}}",
+            srid
+        ),
+        None => "{}".to_owned(),
+    };
+    let metadata = HashMap::from([
+        (
+            EXTENSION_NAME.to_owned(),
+            format!("geoarrow.{}", kind.name()),
+        ),
+        (EXTENSION_METADATA.to_owned(), crs),
+    ]);
+    let field = Field::new("geometry", array.data_type().clone(), true).with_metadata(metadata);
+    Ok((field, array))
+}
+
+/// SRID of an EPSG code in GeoArrow CRS metadata, either as an authority code or the id of a
+/// PROJJSON object.
+fn metadata_srid(metadata: &str) -> Option<i32> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata).ok()?;
+    let crs = metadata.get("crs")?;
+    if let Some(code) = crs.as_str() {
+        return code.strip_prefix("EPSG:")?.parse().ok();
+    }
+    let id = crs.get("id")?;
+    if id.get("authority")?.as_str()? != "EPSG" {
+        return None;
+    }
+    id.get("code")?
+        .as_i64()
+        .and_then(|code| i32::try_from(code).ok())
+}
+
+/// Offsets and values of a `List` or `LargeList` array.
+fn list_parts(array: &dyn Array) -> Result<(Vec<usize>, &ArrayRef), Error> {
+    fn parts<O: OffsetSizeTrait>(array: &GenericListArray<O>) -> (Vec<usize>, &ArrayRef) {
+        let offsets = array.value_offsets().iter().map(|o| o.as_usize()).collect();
+        (offsets, array.values())
+    }
+    match array.data_type() {
+        DataType::List(_) => Ok(parts(array.as_list::<i32>())),
+        DataType::LargeList(_) => Ok(parts(array.as_list::<i64>())),
+        data_type => Err(Error::Other(format!(
+            "expected a GeoArrow list array, got {}",
+            data_type
+        ))),
+    }
+}
+
+/// Interleaved or separated GeoArrow coordinates.
+struct Coords<'a> {
+    /// Coordinate values, one array of all vertices per dimension or a single interleaved one.
+    values: Vec<&'a [f64]>,
+    width: usize,
+    dims: Dims,
+}
+
+impl<'a> Coords<'a> {
+    fn new(array: &'a dyn Array) -> Result<Self, Error> {
+        let float_values = |array: &'a ArrayRef| match array.data_type() {
+            DataType::Float64 => Ok(array.as_primitive::<Float64Type>().values().as_ref()),
+            data_type => Err(Error::Other(format!(
+                "expected Float64 coordinates, got {}",
+                data_type
+            ))),
+        };
+        match array.data_type() {
+            DataType::FixedSizeList(field, width) => {
+                let array = array.as_fixed_size_list();
+                let width = *width as usize;
+                let values = float_values(array.values())?;
+                let start = array.offset() * width;
+                let dims = coord_dims(field.name(), width)?;
+                Ok(Coords {
+                    values: vec![&values[start..start + array.len() * width]],
+                    width,
+                    dims,
+                })
+            }
+            DataType::Struct(fields) => {
+                let array = array.as_struct();
+                let names: String = fields.iter().map(|field| field.name().as_str()).collect();
+                let dims = coord_dims(&names, fields.len())?;
+                let values = array
+                    .columns()
+                    .iter()
+                    .map(float_values)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Coords {
+                    values,
+                    width: 1,
+                    dims,
+                })
+            }
+            data_type => Err(Error::Other(format!(
+                "expected GeoArrow coordinates, got {}",
+                data_type
+            ))),
+        }
+    }
+
+    fn get(&self, i: usize) -> Vec<f64> {
+        match self.values.as_slice() {
+            [interleaved] => interleaved[i * self.width..(i + 1) * self.width].to_vec(),
+            separated => separated.iter().map(|values| values[i]).collect(),
+        }
+    }
+
+    fn range(&self, start: usize, end: usize) -> Vec<Vec<f64>> {
+        (start..end).map(|i| self.get(i)).collect()
+    }
+}
+
+/// Coordinate layout from the dimension names of GeoArrow coordinates, or their number.
+fn coord_dims(names: &str, width: usize) -> Result<Dims, Error> {
+    let dims = match names.to_uppercase().parse() {
+        Ok(dims) => dims,
+        Err(_) => match width {
+            2 => Dims::Xy,
+            3 => Dims::Xyz,
+            4 => Dims::Xyzm,
+            _ => return Err(Error::Other(format!("invalid coordinate width {}", width))),
+        },
+    };
+    if dims.width() != width {
+        return Err(Error::Other(format!(
+            "coordinate width {} does not match {}",
+            width, dims
+        )));
+    }
+    Ok(dims)
+}
+
+/// Convert a GeoArrow native array into an Arrow binary array of EWKB.
+///
+/// `field` carries the GeoArrow extension name, and the SRID written into the EWKB when its CRS
+/// metadata is an EPSG code. Nulls stay null.
+pub fn geoarrow_to_ewkb(
+    field: &Field,
+    array: &dyn Array,
+    byte_order: Endianness,
+) -> Result<BinaryArray, Error> {
+    let extension_name = field
+        .metadata()
+        .get(EXTENSION_NAME)
+        .ok_or_else(|| Error::Other("missing GeoArrow extension name".to_owned()))?;
+    let kind = kind_from_extension_name(extension_name).ok_or_else(|| {
+        Error::Other(format!(
+            "unsupported GeoArrow extension '{}'",
+            extension_name
+        ))
+    })?;
+    let crs = field
+        .metadata()
+        .get(EXTENSION_METADATA)
+        .and_then(|metadata| metadata_srid(metadata));
+
+    // Offsets of each list level down to the coordinates.
+    let mut levels = Vec::new();
+    let mut values = array;
+    for _ in level_names(kind) {
+        let (offsets, inner) = list_parts(values)?;
+        levels.push(offsets);
+        values = inner.as_ref();
+    }
+    let coords = Coords::new(values)?;
+    let dims = Some(coords.dims);
+    let type_name = |name: &str| name.to_owned();
+
+    let mut builder = BinaryBuilder::with_capacity(array.len(), 0);
+    for i in 0..array.len() {
+        if array.is_null(i) {
+            builder.append_null();
+            continue;
+        }
+        let range = |level: usize, i: usize| levels[level][i]..levels[level][i + 1];
+        let line = |level: usize, i: usize| {
+            let range = range(level, i);
+            coords.range(range.start, range.end)
+        };
+        let rings = |level: usize, i: usize| range(level, i).map(|j| line(level + 1, j)).collect();
+        let geom = match kind {
+            Kind::Point => GeoJSONGeometry::Point(geojson::Point {
+                type_name: type_name("Point"),
+                crs,
+                coordinates: coords.get(i),
+                dims,
+            }),
+            Kind::LineString => GeoJSONGeometry::LineString(geojson::LineString {
+                type_name: type_name("LineString"),
+                crs,
+                coordinates: line(0, i),
+                dims,
+            }),
+            Kind::Polygon => GeoJSONGeometry::Polygon(geojson::Polygon {
+                type_name: type_name("Polygon"),
+                crs,
+                coordinates: rings(0, i),
+                dims,
+            }),
+            Kind::MultiPoint => GeoJSONGeometry::MultiPoint(geojson::MultiPoint {
+                type_name: type_name("MultiPoint"),
+                crs,
+                coordinates: line(0, i),
+                dims,
+            }),
+            Kind::MultiLineString => GeoJSONGeometry::MultiLineString(geojson::MultiLineString {
+                type_name: type_name("MultiLineString"),
+                crs,
+                coordinates: rings(0, i),
+                dims,
+            }),
+            Kind::MultiPolygon => GeoJSONGeometry::MultiPolygon(geojson::MultiPolygon {
+                type_name: type_name("MultiPolygon"),
+                crs,
+                coordinates: range(0, i).map(|j| rings(1, j)).collect(),
+                dims,
+            }),
+        };
+        builder.append_value(geom.to_ewkb_with(byte_order)?);
+    }
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod test {
+    use super::arrow_array::{Array, BinaryArray};
+    use super::{binary_to_geoarrow, geoarrow_to_ewkb, BinaryFormat};
+    use crate::encoding::decode_hex;
+    use crate::ewkb::Endianness;
+    use crate::limits::ParseLimits;
+
+    fn binary_array(hex: &[Option<&str>]) -> BinaryArray {
+        let values: Vec<Option<Vec<u8>>> = hex
+            .iter()
+            .map(|hex| hex.map(|hex| decode_hex(hex.as_bytes()).unwrap()))
+            .collect();
+        BinaryArray::from(values.iter().map(Option::as_deref).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_polygon_roundtrip() {
+        let polygon = "0103000020E610000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000";
+        let multipolygon = "0106000020E610000002000000010300000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000010300000001000000050000000000000000002440000000000000244000000000000000C0000000000000244000000000000000C000000000000000C0000000000000244000000000000000C000000000000024400000000000002440";
+        let input = binary_array(&[Some(polygon), None, Some(multipolygon)]);
+        let (field, array) =
+            binary_to_geoarrow(&input, BinaryFormat::Ewkb, &ParseLimits::default()).unwrap();
+        assert_eq!(
+            field.metadata()["ARROW:extension:name"],
+            "geoarrow.multipolygon"
+        );
+        assert_eq!(
+            field.metadata()["ARROW:extension:metadata"],
+            "{\"crs\":\"EPSG:4326\",\"crs_type\":\"authority_code\"}"
+        );
+        assert_eq!(
+            array.data_type().to_string(),
+            "List(non-null List(non-null List(non-null FixedSizeList(2 x non-null Float64, field: 'xy'), field: 'vertices'), field: 'rings'), field: 'polygons')"
+        );
+        assert_eq!(array.len(), 3);
+        assert!(array.is_null(1));
+
+        let output = geoarrow_to_ewkb(&field, &array, Endianness::Little).unwrap();
+        assert!(output.is_null(1));
+        // The polygon comes back as a multipolygon with one member.
+        let (_, roundtrip) =
+            binary_to_geoarrow(&output, BinaryFormat::Ewkb, &ParseLimits::default()).unwrap();
+        assert_eq!(roundtrip.to_data(), array.to_data());
+        assert_eq!(output.value(2), input.value(2));
+    }
+
+    #[test]
+    fn test_point_twkb() {
+        let input = binary_array(&[Some("610805d00fa01f50"), None]);
+        let (field, array) =
+            binary_to_geoarrow(&input, BinaryFormat::Twkb, &ParseLimits::default()).unwrap();
+        assert_eq!(field.metadata()["ARROW:extension:name"], "geoarrow.point");
+        assert_eq!(field.metadata()["ARROW:extension:metadata"], "{}");
+        assert_eq!(
+            array.data_type().to_string(),
+            "FixedSizeList(3 x non-null Float64, field: 'xyz')"
+        );
+        let output = geoarrow_to_ewkb(&field, &array, Endianness::Little).unwrap();
+        assert_eq!(
+            output.value(0),
+            decode_hex(b"0101000080000000000000F03F00000000000000400000000000001040").unwrap()
+        );
+        assert!(output.is_null(1));
+    }
+
+    #[test]
+    fn test_mixed_types() {
+        let input = binary_array(&[
+            Some("0101000000000000000000244000000000000034C0"),
+            Some("0102000000020000000000000000000000000000000000000000000000000000400000000000000000"),
+        ]);
+        let err = binary_to_geoarrow(&input, BinaryFormat::Ewkb, &ParseLimits::default());
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "Other(\"cannot store point and linestring geometries in one GeoArrow array\")"
+        );
+        assert!("geojson".parse::<BinaryFormat>().is_err());
+    }
+
+    #[test]
+    fn test_mixed_srids() {
+        let wgs84 = "0101000020E6100000000000000000244000000000000034C0";
+        let etrs_tm35fin = "0101000020FB0B0000000000000000244000000000000034C0";
+        let input = binary_array(&[Some(wgs84), Some(etrs_tm35fin)]);
+        let err = binary_to_geoarrow(&input, BinaryFormat::Ewkb, &ParseLimits::default());
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "Other(\"cannot store geometries with SRID 4326 and SRID 3067 in one GeoArrow array\")"
+        );
+        let input = binary_array(&[
+            Some(wgs84),
+            None,
+            Some("0101000000000000000000244000000000000034C0"),
+        ]);
+        let err = binary_to_geoarrow(&input, BinaryFormat::Ewkb, &ParseLimits::default());
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "Other(\"cannot store geometries with SRID 4326 and no SRID in one GeoArrow array\")"
+        );
+    }
+}
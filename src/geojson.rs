@@ -2,7 +2,9 @@
 extern crate serde;
 extern crate serde_json;
 
+use std::convert::TryFrom;
 use std::fmt::Display;
+use std::str::FromStr;
 
 use error::Error;
 use ewkb;
@@ -11,10 +13,14 @@ use ewkb::*;
 use ewkb::{
     AsEwkbLineString, AsEwkbMultiLineString, AsEwkbMultiPoint, AsEwkbMultiPolygon, AsEwkbPolygon,
 };
+use flat;
+use limits::ParseLimits;
+use reproject::{Transform, TransformOptions};
 use twkb;
+use twkb::TwkbGeom;
 use types::{
-    LineString as LineStringTrait, MultiPolygon as MultiPolygonTrait, Point as PointTrait,
-    Polygon as PolygonTrait,
+    LineString as LineStringTrait, MultiLineString as MultiLineStringTrait,
+    MultiPolygon as MultiPolygonTrait, Point as PointTrait, Polygon as PolygonTrait,
 };
 
 use self::serde::ser::{SerializeStruct, Serializer};
@@ -32,6 +38,7 @@ pub enum GeometryType {
     None,
 }
 
+#[derive(PartialEq, Clone, Debug)]
 pub enum GeoJSONGeometry {
     Point(Point),
     LineString(LineString),
@@ -53,36 +60,25 @@ impl GeoJSONEncode for GeoJSONGeometry {
         }
     }
 
-    fn has_z(&self) -> bool {
+    fn dims(&self) -> Dims {
         match self {
-            GeoJSONGeometry::Point(g) => g.has_z(),
-            GeoJSONGeometry::LineString(g) => g.has_z(),
-            GeoJSONGeometry::Polygon(g) => g.has_z(),
-            GeoJSONGeometry::MultiPoint(g) => g.has_z(),
-            GeoJSONGeometry::MultiLineString(g) => g.has_z(),
-            GeoJSONGeometry::MultiPolygon(g) => g.has_z(),
+            GeoJSONGeometry::Point(g) => g.dims(),
+            GeoJSONGeometry::LineString(g) => g.dims(),
+            GeoJSONGeometry::Polygon(g) => g.dims(),
+            GeoJSONGeometry::MultiPoint(g) => g.dims(),
+            GeoJSONGeometry::MultiLineString(g) => g.dims(),
+            GeoJSONGeometry::MultiPolygon(g) => g.dims(),
         }
     }
 
-    fn has_zm(&self) -> bool {
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
         match self {
-            GeoJSONGeometry::Point(g) => g.has_zm(),
-            GeoJSONGeometry::LineString(g) => g.has_zm(),
-            GeoJSONGeometry::Polygon(g) => g.has_zm(),
-            GeoJSONGeometry::MultiPoint(g) => g.has_zm(),
-            GeoJSONGeometry::MultiLineString(g) => g.has_zm(),
-            GeoJSONGeometry::MultiPolygon(g) => g.has_zm(),
-        }
-    }
-
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
-        match self {
-            GeoJSONGeometry::Point(g) => g.to_ewkb(),
-            GeoJSONGeometry::LineString(g) => g.to_ewkb(),
-            GeoJSONGeometry::Polygon(g) => g.to_ewkb(),
-            GeoJSONGeometry::MultiPoint(g) => g.to_ewkb(),
-            GeoJSONGeometry::MultiLineString(g) => g.to_ewkb(),
-            GeoJSONGeometry::MultiPolygon(g) => g.to_ewkb(),
+            GeoJSONGeometry::Point(g) => g.to_ewkb_with(byte_order),
+            GeoJSONGeometry::LineString(g) => g.to_ewkb_with(byte_order),
+            GeoJSONGeometry::Polygon(g) => g.to_ewkb_with(byte_order),
+            GeoJSONGeometry::MultiPoint(g) => g.to_ewkb_with(byte_order),
+            GeoJSONGeometry::MultiLineString(g) => g.to_ewkb_with(byte_order),
+            GeoJSONGeometry::MultiPolygon(g) => g.to_ewkb_with(byte_order),
         }
     }
 
@@ -119,15 +115,110 @@ impl GeoJSONEncode for GeoJSONGeometry {
         }
     }
 
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error> {
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
         match self {
-            GeoJSONGeometry::Point(g) => g.transform(xform),
-            GeoJSONGeometry::LineString(g) => g.transform(xform),
-            GeoJSONGeometry::Polygon(g) => g.transform(xform),
-            GeoJSONGeometry::MultiPoint(g) => g.transform(xform),
-            GeoJSONGeometry::MultiLineString(g) => g.transform(xform),
-            GeoJSONGeometry::MultiPolygon(g) => g.transform(xform),
+            GeoJSONGeometry::Point(g) => g.transform_with(xform, options),
+            GeoJSONGeometry::LineString(g) => g.transform_with(xform, options),
+            GeoJSONGeometry::Polygon(g) => g.transform_with(xform, options),
+            GeoJSONGeometry::MultiPoint(g) => g.transform_with(xform, options),
+            GeoJSONGeometry::MultiLineString(g) => g.transform_with(xform, options),
+            GeoJSONGeometry::MultiPolygon(g) => g.transform_with(xform, options),
+        }
+    }
+}
+
+/// Binary geometry format of Arrow binary arrays and bytea columns.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum BinaryFormat {
+    /// EWKB, which includes WKB without Z or M values.
+    #[default]
+    Ewkb,
+    /// TWKB.
+    Twkb,
+}
+
+impl FromStr for BinaryFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<BinaryFormat, Error> {
+        match s {
+            "ewkb" | "wkb" => Ok(BinaryFormat::Ewkb),
+            "twkb" => Ok(BinaryFormat::Twkb),
+            _ => Err(Error::Other(format!(
+                "invalid format '{}' - expected one of ewkb, wkb, twkb",
+                s
+            ))),
+        }
+    }
+}
+
+/// Read one geometry in the given format, failing on trailing bytes.
+pub fn read_binary(
+    data: &[u8],
+    format: BinaryFormat,
+    limits: &ParseLimits,
+) -> Result<GeoJSONGeometry, Error> {
+    let mut data = data;
+    match format {
+        BinaryFormat::Ewkb => {
+            GeoJSONGeometry::try_from(&ewkb::Geometry::read_ewkb_strict(&mut data, limits)?)
+        }
+        BinaryFormat::Twkb => Ok(GeoJSONGeometry::from(&twkb::Geometry::read_twkb_strict(
+            &mut data, limits,
+        )?)),
+    }
+}
+
+/// Read the geometry at the start of `data` in the given format, returning it along with the
+/// number of bytes it took.
+pub fn read_binary_prefix(
+    data: &[u8],
+    format: BinaryFormat,
+    limits: &ParseLimits,
+) -> Result<(GeoJSONGeometry, usize), Error> {
+    let mut raw = data;
+    let geom = match format {
+        BinaryFormat::Ewkb => {
+            GeoJSONGeometry::try_from(&ewkb::Geometry::read_ewkb_limited(&mut raw, limits)?)?
+        }
+        BinaryFormat::Twkb => {
+            GeoJSONGeometry::from(&twkb::Geometry::read_twkb_limited(&mut raw, limits)?)
+        }
+    };
+    Ok((geom, data.len() - raw.len()))
+}
+
+impl From<&twkb::Geometry> for GeoJSONGeometry {
+    fn from(geom: &twkb::Geometry) -> Self {
+        match geom {
+            twkb::Geometry::Point(g) => GeoJSONGeometry::Point(g.to_geojson()),
+            twkb::Geometry::LineString(g) => GeoJSONGeometry::LineString(g.to_geojson()),
+            twkb::Geometry::Polygon(g) => GeoJSONGeometry::Polygon(g.to_geojson()),
+            twkb::Geometry::MultiPoint(g) => GeoJSONGeometry::MultiPoint(g.to_geojson()),
+            twkb::Geometry::MultiLineString(g) => GeoJSONGeometry::MultiLineString(g.to_geojson()),
+            twkb::Geometry::MultiPolygon(g) => GeoJSONGeometry::MultiPolygon(g.to_geojson()),
+        }
+    }
+}
+
+impl TryFrom<&ewkb::Geometry> for GeoJSONGeometry {
+    type Error = Error;
+
+    fn try_from(geom: &ewkb::Geometry) -> Result<Self, Error> {
+        match geom {
+            GeometryT::Point(g) => Ok(GeoJSONGeometry::Point(g.to_geojson())),
+            GeometryT::LineString(g) => Ok(GeoJSONGeometry::LineString(g.to_geojson())),
+            GeometryT::Polygon(g) => Ok(GeoJSONGeometry::Polygon(g.to_geojson())),
+            GeometryT::MultiPoint(g) => Ok(GeoJSONGeometry::MultiPoint(g.to_geojson())),
+            GeometryT::MultiLineString(g) => Ok(GeoJSONGeometry::MultiLineString(g.to_geojson())),
+            GeometryT::MultiPolygon(g) => Ok(GeoJSONGeometry::MultiPolygon(g.to_geojson())),
+            GeometryT::GeometryCollection(_) => Err(Error::Other(
+                "not implemented for GeometryCollection".to_owned(),
+            )),
         }
     }
 }
@@ -147,6 +238,103 @@ impl Display for GeometryType {
     }
 }
 
+/// Coordinate layout of a geometry: X and Y, optionally followed by Z and M values.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Dims {
+    #[serde(rename = "XY")]
+    Xy,
+    #[serde(rename = "XYZ")]
+    Xyz,
+    #[serde(rename = "XYM")]
+    Xym,
+    #[serde(rename = "XYZM")]
+    Xyzm,
+}
+
+impl Dims {
+    fn of<P: PointTrait + ?Sized>(point: &P) -> Dims {
+        Dims::new(point.opt_z().is_some(), point.opt_m().is_some())
+    }
+
+    pub(crate) fn new(has_z: bool, has_m: bool) -> Dims {
+        match (has_z, has_m) {
+            (false, false) => Dims::Xy,
+            (true, false) => Dims::Xyz,
+            (false, true) => Dims::Xym,
+            (true, true) => Dims::Xyzm,
+        }
+    }
+
+    /// Whether coordinates have a Z value.
+    pub fn has_z(&self) -> bool {
+        matches!(self, Dims::Xyz | Dims::Xyzm)
+    }
+
+    /// Whether coordinates have an M value.
+    pub fn has_m(&self) -> bool {
+        matches!(self, Dims::Xym | Dims::Xyzm)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dims::Xy => "XY",
+            Dims::Xyz => "XYZ",
+            Dims::Xym => "XYM",
+            Dims::Xyzm => "XYZM",
+        }
+    }
+
+    /// Number of values per coordinate.
+    pub fn width(&self) -> usize {
+        match self {
+            Dims::Xy => 2,
+            Dims::Xyz | Dims::Xym => 3,
+            Dims::Xyzm => 4,
+        }
+    }
+}
+
+impl Display for Dims {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Dims {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Dims, Error> {
+        match s {
+            "XY" => Ok(Dims::Xy),
+            "XYZ" => Ok(Dims::Xyz),
+            "XYM" => Ok(Dims::Xym),
+            "XYZM" => Ok(Dims::Xyzm),
+            _ => Err(Error::Other(format!(
+                "invalid dims '{}' - expected one of XY, XYZ, XYM, XYZM",
+                s
+            ))),
+        }
+    }
+}
+
+/// Layout of coordinates with `len` values. Three values are taken as XYZ.
+fn infer_dims(len: Option<usize>) -> Dims {
+    match len {
+        Some(3) => Dims::Xyz,
+        Some(4) => Dims::Xyzm,
+        _ => Dims::Xy,
+    }
+}
+
+/// Layout of the first of `points`, if any.
+fn dims_of_first<'a, P, I>(mut points: I) -> Option<Dims>
+where
+    P: 'a + PointTrait,
+    I: Iterator<Item = &'a P>,
+{
+    points.next().map(Dims::of)
+}
+
 pub trait GeoJSONPoint: Send + Sync {
     fn to_geojson(&self) -> Point;
 }
@@ -172,14 +360,33 @@ pub trait GeoJSONMultiPolygon: Send + Sync {
 
 pub trait GeoJSONEncode: Send + Sync {
     fn as_str(&self) -> String;
-    fn has_z(&self) -> bool;
-    fn has_zm(&self) -> bool;
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error>;
+    /// Coordinate layout of the geometry: `dims` if set, otherwise inferred from the number of
+    /// values in the first coordinate.
+    fn dims(&self) -> Dims;
+    fn has_z(&self) -> bool {
+        self.dims() == Dims::Xyz
+    }
+    fn has_m(&self) -> bool {
+        self.dims() == Dims::Xym
+    }
+    fn has_zm(&self) -> bool {
+        self.dims() == Dims::Xyzm
+    }
+    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
+        self.to_ewkb_with(Endianness::Little)
+    }
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error>;
     fn srid(&self) -> Option<i32>;
     fn set_srid(&mut self, srid: i32);
     fn geom_type(&self) -> GeometryType;
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error>;
+    fn transform(&mut self, xform: &Transform) -> Result<(), Error> {
+        self.transform_with(xform, &TransformOptions::default())
+    }
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error>;
 }
 
 #[derive(Serialize)]
@@ -209,29 +416,91 @@ pub struct Point {
     #[serde(serialize_with = "crs_serializer")]
     pub crs: Option<i32>,
     pub coordinates: Vec<f64>,
+    #[serde(skip_serializing)]
+    pub dims: Option<Dims>,
 }
 
-fn to_ewkb_point(crds: &[f64], srid: Option<i32>) -> ewkb::Point {
-    ewkb::Point::new(crds[0], crds[1], None, None, srid)
+/// EWKB point types written for each coordinate layout.
+trait FromCrds: PointTrait + EwkbRead + for<'a> AsEwkbPoint<'a> {
+    fn from_crds(crds: &[f64], srid: Option<i32>) -> Result<Self, Error>;
 }
 
-fn to_ewkb_pointz(crds: &[f64], srid: Option<i32>) -> ewkb::PointZ {
-    ewkb::PointZ::new(crds[0], crds[1], crds[2], None, srid)
+impl FromCrds for ewkb::Point {
+    fn from_crds(crds: &[f64], srid: Option<i32>) -> Result<Self, Error> {
+        match *crds {
+            [x, y, ..] => Ok(ewkb::Point::new(x, y, None, None, srid)),
+            _ => Err(invalid_coordinate(crds, 2)),
+        }
+    }
 }
 
-fn to_ewkb_pointzm(crds: &[f64], srid: Option<i32>) -> ewkb::PointZM {
-    ewkb::PointZM::new(crds[0], crds[1], crds[2], crds[3], srid)
+impl FromCrds for ewkb::PointZ {
+    fn from_crds(crds: &[f64], srid: Option<i32>) -> Result<Self, Error> {
+        match *crds {
+            [x, y, z, ..] => Ok(ewkb::PointZ::new(x, y, z, None, srid)),
+            _ => Err(invalid_coordinate(crds, 3)),
+        }
+    }
+}
+
+impl FromCrds for ewkb::PointM {
+    fn from_crds(crds: &[f64], srid: Option<i32>) -> Result<Self, Error> {
+        match *crds {
+            [x, y, m, ..] => Ok(ewkb::PointM::new(x, y, m, None, srid)),
+            _ => Err(invalid_coordinate(crds, 3)),
+        }
+    }
+}
+
+impl FromCrds for ewkb::PointZM {
+    fn from_crds(crds: &[f64], srid: Option<i32>) -> Result<Self, Error> {
+        match *crds {
+            [x, y, z, m, ..] => Ok(ewkb::PointZM::new(x, y, z, m, srid)),
+            _ => Err(invalid_coordinate(crds, 4)),
+        }
+    }
+}
+
+/// Write a geometry as EWKB with points of type `P`.
+trait WriteEwkbAs {
+    fn write_ewkb_as<P: FromCrds>(&self, byte_order: Endianness) -> Result<Vec<u8>, Error>;
+}
+
+fn write_ewkb_dims<G>(geom: &G, byte_order: Endianness) -> Result<Vec<u8>, Error>
+where
+    G: GeoJSONEncode + WriteEwkbAs,
+{
+    match geom.dims() {
+        Dims::Xy => geom.write_ewkb_as::<ewkb::Point>(byte_order),
+        Dims::Xyz => geom.write_ewkb_as::<ewkb::PointZ>(byte_order),
+        Dims::Xym => geom.write_ewkb_as::<ewkb::PointM>(byte_order),
+        Dims::Xyzm => geom.write_ewkb_as::<ewkb::PointZM>(byte_order),
+    }
+}
+
+fn invalid_coordinate(crds: &[f64], dims: usize) -> Error {
+    Error::Write(format!(
+        "invalid coordinate {:?} - expected {} values",
+        crds, dims
+    ))
+}
+
+impl WriteEwkbAs for Point {
+    fn write_ewkb_as<P: FromCrds>(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::with_capacity(9 + 8 * 4);
+        let geom = P::from_crds(&self.coordinates, self.crs)?;
+        geom.as_ewkb().write_ewkb_with(&mut data, byte_order)?;
+        Ok(data)
+    }
 }
 
 impl GeoJSONEncode for Point {
     fn as_str(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
-    fn has_z(&self) -> bool {
-        self.coordinates.len() == 3
-    }
-    fn has_zm(&self) -> bool {
-        self.coordinates.len() == 4
+    fn dims(&self) -> Dims {
+        self.dims
+            .unwrap_or_else(|| infer_dims(Some(self.coordinates.len())))
     }
     fn srid(&self) -> Option<i32> {
         self.crs
@@ -240,29 +509,23 @@ impl GeoJSONEncode for Point {
         self.crs = Some(srid);
     }
 
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
-        let mut data = Vec::with_capacity(9 + 8 * 3);
-        if self.has_zm() {
-            let geom = to_ewkb_pointzm(&self.coordinates, self.crs);
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else if self.has_z() {
-            let geom = to_ewkb_pointz(&self.coordinates, self.crs);
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else {
-            let geom = to_ewkb_point(&self.coordinates, self.crs);
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        }
-        Ok(data)
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        write_ewkb_dims(self, byte_order)
     }
 
     fn geom_type(&self) -> GeometryType {
         GeometryType::Point
     }
 
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error> {
-        use crate::reproject::{xform_crds, AsCrds};
-        xform_crds(&mut self.as_crds(), &xform)
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        use crate::reproject::{xform_crds_with, AsCrds, DropVertices};
+        let dropped = xform_crds_with(&mut self.as_crds(), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
     }
 }
 
@@ -271,6 +534,7 @@ impl GeoJSONPoint for twkb::Point {
         Point {
             type_name: "Point".to_owned(),
             crs: None,
+            dims: Some(Dims::of(self)),
             coordinates: self.crds(),
         }
     }
@@ -281,6 +545,7 @@ impl GeoJSONPoint for ewkb::Point {
         Point {
             type_name: "Point".to_owned(),
             crs: self.srid,
+            dims: Some(Dims::of(self)),
             coordinates: self.crds(),
         }
     }
@@ -293,6 +558,8 @@ pub struct LineString {
     #[serde(serialize_with = "crs_serializer")]
     pub crs: Option<i32>,
     pub coordinates: Vec<Vec<f64>>,
+    #[serde(skip_serializing)]
+    pub dims: Option<Dims>,
 }
 
 impl GeoJSONLineString for twkb::LineString {
@@ -300,6 +567,7 @@ impl GeoJSONLineString for twkb::LineString {
         LineString {
             type_name: "LineString".to_owned(),
             crs: None,
+            dims: dims_of_first(self.points.iter()),
             coordinates: self.points().map(|x| x.crds()).collect(),
         }
     }
@@ -310,20 +578,34 @@ impl GeoJSONLineString for ewkb::LineString {
         LineString {
             type_name: "LineString".to_owned(),
             crs: self.srid,
+            dims: dims_of_first(self.points.iter()),
             coordinates: self.points().map(|x| x.crds()).collect(),
         }
     }
 }
 
+impl WriteEwkbAs for LineString {
+    fn write_ewkb_as<P: FromCrds>(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::with_capacity(9 + 8 * 3 * self.coordinates.len());
+        let mut geom = LineStringT::<P>::new();
+        geom.points = self
+            .coordinates
+            .iter()
+            .map(|crds| P::from_crds(crds, self.crs))
+            .collect::<Result<_, _>>()?;
+        geom.srid = self.crs;
+        geom.as_ewkb().write_ewkb_with(&mut data, byte_order)?;
+        Ok(data)
+    }
+}
+
 impl GeoJSONEncode for LineString {
     fn as_str(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
-    fn has_z(&self) -> bool {
-        !self.coordinates.is_empty() && self.coordinates[0].len() == 3
-    }
-    fn has_zm(&self) -> bool {
-        !self.coordinates.is_empty() && self.coordinates[0].len() == 4
+    fn dims(&self) -> Dims {
+        self.dims
+            .unwrap_or_else(|| infer_dims(self.coordinates.first().map(Vec::len)))
     }
     fn srid(&self) -> Option<i32> {
         self.crs
@@ -335,46 +617,18 @@ impl GeoJSONEncode for LineString {
         GeometryType::LineString
     }
 
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
-        let mut data = Vec::with_capacity(9 + 8 * 3 * self.coordinates.len());
-        if self.has_zm() {
-            let mut geom = ewkb::LineStringZM::new();
-            let pnts = self
-                .coordinates
-                .iter()
-                .map(|crds| to_ewkb_pointzm(crds, self.crs))
-                .collect();
-            geom.points = pnts;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else if self.has_z() {
-            let mut geom = ewkb::LineStringZ::new();
-            let pnts = self
-                .coordinates
-                .iter()
-                .map(|crds| to_ewkb_pointz(crds, self.crs))
-                .collect();
-            geom.points = pnts;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else {
-            let mut geom = ewkb::LineString::new();
-            let pnts = self
-                .coordinates
-                .iter()
-                .map(|crds| to_ewkb_point(crds, self.crs))
-                .collect();
-            geom.points = pnts;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        }
-
-        Ok(data)
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        write_ewkb_dims(self, byte_order)
     }
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error> {
-        use crate::reproject::{xform_crds, AsCrds};
-        xform_crds(&mut self.as_crds(), &xform)
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        use crate::reproject::{xform_crds_with, AsCrds, DropVertices};
+        let dropped = xform_crds_with(&mut self.as_crds(), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
     }
 }
 
@@ -385,27 +639,42 @@ pub struct Polygon {
     #[serde(serialize_with = "crs_serializer")]
     pub crs: Option<i32>,
     pub coordinates: Vec<Vec<Vec<f64>>>,
+    #[serde(skip_serializing)]
+    pub dims: Option<Dims>,
+}
+
+impl WriteEwkbAs for Polygon {
+    fn write_ewkb_as<P: FromCrds>(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        let mut data = vec![];
+        let mut geom = PolygonT::<P>::new();
+        geom.rings = self
+            .coordinates
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .map(|crds| P::from_crds(crds, self.crs))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        geom.srid = self.crs;
+        geom.as_ewkb().write_ewkb_with(&mut data, byte_order)?;
+        Ok(data)
+    }
 }
 
 impl GeoJSONEncode for Polygon {
     fn as_str(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
-    fn has_z(&self) -> bool {
-        if !self.coordinates.is_empty() {
-            let ring = &self.coordinates[0];
-            !ring.is_empty() && ring[0].len() == 3
-        } else {
-            false
-        }
-    }
-    fn has_zm(&self) -> bool {
-        if !self.coordinates.is_empty() {
-            let ring = &self.coordinates[0];
-            !ring.is_empty() && ring[0].len() == 4
-        } else {
-            false
-        }
+    fn dims(&self) -> Dims {
+        self.dims.unwrap_or_else(|| {
+            infer_dims(
+                self.coordinates
+                    .first()
+                    .and_then(|ring| ring.first())
+                    .map(Vec::len),
+            )
+        })
     }
     fn srid(&self) -> Option<i32> {
         self.crs
@@ -416,57 +685,18 @@ impl GeoJSONEncode for Polygon {
     fn geom_type(&self) -> GeometryType {
         GeometryType::Polygon
     }
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
-        let mut data = vec![];
-        if self.has_zm() {
-            let mut geom = ewkb::PolygonZM::new();
-            let rings = self
-                .coordinates
-                .iter()
-                .map(|ring| {
-                    ring.iter()
-                        .map(|crds| to_ewkb_pointzm(crds, self.crs))
-                        .collect()
-                })
-                .collect();
-            geom.rings = rings;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else if self.has_z() {
-            let mut geom = ewkb::PolygonZ::new();
-            let rings = self
-                .coordinates
-                .iter()
-                .map(|ring| {
-                    ring.iter()
-                        .map(|crds| to_ewkb_pointz(crds, self.crs))
-                        .collect()
-                })
-                .collect();
-            geom.rings = rings;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else {
-            let mut geom = ewkb::Polygon::new();
-            let rings = self
-                .coordinates
-                .iter()
-                .map(|ring| {
-                    ring.iter()
-                        .map(|crds| to_ewkb_point(crds, self.crs))
-                        .collect()
-                })
-                .collect();
-            geom.rings = rings;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        }
-        Ok(data)
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        write_ewkb_dims(self, byte_order)
     }
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error> {
-        use crate::reproject::{xform_crds, AsCrds};
-        xform_crds(&mut self.as_crds(), &xform)
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        use crate::reproject::{xform_crds_with, AsCrds, DropVertices};
+        let dropped = xform_crds_with(&mut self.as_crds(), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
     }
 }
 
@@ -482,6 +712,7 @@ impl GeoJSONPolygon for twkb::Polygon {
         Polygon {
             type_name: "Polygon".to_owned(),
             crs: None,
+            dims: dims_of_first(self.rings.iter().flat_map(|ring| ring.points.iter())),
             coordinates: rings,
         }
     }
@@ -499,6 +730,7 @@ impl GeoJSONPolygon for ewkb::Polygon {
         Polygon {
             type_name: "Polygon".to_owned(),
             crs: self.srid,
+            dims: dims_of_first(self.rings.iter().flat_map(|ring| ring.points.iter())),
             coordinates: rings,
         }
     }
@@ -511,12 +743,15 @@ pub struct MultiPoint {
     #[serde(serialize_with = "crs_serializer")]
     pub crs: Option<i32>,
     pub coordinates: Vec<Vec<f64>>,
+    #[serde(skip_serializing)]
+    pub dims: Option<Dims>,
 }
 impl GeoJSONMultiPoint for twkb::MultiPoint {
     fn to_geojson(&self) -> MultiPoint {
         MultiPoint {
             type_name: "MultiPoint".to_owned(),
             crs: None,
+            dims: dims_of_first(self.points.iter()),
             coordinates: self.points.iter().map(|pnt| pnt.crds()).collect(),
         }
     }
@@ -527,20 +762,34 @@ impl GeoJSONMultiPoint for ewkb::MultiPoint {
         MultiPoint {
             type_name: "MultiPoint".to_owned(),
             crs: self.srid,
+            dims: dims_of_first(self.points.iter()),
             coordinates: self.points.iter().map(|pnt| pnt.crds()).collect(),
         }
     }
 }
 
+impl WriteEwkbAs for MultiPoint {
+    fn write_ewkb_as<P: FromCrds>(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        let mut data = vec![];
+        let mut geom = MultiPointT::<P>::new();
+        geom.points = self
+            .coordinates
+            .iter()
+            .map(|crds| P::from_crds(crds, self.crs))
+            .collect::<Result<_, _>>()?;
+        geom.srid = self.crs;
+        geom.as_ewkb().write_ewkb_with(&mut data, byte_order)?;
+        Ok(data)
+    }
+}
+
 impl GeoJSONEncode for MultiPoint {
     fn as_str(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
-    fn has_z(&self) -> bool {
-        !self.coordinates.is_empty() && self.coordinates[0].len() == 3
-    }
-    fn has_zm(&self) -> bool {
-        !self.coordinates.is_empty() && self.coordinates[0].len() == 4
+    fn dims(&self) -> Dims {
+        self.dims
+            .unwrap_or_else(|| infer_dims(self.coordinates.first().map(Vec::len)))
     }
     fn srid(&self) -> Option<i32> {
         self.crs
@@ -551,45 +800,18 @@ impl GeoJSONEncode for MultiPoint {
     fn geom_type(&self) -> GeometryType {
         GeometryType::MultiPoint
     }
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
-        let mut data = vec![];
-        if self.has_zm() {
-            let mut geom = ewkb::MultiPointZM::new();
-            let pnts = self
-                .coordinates
-                .iter()
-                .map(|crds| to_ewkb_pointzm(crds, self.crs))
-                .collect();
-            geom.points = pnts;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else if self.has_z() {
-            let mut geom = ewkb::MultiPointZ::new();
-            let pnts = self
-                .coordinates
-                .iter()
-                .map(|crds| to_ewkb_pointz(crds, self.crs))
-                .collect();
-            geom.points = pnts;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else {
-            let mut geom = ewkb::MultiPoint::new();
-            let pnts = self
-                .coordinates
-                .iter()
-                .map(|crds| to_ewkb_point(crds, self.crs))
-                .collect();
-            geom.points = pnts;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        }
-        Ok(data)
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        write_ewkb_dims(self, byte_order)
     }
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error> {
-        use crate::reproject::{xform_crds, AsCrds};
-        xform_crds(&mut self.as_crds(), &xform)
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        use crate::reproject::{xform_crds_with, AsCrds, DropVertices};
+        let dropped = xform_crds_with(&mut self.as_crds(), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
     }
 }
 
@@ -600,12 +822,15 @@ pub struct MultiLineString {
     #[serde(serialize_with = "crs_serializer")]
     pub crs: Option<i32>,
     pub coordinates: Vec<Vec<Vec<f64>>>,
+    #[serde(skip_serializing)]
+    pub dims: Option<Dims>,
 }
 impl GeoJSONMultiLineString for twkb::MultiLineString {
     fn to_geojson(&self) -> MultiLineString {
         MultiLineString {
             type_name: "MultiLineString".to_owned(),
             crs: None,
+            dims: dims_of_first(self.lines.iter().flat_map(|line| line.points.iter())),
             coordinates: self
                 .lines
                 .iter()
@@ -620,6 +845,7 @@ impl GeoJSONMultiLineString for ewkb::MultiLineString {
         MultiLineString {
             type_name: "MultiLineString".to_owned(),
             crs: self.srid,
+            dims: dims_of_first(self.lines.iter().flat_map(|line| line.points.iter())),
             coordinates: self
                 .lines
                 .iter()
@@ -629,25 +855,38 @@ impl GeoJSONMultiLineString for ewkb::MultiLineString {
     }
 }
 
+impl WriteEwkbAs for MultiLineString {
+    fn write_ewkb_as<P: FromCrds>(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::with_capacity(9 + 8 * 3 * self.coordinates.len());
+        let mut geom = MultiLineStringT::<P>::new();
+        geom.lines = self
+            .coordinates
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|crds| P::from_crds(crds, self.crs))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        geom.srid = self.crs;
+        geom.as_ewkb().write_ewkb_with(&mut data, byte_order)?;
+        Ok(data)
+    }
+}
+
 impl GeoJSONEncode for MultiLineString {
     fn as_str(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
-    fn has_z(&self) -> bool {
-        if !self.coordinates.is_empty() {
-            let line = &self.coordinates[0];
-            !line.is_empty() && line[0].len() == 3
-        } else {
-            false
-        }
-    }
-    fn has_zm(&self) -> bool {
-        if !self.coordinates.is_empty() {
-            let line = &self.coordinates[0];
-            !line.is_empty() && line[0].len() == 4
-        } else {
-            false
-        }
+    fn dims(&self) -> Dims {
+        self.dims.unwrap_or_else(|| {
+            infer_dims(
+                self.coordinates
+                    .first()
+                    .and_then(|line| line.first())
+                    .map(Vec::len),
+            )
+        })
     }
     fn srid(&self) -> Option<i32> {
         self.crs
@@ -659,60 +898,18 @@ impl GeoJSONEncode for MultiLineString {
         GeometryType::MultiLineString
     }
 
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
-        let mut data = Vec::with_capacity(9 + 8 * 3 * self.coordinates.len());
-        if self.has_zm() {
-            let mut geom = ewkb::MultiLineStringZM::new();
-            let lines = self
-                .coordinates
-                .iter()
-                .map(|lines| {
-                    lines
-                        .iter()
-                        .map(|crds| to_ewkb_pointzm(crds, self.crs))
-                        .collect()
-                })
-                .collect();
-            geom.lines = lines;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else if self.has_z() {
-            let mut geom = ewkb::MultiLineStringZ::new();
-            let lines = self
-                .coordinates
-                .iter()
-                .map(|lines| {
-                    lines
-                        .iter()
-                        .map(|crds| to_ewkb_pointz(crds, self.crs))
-                        .collect()
-                })
-                .collect();
-            geom.lines = lines;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else {
-            let mut geom = ewkb::MultiLineString::new();
-            let lines = self
-                .coordinates
-                .iter()
-                .map(|lines| {
-                    lines
-                        .iter()
-                        .map(|crds| to_ewkb_point(crds, self.crs))
-                        .collect()
-                })
-                .collect();
-            geom.lines = lines;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        }
-        Ok(data)
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        write_ewkb_dims(self, byte_order)
     }
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error> {
-        use crate::reproject::{xform_crds, AsCrds};
-        xform_crds(&mut self.as_crds(), &xform)
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        use crate::reproject::{xform_crds_with, AsCrds, DropVertices};
+        let dropped = xform_crds_with(&mut self.as_crds(), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
     }
 }
 
@@ -723,36 +920,46 @@ pub struct MultiPolygon {
     #[serde(serialize_with = "crs_serializer")]
     pub crs: Option<i32>,
     pub coordinates: Vec<Vec<Vec<Vec<f64>>>>,
+    #[serde(skip_serializing)]
+    pub dims: Option<Dims>,
 }
+impl WriteEwkbAs for MultiPolygon {
+    fn write_ewkb_as<P: FromCrds>(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        let mut data = vec![];
+        let mut geom = MultiPolygonT::<P>::new();
+        geom.polygons = self
+            .coordinates
+            .iter()
+            .map(|poly| {
+                poly.iter()
+                    .map(|ring| {
+                        ring.iter()
+                            .map(|crds| P::from_crds(crds, self.crs))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        geom.srid = self.crs;
+        geom.as_ewkb().write_ewkb_with(&mut data, byte_order)?;
+        Ok(data)
+    }
+}
+
 impl GeoJSONEncode for MultiPolygon {
     fn as_str(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
-    fn has_z(&self) -> bool {
-        if !self.coordinates.is_empty() {
-            let poly = &self.coordinates[0];
-            if !poly.is_empty() {
-                let ring = &poly[0];
-                !ring.is_empty() && ring[0].len() == 3
-            } else {
-                false
-            }
-        } else {
-            false
-        }
-    }
-    fn has_zm(&self) -> bool {
-        if !self.coordinates.is_empty() {
-            let poly = &self.coordinates[0];
-            if !poly.is_empty() {
-                let ring = &poly[0];
-                !ring.is_empty() && ring[0].len() == 4
-            } else {
-                false
-            }
-        } else {
-            false
-        }
+    fn dims(&self) -> Dims {
+        self.dims.unwrap_or_else(|| {
+            infer_dims(
+                self.coordinates
+                    .first()
+                    .and_then(|poly| poly.first())
+                    .and_then(|ring| ring.first())
+                    .map(Vec::len),
+            )
+        })
     }
     fn srid(&self) -> Option<i32> {
         self.crs
@@ -763,69 +970,18 @@ impl GeoJSONEncode for MultiPolygon {
     fn geom_type(&self) -> GeometryType {
         GeometryType::MultiPolygon
     }
-    fn to_ewkb(&self) -> Result<Vec<u8>, Error> {
-        let mut data = vec![];
-        if self.has_zm() {
-            let mut geom = ewkb::MultiPolygonZM::new();
-            let polys = self
-                .coordinates
-                .iter()
-                .map(|poly| {
-                    poly.iter()
-                        .map(|ring| {
-                            ring.iter()
-                                .map(|crds| to_ewkb_pointzm(crds, self.crs))
-                                .collect()
-                        })
-                        .collect()
-                })
-                .collect();
-            geom.polygons = polys;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else if self.has_z() {
-            let mut geom = ewkb::MultiPolygonZ::new();
-            let polys = self
-                .coordinates
-                .iter()
-                .map(|poly| {
-                    poly.iter()
-                        .map(|ring| {
-                            ring.iter()
-                                .map(|crds| to_ewkb_pointz(crds, self.crs))
-                                .collect()
-                        })
-                        .collect()
-                })
-                .collect();
-            geom.polygons = polys;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        } else {
-            let mut geom = ewkb::MultiPolygon::new();
-            let polys = self
-                .coordinates
-                .iter()
-                .map(|poly| {
-                    poly.iter()
-                        .map(|ring| {
-                            ring.iter()
-                                .map(|crds| to_ewkb_point(crds, self.crs))
-                                .collect()
-                        })
-                        .collect()
-                })
-                .collect();
-            geom.polygons = polys;
-            geom.srid = self.crs;
-            geom.as_ewkb().write_ewkb(&mut data)?;
-        }
-        Ok(data)
+    fn to_ewkb_with(&self, byte_order: Endianness) -> Result<Vec<u8>, Error> {
+        write_ewkb_dims(self, byte_order)
     }
-    #[cfg(feature = "proj")]
-    fn transform(&mut self, xform: &proj::Proj) -> Result<(), Error> {
-        use crate::reproject::{xform_crds, AsCrds};
-        xform_crds(&mut self.as_crds(), &xform)
+    fn transform_with(
+        &mut self,
+        xform: &Transform,
+        options: &TransformOptions,
+    ) -> Result<(), Error> {
+        use crate::reproject::{xform_crds_with, AsCrds, DropVertices};
+        let dropped = xform_crds_with(&mut self.as_crds(), xform, options)?;
+        self.drop_vertices(&dropped);
+        Ok(())
     }
 }
 
@@ -845,6 +1001,12 @@ impl GeoJSONMultiPolygon for twkb::MultiPolygon {
         MultiPolygon {
             type_name: "MultiPolygon".to_owned(),
             crs: None,
+            dims: dims_of_first(
+                self.polygons
+                    .iter()
+                    .flat_map(|poly| poly.rings.iter())
+                    .flat_map(|ring| ring.points.iter()),
+            ),
             coordinates: polygons,
         }
     }
@@ -866,18 +1028,106 @@ impl GeoJSONMultiPolygon for ewkb::MultiPolygon {
         MultiPolygon {
             type_name: "MultiPolygon".to_owned(),
             crs: self.srid,
+            dims: dims_of_first(
+                self.polygons
+                    .iter()
+                    .flat_map(|poly| poly.rings.iter())
+                    .flat_map(|ring| ring.points.iter()),
+            ),
             coordinates: polygons,
         }
     }
 }
 
+// --- Flat geometries
+
+fn flat_coordinates<C: flat::CoordType>(coords: &[C]) -> Vec<Vec<f64>> {
+    coords.iter().map(|crd| crd.values().to_vec()).collect()
+}
+
+fn flat_dims<C: flat::CoordType>(coords: &[C]) -> Option<Dims> {
+    if coords.is_empty() {
+        None
+    } else {
+        Some(C::DIMS)
+    }
+}
+
+impl<C: flat::CoordType> GeoJSONLineString for flat::LineStringT<C> {
+    fn to_geojson(&self) -> LineString {
+        LineString {
+            type_name: "LineString".to_owned(),
+            crs: self.srid,
+            dims: flat_dims(&self.coords),
+            coordinates: flat_coordinates(&self.coords),
+        }
+    }
+}
+
+impl<C: flat::CoordType> GeoJSONPolygon for flat::PolygonT<C> {
+    fn to_geojson(&self) -> Polygon {
+        Polygon {
+            type_name: "Polygon".to_owned(),
+            crs: self.srid,
+            dims: flat_dims(self.coords()),
+            coordinates: self
+                .rings()
+                .map(|ring| flat_coordinates(ring.as_slice()))
+                .collect(),
+        }
+    }
+}
+
+impl<C: flat::CoordType> GeoJSONMultiPoint for flat::MultiPointT<C> {
+    fn to_geojson(&self) -> MultiPoint {
+        MultiPoint {
+            type_name: "MultiPoint".to_owned(),
+            crs: self.srid,
+            dims: flat_dims(&self.coords),
+            coordinates: flat_coordinates(&self.coords),
+        }
+    }
+}
+
+impl<C: flat::CoordType> GeoJSONMultiLineString for flat::MultiLineStringT<C> {
+    fn to_geojson(&self) -> MultiLineString {
+        MultiLineString {
+            type_name: "MultiLineString".to_owned(),
+            crs: self.srid,
+            dims: flat_dims(self.coords()),
+            coordinates: self
+                .lines()
+                .map(|line| flat_coordinates(line.as_slice()))
+                .collect(),
+        }
+    }
+}
+
+impl<C: flat::CoordType> GeoJSONMultiPolygon for flat::MultiPolygonT<C> {
+    fn to_geojson(&self) -> MultiPolygon {
+        MultiPolygon {
+            type_name: "MultiPolygon".to_owned(),
+            crs: self.srid,
+            dims: self
+                .polygons
+                .iter()
+                .find_map(|poly| flat_dims(poly.coords())),
+            coordinates: self
+                .polygons
+                .iter()
+                .map(|poly| poly.to_geojson().coordinates)
+                .collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
         ewkb::{self, EwkbRead},
         geojson::{
-            GeoJSONEncode, GeoJSONLineString, GeoJSONMultiLineString, GeoJSONMultiPoint,
-            GeoJSONMultiPolygon, GeoJSONPoint, GeoJSONPolygon,
+            Dims, GeoJSONEncode, GeoJSONLineString, GeoJSONMultiLineString, GeoJSONMultiPoint,
+            GeoJSONMultiPolygon, GeoJSONPoint, GeoJSONPolygon, LineString, MultiPolygon, Point,
         },
         twkb::{self, TwkbGeom},
     };
@@ -916,7 +1166,7 @@ mod test {
         let geojson_point = point.to_geojson();
         assert_eq!(
             format!("{:.0?}", geojson_point),
-            "Point { type_name: \"Point\", crs: None, coordinates: [1, 2, 4] }"
+            "Point { type_name: \"Point\", crs: None, coordinates: [1, 2, 4], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.0?}", geojson_point.as_str()),
@@ -926,6 +1176,35 @@ mod test {
         assert_eq!(encoded, ewkb_data);
     }
 
+    #[test]
+    fn test_to_ewkb_invalid_coordinates() {
+        let point = Point {
+            type_name: "Point".to_string(),
+            crs: None,
+            dims: None,
+            coordinates: vec![1.0],
+        };
+        assert!(point.to_ewkb().is_err());
+        // Dimensions are taken from the first coordinate
+        let line = LineString {
+            type_name: "LineString".to_string(),
+            crs: None,
+            dims: None,
+            coordinates: vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0]],
+        };
+        assert_eq!(
+            line.to_ewkb().unwrap_err().to_string(),
+            "Write(\"invalid coordinate [4.0, 5.0] - expected 3 values\")"
+        );
+        let polygon = MultiPolygon {
+            type_name: "MultiPolygon".to_string(),
+            crs: None,
+            dims: None,
+            coordinates: vec![vec![vec![vec![1.0, 2.0], vec![]]]],
+        };
+        assert!(polygon.to_ewkb().is_err());
+    }
+
     #[test]
     fn test_ewkb_readwrite_line() {
         let ewkb_data = hex_to_vec("010200008002000000000000000000f03f0000000000000040000000000000144000000000000024400000000000002e400000000000003640"); // 3D LineString
@@ -934,7 +1213,7 @@ mod test {
 
         assert_eq!(
             format!("{:.0?}", geojson_line),
-            "LineString { type_name: \"LineString\", crs: None, coordinates: [[1, 2, 5], [10, 15, 22]] }"
+            "LineString { type_name: \"LineString\", crs: None, coordinates: [[1, 2, 5], [10, 15, 22]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.0?}", geojson_line.as_str()),
@@ -952,7 +1231,7 @@ mod test {
 
         assert_eq!(
             format!("{:.2?}", geojson_poly),
-            "Polygon { type_name: \"Polygon\", crs: Some(4326), coordinates: [[[24.95, 60.32, 3.00], [24.93, 60.31, 3.00], [24.95, 60.31, 3.00], [24.98, 60.31, 3.00], [24.99, 60.32, 3.00], [24.98, 60.33, 3.00], [24.95, 60.32, 3.00]]] }"
+            "Polygon { type_name: \"Polygon\", crs: Some(4326), coordinates: [[[24.95, 60.32, 3.00], [24.93, 60.31, 3.00], [24.95, 60.31, 3.00], [24.98, 60.31, 3.00], [24.99, 60.32, 3.00], [24.98, 60.33, 3.00], [24.95, 60.32, 3.00]]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.2?}", geojson_poly.as_str()),
@@ -972,7 +1251,7 @@ mod test {
         let geojson_geom = geom.to_geojson();
         assert_eq!(
             format!("{:.1?}", geojson_geom),
-            "MultiPoint { type_name: \"MultiPoint\", crs: None, coordinates: [[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]] }"
+            "MultiPoint { type_name: \"MultiPoint\", crs: None, coordinates: [[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.1?}", geojson_geom.as_str()),
@@ -990,7 +1269,7 @@ mod test {
         let geojson_geom = geom.to_geojson();
         assert_eq!(
             format!("{:.1?}", geojson_geom),
-            "MultiLineString { type_name: \"MultiLineString\", crs: None, coordinates: [[[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]], [[30.0, 40.0, 0.0], [35.0, 45.0, 5.0]]] }"
+            "MultiLineString { type_name: \"MultiLineString\", crs: None, coordinates: [[[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]], [[30.0, 40.0, 0.0], [35.0, 45.0, 5.0]]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.1?}", geojson_geom.as_str()),
@@ -1005,7 +1284,7 @@ mod test {
         let ewkb_data = hex_to_vec("01060000800100000001030000800100000004000000a01a2fdd1e67114191ed7cff238f5941000000000000000052b81e0517671141931804ce228f594100000000000000009cc420b0036711417b14ae1f238f59410000000000000000a01a2fdd1e67114191ed7cff238f59410000000000000000"); // 2D MultiPolygon
         let poly = ewkb::MultiPolygon::read_ewkb(&mut ewkb_data.as_slice()).unwrap();
         let geojson_geom = poly.to_geojson();
-        assert_eq!(format!("{:.1?}", geojson_geom), "MultiPolygon { type_name: \"MultiPolygon\", crs: None, coordinates: [[[[285127.7, 6700176.0, 0.0], [285125.8, 6700171.2, 0.0], [285120.9, 6700172.5, 0.0], [285127.7, 6700176.0, 0.0]]]] }");
+        assert_eq!(format!("{:.1?}", geojson_geom), "MultiPolygon { type_name: \"MultiPolygon\", crs: None, coordinates: [[[[285127.7, 6700176.0, 0.0], [285125.8, 6700171.2, 0.0], [285120.9, 6700172.5, 0.0], [285127.7, 6700176.0, 0.0]]]], dims: Some(Xyz) }");
         assert_eq!(format!("{:.1?}", geojson_geom.as_str()), "\"{\\\"type\\\":\\\"MultiPolygon\\\",\\\"crs\\\":null,\\\"coordinates\\\":[[[[285127.716,6700175.992,0.0],[285125.755,6700171.219,0.0],[285120.922,6700172.495,0.0],[285127.716,6700175.992,0.0]]]]}\"");
         let encoded = geojson_geom.to_ewkb().unwrap();
         assert_eq!(encoded, ewkb_data);
@@ -1018,7 +1297,7 @@ mod test {
         let geojson_geom = geom.to_geojson();
         assert_eq!(
             format!("{:.1?}", geojson_geom),
-            "Point { type_name: \"Point\", crs: None, coordinates: [10.0, 20.0, 5.0] }"
+            "Point { type_name: \"Point\", crs: None, coordinates: [10.0, 20.0, 5.0], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.1?}", geojson_geom.as_str()),
@@ -1033,7 +1312,7 @@ mod test {
         let geojson_geom = geom.to_geojson();
         assert_eq!(
             format!("{:.1?}", geojson_geom),
-            "LineString { type_name: \"LineString\", crs: None, coordinates: [[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]] }"
+            "LineString { type_name: \"LineString\", crs: None, coordinates: [[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.1?}", geojson_geom.as_str()),
@@ -1048,7 +1327,7 @@ mod test {
         let geojson_geom = geom.to_geojson();
         assert_eq!(
             format!("{:.1?}", geojson_geom),
-            "Polygon { type_name: \"Polygon\", crs: None, coordinates: [[[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0], [10.0, 20.0, 0.0]]] }"
+            "Polygon { type_name: \"Polygon\", crs: None, coordinates: [[[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0], [10.0, 20.0, 0.0]]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.1?}", geojson_geom.as_str()),
@@ -1063,7 +1342,7 @@ mod test {
         let geojson_geom = geom.to_geojson();
         assert_eq!(
             format!("{:.1?}", geojson_geom),
-            "MultiPoint { type_name: \"MultiPoint\", crs: None, coordinates: [[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]] }"
+            "MultiPoint { type_name: \"MultiPoint\", crs: None, coordinates: [[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.1?}", geojson_geom.as_str()),
@@ -1079,7 +1358,7 @@ mod test {
         let geojson_geom = geom.to_geojson();
         assert_eq!(
             format!("{:.1?}", geojson_geom),
-            "MultiLineString { type_name: \"MultiLineString\", crs: None, coordinates: [[[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]], [[30.0, 40.0, 0.0], [35.0, 45.0, 5.0]]] }"
+            "MultiLineString { type_name: \"MultiLineString\", crs: None, coordinates: [[[10.0, 20.0, 0.0], [15.0, 25.0, 5.0], [20.0, 30.0, 10.0]], [[30.0, 40.0, 0.0], [35.0, 45.0, 5.0]]], dims: Some(Xyz) }"
         );
         assert_eq!(
             format!("{:.1?}", geojson_geom.as_str()),
@@ -1092,8 +1371,8 @@ mod test {
         let twkb = hex_to_vec("660801010104c8d0f58f02f0c9e4f53100d11ec94a00c14bf81300946ad23600"); // 2D MultiPolygon
         let poly = twkb::MultiPolygon::read_twkb(&mut twkb.as_slice()).unwrap();
         let geojson_poly = poly.to_geojson();
-        assert_eq!(format!("{:.1?}", geojson_poly), "MultiPolygon { type_name: \"MultiPolygon\", crs: None, coordinates: [[[[285127.7, 6700176.0, 0.0], [285125.8, 6700171.2, 0.0], [285120.9, 6700172.5, 0.0], [285127.7, 6700176.0, 0.0]]]] }");
-        assert_eq!(format!("{:.1?}", geojson_poly.as_str()), "\"{\\\"type\\\":\\\"MultiPolygon\\\",\\\"crs\\\":null,\\\"coordinates\\\":[[[[285127.716,6700175.992,0.0],[285125.755,6700171.219,0.0],[285120.922,6700172.494999999,0.0],[285127.716,6700175.992,0.0]]]]}\"");
+        assert_eq!(format!("{:.1?}", geojson_poly), "MultiPolygon { type_name: \"MultiPolygon\", crs: None, coordinates: [[[[285127.7, 6700176.0, 0.0], [285125.8, 6700171.2, 0.0], [285120.9, 6700172.5, 0.0], [285127.7, 6700176.0, 0.0]]]], dims: Some(Xyz) }");
+        assert_eq!(format!("{:.1?}", geojson_poly.as_str()), "\"{\\\"type\\\":\\\"MultiPolygon\\\",\\\"crs\\\":null,\\\"coordinates\\\":[[[[285127.716,6700175.992,0.0],[285125.755,6700171.219,0.0],[285120.922,6700172.495,0.0],[285127.716,6700175.992,0.0]]]]}\"");
     }
 
     #[test]
@@ -1105,7 +1384,7 @@ mod test {
         let geojson_point = point.to_geojson();
         assert_eq!(
             format!("{:.0?}", geojson_point),
-            "Point { type_name: \"Point\", crs: None, coordinates: [1, 2, 4, 5] }"
+            "Point { type_name: \"Point\", crs: None, coordinates: [1, 2, 4, 5], dims: Some(Xyzm) }"
         );
         assert_eq!(
             format!("{:.0?}", geojson_point.as_str()),
@@ -1115,6 +1394,42 @@ mod test {
         assert_eq!(encoded, ewkb_data);
     }
 
+    #[test]
+    fn test_ewkb_readwrite_m() {
+        let ewkb_data = hex_to_vec("0101000040000000000000f03f00000000000000400000000000001040"); // M Point
+        let point = ewkb::Point::read_ewkb(&mut ewkb_data.as_slice()).unwrap();
+        let mut geojson_point = point.to_geojson();
+        assert_eq!(
+            format!("{:.0?}", geojson_point),
+            "Point { type_name: \"Point\", crs: None, coordinates: [1, 2, 4], dims: Some(Xym) }"
+        );
+        assert!(geojson_point.has_m());
+        assert_eq!(geojson_point.to_ewkb().unwrap(), ewkb_data);
+        // Without explicit dims three values are taken as XYZ
+        geojson_point.dims = None;
+        assert!(geojson_point.has_z());
+
+        let ewkb_data = hex_to_vec("010200004002000000000000000000f03f0000000000000040000000000000144000000000000024400000000000002e400000000000003640"); // M LineString
+        let line = ewkb::LineString::read_ewkb(&mut ewkb_data.as_slice()).unwrap();
+        let geojson_line = line.to_geojson();
+        assert_eq!(geojson_line.dims, Some(Dims::Xym));
+        assert_eq!(geojson_line.to_ewkb().unwrap(), ewkb_data);
+        let json: serde_json::Value = serde_json::from_str(&geojson_line.as_str()).unwrap();
+        // The layout is not part of the GeoJSON output
+        assert!(json.get("dims").is_none());
+        assert_eq!(
+            "XYZ".parse::<Dims>().unwrap().to_string(),
+            Dims::Xyz.as_str()
+        );
+        assert!("XZ".parse::<Dims>().is_err());
+
+        // SELECT ST_AsTWKB('POINT M (1 2 4)'::geometry)
+        let point = twkb::Point::read_twkb(&mut hex_to_vec("010802020408").as_slice()).unwrap();
+        let geojson_point = point.to_geojson();
+        assert_eq!(geojson_point.coordinates, vec![1.0, 2.0, 4.0]);
+        assert_eq!(geojson_point.dims, Some(Dims::Xym));
+    }
+
     #[test]
     fn test_ewkb_readwrite_linezm() {
         let ewkb_data = hex_to_vec("01020000c002000000000000000000f03f00000000000000400000000000001040000000000000144000000000000024400000000000002e4000000000000034400000000000003840"); // 3D LineString
@@ -1123,7 +1438,7 @@ mod test {
 
         assert_eq!(
             format!("{:.0?}", geojson_line),
-            "LineString { type_name: \"LineString\", crs: None, coordinates: [[1, 2, 4, 5], [10, 15, 20, 24]] }"
+            "LineString { type_name: \"LineString\", crs: None, coordinates: [[1, 2, 4, 5], [10, 15, 20, 24]], dims: Some(Xyzm) }"
         );
         assert_eq!(
             format!("{:.0?}", geojson_line.as_str()),
@@ -1141,7 +1456,7 @@ mod test {
 
         assert_eq!(
             format!("{:.2?}", geojson_poly),
-            "Polygon { type_name: \"Polygon\", crs: Some(4326), coordinates: [[[24.95, 60.32, 3.00, 10.00], [24.93, 60.31, 3.00, 20.00], [24.95, 60.31, 3.00, 30.00], [24.98, 60.31, 3.00, 40.00], [24.99, 60.32, 3.00, 50.00], [24.98, 60.33, 3.00, 60.00], [24.95, 60.32, 3.00, 10.00]]] }"
+            "Polygon { type_name: \"Polygon\", crs: Some(4326), coordinates: [[[24.95, 60.32, 3.00, 10.00], [24.93, 60.31, 3.00, 20.00], [24.95, 60.31, 3.00, 30.00], [24.98, 60.31, 3.00, 40.00], [24.99, 60.32, 3.00, 50.00], [24.98, 60.33, 3.00, 60.00], [24.95, 60.32, 3.00, 10.00]]], dims: Some(Xyzm) }"
         );
         assert_eq!(
             format!("{:.2?}", geojson_poly.as_str()),
@@ -1159,7 +1474,7 @@ mod test {
 
         assert_eq!(
             format!("{:.2?}", geojson_poly),
-            "MultiPoint { type_name: \"MultiPoint\", crs: Some(4326), coordinates: [[10.00, 20.00, 0.00, 5.00], [15.00, 25.00, 5.00, 10.00], [20.00, 30.00, 10.00, 15.00]] }"
+            "MultiPoint { type_name: \"MultiPoint\", crs: Some(4326), coordinates: [[10.00, 20.00, 0.00, 5.00], [15.00, 25.00, 5.00, 10.00], [20.00, 30.00, 10.00, 15.00]], dims: Some(Xyzm) }"
         );
         assert_eq!(
             format!("{:.2?}", geojson_poly.as_str()),
@@ -1177,7 +1492,7 @@ mod test {
 
         assert_eq!(
             format!("{:.2?}", geojson_multiline),
-            "MultiLineString { type_name: \"MultiLineString\", crs: Some(4326), coordinates: [[[10.00, 20.00, 0.00, 5.00], [15.00, 25.00, 5.00, 10.00]], [[20.00, 30.00, 10.00, 15.00], [25.00, 35.00, 15.00, 20.00]]] }"
+            "MultiLineString { type_name: \"MultiLineString\", crs: Some(4326), coordinates: [[[10.00, 20.00, 0.00, 5.00], [15.00, 25.00, 5.00, 10.00]], [[20.00, 30.00, 10.00, 15.00], [25.00, 35.00, 15.00, 20.00]]], dims: Some(Xyzm) }"
         );
         assert_eq!(
             format!("{:.2?}", geojson_multiline.as_str()),
@@ -1194,7 +1509,7 @@ mod test {
         let geojson_multipolygon = multipolygon.to_geojson();
         assert_eq!(
             format!("{:.2?}", geojson_multipolygon),
-            "MultiPolygon { type_name: \"MultiPolygon\", crs: Some(4326), coordinates: [[[[10.00, 20.00, 0.00, 5.00], [15.00, 25.00, 5.00, 10.00], [20.00, 30.00, 10.00, 15.00], [10.00, 20.00, 0.00, 5.00]]], [[[20.00, 30.00, 10.00, 15.00], [25.00, 35.00, 15.00, 20.00], [30.00, 40.00, 20.00, 25.00], [20.00, 30.00, 10.00, 15.00]]]] }"
+            "MultiPolygon { type_name: \"MultiPolygon\", crs: Some(4326), coordinates: [[[[10.00, 20.00, 0.00, 5.00], [15.00, 25.00, 5.00, 10.00], [20.00, 30.00, 10.00, 15.00], [10.00, 20.00, 0.00, 5.00]]], [[[20.00, 30.00, 10.00, 15.00], [25.00, 35.00, 15.00, 20.00], [30.00, 40.00, 20.00, 25.00], [20.00, 30.00, 10.00, 15.00]]]], dims: Some(Xyzm) }"
         );
         assert_eq!(
             format!("{:.2?}", geojson_multipolygon.as_str()),
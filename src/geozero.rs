@@ -0,0 +1,530 @@
+// geozero support
+//
+// Readers drive any `GeomProcessor` through a visitor translating the events of
+// `visit::Visitor`, and `EwkbWriter` translates processor events back into those of the EWKB
+// writing visitor.
+extern crate geozero;
+
+use self::geozero::error::{GeozeroError, Result as GeozeroResult};
+pub use self::geozero::{CoordDimensions, GeomProcessor, GeozeroGeometry};
+use std::io::{Read, Write};
+
+use crate::error::Error;
+use crate::ewkb::{self, Endianness, PointType};
+use crate::geojson::Dims;
+use crate::limits::{expect_end, ParseLimits};
+use crate::twkb;
+use crate::visit::{self, Visitor};
+
+impl From<GeozeroError> for Error {
+    fn from(e: GeozeroError) -> Error {
+        Error::Other(format!("geozero error: {}", e))
+    }
+}
+
+fn geozero_error(e: Error) -> GeozeroError {
+    GeozeroError::Geometry(e.to_string())
+}
+
+fn dims_of(point_type: PointType) -> CoordDimensions {
+    match point_type {
+        PointType::Point => CoordDimensions::xy(),
+        PointType::PointZ => CoordDimensions::xyz(),
+        PointType::PointM => CoordDimensions::xym(),
+        PointType::PointZM => CoordDimensions::xyzm(),
+    }
+}
+
+/// Read an EWKB geometry, passing it to `processor` while reading instead of building it.
+///
+/// The SRID is passed before the geometry. Points with NaN coordinates are passed as empty
+/// points, except in multipoints.
+pub fn process_ewkb<R: Read, P: GeomProcessor>(
+    raw: &mut R,
+    processor: &mut P,
+    limits: &ParseLimits,
+) -> Result<(), Error> {
+    ewkb::visit_ewkb(raw, &mut ProcessorVisitor::new(processor), limits)
+}
+
+/// Read a TWKB geometry, passing it to `processor` while reading instead of building it.
+pub fn process_twkb<R: Read, P: GeomProcessor>(
+    raw: &mut R,
+    processor: &mut P,
+    limits: &ParseLimits,
+) -> Result<(), Error> {
+    twkb::visit_twkb(raw, &mut ProcessorVisitor::new(processor), limits)
+}
+
+/// EWKB bytes of one geometry, read with default limits when processed.
+#[derive(Clone, Copy, Debug)]
+pub struct Ewkb<'a>(pub &'a [u8]);
+
+impl GeozeroGeometry for Ewkb<'_> {
+    fn process_geom<P: GeomProcessor>(&self, processor: &mut P) -> GeozeroResult<()> {
+        let mut raw = self.0;
+        process_ewkb(&mut raw, processor, &ParseLimits::default())
+            .and_then(|_| expect_end(&mut raw))
+            .map_err(geozero_error)
+    }
+
+    fn dims(&self) -> CoordDimensions {
+        dims_of(ewkb::get_point_type(self.0))
+    }
+
+    fn srid(&self) -> Option<i32> {
+        ewkb::get_srid(self.0)
+    }
+}
+
+/// TWKB bytes of one geometry, read with default limits when processed.
+#[derive(Clone, Copy, Debug)]
+pub struct Twkb<'a>(pub &'a [u8]);
+
+impl GeozeroGeometry for Twkb<'_> {
+    fn process_geom<P: GeomProcessor>(&self, processor: &mut P) -> GeozeroResult<()> {
+        let mut raw = self.0;
+        process_twkb(&mut raw, processor, &ParseLimits::default())
+            .and_then(|_| expect_end(&mut raw))
+            .map_err(geozero_error)
+    }
+
+    fn dims(&self) -> CoordDimensions {
+        // Extended dimensions follow the type and metadata header bytes.
+        match self.0.get(..3) {
+            Some([_, metadata, ext]) if metadata & 0b1000 != 0 => CoordDimensions {
+                z: ext & 0b01 != 0,
+                m: ext & 0b10 != 0,
+                ..CoordDimensions::xy()
+            },
+            _ => CoordDimensions::xy(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Point,
+    LineString,
+    Polygon,
+    Ring,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+    Collection,
+}
+
+/// Geometry being passed, with its index in its parent and the count of its members so far.
+struct Part {
+    kind: Kind,
+    idx: usize,
+    members: usize,
+}
+
+/// Visitor passing geometries on to a geozero processor.
+struct ProcessorVisitor<'a, P> {
+    processor: &'a mut P,
+    parts: Vec<Part>,
+    empty_point: bool,
+}
+
+impl<'a, P: GeomProcessor> ProcessorVisitor<'a, P> {
+    fn new(processor: &'a mut P) -> Self {
+        ProcessorVisitor {
+            processor,
+            parts: Vec::new(),
+            empty_point: false,
+        }
+    }
+
+    fn parent(&self) -> Option<Kind> {
+        self.parts.last().map(|part| part.kind)
+    }
+
+    /// Index of a new member of the current part.
+    fn next_idx(&mut self) -> usize {
+        match self.parts.last_mut() {
+            Some(part) => {
+                part.members += 1;
+                part.members - 1
+            }
+            None => 0,
+        }
+    }
+
+    fn begin(&mut self, kind: Kind) -> usize {
+        let idx = self.next_idx();
+        self.parts.push(Part {
+            kind,
+            idx,
+            members: 0,
+        });
+        idx
+    }
+
+    fn end(&mut self) -> usize {
+        self.parts.pop().map_or(0, |part| part.idx)
+    }
+}
+
+impl<P: GeomProcessor> Visitor for ProcessorVisitor<'_, P> {
+    fn srid(&mut self, srid: Option<i32>) -> Result<(), Error> {
+        Ok(self.processor.srid(srid)?)
+    }
+
+    fn coord(&mut self, x: f64, y: f64, z: Option<f64>, m: Option<f64>) -> Result<(), Error> {
+        let idx = self.next_idx();
+        if self.parent() == Some(Kind::Point) {
+            let point_idx = self.parts.last().map_or(0, |part| part.idx);
+            if x.is_nan() && y.is_nan() {
+                self.empty_point = true;
+                return Ok(self.processor.empty_point(point_idx)?);
+            }
+            self.processor.point_begin(point_idx)?;
+        }
+        if self.processor.multi_dim() {
+            self.processor.coordinate(x, y, z, m, None, None, idx)?;
+        } else {
+            self.processor.xy(x, y, idx)?;
+        }
+        Ok(())
+    }
+
+    fn begin_point(&mut self, _dims: Dims) -> Result<(), Error> {
+        self.begin(Kind::Point);
+        self.empty_point = false;
+        Ok(())
+    }
+
+    fn end_point(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        if !self.empty_point {
+            self.processor.point_end(idx)?;
+        }
+        Ok(())
+    }
+
+    fn begin_linestring(&mut self, _dims: Dims, size: usize) -> Result<(), Error> {
+        let tagged = self.parent() != Some(Kind::MultiLineString);
+        let idx = self.begin(Kind::LineString);
+        Ok(self.processor.linestring_begin(tagged, size, idx)?)
+    }
+
+    fn end_linestring(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        let tagged = self.parent() != Some(Kind::MultiLineString);
+        Ok(self.processor.linestring_end(tagged, idx)?)
+    }
+
+    fn begin_polygon(&mut self, _dims: Dims, size: usize) -> Result<(), Error> {
+        let tagged = self.parent() != Some(Kind::MultiPolygon);
+        let idx = self.begin(Kind::Polygon);
+        Ok(self.processor.polygon_begin(tagged, size, idx)?)
+    }
+
+    fn begin_ring(&mut self, size: usize) -> Result<(), Error> {
+        let idx = self.begin(Kind::Ring);
+        Ok(self.processor.linestring_begin(false, size, idx)?)
+    }
+
+    fn end_ring(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        Ok(self.processor.linestring_end(false, idx)?)
+    }
+
+    fn end_polygon(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        let tagged = self.parent() != Some(Kind::MultiPolygon);
+        Ok(self.processor.polygon_end(tagged, idx)?)
+    }
+
+    fn begin_multipoint(&mut self, _dims: Dims, size: usize) -> Result<(), Error> {
+        let idx = self.begin(Kind::MultiPoint);
+        Ok(self.processor.multipoint_begin(size, idx)?)
+    }
+
+    fn end_multipoint(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        Ok(self.processor.multipoint_end(idx)?)
+    }
+
+    fn begin_multilinestring(&mut self, _dims: Dims, size: usize) -> Result<(), Error> {
+        let idx = self.begin(Kind::MultiLineString);
+        Ok(self.processor.multilinestring_begin(size, idx)?)
+    }
+
+    fn end_multilinestring(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        Ok(self.processor.multilinestring_end(idx)?)
+    }
+
+    fn begin_multipolygon(&mut self, _dims: Dims, size: usize) -> Result<(), Error> {
+        let idx = self.begin(Kind::MultiPolygon);
+        Ok(self.processor.multipolygon_begin(size, idx)?)
+    }
+
+    fn end_multipolygon(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        Ok(self.processor.multipolygon_end(idx)?)
+    }
+
+    fn begin_collection(&mut self, _dims: Dims, size: usize) -> Result<(), Error> {
+        let idx = self.begin(Kind::Collection);
+        Ok(self.processor.geometrycollection_begin(size, idx)?)
+    }
+
+    fn end_collection(&mut self) -> Result<(), Error> {
+        let idx = self.end();
+        Ok(self.processor.geometrycollection_end(idx)?)
+    }
+}
+
+/// Processor writing EWKB into `out`.
+///
+/// Coordinates are written with the dimensions given to [`EwkbWriter::new`], missing Z and M
+/// values as 0. The SRID is written if passed before the geometry.
+///
+/// ```ignore
+/// let mut writer = EwkbWriter::new(Vec::new(), CoordDimensions::xy(), Endianness::Little);
+/// Twkb(&twkb).process_geom(&mut writer)?;
+/// let ewkb = writer.into_inner();
+/// ```
+pub struct EwkbWriter<W: Write> {
+    writer: visit::EwkbWriter<W>,
+    dims: Dims,
+    started: bool,
+    in_multipoint: bool,
+    in_polygon: bool,
+}
+
+impl<W: Write> EwkbWriter<W> {
+    pub fn new(out: W, dims: CoordDimensions, byte_order: Endianness) -> Self {
+        EwkbWriter {
+            writer: visit::EwkbWriter::new(out, byte_order),
+            dims: Dims::new(dims.z, dims.m),
+            started: false,
+            in_multipoint: false,
+            in_polygon: false,
+        }
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    fn write<F>(&mut self, write: F) -> GeozeroResult<()>
+    where
+        F: FnOnce(&mut visit::EwkbWriter<W>, Dims) -> Result<(), Error>,
+    {
+        self.started = true;
+        write(&mut self.writer, self.dims).map_err(geozero_error)
+    }
+}
+
+impl<W: Write> GeomProcessor for EwkbWriter<W> {
+    fn dimensions(&self) -> CoordDimensions {
+        CoordDimensions {
+            z: self.dims.has_z(),
+            m: self.dims.has_m(),
+            ..CoordDimensions::xy()
+        }
+    }
+
+    fn srid(&mut self, srid: Option<i32>) -> GeozeroResult<()> {
+        if !self.started {
+            self.writer.srid(srid).map_err(geozero_error)?;
+        }
+        Ok(())
+    }
+
+    fn xy(&mut self, x: f64, y: f64, _idx: usize) -> GeozeroResult<()> {
+        self.write(|w, _| w.coord(x, y, None, None))
+    }
+
+    fn coordinate(
+        &mut self,
+        x: f64,
+        y: f64,
+        z: Option<f64>,
+        m: Option<f64>,
+        _t: Option<f64>,
+        _tm: Option<u64>,
+        _idx: usize,
+    ) -> GeozeroResult<()> {
+        self.write(|w, _| w.coord(x, y, z, m))
+    }
+
+    fn empty_point(&mut self, _idx: usize) -> GeozeroResult<()> {
+        let in_multipoint = self.in_multipoint;
+        self.write(|w, dims| {
+            if !in_multipoint {
+                w.begin_point(dims)?;
+            }
+            w.coord(f64::NAN, f64::NAN, Some(f64::NAN), Some(f64::NAN))
+        })
+    }
+
+    fn point_begin(&mut self, _idx: usize) -> GeozeroResult<()> {
+        self.write(|w, dims| w.begin_point(dims))
+    }
+
+    fn multipoint_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.in_multipoint = true;
+        self.write(|w, dims| w.begin_multipoint(dims, size))
+    }
+
+    fn multipoint_end(&mut self, _idx: usize) -> GeozeroResult<()> {
+        self.in_multipoint = false;
+        self.write(|w, _| w.end_multipoint())
+    }
+
+    fn linestring_begin(&mut self, _tagged: bool, size: usize, _idx: usize) -> GeozeroResult<()> {
+        if self.in_polygon {
+            self.write(|w, _| w.begin_ring(size))
+        } else {
+            self.write(|w, dims| w.begin_linestring(dims, size))
+        }
+    }
+
+    fn multilinestring_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.write(|w, dims| w.begin_multilinestring(dims, size))
+    }
+
+    fn polygon_begin(&mut self, _tagged: bool, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.in_polygon = true;
+        self.write(|w, dims| w.begin_polygon(dims, size))
+    }
+
+    fn polygon_end(&mut self, _tagged: bool, _idx: usize) -> GeozeroResult<()> {
+        self.in_polygon = false;
+        Ok(())
+    }
+
+    fn multipolygon_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.write(|w, dims| w.begin_multipolygon(dims, size))
+    }
+
+    fn geometrycollection_begin(&mut self, size: usize, _idx: usize) -> GeozeroResult<()> {
+        self.write(|w, dims| w.begin_collection(dims, size))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::geozero::error::Result as GeozeroResult;
+    use super::{CoordDimensions, EwkbWriter, GeomProcessor, GeozeroGeometry, Twkb};
+    use crate::encoding::decode_hex;
+    use crate::ewkb::{self, Endianness, EwkbRead};
+    use crate::limits::ParseLimits;
+
+    /// Processor recording the events it gets.
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl GeomProcessor for Events {
+        fn srid(&mut self, srid: Option<i32>) -> GeozeroResult<()> {
+            self.0.push(format!("srid {:?}", srid));
+            Ok(())
+        }
+        fn xy(&mut self, x: f64, y: f64, idx: usize) -> GeozeroResult<()> {
+            self.0.push(format!("xy {} {} {}", x, y, idx));
+            Ok(())
+        }
+        fn point_begin(&mut self, idx: usize) -> GeozeroResult<()> {
+            self.0.push(format!("point {}", idx));
+            Ok(())
+        }
+        fn linestring_begin(&mut self, tagged: bool, size: usize, idx: usize) -> GeozeroResult<()> {
+            self.0
+                .push(format!("linestring {} {} {}", tagged, size, idx));
+            Ok(())
+        }
+        fn polygon_begin(&mut self, tagged: bool, size: usize, idx: usize) -> GeozeroResult<()> {
+            self.0.push(format!("polygon {} {} {}", tagged, size, idx));
+            Ok(())
+        }
+        fn geometrycollection_begin(&mut self, size: usize, idx: usize) -> GeozeroResult<()> {
+            self.0.push(format!("collection {} {}", size, idx));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_process_ewkb() {
+        // SELECT 'SRID=4326;GEOMETRYCOLLECTION(POINT(10 -20),LINESTRING(0 0,1 1))'::geometry
+        let ewkb = decode_hex(
+            b"0107000020E6100000020000000101000000000000000000244000000000000034C00102000000020000\
+             0000000000000000000000000000000000000000000000F03F000000000000F03F",
+        )
+        .unwrap();
+        let mut events = Events::default();
+        let limits = ParseLimits::default();
+        super::process_ewkb(&mut ewkb.as_slice(), &mut events, &limits).unwrap();
+        assert_eq!(
+            events.0,
+            vec![
+                "srid Some(4326)",
+                "collection 2 0",
+                "point 0",
+                "xy 10 -20 0",
+                "linestring true 2 1",
+                "xy 0 0 0",
+                "xy 1 1 1",
+            ]
+        );
+
+        let limits = ParseLimits {
+            max_vertices: 2,
+            ..ParseLimits::default()
+        };
+        let mut events = Events::default();
+        assert!(super::process_ewkb(&mut ewkb.as_slice(), &mut events, &limits).is_err());
+    }
+
+    #[test]
+    fn test_process_twkb() {
+        // SELECT encode(ST_AsTWKB('POLYGON((0 0,2 0,2 2,0 2,0 0))'::geometry), 'hex')
+        let twkb = decode_hex(b"0300010500000400000403000003").unwrap();
+        let mut events = Events::default();
+        super::process_twkb(&mut twkb.as_slice(), &mut events, &ParseLimits::default()).unwrap();
+        assert_eq!(
+            events.0,
+            vec![
+                "polygon true 1 0",
+                "linestring false 5 0",
+                "xy 0 0 0",
+                "xy 2 0 1",
+                "xy 2 2 2",
+                "xy 0 2 3",
+                "xy 0 0 4",
+            ]
+        );
+        assert!(Twkb(&twkb[..twkb.len() - 1])
+            .process_geom(&mut Events::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_write_ewkb() {
+        let geometries = [
+            "0102000020E610000002000000000000000000244000000000000034C00000000000000000000000000000E0BF",
+            "01040000A0E61000000200000001010000800000000000000000000000000000F03F000000000000004001010000800000000000000840000000000000104000000000000014C0",
+            "0103000020E610000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000",
+        ];
+        for hex in geometries.iter() {
+            let ewkb = decode_hex(hex.as_bytes()).unwrap();
+            let dims = super::Ewkb(&ewkb).dims();
+            let mut writer = EwkbWriter::new(Vec::new(), dims, Endianness::Little);
+            super::Ewkb(&ewkb).process_geom(&mut writer).unwrap();
+            assert_eq!(writer.into_inner(), ewkb);
+        }
+
+        let twkb = decode_hex(b"0300010500000400000403000003").unwrap();
+        let mut writer = EwkbWriter::new(Vec::new(), CoordDimensions::xy(), Endianness::Big);
+        Twkb(&twkb).process_geom(&mut writer).unwrap();
+        let polygon = ewkb::Polygon::read_ewkb(&mut writer.into_inner().as_slice()).unwrap();
+        assert_eq!(polygon.rings[0].points.len(), 5);
+        assert_eq!(polygon.rings[0].points[2].x, 2.0);
+    }
+}
@@ -1,10 +1,32 @@
+pub mod crs;
+pub mod encoding;
 pub mod error;
 mod types;
 pub use types::{LineString, MultiLineString, MultiPoint, MultiPolygon, Point, Polygon};
 pub mod ewkb;
+pub mod flat;
+#[cfg(feature = "geo-types")]
+mod geo;
+#[cfg(feature = "arrow")]
+pub mod geoarrow;
 pub mod geojson;
+#[cfg(feature = "geozero")]
+pub mod geozero;
+pub mod limits;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod pgcopy;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(test)]
+mod proptests;
 #[cfg(feature = "python")]
 mod pyo;
-#[cfg(feature = "proj")]
-mod reproject;
+#[cfg(any(feature = "arrow", feature = "python"))]
+mod ragged;
+pub mod reproject;
+#[cfg(feature = "sqlx")]
+mod sqlx;
 pub mod twkb;
+pub mod view;
+pub mod visit;
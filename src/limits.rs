@@ -0,0 +1,208 @@
+// Limits for reading untrusted input
+use error::Error;
+use std::convert::TryFrom;
+use std::io::{self, Read};
+
+/// Default maximum nesting depth of geometry collections.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Most items reserved up front for a count read from the input. Larger geometries grow as they
+/// are read, so a bogus count cannot allocate more than the input actually contains.
+const MAX_PREALLOCATED: usize = 1 << 16;
+
+/// Upper bounds for geometries read from EWKB or TWKB.
+///
+/// Readers fail with [`Error::Read`] as soon as the input exceeds a limit. The default limits
+/// only bound the nesting depth of geometry collections.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ParseLimits {
+    /// Maximum number of vertices in the geometry.
+    pub max_vertices: usize,
+    /// Maximum number of parts in the geometry: polygon rings and members of multi-geometries
+    /// and geometry collections.
+    pub max_parts: usize,
+    /// Maximum nesting depth of geometry collections.
+    pub max_depth: usize,
+    /// Maximum size of the input in bytes.
+    pub max_bytes: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_vertices: usize::MAX,
+            max_parts: usize::MAX,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_bytes: usize::MAX,
+        }
+    }
+}
+
+/// Vertices, parts and depth read so far, checked against [`ParseLimits`].
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ParseBudget {
+    limits: ParseLimits,
+    vertices: usize,
+    parts: usize,
+    depth: usize,
+}
+
+impl ParseBudget {
+    pub fn new(limits: &ParseLimits) -> Self {
+        ParseBudget {
+            limits: *limits,
+            vertices: 0,
+            parts: 0,
+            depth: 0,
+        }
+    }
+
+    /// Account for `count` more vertices, returning the count as `usize`.
+    pub fn add_vertices(&mut self, count: u64) -> Result<usize, Error> {
+        self.vertices = add(
+            self.vertices,
+            count,
+            self.limits.max_vertices,
+            "max_vertices",
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Account for `count` more parts, returning the count as `usize`.
+    pub fn add_parts(&mut self, count: u64) -> Result<usize, Error> {
+        self.parts = add(self.parts, count, self.limits.max_parts, "max_parts")?;
+        Ok(count as usize)
+    }
+
+    /// Enter a nested geometry collection.
+    pub fn enter(&mut self) -> Result<(), Error> {
+        if self.depth >= self.limits.max_depth {
+            return Err(exceeded("max_depth", self.limits.max_depth));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Leave a geometry collection entered with [`ParseBudget::enter`].
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+fn add(total: usize, count: u64, limit: usize, name: &str) -> Result<usize, Error> {
+    usize::try_from(count)
+        .ok()
+        .and_then(|count| total.checked_add(count))
+        .filter(|total| *total <= limit)
+        .ok_or_else(|| exceeded(name, limit))
+}
+
+pub(crate) fn exceeded(name: &str, limit: usize) -> Error {
+    Error::Read(format!("input exceeds {} limit of {}", name, limit))
+}
+
+/// Number of items to reserve for a count read from the input.
+pub(crate) fn capacity(count: usize) -> usize {
+    count.min(MAX_PREALLOCATED)
+}
+
+/// Reader failing once more than `remaining` bytes have been read.
+#[doc(hidden)]
+pub struct ByteLimit<'a, R: Read> {
+    inner: &'a mut R,
+    remaining: usize,
+    exceeded: bool,
+}
+
+impl<R: Read> Read for ByteLimit<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read one byte past the limit to tell input ending at the limit from longer input.
+        let max = buf.len().min(self.remaining.saturating_add(1));
+        let n = self.inner.read(&mut buf[..max])?;
+        if n > self.remaining {
+            self.exceeded = true;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "max_bytes"));
+        }
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+/// Run `read` on `raw` with the given limits.
+pub(crate) fn read_limited<R, T, F>(raw: &mut R, limits: &ParseLimits, read: F) -> Result<T, Error>
+where
+    R: Read,
+    F: FnOnce(&mut ByteLimit<'_, R>, &mut ParseBudget) -> Result<T, Error>,
+{
+    let mut raw = ByteLimit {
+        inner: raw,
+        remaining: limits.max_bytes,
+        exceeded: false,
+    };
+    let mut budget = ParseBudget::new(limits);
+    read(&mut raw, &mut budget).map_err(|err| {
+        if raw.exceeded {
+            exceeded("max_bytes", limits.max_bytes)
+        } else {
+            err
+        }
+    })
+}
+
+/// Fail if `raw` has any bytes left.
+pub(crate) fn expect_end<R: Read>(raw: &mut R) -> Result<(), Error> {
+    let trailing = io::copy(raw, &mut io::sink())?;
+    if trailing > 0 {
+        return Err(Error::Read(format!(
+            "{} trailing bytes after geometry",
+            trailing
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_limited, ParseBudget, ParseLimits};
+    use std::io::Read;
+
+    #[test]
+    fn test_parse_budget() {
+        let limits = ParseLimits {
+            max_vertices: 3,
+            max_parts: 1,
+            max_depth: 1,
+            ..Default::default()
+        };
+        let mut budget = ParseBudget::new(&limits);
+        assert_eq!(budget.add_vertices(2).unwrap(), 2);
+        assert!(budget.add_vertices(1).is_ok());
+        assert!(budget.add_vertices(1).is_err());
+        assert!(budget.add_parts(u64::MAX).is_err());
+        assert!(budget.enter().is_ok());
+        assert!(budget.enter().is_err());
+        budget.leave();
+        assert!(budget.enter().is_ok());
+    }
+
+    #[test]
+    fn test_max_bytes() {
+        let limits = ParseLimits {
+            max_bytes: 4,
+            ..Default::default()
+        };
+        let read = |raw: &mut &[u8]| {
+            read_limited(raw, &limits, |raw, _| {
+                let mut buf = Vec::new();
+                raw.read_to_end(&mut buf)?;
+                Ok(buf)
+            })
+        };
+        assert_eq!(read(&mut &[1u8, 2, 3, 4][..]).unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(
+            read(&mut &[1u8, 2, 3, 4, 5][..]).unwrap_err().to_string(),
+            "Read(\"input exceeds max_bytes limit of 4\")"
+        );
+    }
+}
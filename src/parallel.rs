@@ -0,0 +1,495 @@
+// Parallel decoding of large multi-part geometries
+//
+// Members of EWKB multi-geometries and geometry collections are self-delimiting, so once an
+// `EwkbView` has checked the structure of a geometry, its members can be read on their own. The
+// deltas of TWKB continue from one member to the next, so the members of a TWKB multilinestring
+// or multipolygon are found by decoding all deltas once without building anything, after which
+// each member is read starting from the point its first delta is relative to. Small and
+// single-part geometries, input the readers reject, and everything when the thread pool has a
+// single thread are read on the calling thread.
+extern crate rayon;
+
+use self::rayon::prelude::*;
+
+use crate::error::Error;
+use crate::ewkb::{
+    self, EwkbRead, GeometryCollectionT, GeometryT, MultiLineStringT, MultiPolygonT,
+};
+use crate::geojson::{
+    self, BinaryFormat, GeoJSONGeometry, GeoJSONLineString, GeoJSONPolygon, GeometryType,
+};
+use crate::limits::ParseLimits;
+use crate::twkb::{self, TwkbGeom};
+use crate::view::EwkbView;
+
+/// Geometries with fewer vertices are read on the calling thread, as splitting them costs more
+/// than it saves.
+const MIN_VERTICES: usize = 65_536;
+
+/// Fewest bytes a vertex takes: two doubles in EWKB, and a byte per delta of x and y in TWKB.
+const EWKB_VERTEX_BYTES: usize = 16;
+const TWKB_VERTEX_BYTES: usize = 2;
+
+/// Whether the rayon thread pool has more than one thread. With a single thread, handing members
+/// to the pool only adds overhead.
+fn has_threads() -> bool {
+    rayon::current_num_threads() > 1
+}
+
+/// Whether `data` may be large enough for splitting, checked before going through it.
+fn may_split(data: &[u8], vertex_bytes: usize) -> bool {
+    has_threads() && data.len() >= MIN_VERTICES * vertex_bytes
+}
+
+/// Read an EWKB geometry like [`EwkbRead::read_ewkb_strict`], reading the members of large
+/// multi-geometries and geometry collections on the rayon thread pool.
+pub fn read_ewkb(data: &[u8], limits: &ParseLimits) -> Result<ewkb::Geometry, Error> {
+    if !may_split(data, EWKB_VERTEX_BYTES) {
+        return ewkb::Geometry::read_ewkb_strict(&mut &data[..], limits);
+    }
+    match EwkbView::new_limited(data, limits) {
+        Ok(view) => read_ewkb_view(view, limits),
+        // Either invalid or with members in different byte orders, which only the reader reads
+        Err(_) => ewkb::Geometry::read_ewkb_strict(&mut &data[..], limits),
+    }
+}
+
+/// Read a TWKB geometry like [`TwkbGeom::read_twkb_strict`], reading the members of large
+/// multilinestrings and multipolygons on the rayon thread pool.
+///
+/// Finding the members takes a pass over all deltas before reading them, so it pays off on
+/// machines with more than a couple of cores.
+pub fn read_twkb(data: &[u8], limits: &ParseLimits) -> Result<twkb::Geometry, Error> {
+    let members = match split_twkb(data, limits) {
+        Some(members) if members.bytes == data.len() => members,
+        _ => return twkb::Geometry::read_twkb_strict(&mut &data[..], limits),
+    };
+    let geom = match twkb::get_geom_type(data) {
+        GeometryType::MultiLineString => twkb::Geometry::MultiLineString(twkb::MultiLineString {
+            lines: collect(
+                (0..members.len())
+                    .into_par_iter()
+                    .map(|i| members.line(i, limits)),
+            )?,
+            ids: members.ids,
+        }),
+        _ => twkb::Geometry::MultiPolygon(twkb::MultiPolygon {
+            polygons: collect(
+                (0..members.len())
+                    .into_par_iter()
+                    .map(|i| members.polygon(i, limits)),
+            )?,
+            ids: members.ids,
+        }),
+    };
+    Ok(geom)
+}
+
+/// Read one geometry in the given format like [`geojson::read_binary`], reading and converting
+/// the members of large multilinestrings and multipolygons on the rayon thread pool.
+pub fn read_binary(
+    data: &[u8],
+    format: BinaryFormat,
+    limits: &ParseLimits,
+) -> Result<GeoJSONGeometry, Error> {
+    match Split::new(data, format, limits) {
+        Some(split) if split.bytes() == data.len() => split.to_geojson(limits),
+        _ => geojson::read_binary(data, format, limits),
+    }
+}
+
+/// Read the geometry at the start of `data` like [`geojson::read_binary_prefix`], reading and
+/// converting the members of large multilinestrings and multipolygons on the rayon thread pool.
+pub fn read_binary_prefix(
+    data: &[u8],
+    format: BinaryFormat,
+    limits: &ParseLimits,
+) -> Result<(GeoJSONGeometry, usize), Error> {
+    match Split::new(data, format, limits) {
+        Some(split) => Ok((split.to_geojson(limits)?, split.bytes())),
+        None => geojson::read_binary_prefix(data, format, limits),
+    }
+}
+
+/// A large multilinestring or multipolygon at the start of some input, ready for reading its
+/// members in parallel.
+enum Split<'a> {
+    Ewkb(EwkbView<'a>),
+    Twkb(twkb::Members<'a>, GeometryType),
+}
+
+impl<'a> Split<'a> {
+    fn new(data: &'a [u8], format: BinaryFormat, limits: &ParseLimits) -> Option<Split<'a>> {
+        match format {
+            BinaryFormat::Ewkb => {
+                if !may_split(data, EWKB_VERTEX_BYTES) {
+                    return None;
+                }
+                let view = EwkbView::prefix_limited(data, limits).ok()?;
+                let multi = matches!(
+                    view.geometry_type(),
+                    GeometryType::MultiLineString | GeometryType::MultiPolygon
+                );
+                (multi && is_large(&view)).then_some(Split::Ewkb(view))
+            }
+            BinaryFormat::Twkb => split_twkb(data, limits)
+                .map(|members| Split::Twkb(members, twkb::get_geom_type(data))),
+        }
+    }
+
+    /// Bytes taken by the geometry.
+    fn bytes(&self) -> usize {
+        match self {
+            Split::Ewkb(view) => view.as_bytes().len(),
+            Split::Twkb(members, _) => members.bytes,
+        }
+    }
+
+    fn to_geojson(&self, limits: &ParseLimits) -> Result<GeoJSONGeometry, Error> {
+        match self {
+            Split::Ewkb(view) if view.geometry_type() == GeometryType::MultiLineString => {
+                let lines = read_members(view, |member| {
+                    ewkb::LineString::read_ewkb_limited(&mut member.as_bytes(), limits)
+                        .map(|line| line.to_geojson())
+                })?;
+                Ok(multilinestring(lines, view.srid()))
+            }
+            Split::Ewkb(view) => {
+                let polygons = read_members(view, |member| {
+                    ewkb::Polygon::read_ewkb_limited(&mut member.as_bytes(), limits)
+                        .map(|polygon| polygon.to_geojson())
+                })?;
+                Ok(multipolygon(polygons, view.srid()))
+            }
+            Split::Twkb(members, GeometryType::MultiLineString) => Ok(multilinestring(
+                collect(
+                    (0..members.len())
+                        .into_par_iter()
+                        .map(|i| members.line(i, limits).map(|line| line.to_geojson())),
+                )?,
+                None,
+            )),
+            Split::Twkb(members, _) => Ok(multipolygon(
+                collect((0..members.len()).into_par_iter().map(|i| {
+                    members
+                        .polygon(i, limits)
+                        .map(|polygon| polygon.to_geojson())
+                }))?,
+                None,
+            )),
+        }
+    }
+}
+
+/// Read a checked EWKB geometry, splitting it into members if it is large enough.
+fn read_ewkb_view(view: EwkbView, limits: &ParseLimits) -> Result<ewkb::Geometry, Error> {
+    if !is_large(&view) {
+        return ewkb::Geometry::read_ewkb_limited(&mut view.as_bytes(), limits);
+    }
+    let geom = match view.geometry_type() {
+        GeometryType::MultiLineString => GeometryT::MultiLineString(MultiLineStringT {
+            lines: read_members(&view, |member| {
+                ewkb::LineString::read_ewkb_limited(&mut member.as_bytes(), limits)
+            })?,
+            srid: view.srid(),
+        }),
+        GeometryType::MultiPolygon => GeometryT::MultiPolygon(MultiPolygonT {
+            polygons: read_members(&view, |member| {
+                ewkb::Polygon::read_ewkb_limited(&mut member.as_bytes(), limits)
+            })?,
+            srid: view.srid(),
+        }),
+        // Readers do not keep the SRID of collections
+        GeometryType::GeometryCollection => GeometryT::GeometryCollection(GeometryCollectionT {
+            geometries: read_members(&view, |member| read_ewkb_view(member, limits))?,
+            srid: None,
+        }),
+        _ => ewkb::Geometry::read_ewkb_limited(&mut view.as_bytes(), limits)?,
+    };
+    Ok(geom)
+}
+
+fn is_large(view: &EwkbView) -> bool {
+    has_threads() && view.num_members() > 1 && view.num_vertices() >= MIN_VERTICES
+}
+
+/// Members of the TWKB multilinestring or multipolygon at the start of `data`, if it is large
+/// enough for splitting.
+fn split_twkb<'a>(data: &'a [u8], limits: &ParseLimits) -> Option<twkb::Members<'a>> {
+    if !may_split(data, TWKB_VERTEX_BYTES) {
+        return None;
+    }
+    twkb::split_members(data, limits)
+        .filter(|members| members.len() > 1 && members.vertices >= MIN_VERTICES)
+}
+
+fn read_members<T, F>(view: &EwkbView, read: F) -> Result<Vec<T>, Error>
+where
+    T: Send,
+    F: Fn(EwkbView) -> Result<T, Error> + Sync + Send,
+{
+    let members: Vec<EwkbView> = view.members().collect();
+    collect(members.into_par_iter().map(read))
+}
+
+/// Collect the results of reading members in order, failing with the error of the first member
+/// that failed like reading them in order would.
+fn collect<T, I>(results: I) -> Result<Vec<T>, Error>
+where
+    T: Send,
+    I: IndexedParallelIterator<Item = Result<T, Error>>,
+{
+    let results: Vec<Result<T, Error>> = results.collect();
+    results.into_iter().collect()
+}
+
+/// GeoJSON multilinestring of converted `lines`, like converting the whole multilinestring.
+fn multilinestring(lines: Vec<geojson::LineString>, srid: Option<i32>) -> GeoJSONGeometry {
+    GeoJSONGeometry::MultiLineString(geojson::MultiLineString {
+        type_name: "MultiLineString".to_owned(),
+        crs: srid,
+        dims: lines.iter().find_map(|line| line.dims),
+        coordinates: lines.into_iter().map(|line| line.coordinates).collect(),
+    })
+}
+
+/// GeoJSON multipolygon of converted `polygons`, like converting the whole multipolygon.
+fn multipolygon(polygons: Vec<geojson::Polygon>, srid: Option<i32>) -> GeoJSONGeometry {
+    GeoJSONGeometry::MultiPolygon(geojson::MultiPolygon {
+        type_name: "MultiPolygon".to_owned(),
+        crs: srid,
+        dims: polygons.iter().find_map(|polygon| polygon.dims),
+        coordinates: polygons
+            .into_iter()
+            .map(|polygon| polygon.coordinates)
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ewkb::{AsEwkbGeometry, Endianness, EwkbWrite, LineStringT, PolygonT};
+    use crate::limits::ParseLimits;
+    use crate::proptests::{encode_twkb, Shape, TwkbOptions};
+
+    const PARTS: usize = 8;
+    // Just enough for the multilinestrings to be split
+    const RING: usize = 8_200;
+
+    /// An open ring of `RING` vertices with Z values, rounded to three decimals for TWKB.
+    fn ring(part: usize, ring: usize) -> Vec<[f64; 3]> {
+        (0..RING)
+            .map(|i| {
+                let a = i as f64 / RING as f64 * std::f64::consts::TAU;
+                let r = 1.0 / (ring + 1) as f64;
+                let round = |v: f64| (v * 1000.0).round() / 1000.0;
+                [
+                    round(part as f64 * 3.0 + r * a.cos()),
+                    round(r * a.sin()),
+                    i as f64,
+                ]
+            })
+            .collect()
+    }
+
+    fn line(crds: &[[f64; 3]]) -> ewkb::LineString {
+        let mut line = LineStringT::new();
+        line.points = crds
+            .iter()
+            .map(|crd| ewkb::Point::new(crd[0], crd[1], Some(crd[2]), None, None))
+            .collect();
+        line
+    }
+
+    fn polygon(part: usize) -> ewkb::Polygon {
+        let mut polygon = PolygonT::new();
+        for i in 0..2 {
+            let mut ring = ring(part, i);
+            ring.push(ring[0]);
+            polygon.rings.push(line(&ring));
+        }
+        polygon
+    }
+
+    fn ewkb_geometries() -> Vec<ewkb::Geometry> {
+        let polygons = MultiPolygonT {
+            polygons: (0..PARTS).map(polygon).collect(),
+            srid: Some(4326),
+        };
+        let lines = MultiLineStringT {
+            lines: (0..PARTS).map(|part| line(&ring(part, 0))).collect(),
+            srid: Some(3067),
+        };
+        let collection = GeometryCollectionT {
+            geometries: vec![
+                GeometryT::MultiPolygon(polygons.clone()),
+                GeometryT::Point(ewkb::Point::new(1.0, 2.0, Some(3.0), None, None)),
+                GeometryT::MultiLineString(MultiLineStringT {
+                    srid: None,
+                    ..lines.clone()
+                }),
+            ],
+            srid: None,
+        };
+        vec![
+            GeometryT::MultiPolygon(polygons),
+            GeometryT::MultiLineString(lines),
+            GeometryT::GeometryCollection(collection),
+            GeometryT::Polygon(polygon(0)),
+        ]
+    }
+
+    /// Run `f` on a pool of two threads, so that geometries are split on one core too.
+    fn in_pool<F: FnOnce() + Send>(f: F) {
+        let pool = super::rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build();
+        pool.unwrap().install(f)
+    }
+
+    fn to_ewkb(geom: &ewkb::Geometry, byte_order: Endianness) -> Vec<u8> {
+        let mut data = Vec::new();
+        geom.as_ewkb()
+            .write_ewkb_with(&mut data, byte_order)
+            .unwrap();
+        data
+    }
+
+    /// TWKB multipolygon or multilinestring with precision 3 and Z values in whole units.
+    fn to_twkb(parts: &[Vec<Vec<[f64; 3]>>], is_polygon: bool, ids: bool, size: bool) -> Vec<u8> {
+        let units = |line: &Vec<[f64; 3]>| {
+            line.iter()
+                .map(|crd| {
+                    vec![
+                        (crd[0] * 1000.0).round() as i64,
+                        (crd[1] * 1000.0).round() as i64,
+                        crd[2].round() as i64,
+                    ]
+                })
+                .collect()
+        };
+        let shape = if is_polygon {
+            Shape::MultiPolygon(
+                parts
+                    .iter()
+                    .map(|rings| rings.iter().map(units).collect())
+                    .collect(),
+            )
+        } else {
+            Shape::MultiLineString(parts.iter().flatten().map(units).collect())
+        };
+        let options = TwkbOptions {
+            precision: 3,
+            prec_z: 0,
+            prec_m: 0,
+            size,
+            bbox: false,
+            ids,
+        };
+        encode_twkb(&shape, (true, false), options)
+    }
+
+    fn twkb_geometries() -> Vec<Vec<u8>> {
+        let polygons: Vec<Vec<Vec<[f64; 3]>>> = (0..PARTS)
+            .map(|part| (0..2).map(|i| ring(part, i)).collect())
+            .collect();
+        let lines: Vec<Vec<Vec<[f64; 3]>>> = (0..PARTS).map(|part| vec![ring(part, 0)]).collect();
+        vec![
+            to_twkb(&polygons, true, false, false),
+            to_twkb(&polygons, true, true, true),
+            to_twkb(&lines, false, false, true),
+            to_twkb(&lines, false, true, false),
+            to_twkb(&polygons[..1], true, false, false),
+        ]
+    }
+
+    #[test]
+    fn test_read_ewkb() {
+        in_pool(|| {
+            let limits = ParseLimits::default();
+            for geom in ewkb_geometries() {
+                for byte_order in [Endianness::Little, Endianness::Big] {
+                    let data = to_ewkb(&geom, byte_order);
+                    let read = read_ewkb(&data, &limits).unwrap();
+                    assert!(to_ewkb(&read, byte_order) == data);
+                    assert_eq!(
+                        read_binary(&data, BinaryFormat::Ewkb, &limits)
+                            .map_err(|err| err.to_string()),
+                        geojson::read_binary(&data, BinaryFormat::Ewkb, &limits)
+                            .map_err(|err| err.to_string())
+                    );
+                    let stream = [&data[..], &data[..]].concat();
+                    assert_eq!(
+                        read_binary_prefix(&stream, BinaryFormat::Ewkb, &limits)
+                            .map_err(|err| err.to_string()),
+                        geojson::read_binary_prefix(&stream, BinaryFormat::Ewkb, &limits)
+                            .map_err(|err| err.to_string())
+                    );
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_read_twkb() {
+        in_pool(|| {
+            let limits = ParseLimits::default();
+            for data in twkb_geometries() {
+                let expected = twkb::Geometry::read_twkb_strict(&mut &data[..], &limits).unwrap();
+                assert_eq!(read_twkb(&data, &limits).unwrap(), expected);
+                assert_eq!(
+                    read_binary(&data, BinaryFormat::Twkb, &limits).unwrap(),
+                    geojson::read_binary(&data, BinaryFormat::Twkb, &limits).unwrap()
+                );
+                let stream = [&data[..], &data[..]].concat();
+                let (geom, bytes) =
+                    read_binary_prefix(&stream, BinaryFormat::Twkb, &limits).unwrap();
+                assert_eq!(bytes, data.len());
+                assert_eq!(
+                    geom,
+                    geojson::read_binary(&data, BinaryFormat::Twkb, &limits).unwrap()
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn test_read_errors() {
+        in_pool(|| {
+            // Errors are the ones of reading on one thread
+            let limits = ParseLimits {
+                max_vertices: 50_000,
+                ..ParseLimits::default()
+            };
+            let ewkb = to_ewkb(&ewkb_geometries()[0], Endianness::Little);
+            let twkb = &twkb_geometries()[1];
+            let mut trailing = ewkb.clone();
+            trailing.push(0);
+            for (data, format, limits) in [
+                (&ewkb[..], BinaryFormat::Ewkb, &limits),
+                (
+                    &ewkb[..ewkb.len() - 1],
+                    BinaryFormat::Ewkb,
+                    &ParseLimits::default(),
+                ),
+                (&trailing[..], BinaryFormat::Ewkb, &ParseLimits::default()),
+                (&twkb[..], BinaryFormat::Twkb, &limits),
+                (
+                    &twkb[..twkb.len() - 1],
+                    BinaryFormat::Twkb,
+                    &ParseLimits::default(),
+                ),
+            ] {
+                let expected = geojson::read_binary(data, format, limits).unwrap_err();
+                let err = read_binary(data, format, limits).unwrap_err();
+                assert_eq!(err.to_string(), expected.to_string());
+                let err = match format {
+                    BinaryFormat::Ewkb => read_ewkb(data, limits).unwrap_err(),
+                    BinaryFormat::Twkb => read_twkb(data, limits).unwrap_err(),
+                };
+                assert_eq!(err.to_string(), expected.to_string());
+            }
+        });
+    }
+}
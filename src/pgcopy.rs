@@ -0,0 +1,236 @@
+// PostgreSQL binary COPY streams
+//
+// `COPY ... TO STDOUT (FORMAT binary)` writes a signature and header followed by tuples. Each
+// tuple starts with a 16-bit field count and each field with a 32-bit length, -1 meaning NULL.
+// A field count of -1 ends the stream. All integers are big-endian. PostGIS sends geometry
+// columns as EWKB, and bytea columns carry their bytes as they are, such as TWKB from
+// `ST_AsTWKB`. See https://www.postgresql.org/docs/current/sql-copy.html#id-1.9.3.55.9.4
+extern crate byteorder;
+
+use self::byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use crate::error::Error;
+use crate::geojson::{self, BinaryFormat, GeoJSONGeometry};
+use crate::limits::ParseLimits;
+
+/// Signature starting every binary COPY stream.
+pub const SIGNATURE: &[u8; 11] = b"PGCOPY\n\xff\r\n\0";
+
+/// Header flag telling that each tuple starts with an OID field.
+const HAS_OIDS: u32 = 1 << 16;
+
+/// Fields of one tuple, `None` for NULL.
+pub type Row = Vec<Option<Vec<u8>>>;
+
+fn unexpected_eof(err: io::Error) -> Error {
+    match err.kind() {
+        io::ErrorKind::UnexpectedEof => Error::Read("unexpected end of COPY data".to_owned()),
+        _ => err.into(),
+    }
+}
+
+/// Reader of binary COPY streams, iterating over the tuples.
+///
+/// ```ignore
+/// for row in CopyReader::new(file)? {
+///     let row = row?;
+///     let geom = pgcopy::read_geometry(row[1].as_deref(), BinaryFormat::Ewkb, &limits)?;
+/// }
+/// ```
+pub struct CopyReader<R> {
+    raw: R,
+    has_oids: bool,
+    done: bool,
+}
+
+impl<R: Read> CopyReader<R> {
+    /// Read the signature and header of `raw`.
+    pub fn new(mut raw: R) -> Result<Self, Error> {
+        let mut signature = [0u8; 11];
+        raw.read_exact(&mut signature).map_err(unexpected_eof)?;
+        if &signature != SIGNATURE {
+            return Err(Error::Read("invalid COPY signature".to_owned()));
+        }
+        let flags = raw.read_u32::<BigEndian>().map_err(unexpected_eof)?;
+        if flags & 0xffff_0000 & !HAS_OIDS != 0 {
+            return Err(Error::Read(format!("unsupported COPY flags {:#x}", flags)));
+        }
+        let extension = raw.read_u32::<BigEndian>().map_err(unexpected_eof)?;
+        let skipped = io::copy(&mut (&mut raw).take(extension as u64), &mut io::sink())?;
+        if skipped != extension as u64 {
+            return Err(Error::Read("unexpected end of COPY data".to_owned()));
+        }
+        Ok(CopyReader {
+            raw,
+            has_oids: flags & HAS_OIDS != 0,
+            done: false,
+        })
+    }
+
+    /// Whether tuples carry OIDs, which are skipped when reading.
+    pub fn has_oids(&self) -> bool {
+        self.has_oids
+    }
+
+    fn read_field(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        let len = self.raw.read_i32::<BigEndian>().map_err(unexpected_eof)?;
+        if len == -1 {
+            return Ok(None);
+        }
+        let len = u64::try_from(len)
+            .map_err(|_| Error::Read(format!("invalid COPY field length {}", len)))?;
+        // Grow while reading so a bogus length cannot allocate more than the input contains.
+        let mut field = Vec::new();
+        (&mut self.raw).take(len).read_to_end(&mut field)?;
+        if field.len() as u64 != len {
+            return Err(Error::Read("unexpected end of COPY data".to_owned()));
+        }
+        Ok(Some(field))
+    }
+
+    fn read_row(&mut self) -> Result<Option<Row>, Error> {
+        let count = self.raw.read_i16::<BigEndian>().map_err(unexpected_eof)?;
+        if count == -1 {
+            return Ok(None);
+        }
+        let count = usize::try_from(count)
+            .map_err(|_| Error::Read(format!("invalid COPY field count {}", count)))?;
+        if self.has_oids {
+            self.read_field()?;
+        }
+        (0..count)
+            .map(|_| self.read_field())
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+}
+
+impl<R: Read> Iterator for CopyReader<R> {
+    type Item = Result<Row, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read_row();
+        self.done = !matches!(result, Ok(Some(_)));
+        result.transpose()
+    }
+}
+
+/// Read a geometry field of a tuple, `None` for NULL.
+pub fn read_geometry(
+    field: Option<&[u8]>,
+    format: BinaryFormat,
+    limits: &ParseLimits,
+) -> Result<Option<GeoJSONGeometry>, Error> {
+    field
+        .map(|data| geojson::read_binary(data, format, limits))
+        .transpose()
+}
+
+/// Writer of binary COPY streams for `COPY ... FROM STDIN (FORMAT binary)`.
+///
+/// Fields must be in the binary format of their column: EWKB for geometry columns, raw bytes
+/// for bytea columns. [`CopyWriter::finish`] writes the trailer ending the stream.
+pub struct CopyWriter<W: Write> {
+    raw: W,
+}
+
+impl<W: Write> CopyWriter<W> {
+    /// Write the signature and header into `raw`.
+    pub fn new(mut raw: W) -> Result<Self, Error> {
+        raw.write_all(SIGNATURE)?;
+        raw.write_u32::<BigEndian>(0)?;
+        raw.write_u32::<BigEndian>(0)?;
+        Ok(CopyWriter { raw })
+    }
+
+    /// Write one tuple, `None` fields as NULL.
+    pub fn write_row(&mut self, fields: &[Option<&[u8]>]) -> Result<(), Error> {
+        let count = i16::try_from(fields.len())
+            .map_err(|_| Error::Write(format!("too many COPY fields {}", fields.len())))?;
+        self.raw.write_i16::<BigEndian>(count)?;
+        for field in fields {
+            match field {
+                Some(data) => {
+                    let len = i32::try_from(data.len()).map_err(|_| {
+                        Error::Write(format!("COPY field too large {}", data.len()))
+                    })?;
+                    self.raw.write_i32::<BigEndian>(len)?;
+                    self.raw.write_all(data)?;
+                }
+                None => self.raw.write_i32::<BigEndian>(-1)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the trailer and return the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.raw.write_i16::<BigEndian>(-1)?;
+        Ok(self.raw)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_geometry, CopyReader, CopyWriter};
+    use crate::encoding::decode_hex;
+    use crate::geojson::{BinaryFormat, GeoJSONGeometry};
+    use crate::limits::ParseLimits;
+
+    #[test]
+    fn test_read_captured_copy() {
+        // Output of COPY (SELECT 1::int4, 'SRID=4326;POINT(10 -20)'::geometry,
+        // ST_AsTWKB(NULL::geometry)) TO STDOUT (FORMAT binary)
+        let data = decode_hex(
+            b"5047434f50590aff0d0a00000000000000000000030000000400000001000000190101000020e6100000\
+             000000000000244000000000000034c0ffffffffffff",
+        )
+        .unwrap();
+        let rows = CopyReader::new(&data[..])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], Some(vec![0, 0, 0, 1]));
+        assert_eq!(rows[0][2], None);
+        let limits = ParseLimits::default();
+        let geom = read_geometry(rows[0][1].as_deref(), BinaryFormat::Ewkb, &limits).unwrap();
+        match geom {
+            Some(GeoJSONGeometry::Point(point)) => {
+                assert_eq!(point.coordinates, vec![10.0, -20.0]);
+                assert_eq!(point.crs, Some(4326));
+            }
+            geom => panic!("unexpected geometry {:?}", geom),
+        }
+        assert_eq!(
+            read_geometry(None, BinaryFormat::Twkb, &limits).unwrap(),
+            None
+        );
+
+        assert!(CopyReader::new(&data[..10]).is_err());
+        let truncated = CopyReader::new(&data[..data.len() - 4])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>();
+        assert!(truncated.is_err());
+    }
+
+    #[test]
+    fn test_write_read_roundtrip() {
+        let twkb = decode_hex(b"610805d00fa01f50").unwrap();
+        let mut writer = CopyWriter::new(Vec::new()).unwrap();
+        writer.write_row(&[Some(&twkb), None]).unwrap();
+        writer.write_row(&[None, Some(b"")]).unwrap();
+        let data = writer.finish().unwrap();
+
+        let mut reader = CopyReader::new(&data[..]).unwrap();
+        assert!(!reader.has_oids());
+        assert_eq!(reader.next().unwrap().unwrap(), vec![Some(twkb), None]);
+        assert_eq!(reader.next().unwrap().unwrap(), vec![None, Some(vec![])]);
+        assert!(reader.next().is_none());
+    }
+}
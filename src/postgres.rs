@@ -0,0 +1,151 @@
+// postgres-types support
+//
+// PostGIS sends `geometry` and `geography` values as EWKB in binary format, which is what
+// tokio-postgres and postgres request for these types. The impls read and write it directly.
+extern crate bytes;
+extern crate postgres_types;
+
+use self::bytes::{BufMut, BytesMut};
+use self::postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::error::Error as StdError;
+
+use crate::ewkb::{self, *};
+use crate::geojson::{self, BinaryFormat, GeoJSONEncode, GeoJSONGeometry};
+use crate::limits::ParseLimits;
+
+type BoxError = Box<dyn StdError + Sync + Send>;
+
+fn accepts_geometry(ty: &Type) -> bool {
+    matches!(ty.name(), "geometry" | "geography")
+}
+
+macro_rules! impl_sql_for_ewkb_type {
+    ($($geotype:ident),*) => {
+        $(
+            impl<'a> FromSql<'a> for ewkb::$geotype {
+                fn from_sql(_: &Type, mut raw: &'a [u8]) -> Result<Self, BoxError> {
+                    Ok(Self::read_ewkb_strict(&mut raw, &ParseLimits::default())?)
+                }
+
+                fn accepts(ty: &Type) -> bool {
+                    accepts_geometry(ty)
+                }
+            }
+
+            impl ToSql for ewkb::$geotype {
+                fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+                    self.as_ewkb().write_ewkb(&mut out.writer())?;
+                    Ok(IsNull::No)
+                }
+
+                fn accepts(ty: &Type) -> bool {
+                    accepts_geometry(ty)
+                }
+
+                to_sql_checked!();
+            }
+        )*
+    };
+}
+
+impl_sql_for_ewkb_type!(Point, PointZ, PointM, PointZM);
+impl_sql_for_ewkb_type!(LineString, LineStringZ, LineStringM, LineStringZM);
+impl_sql_for_ewkb_type!(Polygon, PolygonZ, PolygonM, PolygonZM);
+impl_sql_for_ewkb_type!(MultiPoint, MultiPointZ, MultiPointM, MultiPointZM);
+impl_sql_for_ewkb_type!(
+    MultiLineString,
+    MultiLineStringZ,
+    MultiLineStringM,
+    MultiLineStringZM
+);
+impl_sql_for_ewkb_type!(MultiPolygon, MultiPolygonZ, MultiPolygonM, MultiPolygonZM);
+impl_sql_for_ewkb_type!(Geometry, GeometryZ, GeometryM, GeometryZM);
+impl_sql_for_ewkb_type!(
+    GeometryCollection,
+    GeometryCollectionZ,
+    GeometryCollectionM,
+    GeometryCollectionZM
+);
+
+impl<'a> FromSql<'a> for GeoJSONGeometry {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        Ok(geojson::read_binary(
+            raw,
+            BinaryFormat::Ewkb,
+            &ParseLimits::default(),
+        )?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts_geometry(ty)
+    }
+}
+
+impl ToSql for GeoJSONGeometry {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        out.put_slice(&self.to_ewkb()?);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts_geometry(ty)
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::bytes::BytesMut;
+    use super::postgres_types::{FromSql, Kind, ToSql, Type};
+    use crate::encoding::decode_hex;
+    use crate::ewkb::{self, EwkbRead};
+    use crate::geojson::GeoJSONGeometry;
+    use crate::limits::ParseLimits;
+
+    fn geometry_type(name: &str) -> Type {
+        Type::new(name.to_owned(), 16394, Kind::Simple, "public".to_owned())
+    }
+
+    #[test]
+    fn test_geometry_roundtrip() {
+        // SELECT 'SRID=4326;LINESTRING(10 -20, 0 -0.5)'::geometry in binary format
+        let raw = decode_hex(
+            b"0102000020E610000002000000000000000000244000000000000034C00000000000000000000000000000E0BF",
+        )
+        .unwrap();
+        let ty = geometry_type("geometry");
+        let line = ewkb::LineString::from_sql(&ty, &raw).unwrap();
+        assert_eq!(line.srid, Some(4326));
+        assert_eq!(line.points.len(), 2);
+        let mut out = BytesMut::new();
+        line.to_sql_checked(&ty, &mut out).unwrap();
+        assert_eq!(&out[..], &raw[..]);
+
+        let geom = ewkb::Geometry::from_sql(&ty, &raw).unwrap();
+        assert!(matches!(geom, ewkb::GeometryT::LineString(_)));
+        assert!(ewkb::Point::from_sql(&ty, &raw).is_err());
+        assert!(ewkb::LineString::from_sql(&ty, &raw[..raw.len() - 1]).is_err());
+
+        assert!(<ewkb::Geometry as FromSql>::accepts(&geometry_type(
+            "geography"
+        )));
+        assert!(!<ewkb::Geometry as FromSql>::accepts(&Type::BYTEA));
+        assert!(line.to_sql_checked(&Type::BYTEA, &mut out).is_err());
+    }
+
+    #[test]
+    fn test_geojson_roundtrip() {
+        let raw = decode_hex(b"0101000020E6100000000000000000244000000000000034C0").unwrap();
+        let ty = geometry_type("geography");
+        let geom = GeoJSONGeometry::from_sql(&ty, &raw).unwrap();
+        match &geom {
+            GeoJSONGeometry::Point(point) => assert_eq!(point.coordinates, vec![10.0, -20.0]),
+            geom => panic!("unexpected geometry {:?}", geom),
+        }
+        let mut out = BytesMut::new();
+        geom.to_sql(&ty, &mut out).unwrap();
+        let point = ewkb::Point::read_ewkb_strict(&mut &out[..], &ParseLimits::default());
+        assert_eq!(point.unwrap().srid, Some(4326));
+    }
+}
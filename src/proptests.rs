@@ -0,0 +1,540 @@
+// Property-based round-trip tests
+extern crate approx;
+extern crate proptest;
+
+use self::approx::relative_eq;
+use self::proptest::collection::vec;
+use self::proptest::prelude::*;
+use ewkb::{
+    self, AsEwkbGeometry, AsEwkbLineString, AsEwkbMultiLineString, AsEwkbMultiPoint,
+    AsEwkbMultiPolygon, AsEwkbPoint, AsEwkbPolygon, Endianness, EwkbRead, EwkbWrite, GeometryT,
+};
+use flat::Coord;
+use geojson::{self, Dims, GeoJSONEncode, GeoJSONGeometry};
+use limits::ParseLimits;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::ops::Range;
+use twkb::{self, TwkbGeom};
+use types::Point as PointTrait;
+use view::EwkbView;
+
+/// Geometry with coordinates of type `C`, used as the expected value of round-trips.
+#[derive(PartialEq, Clone, Debug)]
+pub(crate) enum Shape<C> {
+    Point(C),
+    LineString(Vec<C>),
+    Polygon(Vec<Vec<C>>),
+    MultiPoint(Vec<C>),
+    MultiLineString(Vec<Vec<C>>),
+    MultiPolygon(Vec<Vec<Vec<C>>>),
+}
+
+impl<C> Shape<C> {
+    fn map<D, F: Fn(&C) -> D>(&self, f: F) -> Shape<D> {
+        let line = |line: &Vec<C>| line.iter().map(&f).collect::<Vec<_>>();
+        let poly = |rings: &Vec<Vec<C>>| rings.iter().map(line).collect::<Vec<_>>();
+        match self {
+            Shape::Point(crd) => Shape::Point(f(crd)),
+            Shape::LineString(points) => Shape::LineString(line(points)),
+            Shape::Polygon(rings) => Shape::Polygon(poly(rings)),
+            Shape::MultiPoint(points) => Shape::MultiPoint(line(points)),
+            Shape::MultiLineString(lines) => {
+                Shape::MultiLineString(lines.iter().map(line).collect())
+            }
+            Shape::MultiPolygon(polys) => Shape::MultiPolygon(polys.iter().map(poly).collect()),
+        }
+    }
+
+    fn coordinates(&self) -> Vec<&C> {
+        match self {
+            Shape::Point(crd) => vec![crd],
+            Shape::LineString(points) | Shape::MultiPoint(points) => points.iter().collect(),
+            Shape::Polygon(lines) | Shape::MultiLineString(lines) => {
+                lines.iter().flatten().collect()
+            }
+            Shape::MultiPolygon(polys) => polys.iter().flatten().flatten().collect(),
+        }
+    }
+
+    fn type_id(&self) -> u8 {
+        match self {
+            Shape::Point(_) => 1,
+            Shape::LineString(_) => 2,
+            Shape::Polygon(_) => 3,
+            Shape::MultiPoint(_) => 4,
+            Shape::MultiLineString(_) => 5,
+            Shape::MultiPolygon(_) => 6,
+        }
+    }
+}
+
+fn ring<S>(crd: S) -> impl Strategy<Value = Vec<S::Value>>
+where
+    S: Strategy,
+    S::Value: Clone,
+{
+    vec(crd, 3..8).prop_map(|mut ring| {
+        ring.push(ring[0].clone());
+        ring
+    })
+}
+
+fn shape<S>(crd: S) -> BoxedStrategy<Shape<S::Value>>
+where
+    S: Strategy + Clone + 'static,
+    S::Value: Clone + Debug,
+{
+    prop_oneof![
+        crd.clone().prop_map(Shape::Point),
+        vec(crd.clone(), 2..8).prop_map(Shape::LineString),
+        vec(ring(crd.clone()), 1..4).prop_map(Shape::Polygon),
+        vec(crd.clone(), 1..8).prop_map(Shape::MultiPoint),
+        vec(vec(crd.clone(), 2..8), 1..4).prop_map(Shape::MultiLineString),
+        vec(vec(ring(crd), 1..3), 1..3).prop_map(Shape::MultiPolygon),
+    ]
+    .boxed()
+}
+
+/// Dimensions as (has_z, has_m). Coordinates hold x, y, then z and m if present.
+fn dims() -> impl Strategy<Value = (bool, bool)> {
+    (any::<bool>(), any::<bool>())
+}
+
+fn ndims((has_z, has_m): (bool, bool)) -> usize {
+    2 + has_z as usize + has_m as usize
+}
+
+fn value() -> Range<f64> {
+    -1e12..1e12f64
+}
+
+fn crds<P: PointTrait>(point: &P) -> Vec<f64> {
+    let mut crds = vec![point.x(), point.y()];
+    crds.extend(point.opt_z());
+    crds.extend(point.opt_m());
+    crds
+}
+
+fn ewkb_shape(geom: &ewkb::Geometry) -> Shape<Vec<f64>> {
+    let line = |line: &ewkb::LineString| line.points.iter().map(crds).collect::<Vec<_>>();
+    let poly = |poly: &ewkb::Polygon| poly.rings.iter().map(line).collect::<Vec<_>>();
+    match geom {
+        GeometryT::Point(point) => Shape::Point(crds(point)),
+        GeometryT::LineString(g) => Shape::LineString(line(g)),
+        GeometryT::Polygon(g) => Shape::Polygon(poly(g)),
+        GeometryT::MultiPoint(g) => Shape::MultiPoint(g.points.iter().map(crds).collect()),
+        GeometryT::MultiLineString(g) => Shape::MultiLineString(g.lines.iter().map(line).collect()),
+        GeometryT::MultiPolygon(g) => Shape::MultiPolygon(g.polygons.iter().map(poly).collect()),
+        GeometryT::GeometryCollection(_) => panic!("unexpected geometry collection"),
+    }
+}
+
+fn twkb_shape(geom: &twkb::Geometry) -> Shape<Vec<f64>> {
+    let line = |line: &twkb::LineString| line.points.iter().map(crds).collect::<Vec<_>>();
+    let poly = |poly: &twkb::Polygon| poly.rings.iter().map(line).collect::<Vec<_>>();
+    match geom {
+        twkb::Geometry::Point(point) => Shape::Point(crds(point)),
+        twkb::Geometry::LineString(g) => Shape::LineString(line(g)),
+        twkb::Geometry::Polygon(g) => Shape::Polygon(poly(g)),
+        twkb::Geometry::MultiPoint(g) => Shape::MultiPoint(g.points.iter().map(crds).collect()),
+        twkb::Geometry::MultiLineString(g) => {
+            Shape::MultiLineString(g.lines.iter().map(line).collect())
+        }
+        twkb::Geometry::MultiPolygon(g) => {
+            Shape::MultiPolygon(g.polygons.iter().map(poly).collect())
+        }
+    }
+}
+
+fn geojson_dims(dims: (bool, bool)) -> Dims {
+    match dims {
+        (false, false) => Dims::Xy,
+        (true, false) => Dims::Xyz,
+        (false, true) => Dims::Xym,
+        (true, true) => Dims::Xyzm,
+    }
+}
+
+fn to_geojson(shape: &Shape<Vec<f64>>, crs: Option<i32>, dims: Option<Dims>) -> GeoJSONGeometry {
+    let type_name = match shape {
+        Shape::Point(_) => "Point",
+        Shape::LineString(_) => "LineString",
+        Shape::Polygon(_) => "Polygon",
+        Shape::MultiPoint(_) => "MultiPoint",
+        Shape::MultiLineString(_) => "MultiLineString",
+        Shape::MultiPolygon(_) => "MultiPolygon",
+    }
+    .to_owned();
+    match shape.clone() {
+        Shape::Point(coordinates) => GeoJSONGeometry::Point(geojson::Point {
+            type_name,
+            crs,
+            coordinates,
+            dims,
+        }),
+        Shape::LineString(coordinates) => GeoJSONGeometry::LineString(geojson::LineString {
+            type_name,
+            crs,
+            coordinates,
+            dims,
+        }),
+        Shape::Polygon(coordinates) => GeoJSONGeometry::Polygon(geojson::Polygon {
+            type_name,
+            crs,
+            coordinates,
+            dims,
+        }),
+        Shape::MultiPoint(coordinates) => GeoJSONGeometry::MultiPoint(geojson::MultiPoint {
+            type_name,
+            crs,
+            coordinates,
+            dims,
+        }),
+        Shape::MultiLineString(coordinates) => {
+            GeoJSONGeometry::MultiLineString(geojson::MultiLineString {
+                type_name,
+                crs,
+                coordinates,
+                dims,
+            })
+        }
+        Shape::MultiPolygon(coordinates) => GeoJSONGeometry::MultiPolygon(geojson::MultiPolygon {
+            type_name,
+            crs,
+            coordinates,
+            dims,
+        }),
+    }
+}
+
+// --- Reference encoders, written independently of the crate's writers
+
+/// Encode `shape` as EWKB, with nested geometries written the way PostGIS does.
+fn encode_ewkb(
+    shape: &Shape<Vec<f64>>,
+    (has_z, has_m): (bool, bool),
+    srid: Option<i32>,
+    big_endian: bool,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    let u32_bytes = |v: u32| {
+        if big_endian {
+            v.to_be_bytes()
+        } else {
+            v.to_le_bytes()
+        }
+    };
+    let header = |out: &mut Vec<u8>, type_id: u8, srid: Option<i32>| {
+        out.push(if big_endian { 0 } else { 1 });
+        let mut type_id = type_id as u32;
+        if has_z {
+            type_id |= 0x8000_0000;
+        }
+        if has_m {
+            type_id |= 0x4000_0000;
+        }
+        if srid.is_some() {
+            type_id |= 0x2000_0000;
+        }
+        out.extend(u32_bytes(type_id));
+        if let Some(srid) = srid {
+            out.extend(u32_bytes(srid as u32));
+        }
+    };
+    let point = |out: &mut Vec<u8>, crd: &Vec<f64>| {
+        for v in crd {
+            out.extend(if big_endian {
+                v.to_be_bytes()
+            } else {
+                v.to_le_bytes()
+            });
+        }
+    };
+    let points = |out: &mut Vec<u8>, crds: &Vec<Vec<f64>>| {
+        out.extend(u32_bytes(crds.len() as u32));
+        crds.iter().for_each(|crd| point(out, crd));
+    };
+    let rings = |out: &mut Vec<u8>, rings: &Vec<Vec<Vec<f64>>>| {
+        out.extend(u32_bytes(rings.len() as u32));
+        rings.iter().for_each(|ring| points(out, ring));
+    };
+    header(&mut out, shape.type_id(), srid);
+    match shape {
+        Shape::Point(crd) => point(&mut out, crd),
+        Shape::LineString(crds) => points(&mut out, crds),
+        Shape::Polygon(lines) => rings(&mut out, lines),
+        Shape::MultiPoint(crds) => {
+            out.extend(u32_bytes(crds.len() as u32));
+            for crd in crds {
+                header(&mut out, 1, None);
+                point(&mut out, crd);
+            }
+        }
+        Shape::MultiLineString(lines) => {
+            out.extend(u32_bytes(lines.len() as u32));
+            for line in lines {
+                header(&mut out, 2, None);
+                points(&mut out, line);
+            }
+        }
+        Shape::MultiPolygon(polys) => {
+            out.extend(u32_bytes(polys.len() as u32));
+            for poly in polys {
+                header(&mut out, 3, None);
+                rings(&mut out, poly);
+            }
+        }
+    }
+    out
+}
+
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TwkbOptions {
+    pub(crate) precision: i8,
+    pub(crate) prec_z: u8,
+    pub(crate) prec_m: u8,
+    pub(crate) size: bool,
+    pub(crate) bbox: bool,
+    /// Write an id list, numbering the members of multi-geometries from 0.
+    pub(crate) ids: bool,
+}
+
+fn twkb_options() -> impl Strategy<Value = TwkbOptions> {
+    (
+        -7i8..=7,
+        0u8..=7,
+        0u8..=7,
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(|(precision, prec_z, prec_m, size, bbox, ids)| TwkbOptions {
+            precision,
+            prec_z,
+            prec_m,
+            size,
+            bbox,
+            ids,
+        })
+}
+
+/// Encode `shape` with coordinates in units of the precision of each dimension as TWKB.
+pub(crate) fn encode_twkb(
+    shape: &Shape<Vec<i64>>,
+    dims: (bool, bool),
+    options: TwkbOptions,
+) -> Vec<u8> {
+    let (has_z, has_m) = dims;
+    let mut body = Vec::new();
+    let ids = options.ids
+        && matches!(
+            shape,
+            Shape::MultiPoint(_) | Shape::MultiLineString(_) | Shape::MultiPolygon(_)
+        );
+    let idlist = |body: &mut Vec<u8>, count: usize| {
+        if ids {
+            (0..count).for_each(|id| varint(body, zigzag(id as i64)));
+        }
+    };
+    let mut prev = vec![0i64; ndims(dims)];
+    let mut points = |body: &mut Vec<u8>, crds: &[Vec<i64>], with_count: bool| {
+        if with_count {
+            varint(body, crds.len() as u64);
+        }
+        for crd in crds {
+            for (prev, v) in prev.iter_mut().zip(crd) {
+                varint(body, zigzag(v - *prev));
+                *prev = *v;
+            }
+        }
+    };
+    match shape {
+        Shape::Point(crd) => points(&mut body, std::slice::from_ref(crd), false),
+        Shape::LineString(crds) => points(&mut body, crds, true),
+        Shape::MultiPoint(crds) => {
+            varint(&mut body, crds.len() as u64);
+            idlist(&mut body, crds.len());
+            points(&mut body, crds, false);
+        }
+        Shape::Polygon(lines) => {
+            varint(&mut body, lines.len() as u64);
+            lines.iter().for_each(|line| points(&mut body, line, true));
+        }
+        Shape::MultiLineString(lines) => {
+            varint(&mut body, lines.len() as u64);
+            idlist(&mut body, lines.len());
+            lines.iter().for_each(|line| points(&mut body, line, true));
+        }
+        Shape::MultiPolygon(polys) => {
+            varint(&mut body, polys.len() as u64);
+            idlist(&mut body, polys.len());
+            for rings in polys {
+                varint(&mut body, rings.len() as u64);
+                rings.iter().for_each(|ring| points(&mut body, ring, true));
+            }
+        }
+    }
+    let mut bbox = Vec::new();
+    if options.bbox {
+        let crds = shape.coordinates();
+        for dim in 0..ndims(dims) {
+            let min = crds.iter().map(|crd| crd[dim]).min().unwrap();
+            let max = crds.iter().map(|crd| crd[dim]).max().unwrap();
+            varint(&mut bbox, zigzag(min));
+            varint(&mut bbox, zigzag(max - min));
+        }
+    }
+
+    let mut out = vec![shape.type_id() | ((zigzag(options.precision as i64) as u8) << 4)];
+    let has_ext = has_z || has_m;
+    out.push(
+        options.bbox as u8 | (options.size as u8) << 1 | (ids as u8) << 2 | (has_ext as u8) << 3,
+    );
+    if has_ext {
+        out.push(has_z as u8 | (has_m as u8) << 1 | options.prec_z << 2 | options.prec_m << 5);
+    }
+    if options.size {
+        varint(&mut out, (bbox.len() + body.len()) as u64);
+    }
+    out.extend(bbox);
+    out.extend(body);
+    out
+}
+
+fn scale(units: i64, precision: i8) -> f64 {
+    units as f64 / 10f64.powi(precision as i32)
+}
+
+fn assert_close(actual: &Shape<Vec<f64>>, expected: &Shape<Vec<f64>>) {
+    assert_eq!(actual.map(|_| ()), expected.map(|_| ()));
+    for (a, e) in actual.coordinates().iter().zip(expected.coordinates()) {
+        assert_eq!(a.len(), e.len(), "{:?} != {:?}", a, e);
+        for (a, e) in a.iter().zip(e) {
+            assert!(
+                relative_eq!(a, e, epsilon = 1e-6, max_relative = 1e-9),
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+}
+
+fn write_ewkb(geom: &twkb::Geometry) -> Vec<u8> {
+    let mut out = Vec::new();
+    match geom {
+        twkb::Geometry::Point(g) => g.as_ewkb().write_ewkb(&mut out),
+        twkb::Geometry::LineString(g) => g.as_ewkb().write_ewkb(&mut out),
+        twkb::Geometry::Polygon(g) => g.as_ewkb().write_ewkb(&mut out),
+        twkb::Geometry::MultiPoint(g) => g.as_ewkb().write_ewkb(&mut out),
+        twkb::Geometry::MultiLineString(g) => g.as_ewkb().write_ewkb(&mut out),
+        twkb::Geometry::MultiPolygon(g) => g.as_ewkb().write_ewkb(&mut out),
+    }
+    .unwrap();
+    out
+}
+
+proptest! {
+    #[test]
+    fn geojson_ewkb_roundtrip(
+        (shape, dims, srid) in dims().prop_flat_map(|dims| {
+            (shape(vec(value(), ndims(dims))), Just(dims), proptest::option::of(1..100_000i32))
+        }),
+        byte_order in prop_oneof![Just(Endianness::Little), Just(Endianness::Big)]
+    ) {
+        let geom = to_geojson(&shape, srid, Some(geojson_dims(dims)));
+        let encoded = geom.to_ewkb_with(byte_order).unwrap();
+        prop_assert_eq!(ewkb::get_byte_order(&encoded), byte_order);
+        let decoded = ewkb::Geometry::read_ewkb_strict(&mut encoded.as_slice(), &ParseLimits::default()).unwrap();
+        prop_assert_eq!(GeoJSONGeometry::try_from(&decoded).unwrap(), geom);
+    }
+
+    #[test]
+    fn ewkb_byte_orders(
+        (shape, dims, srid) in dims().prop_flat_map(|dims| {
+            (shape(vec(value(), ndims(dims))), Just(dims), proptest::option::of(1..100_000i32))
+        })
+    ) {
+        let limits = ParseLimits::default();
+        let le = encode_ewkb(&shape, dims, srid, false);
+        let be = encode_ewkb(&shape, dims, srid, true);
+        let from_le = ewkb::Geometry::read_ewkb_strict(&mut le.as_slice(), &limits).unwrap();
+        let from_be = ewkb::Geometry::read_ewkb_strict(&mut be.as_slice(), &limits).unwrap();
+        prop_assert_eq!(ewkb_shape(&from_le), ewkb_shape(&from_be));
+        prop_assert_eq!(ewkb_shape(&from_le), shape);
+        prop_assert_eq!(GeoJSONGeometry::try_from(&from_le).unwrap().srid(), srid);
+
+        // Written back in either byte order, the geometry encodes exactly as the input
+        let mut written = Vec::new();
+        from_be.as_ewkb().write_ewkb_with(&mut written, Endianness::Little).unwrap();
+        prop_assert_eq!(&written, &le);
+        written.clear();
+        from_le.as_ewkb().write_ewkb_with(&mut written, Endianness::Big).unwrap();
+        prop_assert_eq!(&written, &be);
+    }
+
+    #[test]
+    fn twkb_matches_ewkb(
+        (units, dims, options, big_endian) in dims().prop_flat_map(|dims| {
+            (shape(vec(-1_000_000_000..1_000_000_000i64, ndims(dims))), Just(dims), twkb_options(), any::<bool>())
+        })
+    ) {
+        let limits = ParseLimits::default();
+        let precisions: Vec<i8> = match dims {
+            (true, true) => vec![options.precision, options.precision, options.prec_z as i8, options.prec_m as i8],
+            (true, false) => vec![options.precision, options.precision, options.prec_z as i8],
+            (false, true) => vec![options.precision, options.precision, options.prec_m as i8],
+            (false, false) => vec![options.precision, options.precision],
+        };
+        let expected = units.map(|crd| crd.iter().zip(&precisions).map(|(v, p)| scale(*v, *p)).collect::<Vec<_>>());
+
+        let twkb = encode_twkb(&units, dims, options);
+        let from_twkb = twkb::Geometry::read_twkb_strict(&mut twkb.as_slice(), &limits).unwrap();
+        assert_close(&twkb_shape(&from_twkb), &expected);
+
+        let ewkb = encode_ewkb(&expected, dims, None, big_endian);
+        let from_ewkb = ewkb::Geometry::read_ewkb_strict(&mut ewkb.as_slice(), &limits).unwrap();
+        assert_close(&twkb_shape(&from_twkb), &ewkb_shape(&from_ewkb));
+
+        // TWKB converted to EWKB keeps its coordinates exactly
+        let converted = write_ewkb(&from_twkb);
+        let from_converted = ewkb::Geometry::read_ewkb_strict(&mut converted.as_slice(), &limits).unwrap();
+        prop_assert_eq!(ewkb_shape(&from_converted), twkb_shape(&from_twkb));
+    }
+
+    #[test]
+    fn ewkb_view_matches_shape(
+        (shape, dims, srid) in dims().prop_flat_map(|dims| {
+            (shape(vec(value(), ndims(dims))), Just(dims), proptest::option::of(1..100_000i32))
+        }),
+        big_endian in any::<bool>()
+    ) {
+        let data = encode_ewkb(&shape, dims, srid, big_endian);
+        let view = EwkbView::new(&data).unwrap();
+        let crds = shape.coordinates();
+        prop_assert_eq!(view.srid(), srid);
+        prop_assert_eq!(view.dims(), geojson_dims(dims));
+        prop_assert_eq!(view.num_vertices(), crds.len());
+        prop_assert_eq!(
+            view.first_coord::<Coord>().map(|crd| crd.0.to_vec()),
+            crds.first().map(|crd| crd[..2].to_vec())
+        );
+        let bbox = view.bbox();
+        let min = |i: usize| crds.iter().map(|crd| crd[i]).fold(f64::INFINITY, f64::min);
+        let max = |i: usize| crds.iter().map(|crd| crd[i]).fold(f64::NEG_INFINITY, f64::max);
+        prop_assert_eq!((bbox.min_x, bbox.min_y, bbox.max_x, bbox.max_y), (min(0), min(1), max(0), max(1)));
+    }
+}
@@ -1,17 +1,23 @@
 // Python bindings
-#[cfg(feature = "proj")]
-extern crate proj;
 extern crate pyo3;
+use crate::encoding::{self, Encoding};
 use crate::error;
 use crate::ewkb::{
     AsEwkbLineString, AsEwkbMultiLineString, AsEwkbMultiPoint, AsEwkbMultiPolygon, AsEwkbPoint,
-    AsEwkbPolygon, EwkbRead, EwkbWrite,
+    AsEwkbPolygon, Endianness, EwkbRead, EwkbWrite,
 };
-use crate::geojson::{GeoJSONEncode, GeoJSONGeometry, GeometryType};
+use crate::geojson::{BinaryFormat, Dims, GeoJSONEncode, GeoJSONGeometry, GeometryType};
+use crate::limits::{expect_end, ParseLimits};
+#[cfg(feature = "rayon")]
+use crate::parallel;
+use crate::ragged::{Kind, Ragged};
+use crate::reproject::{get_transform, ErrorPolicy, TransformOptions};
+use crate::visit::{CrsFormat, GeoJsonWriter};
 
+use self::pyo3::buffer::{Element, PyBuffer};
 use self::pyo3::prelude::*;
 use self::pyo3::types::IntoPyDict;
-use self::pyo3::types::{PyBytes, PyDict};
+use self::pyo3::types::{PyBytes, PyDict, PyList, PyTuple};
 use error::Error as WKBError;
 use ewkb;
 use geojson;
@@ -33,6 +39,22 @@ impl From<WKBError> for PyErr {
 }
 
 use self::pyo3::exceptions::PyValueError;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::slice;
+use std::sync::{OnceLock, RwLock};
+
+fn parse_limits_lock() -> &'static RwLock<ParseLimits> {
+    static LIMITS: OnceLock<RwLock<ParseLimits>> = OnceLock::new();
+    LIMITS.get_or_init(|| RwLock::new(ParseLimits::default()))
+}
+
+/// Limits applied to all EWKB and TWKB input.
+fn parse_limits() -> ParseLimits {
+    *parse_limits_lock()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+}
 
 fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
     let type_result = match data.get_item("type") {
@@ -56,6 +78,14 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
         }
     };
 
+    let dims = match data.get_item("dims") {
+        Some(dims) => {
+            let dims: Option<&str> = dims.extract()?;
+            dims.map(str::parse).transpose()?
+        }
+        None => None,
+    };
+
     let data: GeoJSONGeometry = match type_name {
         "Point" => {
             let crds: Vec<f64> = crds_result.extract()?;
@@ -63,6 +93,7 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
                 type_name: type_name.to_owned(),
                 crs,
                 coordinates: crds,
+                dims,
             })
         }
         "LineString" => {
@@ -71,6 +102,7 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
                 type_name: type_name.to_owned(),
                 crs,
                 coordinates: crds,
+                dims,
             })
         }
         "Polygon" => {
@@ -79,6 +111,7 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
                 type_name: type_name.to_owned(),
                 crs,
                 coordinates: crds,
+                dims,
             })
         }
         "MultiPoint" => {
@@ -87,6 +120,7 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
                 type_name: type_name.to_owned(),
                 crs,
                 coordinates: crds,
+                dims,
             })
         }
         "MultiLineString" => {
@@ -95,6 +129,7 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
                 type_name: type_name.to_owned(),
                 crs,
                 coordinates: crds,
+                dims,
             })
         }
         "MultiPolygon" => {
@@ -103,6 +138,7 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
                 type_name: type_name.to_owned(),
                 crs,
                 coordinates: crds,
+                dims,
             })
         }
         _ => return Result::Err(PyValueError::new_err("invalid geoemetry type".to_owned())),
@@ -110,6 +146,136 @@ fn pydict_to_geojson(data: &PyDict) -> Result<GeoJSONGeometry, PyErr> {
     Ok(data)
 }
 
+/// GeoJSON geometry of a GeoJSON-like dict, or of an object implementing `__geo_interface__`
+/// such as a Shapely geometry. Features are read as their geometry.
+fn pyobject_to_geojson(data: &PyAny) -> Result<GeoJSONGeometry, PyErr> {
+    let data = if data.hasattr("__geo_interface__")? {
+        data.getattr("__geo_interface__")?
+    } else {
+        data
+    };
+    let dict: &PyDict = data.downcast().map_err(|_| {
+        PyValueError::new_err(
+            "expected a GeoJSON-like dict or an object implementing __geo_interface__",
+        )
+    })?;
+    match dict.get_item("type") {
+        Some(type_name) if type_name.eq("Feature")? => match dict.get_item("geometry") {
+            Some(geometry) => pyobject_to_geojson(geometry),
+            None => Err(PyValueError::new_err("invalid geojson".to_owned())),
+        },
+        _ => pydict_to_geojson(dict),
+    }
+}
+
+/// Shapely geometries built from ragged arrays with `shapely.from_ragged_array`, `None` for
+/// missing geometries. SRIDs are kept with `shapely.set_srid`.
+fn geojson_to_shapely(
+    py: Python<'_>,
+    geoms: &[Option<GeoJSONGeometry>],
+) -> PyResult<Vec<PyObject>> {
+    let shapely = py.import("shapely")?;
+    let numpy = py.import("numpy")?;
+    let ndarray = |data: Vec<u8>, dtype: &str| {
+        let kwargs = [("dtype", dtype)].into_py_dict(py);
+        numpy.call_method("frombuffer", (PyBytes::new(py, &data),), Some(kwargs))
+    };
+
+    // Geometries of one type and coordinate layout share a ragged array.
+    let mut groups: Vec<((Kind, Dims), Vec<usize>)> = Vec::new();
+    for (i, geom) in geoms.iter().enumerate() {
+        let geom = match geom {
+            Some(geom) => geom,
+            None => continue,
+        };
+        let key = (Kind::of(geom), geom.dims());
+        if matches!(key.1, Dims::Xym | Dims::Xyzm) {
+            return Err(
+                WKBError::Other("Shapely geometries cannot have M values".to_owned()).into(),
+            );
+        }
+        match groups.iter_mut().find(|(group, _)| *group == key) {
+            Some((_, indices)) => indices.push(i),
+            None => groups.push((key, vec![i])),
+        }
+    }
+
+    let mut shapes = vec![py.None(); geoms.len()];
+    for ((kind, dims), indices) in groups {
+        let mut ragged = Ragged::new(kind, dims);
+        for &i in &indices {
+            ragged.push(kind, geoms[i].as_ref());
+        }
+        let coords: Vec<u8> = ragged.coords.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        let coords = ndarray(coords, "float64")?.call_method1("reshape", (-1, ragged.width))?;
+        // Shapely takes the offsets from the innermost level outwards.
+        let offsets = ragged
+            .offsets
+            .iter()
+            .rev()
+            .map(|offsets| {
+                ndarray(
+                    offsets.iter().flat_map(|o| o.to_ne_bytes()).collect(),
+                    "int64",
+                )
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let offsets = match offsets.is_empty() {
+            true => py.None(),
+            false => PyTuple::new(py, offsets).to_object(py),
+        };
+        let geometry_type = shapely
+            .getattr("GeometryType")?
+            .getattr(kind.name().to_uppercase().as_str())?;
+        let mut array =
+            shapely.call_method1("from_ragged_array", (geometry_type, coords, offsets))?;
+        let srids: Vec<i32> = indices
+            .iter()
+            .map(|&i| geoms[i].as_ref().and_then(|geom| geom.srid()).unwrap_or(0))
+            .collect();
+        if srids.iter().any(|&srid| srid != 0) {
+            array = shapely.call_method1("set_srid", (array, srids))?;
+        }
+        let array: Vec<PyObject> = array.call_method0("tolist")?.extract()?;
+        for (i, shape) in indices.into_iter().zip(array) {
+            shapes[i] = shape;
+        }
+    }
+    Ok(shapes)
+}
+
+/// List of GeoJSON-like dicts, or Shapely geometries with `as_shapely`.
+fn geojson_to_pylist<'a>(
+    py: Python<'a>,
+    geoms: &[Option<GeoJSONGeometry>],
+    as_shapely: Option<bool>,
+) -> PyResult<&'a PyList> {
+    if as_shapely.unwrap_or(false) {
+        return Ok(PyList::new(py, geojson_to_shapely(py, geoms)?));
+    }
+    let list = PyList::empty(py);
+    for geom in geoms {
+        match geom {
+            Some(geom) => list.append(geojson_to_pydict(py, geom)?)?,
+            None => list.append(py.None())?,
+        }
+    }
+    Ok(list)
+}
+
+/// GeoJSON-like dict, or a Shapely geometry with `as_shapely`.
+fn geojson_to_pyobject(
+    py: Python<'_>,
+    geom: GeoJSONGeometry,
+    as_shapely: Option<bool>,
+) -> PyResult<PyObject> {
+    if as_shapely.unwrap_or(false) {
+        let mut shapes = geojson_to_shapely(py, &[Some(geom)])?;
+        return Ok(shapes.remove(0));
+    }
+    Ok(geojson_to_pydict(py, &geom)?.to_object(py))
+}
+
 fn geojson_to_pydict<'a>(py: Python<'a>, geom: &GeoJSONGeometry) -> Result<&'a PyDict, PyErr> {
     let key_vals: Vec<(&str, PyObject)> = match geom {
         GeoJSONGeometry::Point(g) => {
@@ -117,6 +283,7 @@ fn geojson_to_pydict<'a>(py: Python<'a>, geom: &GeoJSONGeometry) -> Result<&'a P
                 ("type", g.type_name.to_object(py)),
                 ("crs", g.crs.to_object(py)),
                 ("coordinates", g.coordinates.to_object(py)),
+                ("dims", g.dims().as_str().to_object(py)),
             ]
         }
         GeoJSONGeometry::LineString(g) => {
@@ -124,6 +291,7 @@ fn geojson_to_pydict<'a>(py: Python<'a>, geom: &GeoJSONGeometry) -> Result<&'a P
                 ("type", g.type_name.to_object(py)),
                 ("crs", g.crs.to_object(py)),
                 ("coordinates", g.coordinates.to_object(py)),
+                ("dims", g.dims().as_str().to_object(py)),
             ]
         }
         GeoJSONGeometry::Polygon(g) => {
@@ -131,6 +299,7 @@ fn geojson_to_pydict<'a>(py: Python<'a>, geom: &GeoJSONGeometry) -> Result<&'a P
                 ("type", g.type_name.to_object(py)),
                 ("crs", g.crs.to_object(py)),
                 ("coordinates", g.coordinates.to_object(py)),
+                ("dims", g.dims().as_str().to_object(py)),
             ]
         }
         GeoJSONGeometry::MultiPoint(g) => {
@@ -138,6 +307,7 @@ fn geojson_to_pydict<'a>(py: Python<'a>, geom: &GeoJSONGeometry) -> Result<&'a P
                 ("type", g.type_name.to_object(py)),
                 ("crs", g.crs.to_object(py)),
                 ("coordinates", g.coordinates.to_object(py)),
+                ("dims", g.dims().as_str().to_object(py)),
             ]
         }
         GeoJSONGeometry::MultiLineString(g) => {
@@ -145,6 +315,7 @@ fn geojson_to_pydict<'a>(py: Python<'a>, geom: &GeoJSONGeometry) -> Result<&'a P
                 ("type", g.type_name.to_object(py)),
                 ("crs", g.crs.to_object(py)),
                 ("coordinates", g.coordinates.to_object(py)),
+                ("dims", g.dims().as_str().to_object(py)),
             ]
         }
         GeoJSONGeometry::MultiPolygon(g) => {
@@ -152,108 +323,341 @@ fn geojson_to_pydict<'a>(py: Python<'a>, geom: &GeoJSONGeometry) -> Result<&'a P
                 ("type", g.type_name.to_object(py)),
                 ("crs", g.crs.to_object(py)),
                 ("coordinates", g.coordinates.to_object(py)),
+                ("dims", g.dims().as_str().to_object(py)),
             ]
         }
     };
     Ok(key_vals.into_py_dict(py))
 }
 
-fn parse_twkb_to_geojson(mut data: &[u8]) -> Result<GeoJSONGeometry, error::Error> {
-    let geom_type = twkb::get_geom_type(&[data[0]]);
-    match geom_type {
+fn parse_twkb_to_geojson(mut data: &[u8], strict: bool) -> Result<GeoJSONGeometry, error::Error> {
+    let limits = parse_limits();
+    let geom_type = twkb::get_geom_type(data);
+    let geom = match geom_type {
         GeometryType::Point => {
-            let geom = twkb::Point::read_twkb(&mut data)?;
-            Ok(GeoJSONGeometry::Point(geom.to_geojson()))
+            let geom = twkb::Point::read_twkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::Point(geom.to_geojson())
         }
         GeometryType::LineString => {
-            let geom = twkb::LineString::read_twkb(&mut data)?;
-            Ok(GeoJSONGeometry::LineString(geom.to_geojson()))
+            let geom = twkb::LineString::read_twkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::LineString(geom.to_geojson())
         }
         GeometryType::Polygon => {
-            let geom = twkb::Polygon::read_twkb(&mut data)?;
-            Ok(GeoJSONGeometry::Polygon(geom.to_geojson()))
+            let geom = twkb::Polygon::read_twkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::Polygon(geom.to_geojson())
         }
         GeometryType::MultiPoint => {
-            let geom = twkb::MultiPoint::read_twkb(&mut data)?;
-            Ok(GeoJSONGeometry::MultiPoint(geom.to_geojson()))
+            let geom = twkb::MultiPoint::read_twkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::MultiPoint(geom.to_geojson())
         }
         GeometryType::MultiLineString => {
-            let geom = twkb::MultiLineString::read_twkb(&mut data)?;
-            Ok(GeoJSONGeometry::MultiLineString(geom.to_geojson()))
+            let geom = twkb::MultiLineString::read_twkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::MultiLineString(geom.to_geojson())
         }
         GeometryType::MultiPolygon => {
-            let geom = twkb::MultiPolygon::read_twkb(&mut data)?;
-            Ok(GeoJSONGeometry::MultiPolygon(geom.to_geojson()))
+            let geom = twkb::MultiPolygon::read_twkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::MultiPolygon(geom.to_geojson())
         }
-        GeometryType::GeometryCollection => Err(WKBError::Other(
-            "not implemented for GeometryCollection".to_owned(),
-        )),
-        GeometryType::None => Err(WKBError::Read("invalid geometry type".to_owned())),
+        GeometryType::GeometryCollection => {
+            return Err(WKBError::Other(
+                "not implemented for GeometryCollection".to_owned(),
+            ))
+        }
+        GeometryType::None => return Err(WKBError::Read("invalid geometry type".to_owned())),
+    };
+    if strict {
+        expect_end(&mut data)?;
     }
+    Ok(geom)
 }
 
-fn parse_ewkb_to_geojson(mut data: &[u8]) -> Result<GeoJSONGeometry, error::Error> {
+fn parse_ewkb_to_geojson(mut data: &[u8], strict: bool) -> Result<GeoJSONGeometry, error::Error> {
+    let limits = parse_limits();
     let geom_type = ewkb::get_geom_type(data);
-    match geom_type {
+    let geom = match geom_type {
         GeometryType::Point => {
-            let geom = ewkb::Point::read_ewkb(&mut data)?;
-            Ok(GeoJSONGeometry::Point(geom.to_geojson()))
+            let geom = ewkb::Point::read_ewkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::Point(geom.to_geojson())
         }
         GeometryType::LineString => {
-            let geom = ewkb::LineString::read_ewkb(&mut data)?;
-            Ok(GeoJSONGeometry::LineString(geom.to_geojson()))
+            let geom = ewkb::LineString::read_ewkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::LineString(geom.to_geojson())
         }
         GeometryType::Polygon => {
-            let geom = ewkb::Polygon::read_ewkb(&mut data)?;
-            Ok(GeoJSONGeometry::Polygon(geom.to_geojson()))
+            let geom = ewkb::Polygon::read_ewkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::Polygon(geom.to_geojson())
         }
         GeometryType::MultiPoint => {
-            let geom = ewkb::MultiPoint::read_ewkb(&mut data)?;
-            Ok(GeoJSONGeometry::MultiPoint(geom.to_geojson()))
+            let geom = ewkb::MultiPoint::read_ewkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::MultiPoint(geom.to_geojson())
         }
         GeometryType::MultiLineString => {
-            let geom = ewkb::MultiLineString::read_ewkb(&mut data)?;
-            Ok(GeoJSONGeometry::MultiLineString(geom.to_geojson()))
+            let geom = ewkb::MultiLineString::read_ewkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::MultiLineString(geom.to_geojson())
         }
         GeometryType::MultiPolygon => {
-            let geom = ewkb::MultiPolygon::read_ewkb(&mut data)?;
-            Ok(GeoJSONGeometry::MultiPolygon(geom.to_geojson()))
+            let geom = ewkb::MultiPolygon::read_ewkb_limited(&mut data, &limits)?;
+            GeoJSONGeometry::MultiPolygon(geom.to_geojson())
+        }
+        GeometryType::GeometryCollection => {
+            return Err(WKBError::Other(
+                "not implemented for GeometryCollection".to_owned(),
+            ))
         }
-        GeometryType::GeometryCollection => Err(WKBError::Other(
-            "not implemented for GeometryCollection".to_owned(),
-        )),
-        GeometryType::None => Err(WKBError::Read("invalid geometry type".to_owned())),
+        GeometryType::None => return Err(WKBError::Read("invalid geometry type".to_owned())),
+    };
+    if strict {
+        expect_end(&mut data)?;
     }
+    Ok(geom)
 }
 
-/// TWKB parse
-#[pyfunction]
-fn twkb_to_geojson<'a>(
+/// Read a geometry for the GeoJSON functions.
+///
+/// With the `rayon` feature, the members of large multi-part geometries are read on the rayon
+/// thread pool. Input the parallel reader fails on or does not read completely when `strict` is
+/// read again on this thread, for the same result or error as without the feature.
+fn read_geojson(
+    data: &[u8],
+    format: BinaryFormat,
+    strict: bool,
+) -> Result<GeoJSONGeometry, error::Error> {
+    #[cfg(feature = "rayon")]
+    if let Ok((geom, bytes)) = parallel::read_binary_prefix(data, format, &parse_limits()) {
+        if !strict || bytes == data.len() {
+            return Ok(geom);
+        }
+    }
+    match format {
+        BinaryFormat::Ewkb => parse_ewkb_to_geojson(data, strict),
+        BinaryFormat::Twkb => parse_twkb_to_geojson(data, strict),
+    }
+}
+
+/// Read geometries stored back to back, reading the members of large multi-part geometries on
+/// the rayon thread pool with the `rayon` feature.
+fn read_geojson_many(
+    data: &[u8],
+    format: BinaryFormat,
+) -> Result<Vec<GeoJSONGeometry>, error::Error> {
+    let limits = parse_limits();
+    let mut geoms = Vec::new();
+    let mut data = data;
+    while !data.is_empty() {
+        #[cfg(feature = "rayon")]
+        let (geom, bytes) = parallel::read_binary_prefix(data, format, &limits)?;
+        #[cfg(not(feature = "rayon"))]
+        let (geom, bytes) = geojson::read_binary_prefix(data, format, &limits)?;
+        geoms.push(geom);
+        data = &data[bytes..];
+    }
+    Ok(geoms)
+}
+
+fn transform_options(
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+) -> Result<TransformOptions, error::Error> {
+    Ok(TransformOptions {
+        on_error: match on_error {
+            Some(on_error) => on_error.parse()?,
+            None => ErrorPolicy::default(),
+        },
+        check_area_of_use: check_area_of_use.unwrap_or(false),
+    })
+}
+
+fn byte_order(byte_order: Option<&str>) -> Result<Endianness, error::Error> {
+    Ok(match byte_order {
+        Some(byte_order) => byte_order.parse()?,
+        None => Endianness::default(),
+    })
+}
+
+/// Binary geometry input: bytes, an object supporting the buffer protocol, or text in the
+/// encoding given by `input_encoding`.
+#[derive(FromPyObject)]
+enum WkbInput<'a> {
+    Bytes(&'a [u8]),
+    Text(&'a str),
+    Buffer(PyBuffer<u8>),
+    /// Buffers of signed bytes, such as pyarrow buffers, which have the format `b`.
+    SignedBuffer(PyBuffer<i8>),
+}
+
+impl WkbInput<'_> {
+    /// Decode the input. Bytes and buffers are raw and text is hex unless `input_encoding` says
+    /// otherwise.
+    fn decode(&self, py: Python<'_>, input_encoding: Option<&str>) -> PyResult<Cow<'_, [u8]>> {
+        Ok(match self.encoded(py, input_encoding)? {
+            (data, Encoding::Bytes) => data,
+            (data, encoding) => Cow::Owned(encoding::decode(&data, encoding)?.into_owned()),
+        })
+    }
+
+    /// The input as it is along with its encoding, for decoding parts of it separately.
+    fn encoded(
+        &self,
+        py: Python<'_>,
+        input_encoding: Option<&str>,
+    ) -> PyResult<(Cow<'_, [u8]>, Encoding)> {
+        let (data, default) = match self {
+            WkbInput::Bytes(data) => (Cow::Borrowed(*data), Encoding::Bytes),
+            WkbInput::Text(data) => (Cow::Borrowed(data.as_bytes()), Encoding::Hex),
+            WkbInput::Buffer(buffer) => (buffer_bytes(py, buffer, |b| b)?, Encoding::Bytes),
+            WkbInput::SignedBuffer(buffer) => {
+                (buffer_bytes(py, buffer, |b| b as u8)?, Encoding::Bytes)
+            }
+        };
+        let encoding = match input_encoding {
+            Some(encoding) => encoding.parse()?,
+            None => default,
+        };
+        Ok((data, encoding))
+    }
+}
+
+/// Contents of a buffer of signed or unsigned bytes, borrowed when it is contiguous.
+fn buffer_bytes<'a, T: Element>(
+    py: Python<'_>,
+    buffer: &'a PyBuffer<T>,
+    to_u8: fn(T) -> u8,
+) -> PyResult<Cow<'a, [u8]>> {
+    if !buffer.is_c_contiguous() {
+        return Ok(Cow::Owned(
+            buffer.to_vec(py)?.into_iter().map(to_u8).collect(),
+        ));
+    }
+    // SAFETY: the buffer is contiguous and its items are single bytes, and it stays exported until
+    // `buffer` is dropped, so it cannot be resized or freed while borrowed. As with other
+    // zero-copy consumers, callers must not write into it during the call.
+    let data = unsafe { slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.len_bytes()) };
+    Ok(Cow::Borrowed(data))
+}
+
+/// Offsets of geometries in a batch: a buffer of 32 or 64-bit integers, or a list.
+#[derive(FromPyObject)]
+enum Offsets {
+    Int32(PyBuffer<i32>),
+    Int64(PyBuffer<i64>),
+    List(Vec<i64>),
+}
+
+impl Offsets {
+    fn to_vec(&self, py: Python<'_>) -> PyResult<Vec<i64>> {
+        Ok(match self {
+            Offsets::Int32(buffer) => buffer.to_vec(py)?.into_iter().map(i64::from).collect(),
+            Offsets::Int64(buffer) => buffer.to_vec(py)?,
+            Offsets::List(offsets) => offsets.clone(),
+        })
+    }
+}
+
+/// Slices of `data` between consecutive `offsets`, as in Arrow binary arrays.
+fn offset_slices<'a>(data: &'a [u8], offsets: &[i64]) -> Result<Vec<&'a [u8]>, error::Error> {
+    let mut slices = Vec::with_capacity(offsets.len().saturating_sub(1));
+    for (i, pair) in offsets.windows(2).enumerate() {
+        let start = usize::try_from(pair[0]).ok();
+        let end = usize::try_from(pair[1]).ok();
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end && end <= data.len() => {
+                slices.push(&data[start..end])
+            }
+            _ => {
+                return Err(WKBError::Other(format!(
+                    "invalid offsets {}..{} of geometry {} in {} bytes",
+                    pair[0],
+                    pair[1],
+                    i,
+                    data.len()
+                )))
+            }
+        }
+    }
+    Ok(slices)
+}
+
+/// Convert the geometries of a batch, mapping empty entries to `None`. Each entry is decoded
+/// from `encoding` on its own, as in Arrow string arrays of hex or base64 values.
+fn geojson_batch<'a, F>(
     py: Python<'a>,
     data: &[u8],
+    encoding: Encoding,
+    offsets: &[i64],
+    as_shapely: Option<bool>,
+    convert: F,
+) -> PyResult<&'a PyList>
+where
+    F: Fn(&[u8]) -> Result<GeoJSONGeometry, error::Error> + Sync,
+{
+    let geoms = py.allow_threads(|| {
+        offset_slices(data, offsets)?
+            .into_iter()
+            .map(|slice| match slice.is_empty() {
+                true => Ok(None),
+                false => convert(&encoding::decode(slice, encoding)?).map(Some),
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    geojson_to_pylist(py, &geoms, as_shapely)
+}
+
+/// Binary geometry output in the encoding given by `output_encoding`, bytes by default.
+fn encode_output(py: Python<'_>, data: &[u8], output_encoding: Option<&str>) -> PyResult<PyObject> {
+    let encoding = match output_encoding {
+        Some(encoding) => encoding.parse()?,
+        None => Encoding::default(),
+    };
+    Ok(match encoding {
+        Encoding::Bytes => PyBytes::new(py, data).to_object(py),
+        Encoding::Hex => encoding::encode_hex(data).to_object(py),
+        Encoding::Base64 => encoding::encode_base64(data).to_object(py),
+    })
+}
+
+fn transform_geojson(
+    geom: &mut GeoJSONGeometry,
     from_srid: Option<i32>,
     to_srid: Option<i32>,
-) -> PyResult<&'a PyDict> {
-    let mut geojson_geom = parse_twkb_to_geojson(data)?;
+    options: &TransformOptions,
+) -> Result<(), error::Error> {
+    match (from_srid, to_srid) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err(WKBError::Other("missing from_srid".to_string())),
+        (Some(_), None) => Err(WKBError::Other("missing to_srid".to_string())),
+        (Some(from_srid), Some(to_srid)) => {
+            let xform = get_transform(from_srid, to_srid)?;
+            geom.transform_with(&xform, options)
+        }
+    }
+}
 
-    #[cfg(feature = "proj")]
-    {
-        use crate::reproject::get_transform;
-        match (from_srid, to_srid) {
-            (None, None) => (),
-            (None, Some(_)) => {
-                return Err(error::Error::Other("missing from_srid".to_string()).into())
-            }
-            (Some(_), None) => {
-                return Err(error::Error::Other("missing to_srid".to_string()).into())
-            }
-            (Some(from_srid), Some(to_srid)) => {
-                let xform = get_transform(from_srid, to_srid)?;
+/// TWKB parse
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn twkb_to_geojson(
+    py: Python<'_>,
+    data: WkbInput,
+    from_srid: Option<i32>,
+    to_srid: Option<i32>,
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    strict: Option<bool>,
+    input_encoding: Option<&str>,
+    as_shapely: Option<bool>,
+) -> PyResult<PyObject> {
+    let data = data.decode(py, input_encoding)?;
+    let strict = strict.unwrap_or(false);
+    let mut geojson_geom = py.allow_threads(|| read_geojson(&data, BinaryFormat::Twkb, strict))?;
 
-                geojson_geom.transform(&xform)?;
-                geojson_geom.set_srid(to_srid);
-            }
-        };
+    let options = transform_options(on_error, check_area_of_use)?;
+    transform_geojson(&mut geojson_geom, from_srid, to_srid, &options)?;
+    if let Some(to_srid) = to_srid {
+        geojson_geom.set_srid(to_srid);
+    }
+
+    if as_shapely.unwrap_or(false) {
+        return geojson_to_pyobject(py, geojson_geom, as_shapely);
     }
 
     let crds = match &geojson_geom {
@@ -269,51 +673,48 @@ fn twkb_to_geojson<'a>(
         ("type", geojson_geom.geom_type().to_string().to_object(py)),
         ("crs", geojson_geom.srid().to_object(py)),
         crds,
+        ("dims", geojson_geom.dims().as_str().to_object(py)),
     ];
-    Ok(key_vals.into_py_dict(py))
+    Ok(key_vals.into_py_dict(py).to_object(py))
 }
 
 /// EWKB parse
 #[pyfunction]
-fn ewkb_to_geojson<'a>(
-    py: Python<'a>,
-    data: &[u8],
+#[allow(clippy::too_many_arguments)]
+fn ewkb_to_geojson(
+    py: Python<'_>,
+    data: WkbInput,
     from_srid: Option<i32>,
     to_srid: Option<i32>,
-) -> PyResult<&'a PyDict> {
-    let mut geojson_geom = parse_ewkb_to_geojson(data)?;
-
-    #[cfg(feature = "proj")]
-    {
-        use crate::reproject::get_transform;
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    strict: Option<bool>,
+    input_encoding: Option<&str>,
+    as_shapely: Option<bool>,
+) -> PyResult<PyObject> {
+    let data = data.decode(py, input_encoding)?;
+    let strict = strict.unwrap_or(false);
+    let mut geojson_geom = py.allow_threads(|| read_geojson(&data, BinaryFormat::Ewkb, strict))?;
 
-        let from_srid = {
-            if to_srid.is_some() {
-                if let Some(from_srid) = from_srid {
-                    Some(from_srid)
-                } else {
-                    geojson_geom.srid()
-                }
+    let from_srid = {
+        if to_srid.is_some() {
+            if let Some(from_srid) = from_srid {
+                Some(from_srid)
             } else {
-                None
-            }
-        };
-
-        match (from_srid, to_srid) {
-            (None, None) => (),
-            (None, Some(_)) => {
-                return Err(error::Error::Other("missing from_srid".to_string()).into())
+                geojson_geom.srid()
             }
-            (Some(_), None) => {
-                return Err(error::Error::Other("missing to_srid".to_string()).into())
-            }
-            (Some(from_srid), Some(to_srid)) => {
-                let xform = get_transform(from_srid, to_srid)?;
+        } else {
+            None
+        }
+    };
+    let options = transform_options(on_error, check_area_of_use)?;
+    transform_geojson(&mut geojson_geom, from_srid, to_srid, &options)?;
+    if let Some(to_srid) = to_srid {
+        geojson_geom.set_srid(to_srid);
+    }
 
-                geojson_geom.transform(&xform)?;
-                geojson_geom.set_srid(to_srid);
-            }
-        };
+    if as_shapely.unwrap_or(false) {
+        return geojson_to_pyobject(py, geojson_geom, as_shapely);
     }
 
     let crds = match &geojson_geom {
@@ -329,108 +730,288 @@ fn ewkb_to_geojson<'a>(
         ("type", geojson_geom.geom_type().to_string().to_object(py)),
         ("crs", geojson_geom.srid().to_object(py)),
         crds,
+        ("dims", geojson_geom.dims().as_str().to_object(py)),
     ];
-    Ok(key_vals.into_py_dict(py))
+    Ok(key_vals.into_py_dict(py).to_object(py))
 }
 
-#[cfg(not(feature = "proj"))]
+/// Write the geometry read by `visit` from `data` as a GeoJSON string.
+fn geojson_str<'d, F>(
+    mut data: &'d [u8],
+    capacity: usize,
+    strict: bool,
+    precision: Option<u8>,
+    crs: Option<&str>,
+    visit: F,
+) -> Result<String, error::Error>
+where
+    F: FnOnce(&mut &'d [u8], &mut GeoJsonWriter, &ParseLimits) -> Result<(), error::Error>,
+{
+    let crs = match crs {
+        Some(crs) => crs.parse()?,
+        None => CrsFormat::default(),
+    };
+    let mut writer = GeoJsonWriter::with_capacity(capacity).crs(crs);
+    if let Some(precision) = precision {
+        writer = writer.precision(precision);
+    }
+    visit(&mut data, &mut writer, &parse_limits())?;
+    if strict {
+        expect_end(&mut data)?;
+    }
+    Ok(writer.finish())
+}
+
+/// TWKB parse into a GeoJSON string
+#[pyfunction]
+fn twkb_to_geojson_str(
+    py: Python<'_>,
+    data: WkbInput,
+    strict: Option<bool>,
+    input_encoding: Option<&str>,
+    precision: Option<u8>,
+    crs: Option<&str>,
+) -> PyResult<String> {
+    let data = data.decode(py, input_encoding)?;
+    // A coordinate takes a few bytes as TWKB and a few dozen characters as GeoJSON.
+    let capacity = data.len().saturating_mul(8);
+    let strict = strict.unwrap_or(false);
+    Ok(geojson_str(
+        &data,
+        capacity,
+        strict,
+        precision,
+        crs,
+        twkb::visit_twkb,
+    )?)
+}
+
+/// EWKB parse into a GeoJSON string
 #[pyfunction]
-fn reproject_not_implemented() -> PyResult<()> {
-    Err(PyValueError::new_err(
-        "reproject_geojson not implemented - use wkbparse-proj package instead".to_owned(),
-    ))
+fn ewkb_to_geojson_str(
+    py: Python<'_>,
+    data: WkbInput,
+    strict: Option<bool>,
+    input_encoding: Option<&str>,
+    precision: Option<u8>,
+    crs: Option<&str>,
+) -> PyResult<String> {
+    let data = data.decode(py, input_encoding)?;
+    // A coordinate value takes 8 bytes as EWKB and up to about 20 characters as GeoJSON, plus
+    // brackets and commas.
+    let capacity = data.len().saturating_mul(3);
+    let strict = strict.unwrap_or(false);
+    Ok(geojson_str(
+        &data,
+        capacity,
+        strict,
+        precision,
+        crs,
+        ewkb::visit_ewkb,
+    )?)
 }
 
+/// TWKB parse of geometries stored back to back
 #[pyfunction]
-fn geojson_to_ewkb<'a>(
+#[allow(clippy::too_many_arguments)]
+fn twkb_to_geojson_many<'a>(
     py: Python<'a>,
-    data: &PyDict,
+    data: WkbInput,
     from_srid: Option<i32>,
     to_srid: Option<i32>,
-) -> PyResult<&'a PyBytes> {
-    let mut geom = pydict_to_geojson(data)?;
-
-    #[cfg(feature = "proj")]
-    {
-        use crate::reproject::get_transform;
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    input_encoding: Option<&str>,
+    as_shapely: Option<bool>,
+) -> PyResult<&'a PyList> {
+    let data = data.decode(py, input_encoding)?;
+    let options = transform_options(on_error, check_area_of_use)?;
+    let mut geoms = Vec::new();
+    for mut geojson_geom in py.allow_threads(|| read_geojson_many(&data, BinaryFormat::Twkb))? {
+        transform_geojson(&mut geojson_geom, from_srid, to_srid, &options)?;
+        if let Some(to_srid) = to_srid {
+            geojson_geom.set_srid(to_srid);
+        }
+        geoms.push(Some(geojson_geom));
+    }
+    geojson_to_pylist(py, &geoms, as_shapely)
+}
 
-        let from_srid = {
-            if to_srid.is_some() {
-                if let Some(from_srid) = from_srid {
-                    Some(from_srid)
-                } else {
-                    geom.srid()
-                }
-            } else {
-                None
-            }
+/// EWKB parse of geometries stored back to back
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn ewkb_to_geojson_many<'a>(
+    py: Python<'a>,
+    data: WkbInput,
+    from_srid: Option<i32>,
+    to_srid: Option<i32>,
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    input_encoding: Option<&str>,
+    as_shapely: Option<bool>,
+) -> PyResult<&'a PyList> {
+    let data = data.decode(py, input_encoding)?;
+    let options = transform_options(on_error, check_area_of_use)?;
+    let mut geoms = Vec::new();
+    for mut geojson_geom in py.allow_threads(|| read_geojson_many(&data, BinaryFormat::Ewkb))? {
+        let from_srid = match to_srid {
+            Some(_) => from_srid.or_else(|| geojson_geom.srid()),
+            None => None,
         };
+        transform_geojson(&mut geojson_geom, from_srid, to_srid, &options)?;
+        if let Some(to_srid) = to_srid {
+            geojson_geom.set_srid(to_srid);
+        }
+        geoms.push(Some(geojson_geom));
+    }
+    geojson_to_pylist(py, &geoms, as_shapely)
+}
 
-        match (from_srid, to_srid) {
-            (None, None) => (),
-            (None, Some(_)) => {
-                return Err(error::Error::Other("missing from_srid".to_string()).into())
-            }
-            (Some(_), None) => {
-                return Err(error::Error::Other("missing to_srid".to_string()).into())
-            }
-            (Some(from_srid), Some(to_srid)) => {
-                let xform = get_transform(from_srid, to_srid)?;
+/// TWKB parse of a batch of geometries delimited by offsets
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn twkb_to_geojson_batch<'a>(
+    py: Python<'a>,
+    data: WkbInput,
+    offsets: Offsets,
+    from_srid: Option<i32>,
+    to_srid: Option<i32>,
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    input_encoding: Option<&str>,
+    as_shapely: Option<bool>,
+) -> PyResult<&'a PyList> {
+    let (data, encoding) = data.encoded(py, input_encoding)?;
+    let offsets = offsets.to_vec(py)?;
+    let options = transform_options(on_error, check_area_of_use)?;
+    geojson_batch(py, &data, encoding, &offsets, as_shapely, |data| {
+        let mut geojson_geom = read_geojson(data, BinaryFormat::Twkb, true)?;
+        transform_geojson(&mut geojson_geom, from_srid, to_srid, &options)?;
+        if let Some(to_srid) = to_srid {
+            geojson_geom.set_srid(to_srid);
+        }
+        Ok(geojson_geom)
+    })
+}
 
-                geom.transform(&xform)?
-            }
+/// EWKB parse of a batch of geometries delimited by offsets
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn ewkb_to_geojson_batch<'a>(
+    py: Python<'a>,
+    data: WkbInput,
+    offsets: Offsets,
+    from_srid: Option<i32>,
+    to_srid: Option<i32>,
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    input_encoding: Option<&str>,
+    as_shapely: Option<bool>,
+) -> PyResult<&'a PyList> {
+    let (data, encoding) = data.encoded(py, input_encoding)?;
+    let offsets = offsets.to_vec(py)?;
+    let options = transform_options(on_error, check_area_of_use)?;
+    geojson_batch(py, &data, encoding, &offsets, as_shapely, |data| {
+        let mut geojson_geom = read_geojson(data, BinaryFormat::Ewkb, true)?;
+        let from_srid = match to_srid {
+            Some(_) => from_srid.or_else(|| geojson_geom.srid()),
+            None => None,
         };
-    }
+        transform_geojson(&mut geojson_geom, from_srid, to_srid, &options)?;
+        if let Some(to_srid) = to_srid {
+            geojson_geom.set_srid(to_srid);
+        }
+        Ok(geojson_geom)
+    })
+}
+
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn geojson_to_ewkb(
+    py: Python<'_>,
+    data: &PyAny,
+    from_srid: Option<i32>,
+    to_srid: Option<i32>,
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    byte_order: Option<&str>,
+    output_encoding: Option<&str>,
+) -> PyResult<PyObject> {
+    let byte_order = self::byte_order(byte_order)?;
+    let mut geom = pyobject_to_geojson(data)?;
 
-    let data = match geom {
-        GeoJSONGeometry::Point(geom) => geom.to_ewkb()?,
-        GeoJSONGeometry::LineString(geom) => geom.to_ewkb()?,
-        GeoJSONGeometry::Polygon(geom) => geom.to_ewkb()?,
-        GeoJSONGeometry::MultiPoint(geom) => geom.to_ewkb()?,
-        GeoJSONGeometry::MultiLineString(geom) => geom.to_ewkb()?,
-        GeoJSONGeometry::MultiPolygon(geom) => geom.to_ewkb()?,
+    let from_srid = {
+        if to_srid.is_some() {
+            if let Some(from_srid) = from_srid {
+                Some(from_srid)
+            } else {
+                geom.srid()
+            }
+        } else {
+            None
+        }
     };
-    Ok(PyBytes::new(py, &data))
+    let options = transform_options(on_error, check_area_of_use)?;
+    transform_geojson(&mut geom, from_srid, to_srid, &options)?;
+
+    let data = geom.to_ewkb_with(byte_order)?;
+    encode_output(py, &data, output_encoding)
 }
 
 #[pyfunction]
-fn twkb_to_ewkb<'a>(py: Python<'a>, mut data: &[u8]) -> PyResult<&'a PyBytes> {
-    let geom_type = twkb::get_geom_type(&[data[0]]);
+#[allow(clippy::too_many_arguments)]
+fn twkb_to_ewkb(
+    py: Python<'_>,
+    data: WkbInput,
+    from_srid: Option<i32>,
+    to_srid: Option<i32>,
+    on_error: Option<&str>,
+    check_area_of_use: Option<bool>,
+    strict: Option<bool>,
+    byte_order: Option<&str>,
+    input_encoding: Option<&str>,
+    output_encoding: Option<&str>,
+) -> PyResult<PyObject> {
+    let byte_order = self::byte_order(byte_order)?;
+    let decoded = data.decode(py, input_encoding)?;
+    let mut data = &decoded[..];
+    let limits = parse_limits();
+    let geom_type = twkb::get_geom_type(data);
     let result = match geom_type {
         GeometryType::Point => {
-            let geom = twkb::Point::read_twkb(&mut data)?;
+            let geom = twkb::Point::read_twkb_limited(&mut data, &limits)?;
             let mut encoded = Vec::with_capacity(9 + 8 * 3);
-            geom.as_ewkb().write_ewkb(&mut encoded)?;
+            geom.as_ewkb().write_ewkb_with(&mut encoded, byte_order)?;
             encoded
         }
         GeometryType::LineString => {
-            let geom = twkb::LineString::read_twkb(&mut data)?;
+            let geom = twkb::LineString::read_twkb_limited(&mut data, &limits)?;
             let mut encoded = Vec::with_capacity(9 + 8 * 3 * geom.points.len());
-            geom.as_ewkb().write_ewkb(&mut encoded)?;
+            geom.as_ewkb().write_ewkb_with(&mut encoded, byte_order)?;
             encoded
         }
         GeometryType::Polygon => {
-            let geom = twkb::Polygon::read_twkb(&mut data)?;
+            let geom = twkb::Polygon::read_twkb_limited(&mut data, &limits)?;
             let n_crds: usize = geom.rings.iter().map(|ring| ring.points.len()).sum();
-            let mut encoded = Vec::with_capacity(9 + 8 * 3 * geom.rings.len() * n_crds);
-            geom.as_ewkb().write_ewkb(&mut encoded)?;
+            let mut encoded = Vec::with_capacity(9 + 8 * 3 * n_crds);
+            geom.as_ewkb().write_ewkb_with(&mut encoded, byte_order)?;
             encoded
         }
         GeometryType::MultiPoint => {
-            let geom = twkb::MultiPoint::read_twkb(&mut data)?;
+            let geom = twkb::MultiPoint::read_twkb_limited(&mut data, &limits)?;
             let mut encoded = Vec::with_capacity(9 + 8 * 3 * geom.points.len());
-            geom.as_ewkb().write_ewkb(&mut encoded)?;
+            geom.as_ewkb().write_ewkb_with(&mut encoded, byte_order)?;
             encoded
         }
         GeometryType::MultiLineString => {
-            let geom = twkb::MultiLineString::read_twkb(&mut data)?;
+            let geom = twkb::MultiLineString::read_twkb_limited(&mut data, &limits)?;
             let n_crds: usize = geom.lines.iter().map(|line| line.points.len()).sum();
             let mut encoded = Vec::with_capacity(9 + 8 * 3 * n_crds);
-            geom.as_ewkb().write_ewkb(&mut encoded)?;
+            geom.as_ewkb().write_ewkb_with(&mut encoded, byte_order)?;
             encoded
         }
         GeometryType::MultiPolygon => {
-            let geom = twkb::MultiPolygon::read_twkb(&mut data)?;
+            let geom = twkb::MultiPolygon::read_twkb_limited(&mut data, &limits)?;
             let n_crds: usize = geom
                 .polygons
                 .iter()
@@ -442,7 +1023,7 @@ fn twkb_to_ewkb<'a>(py: Python<'a>, mut data: &[u8]) -> PyResult<&'a PyBytes> {
                 })
                 .sum();
             let mut encoded = Vec::with_capacity(9 + 8 * 3 * n_crds);
-            geom.as_ewkb().write_ewkb(&mut encoded)?;
+            geom.as_ewkb().write_ewkb_with(&mut encoded, byte_order)?;
             encoded
         }
         GeometryType::GeometryCollection => {
@@ -454,29 +1035,92 @@ fn twkb_to_ewkb<'a>(py: Python<'a>, mut data: &[u8]) -> PyResult<&'a PyBytes> {
             return Err(WKBError::Other("invalid geometry type".to_owned()).into());
         }
     };
-    Ok(PyBytes::new(py, &result))
+    if strict.unwrap_or(false) {
+        expect_end(&mut data)?;
+    }
+    let options = transform_options(on_error, check_area_of_use)?;
+    let result = match (from_srid, to_srid) {
+        (None, None) => result,
+        (None, Some(_)) => return Err(WKBError::Other("missing from_srid".to_string()).into()),
+        (Some(_), None) => return Err(WKBError::Other("missing to_srid".to_string()).into()),
+        (Some(from_srid), Some(to_srid)) => {
+            // max_bytes was checked against the TWKB input, which is smaller than its EWKB
+            let limits = ParseLimits {
+                max_bytes: usize::MAX,
+                ..limits
+            };
+            crate::reproject::reproject_ewkb_limited(
+                &result,
+                to_srid,
+                Some(from_srid),
+                &options,
+                &limits,
+            )?
+        }
+    };
+    encode_output(py, &result, output_encoding)
+}
+
+#[pyfunction]
+fn set_parse_limits(
+    max_vertices: Option<usize>,
+    max_parts: Option<usize>,
+    max_depth: Option<usize>,
+    max_bytes: Option<usize>,
+) {
+    let defaults = ParseLimits::default();
+    let limits = ParseLimits {
+        max_vertices: max_vertices.unwrap_or(defaults.max_vertices),
+        max_parts: max_parts.unwrap_or(defaults.max_parts),
+        max_depth: max_depth.unwrap_or(defaults.max_depth),
+        max_bytes: max_bytes.unwrap_or(defaults.max_bytes),
+    };
+    *parse_limits_lock()
+        .write()
+        .unwrap_or_else(|err| err.into_inner()) = limits;
+}
+
+#[pyfunction]
+fn get_parse_limits(py: Python<'_>) -> PyResult<&PyDict> {
+    let limits = parse_limits();
+    let finite = |limit: usize| (limit != usize::MAX).then_some(limit);
+    let dict = PyDict::new(py);
+    dict.set_item("max_vertices", finite(limits.max_vertices))?;
+    dict.set_item("max_parts", finite(limits.max_parts))?;
+    dict.set_item("max_depth", finite(limits.max_depth))?;
+    dict.set_item("max_bytes", finite(limits.max_bytes))?;
+    Ok(dict)
 }
 
-#[cfg(all(feature = "proj", feature = "python", feature = "extension-module"))]
 mod reproject {
-    use super::geojson_to_pydict;
+    use super::{encode_output, geojson_to_pyobject, parse_limits, transform_options, WkbInput};
+    #[cfg(feature = "proj")]
+    use crate::crs::AreaOfUse;
     use crate::error::Error;
     use crate::geojson::GeoJSONEncode;
-    use crate::pyo::pydict_to_geojson;
+    use crate::pyo::pyobject_to_geojson;
     use crate::reproject::get_transform;
+    #[cfg(feature = "proj")]
+    use crate::reproject::{ProjConfig, ProjOperation};
     use pyo::pyo3::pyfunction;
-    use pyo::pyo3::types::PyDict;
-    use pyo::pyo3::Python;
+    use pyo::pyo3::types::{PyAny, PyDict};
+    use pyo::pyo3::{PyObject, Python};
     use pyo::PyResult;
+    use std::collections::HashMap;
+    #[cfg(feature = "proj")]
+    use std::path::PathBuf;
 
     #[pyfunction]
-    pub(crate) fn reproject_geojson<'a>(
-        py: Python<'a>,
-        data: &PyDict,
+    pub(crate) fn reproject_geojson(
+        py: Python<'_>,
+        data: &PyAny,
         to_srid: i32,
         from_srid: Option<i32>,
-    ) -> PyResult<&'a PyDict> {
-        let mut geom = pydict_to_geojson(data)?;
+        on_error: Option<&str>,
+        check_area_of_use: Option<bool>,
+        as_shapely: Option<bool>,
+    ) -> PyResult<PyObject> {
+        let mut geom = pyobject_to_geojson(data)?;
         let from_srid = {
             if let Some(from_srid) = from_srid {
                 from_srid
@@ -489,10 +1133,315 @@ mod reproject {
                 .into());
             }
         };
+        let options = transform_options(on_error, check_area_of_use)?;
         let xform = get_transform(from_srid, to_srid)?;
-        geom.transform(&xform)?;
+        geom.transform_with(&xform, &options)?;
         geom.set_srid(to_srid);
-        geojson_to_pydict(py, &geom)
+        geojson_to_pyobject(py, geom, as_shapely)
+    }
+
+    #[pyfunction]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn reproject_ewkb(
+        py: Python<'_>,
+        data: WkbInput,
+        to_srid: i32,
+        from_srid: Option<i32>,
+        on_error: Option<&str>,
+        check_area_of_use: Option<bool>,
+        input_encoding: Option<&str>,
+        output_encoding: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let data = data.decode(py, input_encoding)?;
+        let options = transform_options(on_error, check_area_of_use)?;
+        let encoded = crate::reproject::reproject_ewkb_limited(
+            &data,
+            to_srid,
+            from_srid,
+            &options,
+            &parse_limits(),
+        )?;
+        encode_output(py, &encoded, output_encoding)
+    }
+
+    #[pyfunction]
+    pub(crate) fn warm_transforms(pairs: Vec<(i32, i32)>) -> PyResult<()> {
+        crate::reproject::warm_transforms(&pairs)?;
+        Ok(())
+    }
+
+    #[pyfunction]
+    pub(crate) fn clear_transform_cache() {
+        crate::reproject::clear_transform_cache()
+    }
+
+    #[pyfunction]
+    pub(crate) fn set_transform_cache_size(max_size: usize) {
+        crate::reproject::set_transform_cache_size(max_size)
+    }
+
+    #[pyfunction]
+    pub(crate) fn configure_proj(
+        search_paths: Option<Vec<String>>,
+        network: Option<bool>,
+        url_endpoint: Option<String>,
+        operations: Option<HashMap<(i32, i32), &PyAny>>,
+    ) -> PyResult<()> {
+        #[cfg(feature = "proj")]
+        {
+            let mut config = ProjConfig {
+                search_paths: search_paths
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .collect(),
+                network,
+                url_endpoint,
+                ..Default::default()
+            };
+            for (pair, operation) in operations.unwrap_or_default() {
+                let operation = if let Ok(pipeline) = operation.extract::<String>() {
+                    ProjOperation::Pipeline(pipeline)
+                } else if let Ok((west, south, east, north)) = operation.extract() {
+                    ProjOperation::AreaOfInterest(AreaOfUse {
+                        west,
+                        south,
+                        east,
+                        north,
+                    })
+                } else {
+                    return Err(Error::Other(format!(
+                        "operation for {:?} must be a PROJ pipeline or a (west, south, east, north) tuple",
+                        pair
+                    ))
+                    .into());
+                };
+                config.operations.insert(pair, operation);
+            }
+            crate::reproject::set_proj_config(config)?;
+            Ok(())
+        }
+        #[cfg(not(feature = "proj"))]
+        {
+            let _ = (search_paths, network, url_endpoint, operations);
+            Err(Error::Other(
+                "PROJ is not available - use wkbparse-proj package instead".to_string(),
+            )
+            .into())
+        }
+    }
+
+    #[pyfunction]
+    pub(crate) fn transform_cache_info(py: Python<'_>) -> PyResult<&PyDict> {
+        let stats = crate::reproject::transform_cache_stats();
+        let dict = PyDict::new(py);
+        dict.set_item("hits", stats.hits)?;
+        dict.set_item("misses", stats.misses)?;
+        dict.set_item("evictions", stats.evictions)?;
+        dict.set_item("size", stats.size)?;
+        dict.set_item("max_size", stats.max_size)?;
+        Ok(dict)
+    }
+}
+
+#[cfg(feature = "arrow")]
+mod arrow {
+    extern crate arrow_array;
+    extern crate arrow_schema;
+
+    use self::arrow_array::ffi::{from_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+    use self::arrow_array::{make_array, Array, ArrayRef, BinaryArray};
+    use self::arrow_schema::{DataType, Field};
+    use super::parse_limits;
+    use crate::error::Error;
+    use crate::geoarrow::{self, BinaryFormat};
+    use pyo::pyo3::prelude::*;
+    use pyo::pyo3::types::PyCapsule;
+    use std::convert::TryFrom;
+    use std::ffi::{CStr, CString};
+    use std::sync::Arc;
+
+    /// Arrow array exported through the Arrow PyCapsule interface.
+    #[pyclass(module = "wkbparse")]
+    pub(crate) struct ArrowArray {
+        field: Field,
+        array: ArrayRef,
+    }
+
+    #[pymethods]
+    impl ArrowArray {
+        fn __len__(&self) -> usize {
+            self.array.len()
+        }
+
+        fn __arrow_c_schema__<'a>(&self, py: Python<'a>) -> PyResult<&'a PyCapsule> {
+            let schema = FFI_ArrowSchema::try_from(&self.field).map_err(Error::from)?;
+            PyCapsule::new(py, schema, Some(CString::new("arrow_schema")?))
+        }
+
+        #[pyo3(signature = (requested_schema=None))]
+        fn __arrow_c_array__<'a>(
+            &self,
+            py: Python<'a>,
+            requested_schema: Option<&PyAny>,
+        ) -> PyResult<(&'a PyCapsule, &'a PyCapsule)> {
+            // The array is only available in its own type, which consumers may cast.
+            let _ = requested_schema;
+            let array = FFI_ArrowArray::new(&self.array.to_data());
+            Ok((
+                self.__arrow_c_schema__(py)?,
+                PyCapsule::new(py, array, Some(CString::new("arrow_array")?))?,
+            ))
+        }
+    }
+
+    fn capsule<'a>(capsule: &'a PyAny, name: &str) -> PyResult<&'a PyCapsule> {
+        let capsule: &PyCapsule = capsule.downcast()?;
+        if capsule.name()?.and_then(|name| CStr::to_str(name).ok()) != Some(name) {
+            return Err(Error::Other(format!("expected an {} capsule", name)).into());
+        }
+        Ok(capsule)
+    }
+
+    /// Import an object implementing `__arrow_c_array__`.
+    fn import_array(data: &PyAny) -> PyResult<(Field, ArrayRef)> {
+        let (schema, array): (&PyAny, &PyAny) =
+            data.call_method0("__arrow_c_array__")?.extract()?;
+        let schema = capsule(schema, "arrow_schema")?;
+        let array = capsule(array, "arrow_array")?;
+        // SAFETY: the capsules hold an ArrowSchema and an ArrowArray. The array is moved out
+        // and its release callback is left to `from_ffi`.
+        let schema = unsafe { &*(schema.pointer() as *const FFI_ArrowSchema) };
+        let array = unsafe { FFI_ArrowArray::from_raw(array.pointer() as *mut FFI_ArrowArray) };
+        let field = Field::try_from(schema).map_err(Error::from)?;
+        let data = unsafe { from_ffi(array, schema) }.map_err(Error::from)?;
+        Ok((field, make_array(data)))
+    }
+
+    /// Import an Arrow binary array, or build one from a list of bytes.
+    fn import_binary_array(data: &PyAny) -> PyResult<ArrayRef> {
+        if data.hasattr("__arrow_c_array__")? {
+            return Ok(import_array(data)?.1);
+        }
+        let values: Vec<Option<&[u8]>> = data.extract().map_err(|_| {
+            Error::Other("expected an Arrow binary array or a list of bytes".to_owned())
+        })?;
+        Ok(Arc::new(BinaryArray::from(values)))
+    }
+
+    fn to_geoarrow(data: &PyAny, format: BinaryFormat) -> PyResult<ArrowArray> {
+        let array = import_binary_array(data)?;
+        let (field, array) = geoarrow::binary_to_geoarrow(&array, format, &parse_limits())?;
+        Ok(ArrowArray { field, array })
+    }
+
+    #[pyfunction]
+    pub(crate) fn ewkb_to_geoarrow(data: &PyAny) -> PyResult<ArrowArray> {
+        to_geoarrow(data, BinaryFormat::Ewkb)
+    }
+
+    #[pyfunction]
+    pub(crate) fn twkb_to_geoarrow(data: &PyAny) -> PyResult<ArrowArray> {
+        to_geoarrow(data, BinaryFormat::Twkb)
+    }
+
+    #[pyfunction]
+    pub(crate) fn geoarrow_to_ewkb(data: &PyAny, byte_order: Option<&str>) -> PyResult<ArrowArray> {
+        let byte_order = super::byte_order(byte_order)?;
+        let (field, array) = import_array(data)?;
+        let array = geoarrow::geoarrow_to_ewkb(&field, &array, byte_order)?;
+        Ok(ArrowArray {
+            field: Field::new(field.name(), DataType::Binary, true),
+            array: Arc::new(array),
+        })
+    }
+}
+
+mod pgcopy {
+    use super::{byte_order, geojson_to_pydict, parse_limits, pyobject_to_geojson, WkbInput};
+    use crate::error::Error;
+    use crate::geojson::{self, BinaryFormat, GeoJSONEncode};
+    use crate::pgcopy::{CopyReader, CopyWriter};
+    use pyo::pyo3::prelude::*;
+    use pyo::pyo3::types::{PyBytes, PyList};
+    use std::collections::HashMap;
+
+    /// Rows of a binary COPY stream with the given geometry columns decoded.
+    #[pyfunction]
+    pub(crate) fn read_pgcopy<'a>(
+        py: Python<'a>,
+        data: WkbInput,
+        geometry_columns: HashMap<usize, &str>,
+        as_ewkb: Option<bool>,
+    ) -> PyResult<&'a PyList> {
+        let columns = geometry_columns
+            .into_iter()
+            .map(|(column, format)| Ok((column, format.parse::<BinaryFormat>()?)))
+            .collect::<Result<HashMap<_, _>, Error>>()?;
+        let as_ewkb = as_ewkb.unwrap_or(false);
+        let limits = parse_limits();
+        let data = data.decode(py, Some("bytes"))?;
+        let rows = PyList::empty(py);
+        for row in CopyReader::new(&data[..])? {
+            let row = row?;
+            if let Some(column) = columns.keys().find(|&&column| column >= row.len()) {
+                return Err(Error::Read(format!(
+                    "missing geometry column {} in a row of {} fields",
+                    column,
+                    row.len()
+                ))
+                .into());
+            }
+            let fields = PyList::empty(py);
+            for (i, field) in row.into_iter().enumerate() {
+                let field = match (field, columns.get(&i)) {
+                    (None, _) => py.None(),
+                    (Some(field), None) => PyBytes::new(py, &field).to_object(py),
+                    (Some(field), Some(BinaryFormat::Ewkb)) if as_ewkb => {
+                        PyBytes::new(py, &field).to_object(py)
+                    }
+                    (Some(field), Some(&format)) => {
+                        let geom = geojson::read_binary(&field, format, &limits)?;
+                        match as_ewkb {
+                            true => PyBytes::new(py, &geom.to_ewkb()?).to_object(py),
+                            false => geojson_to_pydict(py, &geom)?.to_object(py),
+                        }
+                    }
+                };
+                fields.append(field)?;
+            }
+            rows.append(fields)?;
+        }
+        Ok(rows)
+    }
+
+    /// Binary COPY stream of rows, encoding geometries as EWKB.
+    #[pyfunction]
+    pub(crate) fn write_pgcopy<'a>(
+        py: Python<'a>,
+        rows: &PyAny,
+        byte_order: Option<&str>,
+    ) -> PyResult<&'a PyBytes> {
+        let byte_order = self::byte_order(byte_order)?;
+        let mut writer = CopyWriter::new(Vec::new())?;
+        for row in rows.iter()? {
+            let fields = row?
+                .iter()?
+                .map(|field| {
+                    let field = field?;
+                    if field.is_none() {
+                        Ok(None)
+                    } else if let Ok(data) = field.extract::<WkbInput>() {
+                        Ok(Some(data.decode(py, Some("bytes"))?.into_owned()))
+                    } else {
+                        Ok(Some(pyobject_to_geojson(field)?.to_ewkb_with(byte_order)?))
+                    }
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            let fields: Vec<Option<&[u8]>> = fields.iter().map(|field| field.as_deref()).collect();
+            writer.write_row(&fields)?;
+        }
+        Ok(PyBytes::new(py, &writer.finish()?))
     }
 }
 
@@ -501,11 +1450,34 @@ mod reproject {
 fn wkbparse(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(pyo::twkb_to_geojson, m)?)?;
     m.add_function(wrap_pyfunction!(pyo::ewkb_to_geojson, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::twkb_to_geojson_str, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::ewkb_to_geojson_str, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::twkb_to_geojson_many, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::ewkb_to_geojson_many, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::twkb_to_geojson_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::ewkb_to_geojson_batch, m)?)?;
     m.add_function(wrap_pyfunction!(pyo::geojson_to_ewkb, m)?)?;
     m.add_function(wrap_pyfunction!(pyo::twkb_to_ewkb, m)?)?;
-    #[cfg(feature = "proj")]
+    m.add_function(wrap_pyfunction!(pyo::set_parse_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::get_parse_limits, m)?)?;
     m.add_function(wrap_pyfunction!(pyo::reproject::reproject_geojson, m)?)?;
-    #[cfg(not(feature = "proj"))]
-    m.add_function(wrap_pyfunction!(pyo::reproject_not_implemented, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::reproject::reproject_ewkb, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::reproject::warm_transforms, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::reproject::clear_transform_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        pyo::reproject::set_transform_cache_size,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(pyo::reproject::transform_cache_info, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::reproject::configure_proj, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::pgcopy::read_pgcopy, m)?)?;
+    m.add_function(wrap_pyfunction!(pyo::pgcopy::write_pgcopy, m)?)?;
+    #[cfg(feature = "arrow")]
+    {
+        m.add_class::<self::arrow::ArrowArray>()?;
+        m.add_function(wrap_pyfunction!(pyo::arrow::ewkb_to_geoarrow, m)?)?;
+        m.add_function(wrap_pyfunction!(pyo::arrow::twkb_to_geoarrow, m)?)?;
+        m.add_function(wrap_pyfunction!(pyo::arrow::geoarrow_to_ewkb, m)?)?;
+    }
     Ok(())
 }
@@ -0,0 +1,161 @@
+// Ragged array layout of geometries
+//
+// Geometries of one type are stored as interleaved coordinates and one offset array per list
+// level, as in GeoArrow and `shapely.from_ragged_array`.
+use std::slice;
+
+use crate::geojson::{Dims, GeoJSONGeometry};
+
+/// Geometry types of ragged arrays.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub(crate) enum Kind {
+    Point,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+}
+
+impl Kind {
+    pub(crate) fn of(geom: &GeoJSONGeometry) -> Kind {
+        match geom {
+            GeoJSONGeometry::Point(_) => Kind::Point,
+            GeoJSONGeometry::LineString(_) => Kind::LineString,
+            GeoJSONGeometry::Polygon(_) => Kind::Polygon,
+            GeoJSONGeometry::MultiPoint(_) => Kind::MultiPoint,
+            GeoJSONGeometry::MultiLineString(_) => Kind::MultiLineString,
+            GeoJSONGeometry::MultiPolygon(_) => Kind::MultiPolygon,
+        }
+    }
+
+    /// Type able to store geometries of both types: single geometries widen into their multi
+    /// geometry.
+    #[cfg(feature = "arrow")]
+    pub(crate) fn common(self, other: Kind) -> Option<Kind> {
+        use self::Kind::*;
+        match (self, other) {
+            (a, b) if a == b => Some(a),
+            (Point, MultiPoint) | (MultiPoint, Point) => Some(MultiPoint),
+            (LineString, MultiLineString) | (MultiLineString, LineString) => Some(MultiLineString),
+            (Polygon, MultiPolygon) | (MultiPolygon, Polygon) => Some(MultiPolygon),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Kind::Point => "point",
+            Kind::LineString => "linestring",
+            Kind::Polygon => "polygon",
+            Kind::MultiPoint => "multipoint",
+            Kind::MultiLineString => "multilinestring",
+            Kind::MultiPolygon => "multipolygon",
+        }
+    }
+
+    /// Number of list levels above the coordinates.
+    pub(crate) fn depth(self) -> usize {
+        match self {
+            Kind::Point => 0,
+            Kind::LineString | Kind::MultiPoint => 1,
+            Kind::Polygon | Kind::MultiLineString => 2,
+            Kind::MultiPolygon => 3,
+        }
+    }
+}
+
+/// Geometries nested in lists down to coordinates.
+trait Nested {
+    fn push_to(&self, ragged: &mut Ragged, level: usize);
+}
+
+impl Nested for Vec<f64> {
+    fn push_to(&self, ragged: &mut Ragged, _level: usize) {
+        ragged.push_coord(self);
+    }
+}
+
+impl<T: Nested> Nested for [T] {
+    fn push_to(&self, ragged: &mut Ragged, level: usize) {
+        for item in self {
+            item.push_to(ragged, level + 1);
+        }
+        ragged.close(level);
+    }
+}
+
+impl<T: Nested> Nested for Vec<T> {
+    fn push_to(&self, ragged: &mut Ragged, level: usize) {
+        self.as_slice().push_to(ragged, level)
+    }
+}
+
+/// Geometries of one type as interleaved coordinates and an offset array per list level.
+pub(crate) struct Ragged {
+    /// Number of values per coordinate.
+    pub(crate) width: usize,
+    pub(crate) coords: Vec<f64>,
+    /// Offsets of each list level into the next one, from the outermost inwards.
+    pub(crate) offsets: Vec<Vec<i64>>,
+    /// Whether each geometry is present.
+    pub(crate) validity: Vec<bool>,
+}
+
+impl Ragged {
+    pub(crate) fn new(kind: Kind, dims: Dims) -> Self {
+        Ragged {
+            width: dims.width(),
+            coords: Vec::new(),
+            offsets: vec![vec![0]; kind.depth()],
+            validity: Vec::new(),
+        }
+    }
+
+    fn push_coord(&mut self, crd: &[f64]) {
+        for i in 0..self.width {
+            self.coords.push(crd.get(i).copied().unwrap_or(f64::NAN));
+        }
+    }
+
+    /// End the current list at `level`.
+    fn close(&mut self, level: usize) {
+        let end = match self.offsets.get(level + 1) {
+            Some(offsets) => offsets.len() - 1,
+            None => self.coords.len() / self.width,
+        };
+        self.offsets[level].push(end as i64);
+    }
+
+    /// Append a geometry of type `kind`, or one widening into it, or a missing one.
+    pub(crate) fn push(&mut self, kind: Kind, geom: Option<&GeoJSONGeometry>) {
+        self.validity.push(geom.is_some());
+        let geom = match geom {
+            Some(geom) => geom,
+            None if self.offsets.is_empty() => return self.push_coord(&[]),
+            None => return self.close(0),
+        };
+        match (kind, geom) {
+            (Kind::Point, GeoJSONGeometry::Point(g)) => self.push_coord(&g.coordinates),
+            (Kind::LineString, GeoJSONGeometry::LineString(g)) => g.coordinates.push_to(self, 0),
+            (Kind::Polygon, GeoJSONGeometry::Polygon(g)) => g.coordinates.push_to(self, 0),
+            (Kind::MultiPoint, GeoJSONGeometry::MultiPoint(g)) => g.coordinates.push_to(self, 0),
+            (Kind::MultiPoint, GeoJSONGeometry::Point(g)) => {
+                slice::from_ref(&g.coordinates).push_to(self, 0)
+            }
+            (Kind::MultiLineString, GeoJSONGeometry::MultiLineString(g)) => {
+                g.coordinates.push_to(self, 0)
+            }
+            (Kind::MultiLineString, GeoJSONGeometry::LineString(g)) => {
+                slice::from_ref(&g.coordinates).push_to(self, 0)
+            }
+            (Kind::MultiPolygon, GeoJSONGeometry::MultiPolygon(g)) => {
+                g.coordinates.push_to(self, 0)
+            }
+            (Kind::MultiPolygon, GeoJSONGeometry::Polygon(g)) => {
+                slice::from_ref(&g.coordinates).push_to(self, 0)
+            }
+            _ => unreachable!("geometry type checked against the array type"),
+        }
+    }
+}
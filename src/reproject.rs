@@ -1,66 +1,962 @@
-extern crate dashmap;
+#[cfg(feature = "proj")]
 extern crate proj;
-use self::dashmap::DashMap;
+#[cfg(feature = "proj")]
 use self::proj::Coord;
-use self::proj::Proj;
+#[cfg(feature = "proj")]
+use self::proj::{Area, Proj};
+#[cfg(feature = "proj")]
+extern crate proj_sys;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "rayon")]
+use self::rayon::prelude::*;
+use crate::crs::{AreaOfUse, BuiltinCrs};
 use crate::error::Error;
+use crate::ewkb::{
+    self, AsEwkbGeometry, AsEwkbPoint, EwkbRead, EwkbTransform, EwkbWrite, GeometryT, PointType,
+    PointXyMut,
+};
 use crate::geojson::MultiLineString;
 use crate::geojson::MultiPoint;
 use crate::geojson::MultiPolygon;
 use crate::geojson::Polygon;
 use crate::geojson::{LineString, Point};
-use std::cell::RefCell;
+use crate::limits::ParseLimits;
+use std::collections::HashMap;
+#[cfg(feature = "proj")]
+use std::ffi::{CStr, CString};
+#[cfg(feature = "proj")]
+use std::mem::ManuallyDrop;
+#[cfg(feature = "proj")]
+use std::path::PathBuf;
+#[cfg(feature = "proj")]
+use std::ptr;
+use std::str::FromStr;
+#[cfg(feature = "proj")]
 use std::sync::Arc;
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "proj")]
+use std::sync::{MutexGuard, RwLock};
 
 pub struct Crd<'a>(&'a mut Vec<f64>);
 
-thread_local! {
-    static REPROJECTIONS: RefCell<DashMap<(i32, i32), Arc<Proj>>> = RefCell::new(DashMap::new());
+/// A coordinate transformation between two coordinate systems.
+///
+/// Transformations between the built-in coordinate systems (see [`BuiltinCrs`]) are computed in
+/// pure Rust. Any other pair of EPSG codes is handed to PROJ when the `proj` feature is enabled.
+#[derive(Clone)]
+pub enum Transform {
+    Builtin(BuiltinCrs, BuiltinCrs),
+    #[cfg(feature = "proj")]
+    Proj(Arc<ProjTransform>),
 }
 
-pub fn get_transform(from_srid: i32, to_srid: i32) -> Result<Arc<Proj>, Error> {
-    if let Ok(xform) = REPROJECTIONS.with(|reprojections| {
-        if let Some(res) = reprojections.borrow().get(&(from_srid, to_srid)) {
-            Ok(res.value().clone())
+/// A PROJ transformation that may be shared between threads.
+///
+/// Each `Proj` owns its own PROJ context, which may be used from any thread as long as only one
+/// thread uses it at a time. The mutex guarantees that.
+#[cfg(feature = "proj")]
+pub struct ProjTransform {
+    proj: ManuallyDrop<Mutex<Proj>>,
+    from_srid: i32,
+    to_srid: i32,
+    area: Option<AreaOfUse>,
+}
+
+// SAFETY: a `Proj` is only used while its mutex is locked, so by one thread at a time, which PROJ
+// allows for objects with their own context. Dropping a `Proj` calls `proj_cleanup`, which PROJ
+// does not allow while any other PROJ object is in use. `ProjTransform` therefore never drops its
+// `Proj` while a `ProjUse` exists on any thread, and every creation and use of a `Proj` holds one.
+#[cfg(feature = "proj")]
+unsafe impl Send for ProjTransform {}
+#[cfg(feature = "proj")]
+unsafe impl Sync for ProjTransform {}
+
+#[cfg(feature = "proj")]
+impl Drop for ProjTransform {
+    fn drop(&mut self) {
+        // SAFETY: `self.proj` is not touched again after this.
+        let proj = unsafe { ManuallyDrop::take(&mut self.proj) };
+        let proj = proj.into_inner().unwrap_or_else(|err| err.into_inner());
+        let mut users = proj_users();
+        if users.active == 0 {
+            drop(proj);
         } else {
-            Err(Error::Other(format!("failed to retrieve transform")))
+            users.retired.push(proj);
         }
-    }) {
-        return Ok(xform);
+    }
+}
+
+/// Threads creating or using PROJ objects, and transforms dropped while there were any.
+#[cfg(feature = "proj")]
+#[derive(Default)]
+struct ProjUsers {
+    active: usize,
+    retired: Vec<Proj>,
+}
+
+// SAFETY: retired `Proj` values are not used any more, only dropped while no thread uses PROJ.
+#[cfg(feature = "proj")]
+unsafe impl Send for ProjUsers {}
+
+#[cfg(feature = "proj")]
+fn proj_users() -> MutexGuard<'static, ProjUsers> {
+    static USERS: OnceLock<Mutex<ProjUsers>> = OnceLock::new();
+    USERS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+/// Marks the current thread as using PROJ until dropped.
+///
+/// Retired transforms are dropped when the last user finishes. The users lock is held while
+/// dropping them, so no thread can start using PROJ before `proj_cleanup` has returned.
+#[cfg(feature = "proj")]
+struct ProjUse;
+
+#[cfg(feature = "proj")]
+impl ProjUse {
+    fn new() -> ProjUse {
+        proj_users().active += 1;
+        ProjUse
+    }
+}
+
+#[cfg(feature = "proj")]
+impl Drop for ProjUse {
+    fn drop(&mut self) {
+        let mut users = proj_users();
+        users.active -= 1;
+        if users.active == 0 {
+            users.retired.clear();
+        }
+    }
+}
+
+#[cfg(feature = "proj")]
+impl ProjTransform {
+    fn new(from_srid: i32, to_srid: i32) -> Result<ProjTransform, Error> {
+        let _use = ProjUse::new();
+        // New PROJ contexts copy the default context, so it must not change while creating one.
+        let config = proj_config_lock()
+            .read()
+            .unwrap_or_else(|err| err.into_inner());
+        let from = format!("EPSG:{}", from_srid);
+        let to = format!("EPSG:{}", to_srid);
+        let xform = match config.operations.get(&(from_srid, to_srid)) {
+            Some(ProjOperation::AreaOfInterest(area)) => Proj::new_known_crs(
+                &from,
+                &to,
+                Some(Area::new(area.west, area.south, area.east, area.north)),
+            ),
+            Some(ProjOperation::Pipeline(definition)) => Proj::new(definition),
+            None => Proj::new_known_crs(&from, &to, None),
+        }
+        .map_err(|err| Error::Other(format!("failed to create transform: {}", err)))?;
+        drop(config);
+        let area = match xform.area_of_use() {
+            Ok((Some(area), _)) => Some(AreaOfUse {
+                west: area.west,
+                south: area.south,
+                east: area.east,
+                north: area.north,
+            }),
+            _ => None,
+        };
+        Ok(ProjTransform {
+            proj: ManuallyDrop::new(Mutex::new(xform)),
+            from_srid,
+            to_srid,
+            area,
+        })
+    }
+
+    fn lock(&self) -> (MutexGuard<'_, Proj>, ProjUse) {
+        let user = ProjUse::new();
+        (
+            self.proj.lock().unwrap_or_else(|err| err.into_inner()),
+            user,
+        )
+    }
+}
+
+/// How PROJ picks the operation for transforming between a pair of coordinate systems.
+#[cfg(feature = "proj")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjOperation {
+    /// Let PROJ pick the most accurate operation available within this area of interest, given
+    /// in degrees of longitude and latitude.
+    AreaOfInterest(AreaOfUse),
+    /// Use this PROJ pipeline instead of letting PROJ pick one, for example to force a specific
+    /// NTv2 grid with `+proj=hgridshift +grids=...`.
+    ///
+    /// The pipeline gets coordinates as wkbparse has them: longitude and latitude in degrees or
+    /// easting and northing.
+    Pipeline(String),
+}
+
+/// Configuration of the PROJ context used for creating transforms.
+///
+/// See [`set_proj_config`].
+#[cfg(feature = "proj")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjConfig {
+    /// Directories searched for grid files before the default PROJ data directories.
+    pub search_paths: Vec<PathBuf>,
+    /// Whether PROJ may download missing grids. `None` keeps the PROJ default.
+    pub network: Option<bool>,
+    /// Endpoint grids are downloaded from. `None` keeps the PROJ default.
+    pub url_endpoint: Option<String>,
+    /// Operations to use for `(from_srid, to_srid)` pairs instead of the PROJ default.
+    pub operations: HashMap<(i32, i32), ProjOperation>,
+}
+
+#[cfg(feature = "proj")]
+fn proj_config_lock() -> &'static RwLock<ProjConfig> {
+    static CONFIG: OnceLock<RwLock<ProjConfig>> = OnceLock::new();
+    CONFIG.get_or_init(|| RwLock::new(ProjConfig::default()))
+}
+
+/// Network setting and endpoint of the default PROJ context before it was first configured.
+#[cfg(feature = "proj")]
+fn proj_defaults() -> &'static (bool, CString) {
+    static DEFAULTS: OnceLock<(bool, CString)> = OnceLock::new();
+    DEFAULTS.get_or_init(|| unsafe {
+        let network = proj_sys::proj_context_is_network_enabled(ptr::null_mut()) == 1;
+        let endpoint = proj_sys::proj_context_get_url_endpoint(ptr::null_mut());
+        let endpoint = if endpoint.is_null() {
+            CString::default()
+        } else {
+            CStr::from_ptr(endpoint).to_owned()
+        };
+        (network, endpoint)
+    })
+}
+
+/// Configure the PROJ context used for transforms created from now on.
+///
+/// The configuration replaces any previous one, so `ProjConfig::default()` restores the PROJ
+/// defaults. Cached PROJ transforms are dropped so that they get created again with the new
+/// configuration.
+#[cfg(feature = "proj")]
+pub fn set_proj_config(config: ProjConfig) -> Result<(), Error> {
+    let search_paths = config
+        .search_paths
+        .iter()
+        .map(|path| {
+            path.to_str()
+                .and_then(|path| CString::new(path).ok())
+                .ok_or_else(|| Error::Other(format!("invalid search path: {}", path.display())))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let url_endpoint = match &config.url_endpoint {
+        Some(endpoint) => Some(
+            CString::new(endpoint.as_str())
+                .map_err(|_| Error::Other(format!("invalid url endpoint: {}", endpoint)))?,
+        ),
+        None => None,
     };
+    let mut current = proj_config_lock()
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    let (default_network, default_endpoint) = proj_defaults();
+    // New contexts are copied from the default context, so configuring it configures them all.
+    unsafe {
+        let ctx = ptr::null_mut();
+        let network = config.network.unwrap_or(*default_network);
+        if proj_sys::proj_context_set_enable_network(ctx, network as i32) != network as i32 {
+            let previous = current.network.unwrap_or(*default_network);
+            proj_sys::proj_context_set_enable_network(ctx, previous as i32);
+            return Err(Error::Other(
+                "PROJ was built without network support".to_string(),
+            ));
+        }
+        let endpoint = url_endpoint.as_ref().unwrap_or(default_endpoint);
+        proj_sys::proj_context_set_url_endpoint(ctx, endpoint.as_ptr());
+        // Empty search paths make PROJ fall back to its defaults, which are kept after ours.
+        proj_sys::proj_context_set_search_paths(ctx, 0, ptr::null());
+        let mut paths = search_paths;
+        if !paths.is_empty() {
+            let defaults = proj_sys::proj_info().searchpath;
+            if !defaults.is_null() {
+                let separator = if cfg!(windows) { ';' } else { ':' };
+                paths.extend(
+                    CStr::from_ptr(defaults)
+                        .to_string_lossy()
+                        .split(separator)
+                        .filter(|path| !path.is_empty())
+                        .filter_map(|path| CString::new(path).ok()),
+                );
+            }
+            let pointers: Vec<_> = paths.iter().map(|path| path.as_ptr()).collect();
+            proj_sys::proj_context_set_search_paths(ctx, pointers.len() as i32, pointers.as_ptr());
+        }
+    }
+    *current = config;
+    drop(current);
+    with_cache(|cache| cache.remove_proj());
+    Ok(())
+}
 
-    let xform = Proj::new_known_crs(
-        &format!("EPSG:{}", from_srid),
-        &format!("EPSG:{}", to_srid),
-        None,
-    )
-    .map_err(|err| Error::Other(format!("failed to create transform: {}", err)))?;
-    REPROJECTIONS.with(|reprojections| {
-        reprojections
-            .borrow_mut()
-            .insert((from_srid, to_srid), Arc::new(xform));
-    });
+/// Get the current PROJ context configuration.
+#[cfg(feature = "proj")]
+pub fn proj_config() -> ProjConfig {
+    proj_config_lock()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// A transform locked for converting a batch of coordinates.
+enum Converter<'a> {
+    Builtin(&'a BuiltinCrs, &'a BuiltinCrs),
+    #[cfg(feature = "proj")]
+    Proj(MutexGuard<'a, Proj>, ProjUse),
+}
+
+impl Converter<'_> {
+    /// Convert a coordinate, describing the reason on failure.
+    fn convert(&self, crd: (f64, f64)) -> Result<(f64, f64), String> {
+        match self {
+            Converter::Builtin(from, to) => {
+                if from == to {
+                    return Ok(crd);
+                }
+                let (lon, lat) = from.inverse(crd.0, crd.1).map_err(reason)?;
+                to.forward(lon, lat).map_err(reason)
+            }
+            #[cfg(feature = "proj")]
+            Converter::Proj(xform, _) => xform.convert(crd).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+fn reason(err: Error) -> String {
+    match err {
+        Error::Read(msg) | Error::Write(msg) | Error::Other(msg) => msg,
+    }
+}
+
+impl Transform {
+    fn new(from_srid: i32, to_srid: i32) -> Result<Transform, Error> {
+        if let (Some(from), Some(to)) = (
+            BuiltinCrs::from_srid(from_srid),
+            BuiltinCrs::from_srid(to_srid),
+        ) {
+            return Ok(Transform::Builtin(from, to));
+        }
+        #[cfg(feature = "proj")]
+        return ProjTransform::new(from_srid, to_srid)
+            .map(|xform| Transform::Proj(Arc::new(xform)));
+        #[cfg(not(feature = "proj"))]
+        Err(Error::Other(format!(
+            "transform from EPSG:{} to EPSG:{} is not built in - use wkbparse-proj package instead",
+            from_srid, to_srid
+        )))
+    }
+
+    fn converter(&self) -> Converter<'_> {
+        match self {
+            Transform::Builtin(from, to) => Converter::Builtin(from, to),
+            #[cfg(feature = "proj")]
+            Transform::Proj(xform) => {
+                let (proj, user) = xform.lock();
+                Converter::Proj(proj, user)
+            }
+        }
+    }
+
+    pub fn from_srid(&self) -> i32 {
+        match self {
+            Transform::Builtin(from, _) => from.srid(),
+            #[cfg(feature = "proj")]
+            Transform::Proj(xform) => xform.from_srid,
+        }
+    }
+
+    pub fn to_srid(&self) -> i32 {
+        match self {
+            Transform::Builtin(_, to) => to.srid(),
+            #[cfg(feature = "proj")]
+            Transform::Proj(xform) => xform.to_srid,
+        }
+    }
+
+    /// Bounds in WGS84 degrees within which the transform is valid, if known.
+    ///
+    /// For built-in transforms this is where both coordinate systems are valid, or where the
+    /// source one is if they do not overlap, as between different UTM zones.
+    pub fn area_of_use(&self) -> Option<AreaOfUse> {
+        match self {
+            Transform::Builtin(from, to) => {
+                let from = from.area_of_use();
+                Some(from.intersection(&to.area_of_use()).unwrap_or(from))
+            }
+            #[cfg(feature = "proj")]
+            Transform::Proj(xform) => xform.area,
+        }
+    }
+
+    pub fn convert(&self, crd: (f64, f64)) -> Result<(f64, f64), Error> {
+        self.converter()
+            .convert(crd)
+            .map_err(|reason| Error::Other(format!("reprojection failed: {}", reason)))
+    }
+}
+
+/// What to do with a vertex that cannot be transformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Fail with an error naming the vertex.
+    #[default]
+    Raise,
+    /// Remove the vertex from the geometry. A single point gets NaN coordinates instead.
+    ///
+    /// Polygon rings that lose vertices are closed again. Rings left with fewer than four vertices
+    /// are removed, and so are polygons whose exterior ring is removed.
+    DropVertex,
+    /// Set x and y of the vertex to NaN.
+    Nan,
+    /// Move the vertex onto the nearest edge of the area of use and transform that.
+    Clamp,
+}
+
+impl FromStr for ErrorPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ErrorPolicy, Error> {
+        match s {
+            "raise" => Ok(ErrorPolicy::Raise),
+            "drop_vertex" => Ok(ErrorPolicy::DropVertex),
+            "nan" => Ok(ErrorPolicy::Nan),
+            "clamp" => Ok(ErrorPolicy::Clamp),
+            _ => Err(Error::Other(format!(
+                "invalid error policy '{}' - expected one of raise, drop_vertex, nan, clamp",
+                s
+            ))),
+        }
+    }
+}
+
+/// Options for transforming the coordinates of a geometry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransformOptions {
+    pub on_error: ErrorPolicy,
+    /// Treat vertices outside the area of use of the transform as failed.
+    pub check_area_of_use: bool,
+}
+
+/// One leg of a transform through WGS84 longitude/latitude, used for checking and clamping.
+enum LonLatStep {
+    Identity,
+    Main,
+    Other(Transform),
+}
 
-    REPROJECTIONS.with(|reprojections| {
-        if let Some(res) = reprojections.borrow().get(&(from_srid, to_srid)) {
-            Ok(res.value().clone())
+impl LonLatStep {
+    fn new(from_srid: i32, to_srid: i32, main: &Transform) -> Result<LonLatStep, Error> {
+        if from_srid == to_srid {
+            Ok(LonLatStep::Identity)
+        } else if from_srid == main.from_srid() && to_srid == main.to_srid() {
+            Ok(LonLatStep::Main)
         } else {
-            Err(Error::Other(format!("failed to retrieve transform")))
+            get_transform(from_srid, to_srid).map(LonLatStep::Other)
         }
-    })
+    }
+}
+
+/// Converts the vertices of a single geometry according to [`TransformOptions`].
+struct VertexConverter<'a> {
+    main: Converter<'a>,
+    to_lonlat: Option<Converter<'a>>,
+    from_lonlat: Option<Converter<'a>>,
+    steps: &'a (LonLatStep, LonLatStep),
+    area: Option<AreaOfUse>,
+    options: &'a TransformOptions,
+}
+
+impl<'a> VertexConverter<'a> {
+    fn new(
+        xform: &'a Transform,
+        steps: &'a (LonLatStep, LonLatStep),
+        options: &'a TransformOptions,
+    ) -> VertexConverter<'a> {
+        let converter = |step: &'a LonLatStep| match step {
+            LonLatStep::Other(xform) => Some(xform.converter()),
+            _ => None,
+        };
+        VertexConverter {
+            main: xform.converter(),
+            to_lonlat: converter(&steps.0),
+            from_lonlat: converter(&steps.1),
+            steps,
+            area: xform.area_of_use(),
+            options,
+        }
+    }
+
+    fn step(
+        &self,
+        step: &LonLatStep,
+        converter: &Option<Converter>,
+        crd: (f64, f64),
+    ) -> Result<(f64, f64), String> {
+        match (step, converter) {
+            (LonLatStep::Main, _) => self.main.convert(crd),
+            (_, Some(converter)) => converter.convert(crd),
+            _ => Ok(crd),
+        }
+    }
+
+    fn convert(&self, crd: (f64, f64)) -> Result<(f64, f64), String> {
+        if self.options.check_area_of_use {
+            if let Some(area) = self.area {
+                let (lon, lat) = self.step(&self.steps.0, &self.to_lonlat, crd)?;
+                if !area.contains(lon, lat) {
+                    return Err(format!(
+                        "coordinate ({}, {}) is outside the area of use ({}, {}, {}, {})",
+                        crd.0, crd.1, area.west, area.south, area.east, area.north
+                    ));
+                }
+            }
+        }
+        finite(crd, self.main.convert(crd)?)
+    }
+
+    fn clamp(&self, crd: (f64, f64)) -> Result<(f64, f64), String> {
+        let (lon, lat) = self.step(&self.steps.0, &self.to_lonlat, crd)?;
+        let (lon, lat) = self.area.unwrap_or(AreaOfUse::WORLD).clamp(lon, lat);
+        finite(
+            crd,
+            self.step(&self.steps.1, &self.from_lonlat, (lon, lat))?,
+        )
+    }
+}
+
+fn finite(crd: (f64, f64), res: (f64, f64)) -> Result<(f64, f64), String> {
+    if res.0.is_finite() && res.1.is_finite() {
+        Ok(res)
+    } else {
+        Err(format!(
+            "coordinate ({}, {}) transformed into ({}, {})",
+            crd.0, crd.1, res.0, res.1
+        ))
+    }
+}
+
+/// Default maximum number of transforms kept in the transform cache.
+pub const DEFAULT_CACHE_SIZE: usize = 64;
+
+/// Snapshot of the transform cache counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub size: usize,
+    pub max_size: usize,
+}
+
+/// Least recently used cache of transforms keyed by `(from_srid, to_srid)`.
+struct TransformCache {
+    max_size: usize,
+    entries: HashMap<(i32, i32), (Transform, u64)>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl TransformCache {
+    fn new(max_size: usize) -> TransformCache {
+        TransformCache {
+            max_size,
+            entries: HashMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    fn get(&mut self, key: (i32, i32)) -> Option<Transform> {
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some((xform, last_used)) = self.entries.get_mut(&key) {
+            *last_used = clock;
+            self.hits += 1;
+            Some(xform.clone())
+        } else {
+            self.misses += 1;
+            None
+        }
+    }
+
+    fn insert(&mut self, key: (i32, i32), xform: Transform) {
+        if self.max_size == 0 {
+            return;
+        }
+        self.clock += 1;
+        self.entries.insert(key, (xform, self.clock));
+        self.shrink_to(self.max_size);
+    }
+
+    fn shrink_to(&mut self, max_size: usize) {
+        while self.entries.len() > max_size {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| *key);
+            if let Some(key) = oldest {
+                self.entries.remove(&key);
+                self.evictions += 1;
+            }
+        }
+    }
+
+    /// Remove PROJ transforms, which depend on the PROJ configuration.
+    #[cfg(feature = "proj")]
+    fn remove_proj(&mut self) {
+        self.entries
+            .retain(|_, (xform, _)| matches!(xform, Transform::Builtin(..)));
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            size: self.entries.len(),
+            max_size: self.max_size,
+        }
+    }
+}
+
+fn cache() -> &'static Mutex<TransformCache> {
+    static CACHE: OnceLock<Mutex<TransformCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(TransformCache::new(DEFAULT_CACHE_SIZE)))
+}
+
+fn with_cache<T>(f: impl FnOnce(&mut TransformCache) -> T) -> T {
+    let mut cache = cache().lock().unwrap_or_else(|err| err.into_inner());
+    f(&mut cache)
+}
+
+/// Get a transform from `from_srid` to `to_srid`, creating it on first use.
+///
+/// Transforms are kept in a process-wide cache shared by all threads, so each pair is created once
+/// until it gets evicted. See [`set_transform_cache_size`] for limiting the cache.
+pub fn get_transform(from_srid: i32, to_srid: i32) -> Result<Transform, Error> {
+    let key = (from_srid, to_srid);
+    if let Some(xform) = with_cache(|cache| cache.get(key)) {
+        return Ok(xform);
+    }
+    // Created outside the lock so that slow PROJ initialization does not block other threads.
+    let xform = Transform::new(from_srid, to_srid)?;
+    with_cache(|cache| cache.insert(key, xform.clone()));
+    Ok(xform)
 }
 
-pub fn xform_crds(crds: &mut [Crd], xform: &Proj) -> Result<(), Error> {
-    for crd in crds {
-        let res = xform
-            .convert((crd.0[0], crd.0[1]))
-            .map_err(|err| Error::Other(format!("reprojection failed: {}", err)))?;
-        crd.0[0] = res.0;
-        crd.0[1] = res.1;
+/// Create transforms for the given `(from_srid, to_srid)` pairs ahead of time.
+///
+/// Pairs already in the cache are left as they are. Warming does not count as hits or misses.
+pub fn warm_transforms(pairs: &[(i32, i32)]) -> Result<(), Error> {
+    for &(from_srid, to_srid) in pairs {
+        if with_cache(|cache| cache.entries.contains_key(&(from_srid, to_srid))) {
+            continue;
+        }
+        let xform = Transform::new(from_srid, to_srid)?;
+        with_cache(|cache| cache.insert((from_srid, to_srid), xform));
     }
     Ok(())
 }
 
+/// Remove all transforms from the cache and reset its statistics.
+pub fn clear_transform_cache() {
+    with_cache(|cache| *cache = TransformCache::new(cache.max_size));
+}
+
+/// Set the maximum number of cached transforms, evicting least recently used ones if needed.
+///
+/// A size of 0 disables caching.
+pub fn set_transform_cache_size(max_size: usize) {
+    with_cache(|cache| {
+        cache.max_size = max_size;
+        cache.shrink_to(max_size);
+    });
+}
+
+/// Get hit, miss and eviction counts along with the current and maximum size of the cache.
+pub fn transform_cache_stats() -> CacheStats {
+    with_cache(|cache| cache.stats())
+}
+
+/// Transform x and y coordinates given as mutable references.
+pub fn xform_xy<'a, I>(xys: I, xform: &Transform) -> Result<(), Error>
+where
+    I: IntoIterator<Item = (&'a mut f64, &'a mut f64)>,
+{
+    xform_xy_with(xys, xform, &TransformOptions::default()).map(|_| ())
+}
+
+/// Transform x and y coordinates, handling failing vertices as `options` says.
+///
+/// Returns the indices of vertices to drop when the policy is [`ErrorPolicy::DropVertex`]. Those
+/// vertices are left untransformed.
+pub fn xform_xy_with<'a, I>(
+    xys: I,
+    xform: &Transform,
+    options: &TransformOptions,
+) -> Result<Vec<usize>, Error>
+where
+    I: IntoIterator<Item = (&'a mut f64, &'a mut f64)>,
+{
+    let steps = lonlat_steps(xform, options)?;
+    xform_xy_from(xys, 0, xform, &steps, options)
+}
+
+/// Steps through WGS84 longitude/latitude needed for `options`.
+fn lonlat_steps(
+    xform: &Transform,
+    options: &TransformOptions,
+) -> Result<(LonLatStep, LonLatStep), Error> {
+    if options.check_area_of_use || options.on_error == ErrorPolicy::Clamp {
+        Ok((
+            LonLatStep::new(xform.from_srid(), 4326, xform)?,
+            LonLatStep::new(4326, xform.to_srid(), xform)?,
+        ))
+    } else {
+        Ok((LonLatStep::Identity, LonLatStep::Identity))
+    }
+}
+
+/// Transform x and y coordinates like [`xform_xy_with`], numbering vertices from `first`.
+fn xform_xy_from<'a, I>(
+    xys: I,
+    first: usize,
+    xform: &Transform,
+    steps: &(LonLatStep, LonLatStep),
+    options: &TransformOptions,
+) -> Result<Vec<usize>, Error>
+where
+    I: IntoIterator<Item = (&'a mut f64, &'a mut f64)>,
+{
+    let converter = VertexConverter::new(xform, steps, options);
+    let vertex_error = |i: usize, reason: String| {
+        Error::Other(format!("reprojection failed at vertex {}: {}", i, reason))
+    };
+
+    let mut dropped = Vec::new();
+    for (i, (x, y)) in (first..).zip(xys) {
+        let res = match converter.convert((*x, *y)) {
+            Ok(res) => res,
+            Err(reason) => match options.on_error {
+                ErrorPolicy::Raise => return Err(vertex_error(i, reason)),
+                ErrorPolicy::DropVertex => {
+                    dropped.push(i);
+                    continue;
+                }
+                ErrorPolicy::Nan => (f64::NAN, f64::NAN),
+                ErrorPolicy::Clamp => converter
+                    .clamp((*x, *y))
+                    .map_err(|reason| vertex_error(i, reason))?,
+            },
+        };
+        *x = res.0;
+        *y = res.1;
+    }
+    Ok(dropped)
+}
+
+pub fn xform_crds(crds: &mut [Crd], xform: &Transform) -> Result<(), Error> {
+    xform_crds_with(crds, xform, &TransformOptions::default()).map(|_| ())
+}
+
+/// Transform coordinates, handling failing vertices as `options` says. See [`xform_xy_with`].
+///
+/// With the `rayon` feature, long runs of coordinates are transformed in chunks on the rayon
+/// thread pool when the transform is built in and the pool has more than one thread. PROJ
+/// transforms are used by one thread at a time, so they always run on the calling thread.
+pub fn xform_crds_with(
+    crds: &mut [Crd],
+    xform: &Transform,
+    options: &TransformOptions,
+) -> Result<Vec<usize>, Error> {
+    let steps = lonlat_steps(xform, options)?;
+    #[cfg(feature = "rayon")]
+    {
+        if crds.len() > PARALLEL_CHUNK
+            && matches!(xform, Transform::Builtin(..))
+            && rayon::current_num_threads() > 1
+        {
+            return par_xform_crds(crds, xform, &steps, options);
+        }
+    }
+    xform_xy_from(crds.iter_mut().map(crd_xy), 0, xform, &steps, options)
+}
+
+fn crd_xy<'a>(crd: &'a mut Crd) -> (&'a mut f64, &'a mut f64) {
+    let (x, rest) = crd.0.split_at_mut(1);
+    (&mut x[0], &mut rest[0])
+}
+
+/// Number of coordinates transformed by each task of [`par_xform_crds`].
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK: usize = 4096;
+
+/// Transform coordinates in chunks on the rayon thread pool. Errors and dropped vertices are
+/// reported as if the coordinates had been transformed in order.
+#[cfg(feature = "rayon")]
+fn par_xform_crds(
+    crds: &mut [Crd],
+    xform: &Transform,
+    steps: &(LonLatStep, LonLatStep),
+    options: &TransformOptions,
+) -> Result<Vec<usize>, Error> {
+    let chunks: Vec<Result<Vec<usize>, Error>> = crds
+        .par_chunks_mut(PARALLEL_CHUNK)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let xys = chunk.iter_mut().map(crd_xy);
+            xform_xy_from(xys, i * PARALLEL_CHUNK, xform, steps, options)
+        })
+        .collect();
+    let mut dropped = Vec::new();
+    for chunk in chunks {
+        dropped.extend(chunk?);
+    }
+    Ok(dropped)
+}
+
+/// Geometries that can remove vertices left over by [`ErrorPolicy::DropVertex`].
+pub trait DropVertices {
+    /// Remove vertices by their ascending indices in the order the transform visited them.
+    fn drop_vertices(&mut self, dropped: &[usize]);
+}
+
+/// Retain the items of `items` whose running `index` is not in `dropped`.
+pub(crate) fn retain_vertices<T>(items: &mut Vec<T>, index: &mut usize, dropped: &[usize]) {
+    items.retain(|_| {
+        let keep = dropped.binary_search(index).is_err();
+        *index += 1;
+        keep
+    });
+}
+
+/// Retain the vertices of the rings of a polygon like [`retain_vertices`], keeping the polygon
+/// valid: rings that lost vertices are closed again and removed if left with fewer than four
+/// vertices. Returns `false` if the exterior ring was removed, leaving no polygon.
+pub(crate) fn retain_ring_vertices<R, T, F>(
+    rings: &mut Vec<R>,
+    points: F,
+    index: &mut usize,
+    dropped: &[usize],
+) -> bool
+where
+    T: Clone + PartialEq,
+    F: Fn(&mut R) -> &mut Vec<T>,
+{
+    let mut exterior = true;
+    let mut first = true;
+    rings.retain_mut(|ring| {
+        let points = points(ring);
+        let len = points.len();
+        retain_vertices(points, index, dropped);
+        let mut keep = true;
+        if points.len() < len {
+            if points.first() != points.last() {
+                points.push(points[0].clone());
+            }
+            keep = points.len() >= 4;
+        }
+        if first && !keep {
+            exterior = false;
+        }
+        first = false;
+        keep
+    });
+    if !exterior {
+        rings.clear();
+    }
+    exterior
+}
+
+/// Reproject EWKB-encoded geometry into `to_srid`. The result is EWKB carrying the new SRID,
+/// written in the byte order of the input.
+///
+/// `from_srid` may be omitted if the EWKB header contains the SRID.
+pub fn reproject_ewkb(
+    data: &[u8],
+    to_srid: i32,
+    from_srid: Option<i32>,
+    options: &TransformOptions,
+) -> Result<Vec<u8>, Error> {
+    reproject_ewkb_limited(data, to_srid, from_srid, options, &ParseLimits::default())
+}
+
+/// Reproject EWKB-encoded geometry like [`reproject_ewkb`], failing when the input exceeds
+/// `limits`.
+pub fn reproject_ewkb_limited(
+    data: &[u8],
+    to_srid: i32,
+    from_srid: Option<i32>,
+    options: &TransformOptions,
+    limits: &ParseLimits,
+) -> Result<Vec<u8>, Error> {
+    let from_srid = match from_srid.or_else(|| ewkb::get_srid(data)) {
+        Some(srid) => srid,
+        None => {
+            return Err(Error::Other(
+                "from_srid not provided and data does not have srid".to_string(),
+            ))
+        }
+    };
+    let xform = get_transform(from_srid, to_srid)?;
+    match ewkb::get_point_type(data) {
+        PointType::Point => {
+            reproject_ewkb_geometry::<ewkb::Point>(data, &xform, to_srid, options, limits)
+        }
+        PointType::PointZ => {
+            reproject_ewkb_geometry::<ewkb::PointZ>(data, &xform, to_srid, options, limits)
+        }
+        PointType::PointM => {
+            reproject_ewkb_geometry::<ewkb::PointM>(data, &xform, to_srid, options, limits)
+        }
+        PointType::PointZM => {
+            reproject_ewkb_geometry::<ewkb::PointZM>(data, &xform, to_srid, options, limits)
+        }
+    }
+}
+
+fn reproject_ewkb_geometry<P>(
+    data: &[u8],
+    xform: &Transform,
+    to_srid: i32,
+    options: &TransformOptions,
+    limits: &ParseLimits,
+) -> Result<Vec<u8>, Error>
+where
+    P: crate::types::Point
+        + EwkbRead
+        + EwkbTransform
+        + PointXyMut
+        + Clone
+        + PartialEq
+        + for<'a> AsEwkbPoint<'a>,
+{
+    let mut geom = GeometryT::<P>::read_ewkb_limited(&mut &data[..], limits)?;
+    geom.transform_with(xform, options)?;
+    geom.set_srid(Some(to_srid));
+    // The SRID adds 4 bytes to the header if the input did not have one
+    let mut encoded = Vec::with_capacity(data.len() + 4);
+    geom.as_ewkb()
+        .write_ewkb_with(&mut encoded, ewkb::get_byte_order(data))?;
+    Ok(encoded)
+}
+
+#[cfg(feature = "proj")]
 impl Coord<f64> for &mut Crd<'_> {
     fn x(&self) -> f64 {
         self.0[0]
@@ -123,14 +1019,543 @@ impl<'a> AsCrds<'a> for MultiPolygon {
     }
 }
 
+impl DropVertices for Point {
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        if !dropped.is_empty() {
+            self.coordinates[0] = f64::NAN;
+            self.coordinates[1] = f64::NAN;
+        }
+    }
+}
+
+impl DropVertices for LineString {
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        retain_vertices(&mut self.coordinates, &mut 0, dropped);
+    }
+}
+
+impl DropVertices for Polygon {
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        retain_ring_vertices(&mut self.coordinates, |ring| ring, &mut 0, dropped);
+    }
+}
+
+impl DropVertices for MultiPoint {
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        retain_vertices(&mut self.coordinates, &mut 0, dropped);
+    }
+}
+
+impl DropVertices for MultiLineString {
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        let mut index = 0;
+        for line in self.coordinates.iter_mut() {
+            retain_vertices(line, &mut index, dropped);
+        }
+    }
+}
+
+impl DropVertices for MultiPolygon {
+    fn drop_vertices(&mut self, dropped: &[usize]) {
+        let mut index = 0;
+        self.coordinates
+            .retain_mut(|polygon| retain_ring_vertices(polygon, |ring| ring, &mut index, dropped));
+    }
+}
+
 #[cfg(test)]
 mod test {
     extern crate approx;
     use self::approx::assert_relative_eq;
     use super::get_transform;
-    use crate::geojson::{LineString, MultiLineString, MultiPoint, MultiPolygon, Polygon};
+    use crate::geojson::LineString;
+    #[cfg(feature = "proj")]
+    use crate::geojson::{MultiLineString, MultiPoint, MultiPolygon, Polygon};
+
+    use super::{
+        reproject_ewkb, xform_crds, xform_crds_with, xform_xy_with, AsCrds, CacheStats,
+        ErrorPolicy, Transform, TransformCache, TransformOptions,
+    };
+    use crate::ewkb::{self, AsEwkbLineString, AsEwkbPolygon, EwkbRead, EwkbTransform, EwkbWrite};
+    use crate::geojson::GeoJSONEncode;
+
+    #[test]
+    fn test_transform_cache_lru() {
+        let mut cache = TransformCache::new(2);
+        assert!(cache.get((4326, 3857)).is_none());
+        cache.insert((4326, 3857), Transform::new(4326, 3857).unwrap());
+        cache.insert((3857, 4326), Transform::new(3857, 4326).unwrap());
+        assert!(cache.get((4326, 3857)).is_some());
+
+        // (3857, 4326) is the least recently used entry and gets evicted
+        cache.insert((4326, 4087), Transform::new(4326, 4087).unwrap());
+        assert!(cache.get((3857, 4326)).is_none());
+        assert!(cache.get((4326, 3857)).is_some());
+        assert!(cache.get((4326, 4087)).is_some());
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 3,
+                misses: 2,
+                evictions: 1,
+                size: 2,
+                max_size: 2,
+            }
+        );
+
+        cache.max_size = 1;
+        cache.shrink_to(1);
+        assert!(cache.get((4326, 4087)).is_some());
+        assert_eq!(cache.stats().size, 1);
+        assert_eq!(cache.stats().evictions, 2);
+    }
+
+    #[test]
+    fn test_transform_cache_disabled() {
+        let mut cache = TransformCache::new(0);
+        cache.insert((4326, 3857), Transform::new(4326, 3857).unwrap());
+        assert!(cache.get((4326, 3857)).is_none());
+        assert_eq!(cache.stats().size, 0);
+    }
+
+    #[test]
+    fn test_reproject_builtin_linestring() {
+        // WGS84 / UTM zone 35N matches ETRS-TM35FIN to within a millimetre
+        let mut ls = LineString {
+            coordinates: vec![
+                vec![24.97793, 60.33016, 1.0],
+                vec![24.94841, 60.31733, 2.0],
+                vec![24.92764, 60.30755, 3.0],
+            ],
+            type_name: "LineString".to_string(),
+            crs: None,
+            dims: None,
+        };
+        let xform = get_transform(4326, 32635).unwrap();
+        let mut crds = ls.as_crds();
+        xform_crds(&mut crds, &xform).unwrap();
+
+        let expected = [
+            [388351.126, 6689893.389, 1.0],
+            [386677.069, 6688515.295, 2.0],
+            [385495.829, 6687462.308, 3.0],
+        ];
+        for (i, vtx) in ls.coordinates.iter().enumerate() {
+            let expected_vtx = expected[i];
+            assert_relative_eq!(vtx[0], expected_vtx[0], epsilon = 0.001);
+            assert_relative_eq!(vtx[1], expected_vtx[1], epsilon = 0.001);
+            assert_relative_eq!(vtx[2], expected_vtx[2]);
+        }
+
+        // UTM -> Web Mercator goes through geographic coordinates
+        let xform = get_transform(32635, 3857).unwrap();
+        let mut crds = ls.as_crds();
+        xform_crds(&mut crds, &xform).unwrap();
+        let xform = get_transform(3857, 4326).unwrap();
+        let mut crds = ls.as_crds();
+        xform_crds(&mut crds, &xform).unwrap();
+        assert_relative_eq!(ls.coordinates[0][0], 24.97793, epsilon = 1e-9);
+        assert_relative_eq!(ls.coordinates[0][1], 60.33016, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_reproject_ewkb() {
+        // SRID=4326;LINESTRING Z (1 2 5, 10 15 22)
+        let data = [
+            0x01, 0x02, 0x00, 0x00, 0xa0, 0xe6, 0x10, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0xf0, 0x3f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x14, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x24, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2e, 0x40, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x36, 0x40,
+        ];
+        let encoded = reproject_ewkb(&data, 3857, None, &TransformOptions::default()).unwrap();
+        assert_eq!(ewkb::get_srid(&encoded), Some(3857));
+        let line = ewkb::LineStringZ::read_ewkb(&mut encoded.as_slice()).unwrap();
+        assert_eq!(line.srid, Some(3857));
+        assert_relative_eq!(line.points[0].x, 111319.491, epsilon = 1e-3);
+        assert_relative_eq!(line.points[0].y, 222684.209, epsilon = 1e-3);
+        assert_relative_eq!(line.points[0].z, 5.0);
+        assert_relative_eq!(line.points[1].x, 1113194.908, epsilon = 1e-3);
+        assert_relative_eq!(line.points[1].y, 1689200.14, epsilon = 1e-2);
+        assert_relative_eq!(line.points[1].z, 22.0);
+
+        // Without SRID in the data from_srid is required
+        let encoded =
+            reproject_ewkb(&data, 4326, Some(4326), &TransformOptions::default()).unwrap();
+        assert_eq!(encoded, data);
+        let mut no_srid = vec![0x01, 0x02, 0x00, 0x00, 0x80];
+        no_srid.extend_from_slice(&data[9..]);
+        assert!(reproject_ewkb(&no_srid, 3857, None, &TransformOptions::default()).is_err());
+        let encoded =
+            reproject_ewkb(&no_srid, 3857, Some(4326), &TransformOptions::default()).unwrap();
+        assert_eq!(ewkb::get_srid(&encoded), Some(3857));
+        assert_eq!(encoded.len(), data.len());
+
+        // Big-endian input gives big-endian output
+        let mut xdr = Vec::new();
+        line.as_ewkb()
+            .write_ewkb_with(&mut xdr, ewkb::Endianness::Big)
+            .unwrap();
+        let encoded = reproject_ewkb(&xdr, 4326, None, &TransformOptions::default()).unwrap();
+        assert_eq!(ewkb::get_byte_order(&encoded), ewkb::Endianness::Big);
+        let line = ewkb::LineStringZ::read_ewkb(&mut encoded.as_slice()).unwrap();
+        assert_relative_eq!(line.points[1].x, 10.0, epsilon = 1e-9);
+        assert_relative_eq!(line.points[1].y, 15.0, epsilon = 1e-9);
+
+        // Dropping a vertex that cannot be projected
+        let mut polar = data.to_vec();
+        polar[21..29].copy_from_slice(&90f64.to_le_bytes());
+        let options = TransformOptions {
+            on_error: ErrorPolicy::DropVertex,
+            check_area_of_use: false,
+        };
+        assert!(reproject_ewkb(&polar, 3857, None, &TransformOptions::default()).is_err());
+        let encoded = reproject_ewkb(&polar, 3857, None, &options).unwrap();
+        let line = ewkb::LineStringZ::read_ewkb(&mut encoded.as_slice()).unwrap();
+        assert_eq!(line.points.len(), 1);
+        assert_relative_eq!(line.points[0].z, 22.0);
+    }
+
+    fn polar_line() -> LineString {
+        LineString {
+            coordinates: vec![
+                vec![24.9, 60.1, 1.0],
+                vec![25.0, 90.0, 2.0],
+                vec![25.1, 60.2, 3.0],
+            ],
+            type_name: "LineString".to_string(),
+            crs: None,
+            dims: None,
+        }
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let xform = get_transform(4326, 3857).unwrap();
+        let with_policy = |on_error| TransformOptions {
+            on_error,
+            check_area_of_use: false,
+        };
+
+        let mut ls = polar_line();
+        let err = ls.transform(&xform).unwrap_err();
+        assert!(format!("{}", err).contains("failed at vertex 1"));
+
+        let mut ls = polar_line();
+        ls.transform_with(&xform, &with_policy(ErrorPolicy::Nan))
+            .unwrap();
+        assert_eq!(ls.coordinates.len(), 3);
+        assert!(ls.coordinates[1][0].is_nan() && ls.coordinates[1][1].is_nan());
+        assert_eq!(ls.coordinates[1][2], 2.0);
+
+        let mut ls = polar_line();
+        ls.transform_with(&xform, &with_policy(ErrorPolicy::DropVertex))
+            .unwrap();
+        assert_eq!(ls.coordinates.len(), 2);
+        assert_eq!(ls.coordinates[1][2], 3.0);
+
+        // Clamped onto the northern edge of the Web Mercator area of use
+        let mut ls = polar_line();
+        ls.transform_with(&xform, &with_policy(ErrorPolicy::Clamp))
+            .unwrap();
+        let expected = xform.convert((25.0, 85.06)).unwrap();
+        assert_relative_eq!(ls.coordinates[1][0], expected.0, epsilon = 1e-6);
+        assert_relative_eq!(ls.coordinates[1][1], expected.1, epsilon = 1e-6);
+
+        assert_eq!(
+            "drop_vertex".parse::<ErrorPolicy>().unwrap(),
+            ErrorPolicy::DropVertex
+        );
+        assert!("ignore".parse::<ErrorPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_drop_vertex_polygon() {
+        let xform = get_transform(4326, 3857).unwrap();
+        let options = TransformOptions {
+            on_error: ErrorPolicy::DropVertex,
+            check_area_of_use: false,
+        };
+        let ring = |n: usize| {
+            // The first and closing vertices cannot be projected
+            let mut ring = vec![vec![25.0, 95.0]];
+            ring.extend((1..n - 1).map(|i| vec![25.0 + i as f64, 60.0 + (i % 2) as f64]));
+            ring.push(vec![25.0, 95.0]);
+            ring
+        };
+        let polygon = |rings: Vec<Vec<Vec<f64>>>| crate::geojson::Polygon {
+            type_name: "Polygon".to_string(),
+            crs: None,
+            coordinates: rings,
+            dims: None,
+        };
+
+        // The ring is closed again with its new first vertex
+        let mut poly = polygon(vec![ring(5)]);
+        poly.transform_with(&xform, &options).unwrap();
+        assert_eq!(poly.coordinates[0].len(), 4);
+        assert_eq!(poly.coordinates[0][0], poly.coordinates[0][3]);
+
+        // Holes that become too small are removed, and so are polygons without an exterior
+        let mut poly = polygon(vec![ring(5), ring(4)]);
+        poly.transform_with(&xform, &options).unwrap();
+        assert_eq!(poly.coordinates.len(), 1);
+        let mut poly = polygon(vec![ring(4), ring(5)]);
+        poly.transform_with(&xform, &options).unwrap();
+        assert!(poly.coordinates.is_empty());
+
+        let mut multi = crate::geojson::MultiPolygon {
+            type_name: "MultiPolygon".to_string(),
+            crs: None,
+            coordinates: vec![vec![ring(4)], vec![ring(6), ring(4)]],
+            dims: None,
+        };
+        multi.transform_with(&xform, &options).unwrap();
+        assert_eq!(multi.coordinates.len(), 1);
+        assert_eq!(multi.coordinates[0].len(), 1);
+        assert_eq!(multi.coordinates[0][0].len(), 5);
+
+        // EWKB polygons are fixed the same way
+        let ewkb_polygon = |rings: Vec<Vec<Vec<f64>>>| ewkb::Polygon {
+            rings: rings
+                .into_iter()
+                .map(|ring| {
+                    ring.into_iter()
+                        .map(|crd| ewkb::Point::new(crd[0], crd[1], None, None, None))
+                        .collect()
+                })
+                .collect(),
+            srid: Some(4326),
+        };
+        let mut data = Vec::new();
+        ewkb_polygon(vec![ring(5), ring(4)])
+            .as_ewkb()
+            .write_ewkb(&mut data)
+            .unwrap();
+        let encoded = reproject_ewkb(&data, 3857, None, &options).unwrap();
+        let poly = ewkb::Polygon::read_ewkb(&mut encoded.as_slice()).unwrap();
+        assert_eq!(poly.rings.len(), 1);
+        assert_eq!(poly.rings[0].points.len(), 4);
+        assert_eq!(poly.rings[0].points[0], poly.rings[0].points[3]);
+
+        let mut multi = ewkb::MultiPolygon {
+            polygons: vec![
+                ewkb_polygon(vec![ring(4), ring(5)]),
+                ewkb_polygon(vec![ring(5), ring(4)]),
+            ],
+            srid: Some(4326),
+        };
+        multi.transform_with(&xform, &options).unwrap();
+        assert_eq!(multi.polygons.len(), 1);
+        assert_eq!(multi.polygons[0].rings.len(), 1);
+    }
+
+    #[test]
+    fn test_check_area_of_use() {
+        // Second vertex lies in UTM zone 37, far outside zone 35
+        let line = || LineString {
+            coordinates: vec![vec![24.9, 60.1], vec![40.0, 60.1]],
+            type_name: "LineString".to_string(),
+            crs: None,
+            dims: None,
+        };
+        let xform = get_transform(4326, 32635).unwrap();
+        let mut options = TransformOptions {
+            on_error: ErrorPolicy::Raise,
+            check_area_of_use: true,
+        };
+        let err = line().transform_with(&xform, &options).unwrap_err();
+        assert!(format!("{}", err).contains("vertex 1"));
+        assert!(format!("{}", err).contains("outside the area of use"));
+        assert!(line().transform(&xform).is_ok());
+
+        // Clamping moves the vertex onto the eastern edge of the zone at 30 degrees
+        options.on_error = ErrorPolicy::Clamp;
+        let mut ls = line();
+        ls.transform_with(&xform, &options).unwrap();
+        let expected = xform.convert((30.0, 60.1)).unwrap();
+        assert_relative_eq!(ls.coordinates[1][0], expected.0, epsilon = 1e-6);
+        assert_relative_eq!(ls.coordinates[1][1], expected.1, epsilon = 1e-6);
+
+        // Projected input is checked through its geographic coordinates
+        let xform = get_transform(3857, 32635).unwrap();
+        let mut ls = line();
+        xform_crds(&mut ls.as_crds(), &get_transform(4326, 3857).unwrap()).unwrap();
+        options.on_error = ErrorPolicy::DropVertex;
+        ls.transform_with(&xform, &options).unwrap();
+        assert_eq!(ls.coordinates.len(), 1);
+    }
+
+    #[test]
+    fn test_check_area_of_use_utm_to_utm() {
+        // Zones 33 and 35 do not overlap, nor do northern and southern zones, so vertices are
+        // checked against the source zone
+        let options = TransformOptions {
+            on_error: ErrorPolicy::Raise,
+            check_area_of_use: true,
+        };
+        for (to_srid, valid_lat) in [(32635, 60.1), (32733, 10.0)] {
+            let mut ls = LineString {
+                coordinates: vec![vec![15.0, valid_lat], vec![21.0, valid_lat]],
+                type_name: "LineString".to_string(),
+                crs: None,
+                dims: None,
+            };
+            xform_crds(&mut ls.as_crds(), &get_transform(4326, 32633).unwrap()).unwrap();
+            let xform = get_transform(32633, to_srid).unwrap();
+            assert_eq!(xform.area_of_use().map(|area| area.west), Some(12.0));
+            let err = ls.clone().transform_with(&xform, &options).unwrap_err();
+            assert!(format!("{}", err).contains("vertex 1"));
+            ls.coordinates.pop();
+            ls.transform_with(&xform, &options).unwrap();
+        }
+    }
+
+    #[cfg(feature = "proj")]
+    #[test]
+    fn test_check_area_of_use_proj() {
+        // New York is outside the area of use of the transformation into ETRS89 / TM35FIN
+        let line = || LineString {
+            coordinates: vec![vec![24.9, 60.1], vec![-74.0, 40.7]],
+            type_name: "LineString".to_string(),
+            crs: None,
+            dims: None,
+        };
+        let xform = get_transform(4326, 3067).unwrap();
+        assert!(xform.area_of_use().is_some());
+        let options = TransformOptions {
+            on_error: ErrorPolicy::Raise,
+            check_area_of_use: true,
+        };
+        let err = line().transform_with(&xform, &options).unwrap_err();
+        assert!(format!("{}", err).contains("vertex 1"));
+        assert!(line().transform(&xform).is_ok());
+    }
+
+    #[cfg(feature = "proj")]
+    #[test]
+    fn test_proj_drop_while_in_use() {
+        use super::{proj_users, ProjUse};
+
+        // A transform dropped while another one is in use is kept until PROJ is no longer used.
+        let user = ProjUse::new();
+        let xform = Transform::new(4326, 3067).unwrap();
+        let retired = proj_users().retired.len();
+        drop(xform);
+        assert_eq!(proj_users().retired.len(), retired + 1);
+        drop(user);
+    }
+
+    #[cfg(feature = "proj")]
+    #[test]
+    fn test_proj_config_local_grid() {
+        use super::{proj_config, set_proj_config, ProjConfig, ProjOperation};
+
+        let pipeline = "+proj=pipeline \
+            +step +proj=unitconvert +xy_in=deg +xy_out=rad \
+            +step +proj=hgridshift +grids=test_hgrid.gsb \
+            +step +proj=unitconvert +xy_in=rad +xy_out=deg";
+        let mut config = ProjConfig {
+            network: Some(false),
+            ..Default::default()
+        };
+        config
+            .operations
+            .insert((4326, 4230), ProjOperation::Pipeline(pipeline.to_string()));
+
+        // The grid is not in any default search path.
+        set_proj_config(config.clone()).unwrap();
+        assert!(get_transform(4326, 4230).is_err());
+
+        config.search_paths = vec![concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data").into()];
+        set_proj_config(config.clone()).unwrap();
+        assert_eq!(proj_config(), config);
+        let (x, y) = get_transform(4326, 4230)
+            .unwrap()
+            .convert((4.5, 52.5))
+            .unwrap();
+        assert_relative_eq!(x, 5.875, epsilon = 1e-6);
+        assert_relative_eq!(y, 55.375, epsilon = 1e-6);
+
+        // Restoring the defaults drops the cached pipeline.
+        set_proj_config(ProjConfig::default()).unwrap();
+        let (x, _) = get_transform(4326, 4230)
+            .unwrap()
+            .convert((4.5, 52.5))
+            .unwrap();
+        assert!((x - 5.875).abs() > 0.1);
+    }
+
+    #[test]
+    fn test_reproject_builtin_out_of_range() {
+        let mut ls = LineString {
+            coordinates: vec![vec![24.97793, 60.33016], vec![24.94841, 95.0]],
+            type_name: "LineString".to_string(),
+            crs: None,
+            dims: None,
+        };
+        let xform = get_transform(4326, 3857).unwrap();
+        let mut crds = ls.as_crds();
+        assert!(xform_crds(&mut crds, &xform).is_err());
+    }
+
+    #[test]
+    fn test_xform_crds_long() {
+        // Long enough to be transformed in chunks with the rayon feature, failing at vertices in
+        // the first and in the last chunk
+        let xform = get_transform(4326, 3857).unwrap();
+        let check = || {
+            for on_error in [
+                ErrorPolicy::Raise,
+                ErrorPolicy::DropVertex,
+                ErrorPolicy::Nan,
+            ] {
+                let options = TransformOptions {
+                    on_error,
+                    check_area_of_use: false,
+                };
+                let mut ls = LineString {
+                    coordinates: (0..20_000)
+                        .map(|i| {
+                            let lat = if i % 9_000 == 7 { 95.0 } else { 60.0 };
+                            vec![20.0 + i as f64 * 1e-4, lat]
+                        })
+                        .collect(),
+                    type_name: "LineString".to_string(),
+                    crs: None,
+                    dims: None,
+                };
+                let mut expected = ls.coordinates.clone();
+                let xys = expected.iter_mut().map(|crd| {
+                    let (x, rest) = crd.split_at_mut(1);
+                    (&mut x[0], &mut rest[0])
+                });
+                let expected_res = xform_xy_with(xys, &xform, &options);
+                let res = xform_crds_with(&mut ls.as_crds(), &xform, &options);
+                assert_eq!(format!("{:?}", res), format!("{:?}", expected_res));
+                if res.is_ok() {
+                    assert_eq!(format!("{:?}", ls.coordinates), format!("{:?}", expected));
+                }
+            }
+        };
+        // A pool of two threads, so that the coordinates are split on one core too
+        #[cfg(feature = "rayon")]
+        super::rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap()
+            .install(check);
+        #[cfg(not(feature = "rayon"))]
+        check();
+    }
+
+    #[cfg(not(feature = "proj"))]
+    #[test]
+    fn test_reproject_unsupported_without_proj() {
+        assert!(get_transform(4326, 3067).is_err());
+    }
 
-    use super::{xform_crds, AsCrds};
+    #[cfg(feature = "proj")]
     #[test]
     fn test_reproject_linestring() {
         let mut ls = LineString {
@@ -141,6 +1566,7 @@ mod test {
             ],
             type_name: "LineString".to_string(),
             crs: None,
+            dims: None,
         };
         let xform = get_transform(4326, 3067).unwrap();
         let mut crds = ls.as_crds();
@@ -158,6 +1584,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "proj")]
     #[test]
     fn test_reproject_polygon() {
         let mut polygon = Polygon {
@@ -178,6 +1605,7 @@ mod test {
             ],
             type_name: "Polygon".to_string(),
             crs: None,
+            dims: None,
         };
 
         let xform = get_transform(4326, 3067).unwrap();
@@ -208,6 +1636,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "proj")]
     #[test]
     fn test_reproject_multipoint() {
         let mut multipoint = MultiPoint {
@@ -218,6 +1647,7 @@ mod test {
             ],
             type_name: "MultiPoint".to_string(),
             crs: None,
+            dims: None,
         };
 
         let xform = get_transform(4326, 3067).unwrap();
@@ -238,6 +1668,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "proj")]
     #[test]
     fn test_reproject_multilinestring() {
         let mut multilinestring = MultiLineString {
@@ -247,6 +1678,7 @@ mod test {
             ],
             type_name: "MultiLineString".to_string(),
             crs: None,
+            dims: None,
         };
 
         let xform = get_transform(4326, 3067).unwrap();
@@ -268,6 +1700,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "proj")]
     #[test]
     fn test_reproject_multipolygon() {
         let mut multipolygon = MultiPolygon {
@@ -287,6 +1720,7 @@ mod test {
             ],
             type_name: "MultiPolygon".to_string(),
             crs: None,
+            dims: None,
         };
 
         let xform = get_transform(4326, 3067).unwrap();
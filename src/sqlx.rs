@@ -0,0 +1,156 @@
+// sqlx support
+//
+// PostGIS sends `geometry` and `geography` values as EWKB in binary format and as hex EWKB in
+// text format, used by simple queries. Both are decoded, and values are encoded as binary EWKB.
+extern crate sqlx;
+
+use self::sqlx::encode::IsNull;
+use self::sqlx::error::BoxDynError;
+use self::sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueFormat, PgValueRef, Postgres};
+use self::sqlx::{Decode, Encode, Type, TypeInfo};
+use std::borrow::Cow;
+
+use crate::encoding;
+use crate::error::Error;
+use crate::ewkb::{self, *};
+use crate::geojson::{self, BinaryFormat, GeoJSONEncode, GeoJSONGeometry};
+use crate::limits::ParseLimits;
+
+/// EWKB of a value in the given format.
+fn ewkb_bytes(format: PgValueFormat, raw: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    match format {
+        PgValueFormat::Binary => Ok(Cow::Borrowed(raw)),
+        PgValueFormat::Text => encoding::decode_hex(raw).map(Cow::Owned),
+    }
+}
+
+fn compatible(ty: &PgTypeInfo) -> bool {
+    matches!(ty.name(), "geometry" | "geography")
+}
+
+macro_rules! impl_sqlx_for_ewkb_type {
+    ($($geotype:ident),*) => {
+        $(
+            impl Type<Postgres> for ewkb::$geotype {
+                fn type_info() -> PgTypeInfo {
+                    PgTypeInfo::with_name("geometry")
+                }
+
+                fn compatible(ty: &PgTypeInfo) -> bool {
+                    compatible(ty)
+                }
+            }
+
+            impl<'r> Decode<'r, Postgres> for ewkb::$geotype {
+                fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                    let raw = ewkb_bytes(value.format(), value.as_bytes()?)?;
+                    Ok(Self::read_ewkb_strict(&mut &raw[..], &ParseLimits::default())?)
+                }
+            }
+
+            impl Encode<'_, Postgres> for ewkb::$geotype {
+                fn encode_by_ref(
+                    &self,
+                    buf: &mut PgArgumentBuffer,
+                ) -> Result<IsNull, BoxDynError> {
+                    self.as_ewkb().write_ewkb(&mut **buf)?;
+                    Ok(IsNull::No)
+                }
+            }
+        )*
+    };
+}
+
+impl_sqlx_for_ewkb_type!(Point, PointZ, PointM, PointZM);
+impl_sqlx_for_ewkb_type!(LineString, LineStringZ, LineStringM, LineStringZM);
+impl_sqlx_for_ewkb_type!(Polygon, PolygonZ, PolygonM, PolygonZM);
+impl_sqlx_for_ewkb_type!(MultiPoint, MultiPointZ, MultiPointM, MultiPointZM);
+impl_sqlx_for_ewkb_type!(
+    MultiLineString,
+    MultiLineStringZ,
+    MultiLineStringM,
+    MultiLineStringZM
+);
+impl_sqlx_for_ewkb_type!(MultiPolygon, MultiPolygonZ, MultiPolygonM, MultiPolygonZM);
+impl_sqlx_for_ewkb_type!(Geometry, GeometryZ, GeometryM, GeometryZM);
+impl_sqlx_for_ewkb_type!(
+    GeometryCollection,
+    GeometryCollectionZ,
+    GeometryCollectionM,
+    GeometryCollectionZM
+);
+
+impl Type<Postgres> for GeoJSONGeometry {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("geometry")
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for GeoJSONGeometry {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let raw = ewkb_bytes(value.format(), value.as_bytes()?)?;
+        Ok(geojson::read_binary(
+            &raw,
+            BinaryFormat::Ewkb,
+            &ParseLimits::default(),
+        )?)
+    }
+}
+
+impl Encode<'_, Postgres> for GeoJSONGeometry {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        buf.extend_from_slice(&self.to_ewkb()?);
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ewkb_bytes;
+    use super::sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueFormat, Postgres};
+    use super::sqlx::{Encode, Type};
+    use crate::ewkb::{self, EwkbRead};
+    use crate::geojson::GeoJSONGeometry;
+    use crate::limits::ParseLimits;
+    use std::convert::TryFrom;
+
+    const LINE: &str =
+        "0102000020E610000002000000000000000000244000000000000034C00000000000000000000000000000E0BF";
+
+    #[test]
+    fn test_ewkb_formats() {
+        let raw = ewkb_bytes(PgValueFormat::Text, LINE.as_bytes()).unwrap();
+        assert_eq!(ewkb_bytes(PgValueFormat::Binary, &raw).unwrap(), raw);
+        let line = ewkb::LineString::read_ewkb_strict(&mut &raw[..], &ParseLimits::default());
+        assert_eq!(line.unwrap().srid, Some(4326));
+        assert!(ewkb_bytes(PgValueFormat::Text, b"0102X").is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        let raw = ewkb_bytes(PgValueFormat::Text, LINE.as_bytes()).unwrap();
+        let line = ewkb::LineString::read_ewkb(&mut &raw[..]).unwrap();
+        let mut buf = PgArgumentBuffer::default();
+        assert!(!Encode::<Postgres>::encode_by_ref(&line, &mut buf)
+            .unwrap()
+            .is_null());
+        assert_eq!(&buf[..], &raw[..]);
+
+        let geom = GeoJSONGeometry::try_from(&ewkb::Geometry::read_ewkb(&mut &raw[..]).unwrap());
+        let mut buf = PgArgumentBuffer::default();
+        let is_null = Encode::<Postgres>::encode_by_ref(&geom.unwrap(), &mut buf).unwrap();
+        assert!(!is_null.is_null());
+        assert_eq!(&buf[..], &raw[..]);
+
+        assert!(<ewkb::Geometry as Type<Postgres>>::compatible(
+            &PgTypeInfo::with_name("geography")
+        ));
+        assert!(!<GeoJSONGeometry as Type<Postgres>>::compatible(
+            &PgTypeInfo::with_name("bytea")
+        ));
+    }
+}
@@ -6,11 +6,17 @@
 extern crate byteorder;
 
 use self::byteorder::ReadBytesExt;
-use crate::geojson::GeometryType;
+use crate::geojson::{Dims, GeometryType};
+use crate::limits::{capacity, expect_end, read_limited, ParseBudget, ParseLimits};
+use crate::visit::Visitor;
 use crate::{error::Error, ewkb, types as postgis};
+#[cfg(feature = "rayon")]
+use std::convert::TryFrom;
 use std::f64;
 use std::fmt;
 use std::io::prelude::*;
+use std::io::{self, ErrorKind};
+use std::marker::PhantomData;
 use std::slice::Iter;
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -56,6 +62,7 @@ pub struct TwkbInfo {
     precision: i8,
     has_idlist: bool,
     is_empty_geom: bool,
+    has_bbox: bool,
     size: Option<u64>,
     has_z: bool,
     has_m: bool,
@@ -97,79 +104,64 @@ pub fn get_geom_type(data: &[u8]) -> GeometryType {
 
 pub trait TwkbGeom: fmt::Debug + Sized {
     fn read_twkb<R: Read>(raw: &mut R) -> Result<Self, Error> {
-        let mut twkb_info: TwkbInfo = Default::default();
-        // type_and_prec     byte
-        // metadata_header   byte
-        // [extended_dims]   byte
-        // [size]            uvarint
-        // [bounds]          bbox
-        let type_and_prec = raw.read_u8()?;
-        twkb_info.geom_type = type_and_prec & 0x0F;
-        twkb_info.precision = decode_zig_zag_64(((type_and_prec & 0xF0) >> 4) as u64) as i8;
-        let metadata_header = raw.read_u8()?;
-        let has_bbox = (metadata_header & 0b0001) != 0;
-        let has_size_attribute = (metadata_header & 0b0010) != 0;
-        twkb_info.has_idlist = (metadata_header & 0b0100) != 0;
-        let has_ext_prec_info = (metadata_header & 0b1000) != 0;
-        twkb_info.is_empty_geom = (metadata_header & 0b10000) != 0;
-        if has_ext_prec_info {
-            let ext_prec_info = raw.read_u8()?;
-            twkb_info.has_z = ext_prec_info & 0b0001 != 0;
-            twkb_info.has_m = ext_prec_info & 0b0010 != 0;
-            twkb_info.prec_z = Some((ext_prec_info & 0x1C) >> 2);
-            twkb_info.prec_m = Some((ext_prec_info & 0xE0) >> 5);
-        }
-        if has_size_attribute {
-            twkb_info.size = Some(read_raw_varint64(raw)?);
-        }
-        if has_bbox {
-            let _xmin = read_int64(raw)?;
-            let _deltax = read_int64(raw)?;
-            let _ymin = read_int64(raw)?;
-            let _deltay = read_int64(raw)?;
-            if twkb_info.has_z {
-                let _zmin = read_int64(raw)?;
-                let _deltaz = read_int64(raw)?;
-            }
-            if twkb_info.has_m {
-                let _mmin = read_int64(raw)?;
-                let _deltam = read_int64(raw)?;
-            }
-        }
-        Self::read_twkb_body(raw, &twkb_info)
+        Self::read_twkb_limited(raw, &ParseLimits::default())
+    }
+
+    /// Read a geometry, failing when the input exceeds `limits`.
+    fn read_twkb_limited<R: Read>(raw: &mut R, limits: &ParseLimits) -> Result<Self, Error> {
+        read_limited(raw, limits, |raw, budget| Self::read_twkb_with(raw, budget))
+    }
+
+    /// Read a geometry like [`TwkbGeom::read_twkb_limited`], failing if `raw` has bytes left
+    /// after it.
+    fn read_twkb_strict<R: Read>(raw: &mut R, limits: &ParseLimits) -> Result<Self, Error> {
+        let geom = Self::read_twkb_limited(raw, limits)?;
+        expect_end(raw)?;
+        Ok(geom)
     }
 
     #[doc(hidden)]
-    fn read_twkb_body<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<Self, Error>;
+    fn read_twkb_with<R: Read>(raw: &mut R, budget: &mut ParseBudget) -> Result<Self, Error> {
+        read_geometry(raw, budget).0
+    }
+
+    #[doc(hidden)]
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error>;
 
+    /// Read a point stored as deltas to `prev`, the previous point in units of the precision
+    /// of each dimension.
     #[doc(hidden)]
     fn read_relative_point<R: Read>(
         raw: &mut R,
         twkb_info: &TwkbInfo,
-        x: f64,
-        y: f64,
-        z: Option<f64>,
-        m: Option<f64>,
-    ) -> Result<(f64, f64, Option<f64>, Option<f64>), Error> {
-        let x2 = x + read_varint64_as_f64(raw, twkb_info.precision)?;
-        let y2 = y + read_varint64_as_f64(raw, twkb_info.precision)?;
-        let z2 = if twkb_info.has_z {
-            let dz = read_varint64_as_f64(raw, twkb_info.prec_z.unwrap() as i8)?;
-            z.map(|v| v + dz)
+        prev: &mut [i64; 4],
+    ) -> Result<Point, Error> {
+        let mut read_dim = |i: usize, precision: i8| -> Result<f64, Error> {
+            let delta = decode_zig_zag_64(read_raw_varint64(raw)?);
+            prev[i] = prev[i].wrapping_add(delta);
+            Ok(units_to_f64(prev[i], precision))
+        };
+        let x = read_dim(0, twkb_info.precision)?;
+        let y = read_dim(1, twkb_info.precision)?;
+        let z = if twkb_info.has_z {
+            Some(read_dim(2, twkb_info.prec_z.unwrap() as i8)?)
         } else {
             None
         };
-        let m2 = if twkb_info.has_m {
-            let dm = read_varint64_as_f64(raw, twkb_info.prec_m.unwrap() as i8)?;
-            m.map(|v| v + dm)
+        let m = if twkb_info.has_m {
+            Some(read_dim(3, twkb_info.prec_m.unwrap() as i8)?)
         } else {
             None
         };
-        Ok((x2, y2, z2, m2))
+        Ok(Point::new_from_opt_vals(x, y, z, m))
     }
 
     fn read_idlist<R: Read>(raw: &mut R, size: usize) -> Result<Vec<u64>, Error> {
-        let mut idlist = Vec::with_capacity(size);
+        let mut idlist = Vec::with_capacity(capacity(size));
         for _ in 0..size {
             let id = read_raw_varint64(raw)?;
             idlist.push(id);
@@ -180,6 +172,103 @@ pub trait TwkbGeom: fmt::Debug + Sized {
 
 // --- helper functions for reading ---
 
+fn read_header<R: Read>(raw: &mut R) -> Result<TwkbInfo, Error> {
+    let mut twkb_info: TwkbInfo = Default::default();
+    // type_and_prec     byte
+    // metadata_header   byte
+    // [extended_dims]   byte
+    // [size]            uvarint
+    // [bounds]          bbox
+    let type_and_prec = raw.read_u8()?;
+    twkb_info.geom_type = type_and_prec & 0x0F;
+    twkb_info.precision = decode_zig_zag_64(((type_and_prec & 0xF0) >> 4) as u64) as i8;
+    let metadata_header = raw.read_u8()?;
+    twkb_info.has_bbox = (metadata_header & 0b0001) != 0;
+    let has_size_attribute = (metadata_header & 0b0010) != 0;
+    twkb_info.has_idlist = (metadata_header & 0b0100) != 0;
+    let has_ext_prec_info = (metadata_header & 0b1000) != 0;
+    twkb_info.is_empty_geom = (metadata_header & 0b10000) != 0;
+    if has_ext_prec_info {
+        let ext_prec_info = raw.read_u8()?;
+        twkb_info.has_z = ext_prec_info & 0b0001 != 0;
+        twkb_info.has_m = ext_prec_info & 0b0010 != 0;
+        twkb_info.prec_z = Some((ext_prec_info & 0x1C) >> 2);
+        twkb_info.prec_m = Some((ext_prec_info & 0xE0) >> 5);
+    }
+    if has_size_attribute {
+        twkb_info.size = Some(read_raw_varint64(raw)?);
+    }
+    Ok(twkb_info)
+}
+
+fn read_bbox<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<(), Error> {
+    if twkb_info.has_bbox {
+        let _xmin = read_int64(raw)?;
+        let _deltax = read_int64(raw)?;
+        let _ymin = read_int64(raw)?;
+        let _deltay = read_int64(raw)?;
+        if twkb_info.has_z {
+            let _zmin = read_int64(raw)?;
+            let _deltaz = read_int64(raw)?;
+        }
+        if twkb_info.has_m {
+            let _mmin = read_int64(raw)?;
+            let _deltam = read_int64(raw)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read a geometry, also returning whether `raw` was left at its end.
+///
+/// Only geometries with a size header can be skipped after an error.
+fn read_geometry<G: TwkbGeom, R: Read>(
+    raw: &mut R,
+    budget: &mut ParseBudget,
+) -> (Result<G, Error>, bool) {
+    let twkb_info = match read_header(raw) {
+        Ok(twkb_info) => twkb_info,
+        Err(err) => return (Err(err), false),
+    };
+    match twkb_info.size {
+        Some(size) => read_sized(raw, &twkb_info, size, budget),
+        None => {
+            let result =
+                read_bbox(raw, &twkb_info).and_then(|_| G::read_twkb_body(raw, &twkb_info, budget));
+            let at_end = result.is_ok();
+            (result, at_end)
+        }
+    }
+}
+
+/// Read the bbox and body of a geometry which must span exactly `size` bytes.
+///
+/// Also returns whether `raw` was left at the end of the geometry, which holds even after most
+/// errors as the rest of the geometry is skipped.
+fn read_sized<G: TwkbGeom, R: Read>(
+    raw: &mut R,
+    twkb_info: &TwkbInfo,
+    size: u64,
+    budget: &mut ParseBudget,
+) -> (Result<G, Error>, bool) {
+    let mut sized = raw.take(size);
+    let result = read_bbox(&mut sized, twkb_info)
+        .and_then(|_| G::read_twkb_body(&mut sized, twkb_info, budget));
+    let result = match result {
+        Ok(_) if sized.limit() > 0 => Err(Error::Read(format!(
+            "geometry is {} bytes shorter than its size header",
+            sized.limit()
+        ))),
+        Err(_) if sized.limit() == 0 => Err(Error::Read(format!(
+            "geometry exceeds the {} bytes of its size header",
+            size
+        ))),
+        result => result,
+    };
+    let skipped = io::copy(&mut sized, &mut io::sink()).is_ok();
+    (result, skipped)
+}
+
 fn read_raw_varint64<R: Read>(raw: &mut R) -> Result<u64, Error> {
     // from rust-protobuf
     let mut r: u64 = 0;
@@ -206,18 +295,224 @@ fn decode_zig_zag_64(n: u64) -> i64 {
     ((n >> 1) as i64) ^ (-((n & 1) as i64))
 }
 
-fn varint64_to_f64(varint: u64, precision: i8) -> f64 {
+fn units_to_f64(units: i64, precision: i8) -> f64 {
     if precision >= 0 {
-        (decode_zig_zag_64(varint) as f64) / 10u64.pow(precision as u32) as f64
+        (units as f64) / 10u64.pow(precision as u32) as f64
     } else {
-        (decode_zig_zag_64(varint) as f64) * 10u64.pow(precision.unsigned_abs() as u32) as f64
+        (units as f64) * 10u64.pow(precision.unsigned_abs() as u32) as f64
     }
 }
 
+fn varint64_to_f64(varint: u64, precision: i8) -> f64 {
+    units_to_f64(decode_zig_zag_64(varint), precision)
+}
+
 fn read_varint64_as_f64<R: Read>(raw: &mut R, precision: i8) -> Result<f64, Error> {
     read_raw_varint64(raw).map(|v| varint64_to_f64(v, precision))
 }
 
+/// Read the points of a linestring or of a member of a multilinestring, whose first delta is
+/// relative to `prev`.
+fn read_line_from<R: Read>(
+    raw: &mut R,
+    twkb_info: &TwkbInfo,
+    budget: &mut ParseBudget,
+    prev: &mut [i64; 4],
+) -> Result<LineString, Error> {
+    let mut points: Vec<Point> = Vec::new();
+    let npoints = budget.add_vertices(read_raw_varint64(raw)?)?;
+    points.reserve(capacity(npoints));
+    for _ in 0..npoints {
+        points.push(LineString::read_relative_point(raw, twkb_info, prev)?);
+    }
+    Ok(LineString { points: points })
+}
+
+/// Read the rings of a polygon or of a member of a multipolygon, whose first delta is relative
+/// to `prev`.
+fn read_polygon_from<R: Read>(
+    raw: &mut R,
+    twkb_info: &TwkbInfo,
+    budget: &mut ParseBudget,
+    prev: &mut [i64; 4],
+) -> Result<Polygon, Error> {
+    let mut rings: Vec<LineString> = Vec::new();
+    let nrings = budget.add_parts(read_raw_varint64(raw)?)?;
+    rings.reserve(capacity(nrings));
+    for _ in 0..nrings {
+        let mut ring = read_line_from(raw, twkb_info, budget, prev)?;
+        // close ring, if necessary
+        if let (Some(first), Some(last)) = (ring.points.first(), ring.points.last()) {
+            if first != last {
+                ring.points.push(*first);
+            }
+        }
+        rings.push(ring);
+    }
+    Ok(Polygon { rings: rings })
+}
+
+/// A multilinestring or multipolygon split into members that can be read on their own.
+#[cfg(feature = "rayon")]
+pub(crate) struct Members<'a> {
+    twkb_info: TwkbInfo,
+    pub(crate) ids: Option<Vec<u64>>,
+    /// Bytes of each member and the point its first delta is relative to.
+    members: Vec<(&'a [u8], [i64; 4])>,
+    pub(crate) vertices: usize,
+    /// Bytes taken by the whole geometry.
+    pub(crate) bytes: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl Members<'_> {
+    pub(crate) fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Read member `i` of a multilinestring.
+    pub(crate) fn line(&self, i: usize, limits: &ParseLimits) -> Result<LineString, Error> {
+        let (mut raw, mut prev) = self.members[i];
+        read_line_from(
+            &mut raw,
+            &self.twkb_info,
+            &mut ParseBudget::new(limits),
+            &mut prev,
+        )
+    }
+
+    /// Read member `i` of a multipolygon.
+    pub(crate) fn polygon(&self, i: usize, limits: &ParseLimits) -> Result<Polygon, Error> {
+        let (mut raw, mut prev) = self.members[i];
+        read_polygon_from(
+            &mut raw,
+            &self.twkb_info,
+            &mut ParseBudget::new(limits),
+            &mut prev,
+        )
+    }
+}
+
+/// Find the members of the multilinestring or multipolygon at the start of `data` by decoding
+/// their deltas without building them.
+///
+/// Deltas continue from one member to the next, so this has to go through every coordinate.
+/// Gives `None` for other geometries and for input the readers would not read successfully,
+/// leaving it to them to report errors.
+#[cfg(feature = "rayon")]
+pub(crate) fn split_members<'a>(data: &'a [u8], limits: &ParseLimits) -> Option<Members<'a>> {
+    let mut raw = data;
+    let twkb_info = read_header(&mut raw).ok()?;
+    if !matches!(twkb_info.geom_type, 5 | 6) || twkb_info.is_empty_geom {
+        return None;
+    }
+    // A geometry with a size header must span exactly that many bytes
+    let header = data.len() - raw.len();
+    let data = match twkb_info.size {
+        Some(size) => data.get(..header.checked_add(usize::try_from(size).ok()?)?)?,
+        None => data,
+    };
+    raw = &data[header..];
+    read_bbox(&mut raw, &twkb_info).ok()?;
+    let mut budget = ParseBudget::new(limits);
+    let nmembers = budget.add_parts(read_raw_varint64(&mut raw).ok()?).ok()?;
+    let ids = if twkb_info.has_idlist {
+        Some(MultiPolygon::read_idlist(&mut raw, nmembers).ok()?)
+    } else {
+        None
+    };
+    let mut members = Vec::with_capacity(capacity(nmembers));
+    let mut vertices = 0;
+    let mut prev = [0; 4];
+    for _ in 0..nmembers {
+        let start = data.len() - raw.len();
+        let first = prev;
+        let nlines = if twkb_info.geom_type == 6 {
+            budget.add_parts(read_raw_varint64(&mut raw).ok()?).ok()?
+        } else {
+            1
+        };
+        for _ in 0..nlines {
+            let npoints = budget
+                .add_vertices(read_raw_varint64(&mut raw).ok()?)
+                .ok()?;
+            skip_points(&mut raw, &twkb_info, npoints, &mut prev).ok()?;
+            vertices += npoints;
+        }
+        members.push((&data[start..data.len() - raw.len()], first));
+    }
+    if twkb_info.size.is_some() && !raw.is_empty() {
+        return None;
+    }
+    let bytes = data.len() - raw.len();
+    if bytes > limits.max_bytes {
+        return None;
+    }
+    Some(Members {
+        twkb_info,
+        ids,
+        members,
+        vertices,
+        bytes,
+    })
+}
+
+/// Skip `npoints` points, adding their deltas to `prev`.
+#[cfg(feature = "rayon")]
+fn skip_points<R: Read>(
+    raw: &mut R,
+    twkb_info: &TwkbInfo,
+    npoints: usize,
+    prev: &mut [i64; 4],
+) -> Result<(), Error> {
+    let used = [true, true, twkb_info.has_z, twkb_info.has_m];
+    for _ in 0..npoints {
+        for (i, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+            prev[i] = prev[i].wrapping_add(decode_zig_zag_64(read_raw_varint64(raw)?));
+        }
+    }
+    Ok(())
+}
+
+// ---
+
+/// Any geometry, read according to the type in its header.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Geometry {
+    Point(Point),
+    LineString(LineString),
+    Polygon(Polygon),
+    MultiPoint(MultiPoint),
+    MultiLineString(MultiLineString),
+    MultiPolygon(MultiPolygon),
+}
+
+impl TwkbGeom for Geometry {
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
+        let geom = match twkb_info.geom_type {
+            1 => Geometry::Point(Point::read_twkb_body(raw, twkb_info, budget)?),
+            2 => Geometry::LineString(LineString::read_twkb_body(raw, twkb_info, budget)?),
+            3 => Geometry::Polygon(Polygon::read_twkb_body(raw, twkb_info, budget)?),
+            4 => Geometry::MultiPoint(MultiPoint::read_twkb_body(raw, twkb_info, budget)?),
+            5 => {
+                Geometry::MultiLineString(MultiLineString::read_twkb_body(raw, twkb_info, budget)?)
+            }
+            6 => Geometry::MultiPolygon(MultiPolygon::read_twkb_body(raw, twkb_info, budget)?),
+            7 => {
+                return Err(Error::Other(
+                    "not implemented for GeometryCollection".to_owned(),
+                ))
+            }
+            _ => return Err(Error::Read("invalid geometry type".to_owned())),
+        };
+        Ok(geom)
+    }
+}
+
 // ---
 
 impl Point {
@@ -229,6 +524,22 @@ impl Point {
             m: _m,
         }
     }
+
+    fn point_type(&self) -> ewkb::PointType {
+        match (self.z.is_some(), self.m.is_some()) {
+            (false, false) => ewkb::PointType::Point,
+            (true, false) => ewkb::PointType::PointZ,
+            (false, true) => ewkb::PointType::PointM,
+            (true, true) => ewkb::PointType::PointZM,
+        }
+    }
+}
+
+/// Coordinate dimensions of a geometry, taken from its first point as all points share them.
+fn point_type_of<'a, I: Iterator<Item = &'a Point>>(mut points: I) -> ewkb::PointType {
+    points
+        .next()
+        .map_or(ewkb::PointType::Point, |p| p.point_type())
 }
 
 impl postgis::Point for Point {
@@ -245,25 +556,24 @@ impl postgis::Point for Point {
         self.z
     }
     fn crds(&self) -> Vec<f64> {
-        {
-            if let Some(z) = self.opt_z() {
-                if let Some(m) = self.opt_m() {
-                    [self.x(), self.y(), z, m].to_vec()
-                } else {
-                    [self.x(), self.y(), z].to_vec()
-                }
-            } else {
-                [self.x(), self.y()].to_vec()
-            }
-        }
+        let mut crds = Vec::with_capacity(4);
+        crds.extend([self.x(), self.y()]);
+        crds.extend(self.opt_z());
+        crds.extend(self.opt_m());
+        crds
     }
 }
 
 impl TwkbGeom for Point {
-    fn read_twkb_body<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<Self, Error> {
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
         if twkb_info.is_empty_geom {
             return Ok(Point::new_from_opt_vals(f64::NAN, f64::NAN, None, None));
         }
+        budget.add_vertices(1)?;
         let x = read_varint64_as_f64(raw, twkb_info.precision)?;
         let y = read_varint64_as_f64(raw, twkb_info.precision)?;
         let z = if twkb_info.has_z {
@@ -285,30 +595,26 @@ impl<'a> ewkb::AsEwkbPoint<'a> for Point {
         ewkb::EwkbPoint {
             geom: self,
             srid: None,
-            point_type: ewkb::PointType::Point,
+            point_type: self.point_type(),
         }
     }
 }
 
 impl TwkbGeom for LineString {
-    fn read_twkb_body<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<Self, Error> {
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
         // npoints           uvarint
         // pointarray        varint[]
         let mut points: Vec<Point> = Vec::new();
         if !twkb_info.is_empty_geom {
-            let npoints = read_raw_varint64(raw)?;
-            points.reserve(npoints as usize);
-            let mut x = 0.0;
-            let mut y = 0.0;
-            let mut z = if twkb_info.has_z { Some(0.0) } else { None };
-            let mut m = if twkb_info.has_m { Some(0.0) } else { None };
+            let npoints = budget.add_vertices(read_raw_varint64(raw)?)?;
+            points.reserve(capacity(npoints));
+            let mut prev = [0; 4];
             for _ in 0..npoints {
-                let (x2, y2, z2, m2) = Self::read_relative_point(raw, twkb_info, x, y, z, m)?;
-                points.push(Point::new_from_opt_vals(x2, y2, z2, m2));
-                x = x2;
-                y = y2;
-                z = z2;
-                m = m2;
+                points.push(Self::read_relative_point(raw, twkb_info, &mut prev)?);
             }
         }
         Ok(LineString { points: points })
@@ -330,46 +636,24 @@ impl<'a> ewkb::AsEwkbLineString<'a> for LineString {
         ewkb::EwkbLineString {
             geom: self,
             srid: None,
-            point_type: ewkb::PointType::Point,
+            point_type: point_type_of(self.points.iter()),
         }
     }
 }
 
 impl TwkbGeom for Polygon {
-    fn read_twkb_body<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<Self, Error> {
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
         // nrings            uvarint
         // npoints[0]        uvarint
         // pointarray[0]     varint[]
         // ...
         // npoints[n]        uvarint
         // pointarray[n]     varint[]
-        let mut rings: Vec<LineString> = Vec::new();
-        let nrings = read_raw_varint64(raw)?;
-        rings.reserve(nrings as usize);
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut z = if twkb_info.has_z { Some(0.0) } else { None };
-        let mut m = if twkb_info.has_m { Some(0.0) } else { None };
-        for _ in 0..nrings {
-            let mut points: Vec<Point> = Vec::new();
-            let npoints = read_raw_varint64(raw)?;
-            points.reserve(npoints as usize);
-            let (x0, y0, z0, m0) = (x, y, z, m);
-            for _ in 0..npoints {
-                let (x2, y2, z2, m2) = Self::read_relative_point(raw, twkb_info, x, y, z, m)?;
-                points.push(Point::new_from_opt_vals(x2, y2, z2, m2));
-                x = x2;
-                y = y2;
-                z = z2;
-                m = m2;
-            }
-            // close ring, if necessary
-            if x != x0 && y != y0 && z != z0 && m != m0 {
-                points.push(Point::new_from_opt_vals(x0, y0, z0, m0));
-            }
-            rings.push(LineString { points: points });
-        }
-        Ok(Polygon { rings: rings })
+        read_polygon_from(raw, twkb_info, budget, &mut [0; 4])
     }
 }
 
@@ -392,38 +676,35 @@ impl<'a> ewkb::AsEwkbPolygon<'a> for Polygon {
         ewkb::EwkbPolygon {
             geom: self,
             srid: None,
-            point_type: ewkb::PointType::Point,
+            point_type: point_type_of(self.rings.iter().flat_map(|r| r.points.iter())),
         }
     }
 }
 
 impl TwkbGeom for MultiPoint {
-    fn read_twkb_body<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<Self, Error> {
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
         // npoints           uvarint
         // [idlist]          varint[]
         // pointarray        varint[]
         let mut points: Vec<Point> = Vec::new();
         let mut ids: Option<Vec<u64>> = None;
         if !twkb_info.is_empty_geom {
-            let npoints = read_raw_varint64(raw)?;
-            points.reserve(npoints as usize);
+            let npoints = budget.add_parts(read_raw_varint64(raw)?)?;
+            budget.add_vertices(npoints as u64)?;
+            points.reserve(capacity(npoints));
 
             if twkb_info.has_idlist {
-                let idlist = Self::read_idlist(raw, npoints as usize)?;
+                let idlist = Self::read_idlist(raw, npoints)?;
                 ids = Some(idlist);
             }
 
-            let mut x = 0.0;
-            let mut y = 0.0;
-            let mut z = if twkb_info.has_z { Some(0.0) } else { None };
-            let mut m = if twkb_info.has_m { Some(0.0) } else { None };
+            let mut prev = [0; 4];
             for _ in 0..npoints {
-                let (x2, y2, z2, m2) = Self::read_relative_point(raw, twkb_info, x, y, z, m)?;
-                points.push(Point::new_from_opt_vals(x2, y2, z2, m2));
-                x = x2;
-                y = y2;
-                z = z2;
-                m = m2;
+                points.push(Self::read_relative_point(raw, twkb_info, &mut prev)?);
             }
         }
         Ok(MultiPoint {
@@ -448,13 +729,17 @@ impl<'a> ewkb::AsEwkbMultiPoint<'a> for MultiPoint {
         ewkb::EwkbMultiPoint {
             geom: self,
             srid: None,
-            point_type: ewkb::PointType::Point,
+            point_type: point_type_of(self.points.iter()),
         }
     }
 }
 
 impl TwkbGeom for MultiLineString {
-    fn read_twkb_body<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<Self, Error> {
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
         // nlinestrings      uvarint
         // [idlist]          varint[]
         // npoints[0]        uvarint
@@ -464,31 +749,17 @@ impl TwkbGeom for MultiLineString {
         // pointarray[n]     varint[]
         let mut lines: Vec<LineString> = Vec::new();
         let mut ids: Option<Vec<u64>> = None;
-        let nlines = read_raw_varint64(raw)?;
-        lines.reserve(nlines as usize);
+        let nlines = budget.add_parts(read_raw_varint64(raw)?)?;
+        lines.reserve(capacity(nlines));
 
         if twkb_info.has_idlist {
-            let idlist = Self::read_idlist(raw, nlines as usize)?;
+            let idlist = Self::read_idlist(raw, nlines)?;
             ids = Some(idlist);
         }
 
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut z = if twkb_info.has_z { Some(0.0) } else { None };
-        let mut m = if twkb_info.has_m { Some(0.0) } else { None };
+        let mut prev = [0; 4];
         for _ in 0..nlines {
-            let mut points: Vec<Point> = Vec::new();
-            let npoints = read_raw_varint64(raw)?;
-            points.reserve(npoints as usize);
-            for _ in 0..npoints {
-                let (x2, y2, z2, m2) = Self::read_relative_point(raw, twkb_info, x, y, z, m)?;
-                points.push(Point::new_from_opt_vals(x2, y2, z2, m2));
-                x = x2;
-                y = y2;
-                z = z2;
-                m = m2;
-            }
-            lines.push(LineString { points: points });
+            lines.push(read_line_from(raw, twkb_info, budget, &mut prev)?);
         }
         Ok(MultiLineString {
             lines: lines,
@@ -517,13 +788,17 @@ impl<'a> ewkb::AsEwkbMultiLineString<'a> for MultiLineString {
         ewkb::EwkbMultiLineString {
             geom: self,
             srid: None,
-            point_type: ewkb::PointType::Point,
+            point_type: point_type_of(self.lines.iter().flat_map(|l| l.points.iter())),
         }
     }
 }
 
 impl TwkbGeom for MultiPolygon {
-    fn read_twkb_body<R: Read>(raw: &mut R, twkb_info: &TwkbInfo) -> Result<Self, Error> {
+    fn read_twkb_body<R: Read>(
+        raw: &mut R,
+        twkb_info: &TwkbInfo,
+        budget: &mut ParseBudget,
+    ) -> Result<Self, Error> {
         // npolygons         uvarint
         // [idlist]          varint[]
         // nrings[0]         uvarint
@@ -535,42 +810,17 @@ impl TwkbGeom for MultiPolygon {
         // pointarray[n][m]  varint[]
         let mut polygons: Vec<Polygon> = Vec::new();
         let mut ids: Option<Vec<u64>> = None;
-        let npolygons = read_raw_varint64(raw)?;
-        polygons.reserve(npolygons as usize);
+        let npolygons = budget.add_parts(read_raw_varint64(raw)?)?;
+        polygons.reserve(capacity(npolygons));
 
         if twkb_info.has_idlist {
-            let idlist = Self::read_idlist(raw, npolygons as usize)?;
+            let idlist = Self::read_idlist(raw, npolygons)?;
             ids = Some(idlist);
         }
 
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut z = if twkb_info.has_z { Some(0.0) } else { None };
-        let mut m = if twkb_info.has_m { Some(0.0) } else { None };
+        let mut prev = [0; 4];
         for _ in 0..npolygons {
-            let mut rings: Vec<LineString> = Vec::new();
-            let nrings = read_raw_varint64(raw)?;
-            rings.reserve(nrings as usize);
-            for _ in 0..nrings {
-                let mut points: Vec<Point> = Vec::new();
-                let npoints = read_raw_varint64(raw)?;
-                points.reserve(npoints as usize);
-                let (x0, y0, z0, m0) = (x, y, z, m);
-                for _ in 0..npoints {
-                    let (x2, y2, z2, m2) = Self::read_relative_point(raw, twkb_info, x, y, z, m)?;
-                    points.push(Point::new_from_opt_vals(x2, y2, z2, m2));
-                    x = x2;
-                    y = y2;
-                    z = z2;
-                    m = m2;
-                }
-                // close ring, if necessary
-                if x != x0 && y != y0 && z != z0 && m != m0 {
-                    points.push(Point::new_from_opt_vals(x0, y0, z0, m0));
-                }
-                rings.push(LineString { points: points });
-            }
-            polygons.push(Polygon { rings: rings });
+            polygons.push(read_polygon_from(raw, twkb_info, budget, &mut prev)?);
         }
         Ok(MultiPolygon {
             polygons: polygons,
@@ -608,11 +858,240 @@ impl<'a> ewkb::AsEwkbMultiPolygon<'a> for MultiPolygon {
         ewkb::EwkbMultiPolygon {
             geom: self,
             srid: None,
-            point_type: ewkb::PointType::Point,
+            point_type: point_type_of(
+                self.polygons
+                    .iter()
+                    .flat_map(|p| p.rings.iter())
+                    .flat_map(|r| r.points.iter()),
+            ),
         }
     }
 }
 
+// --- Streams
+
+/// Iterator over geometries stored back to back, see [`read_many`].
+pub struct ReadMany<G, R> {
+    raw: R,
+    limits: ParseLimits,
+    done: bool,
+    geom: PhantomData<G>,
+}
+
+/// Read successive geometries from `raw` until it ends, applying `limits` to each of them.
+///
+/// Reading continues after an invalid geometry if it has a size header, which tells where the
+/// next geometry starts. Otherwise reading stops after the first error.
+///
+/// ```ignore
+/// for geom in twkb::read_many::<twkb::Geometry, _>(file, &ParseLimits::default()) {
+///     println!("{:?}", geom?);
+/// }
+/// ```
+pub fn read_many<G: TwkbGeom, R: Read>(raw: R, limits: &ParseLimits) -> ReadMany<G, R> {
+    ReadMany {
+        raw,
+        limits: *limits,
+        done: false,
+        geom: PhantomData,
+    }
+}
+
+impl<G: TwkbGeom, R: Read> Iterator for ReadMany<G, R> {
+    type Item = Result<G, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut type_and_prec = [0u8];
+        match self.raw.read_exact(&mut type_and_prec) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err.into()));
+            }
+        }
+        let mut raw = (&type_and_prec[..]).chain(&mut self.raw);
+        let mut resumable = false;
+        let result = read_limited(&mut raw, &self.limits, |raw, budget| {
+            let (result, at_end) = read_geometry(raw, budget);
+            resumable = at_end;
+            result
+        });
+        self.done = result.is_err() && !resumable;
+        Some(result)
+    }
+}
+
+// --- Visitors
+
+/// Read a geometry, passing its parts to `visitor` while reading instead of building it.
+///
+/// Like [`TwkbGeom::read_twkb_limited`], rings of polygons are closed if necessary and
+/// geometry collections are not supported. Empty points are passed with NaN coordinates.
+pub fn visit_twkb<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    visitor: &mut V,
+    limits: &ParseLimits,
+) -> Result<(), Error> {
+    read_limited(raw, limits, |raw, budget| {
+        let twkb_info = read_header(raw)?;
+        match twkb_info.size {
+            Some(size) => {
+                let mut sized = raw.take(size);
+                read_bbox(&mut sized, &twkb_info)?;
+                visit_body(&mut sized, &twkb_info, visitor, budget)?;
+                if sized.limit() > 0 {
+                    return Err(Error::Read(format!(
+                        "geometry is {} bytes shorter than its size header",
+                        sized.limit()
+                    )));
+                }
+                Ok(())
+            }
+            None => {
+                read_bbox(raw, &twkb_info)?;
+                visit_body(raw, &twkb_info, visitor, budget)
+            }
+        }
+    })
+}
+
+fn visit_body<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    twkb_info: &TwkbInfo,
+    visitor: &mut V,
+    budget: &mut ParseBudget,
+) -> Result<(), Error> {
+    let dims = Dims::new(twkb_info.has_z, twkb_info.has_m);
+    let empty = twkb_info.is_empty_geom;
+    let mut prev = [0; 4];
+    match twkb_info.geom_type {
+        1 => {
+            visitor.begin_point(dims)?;
+            if empty {
+                visitor.coord(f64::NAN, f64::NAN, None, None)?;
+            } else {
+                budget.add_vertices(1)?;
+                visit_point(raw, twkb_info, &mut prev, visitor)?;
+            }
+            visitor.end_point()
+        }
+        2 => {
+            let size = if empty {
+                0
+            } else {
+                budget.add_vertices(read_raw_varint64(raw)?)?
+            };
+            visitor.begin_linestring(dims, size)?;
+            for _ in 0..size {
+                visit_point(raw, twkb_info, &mut prev, visitor)?;
+            }
+            visitor.end_linestring()
+        }
+        3 => visit_polygon(raw, twkb_info, &mut prev, visitor, budget),
+        4 => {
+            let size = if empty {
+                0
+            } else {
+                budget.add_parts(read_raw_varint64(raw)?)?
+            };
+            budget.add_vertices(size as u64)?;
+            skip_idlist(raw, twkb_info, size)?;
+            visitor.begin_multipoint(dims, size)?;
+            for _ in 0..size {
+                visit_point(raw, twkb_info, &mut prev, visitor)?;
+            }
+            visitor.end_multipoint()
+        }
+        5 => {
+            let size = budget.add_parts(read_raw_varint64(raw)?)?;
+            skip_idlist(raw, twkb_info, size)?;
+            visitor.begin_multilinestring(dims, size)?;
+            for _ in 0..size {
+                let npoints = budget.add_vertices(read_raw_varint64(raw)?)?;
+                visitor.begin_linestring(dims, npoints)?;
+                for _ in 0..npoints {
+                    visit_point(raw, twkb_info, &mut prev, visitor)?;
+                }
+                visitor.end_linestring()?;
+            }
+            visitor.end_multilinestring()
+        }
+        6 => {
+            let size = budget.add_parts(read_raw_varint64(raw)?)?;
+            skip_idlist(raw, twkb_info, size)?;
+            visitor.begin_multipolygon(dims, size)?;
+            for _ in 0..size {
+                visit_polygon(raw, twkb_info, &mut prev, visitor, budget)?;
+            }
+            visitor.end_multipolygon()
+        }
+        7 => Err(Error::Other(
+            "not implemented for GeometryCollection".to_owned(),
+        )),
+        _ => Err(Error::Read("invalid geometry type".to_owned())),
+    }
+}
+
+/// Pass the rings of a polygon, each of which is read before passing it so that it can be
+/// closed.
+fn visit_polygon<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    twkb_info: &TwkbInfo,
+    prev: &mut [i64; 4],
+    visitor: &mut V,
+    budget: &mut ParseBudget,
+) -> Result<(), Error> {
+    let nrings = budget.add_parts(read_raw_varint64(raw)?)?;
+    visitor.begin_polygon(Dims::new(twkb_info.has_z, twkb_info.has_m), nrings)?;
+    let mut points: Vec<Point> = Vec::new();
+    for _ in 0..nrings {
+        let npoints = budget.add_vertices(read_raw_varint64(raw)?)?;
+        points.clear();
+        points.reserve(capacity(npoints));
+        for _ in 0..npoints {
+            points.push(Point::read_relative_point(raw, twkb_info, prev)?);
+        }
+        // close ring, if necessary
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            if first != last {
+                points.push(*first);
+            }
+        }
+        visitor.begin_ring(points.len())?;
+        for point in &points {
+            visitor.coord(point.x, point.y, point.z, point.m)?;
+        }
+        visitor.end_ring()?;
+    }
+    visitor.end_polygon()
+}
+
+fn visit_point<R: Read, V: Visitor + ?Sized>(
+    raw: &mut R,
+    twkb_info: &TwkbInfo,
+    prev: &mut [i64; 4],
+    visitor: &mut V,
+) -> Result<(), Error> {
+    let point = Point::read_relative_point(raw, twkb_info, prev)?;
+    visitor.coord(point.x, point.y, point.z, point.m)
+}
+
+fn skip_idlist<R: Read>(raw: &mut R, twkb_info: &TwkbInfo, size: usize) -> Result<(), Error> {
+    if twkb_info.has_idlist {
+        for _ in 0..size {
+            read_raw_varint64(raw)?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 use ewkb::{
     AsEwkbLineString, AsEwkbMultiLineString, AsEwkbMultiPoint, AsEwkbMultiPolygon, AsEwkbPoint,
@@ -673,6 +1152,65 @@ fn test_read_line() {
     assert_eq!(format!("{:?}", line), "LineString { points: [] }");
 }
 
+#[test]
+#[rustfmt::skip]
+fn test_read_limits() {
+    use crate::limits::ParseLimits;
+    let limits = |max_vertices, max_parts, max_bytes| ParseLimits { max_vertices, max_parts, max_bytes, ..Default::default() };
+    let read = |twkb: &[u8], limits: &ParseLimits| Polygon::read_twkb_limited(&mut &twkb[..], limits).map_err(|err| err.to_string());
+    // SELECT encode(ST_AsTWKB('POLYGON ((0 0, 2 0, 2 2, 0 2, 0 0),(10 10, -2 10, -2 -2, 10 -2, 10 10))'::geometry), 'hex')
+    let twkb = hex_to_vec("03000205000004000004030000030514141700001718000018");
+    assert!(read(&twkb, &limits(10, 2, twkb.len())).is_ok());
+    assert!(read(&twkb, &limits(9, 2, twkb.len())).unwrap_err().contains("max_vertices limit of 9"));
+    assert!(read(&twkb, &limits(10, 1, twkb.len())).unwrap_err().contains("max_parts limit of 1"));
+    assert!(read(&twkb, &limits(10, 2, twkb.len() - 1)).unwrap_err().contains("max_bytes limit of"));
+
+    // Counts far beyond the input are not allocated up front
+    let twkb = hex_to_vec("0200ffffffffffffffff7f");
+    assert!(LineString::read_twkb(&mut twkb.as_slice()).is_err());
+    let twkb = hex_to_vec("0300ffffffffffffffff7f");
+    assert!(Polygon::read_twkb(&mut twkb.as_slice()).is_err());
+    assert!(Point::read_twkb(&mut [].as_slice()).is_err());
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_read_many() {
+    use crate::limits::ParseLimits;
+    let limits = ParseLimits::default();
+    let read_all = |twkb: &[u8]| read_many::<Geometry, _>(twkb, &limits).map(|geom| geom.map_err(|err| err.to_string())).collect::<Vec<_>>();
+    // SELECT encode(ST_AsTWKB('POINT(10 -20)'::geometry), 'hex')
+    //     || encode(ST_AsTWKB('POINT(10 -20)'::geometry, include_sizes => true, include_bbox => true), 'hex')
+    //     || encode(ST_AsTWKB('LINESTRING (10 -20, -0 -0.5)'::geometry, include_sizes => true), 'hex')
+    let twkb = hex_to_vec("010014270103061400270014270202050214271326");
+    let geoms = read_all(&twkb);
+    assert_eq!(geoms.len(), 3);
+    assert_eq!(geoms[0], Ok(Geometry::Point(Point { x: 10.0, y: -20.0, z: None, m: None })));
+    assert_eq!(geoms[0], geoms[1]);
+    assert!(matches!(&geoms[2], Ok(Geometry::LineString(line)) if line.points.len() == 2));
+    assert!(read_all(&[]).is_empty());
+
+    // Geometries with a size header are skipped after an error
+    let geoms = read_all(&hex_to_vec("0102031427ff070201000100142701001427"));
+    assert_eq!(geoms[0], Err("Read(\"geometry is 1 bytes shorter than its size header\")".to_owned()));
+    assert_eq!(geoms[1], Err("Other(\"not implemented for GeometryCollection\")".to_owned()));
+    assert_eq!(geoms[2], geoms[3]);
+    assert_eq!(geoms.len(), 4);
+    // Without one, reading stops
+    let geoms = read_all(&hex_to_vec("070001001427"));
+    assert_eq!(geoms.len(), 1);
+    assert!(geoms[0].is_err());
+
+    // A size header too small for the geometry
+    let twkb = hex_to_vec("0102011427");
+    assert_eq!(Point::read_twkb(&mut twkb.as_slice()).unwrap_err().to_string(), "Read(\"geometry exceeds the 1 bytes of its size header\")");
+
+    // Strict reads fail on trailing bytes
+    let twkb = hex_to_vec("010014270000");
+    assert!(Point::read_twkb(&mut twkb.as_slice()).is_ok());
+    assert_eq!(Point::read_twkb_strict(&mut twkb.as_slice(), &limits).unwrap_err().to_string(), "Read(\"2 trailing bytes after geometry\")");
+}
+
 #[test]
 #[rustfmt::skip]
 fn test_read_polygon() {
@@ -772,3 +1310,11 @@ fn test_write_multipoly() {
     assert_eq!(format!("{:?}", multipoly.as_ewkb()), "EwkbMultiPolygon");
     assert_eq!(multipoly.as_ewkb().to_hex_ewkb(), "010600000002000000010300000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000010300000001000000050000000000000000002440000000000000244000000000000000C0000000000000244000000000000000C000000000000000C0000000000000244000000000000000C000000000000024400000000000002440");
 }
+
+#[test]
+#[rustfmt::skip]
+fn test_write_line_z() {
+    let twkb = hex_to_vec("22080102c8018f03c801c701860302"); // SELECT encode(ST_AsTWKB('LINESTRING Z (10 -20 100, -0 -0.5 101)'::geometry, 1), 'hex')
+    let line = LineString::read_twkb(&mut twkb.as_slice()).unwrap();
+    assert_eq!(line.as_ewkb().to_hex_ewkb(), "010200008002000000000000000000244000000000000034C000000000000059400000000000000000000000000000E0BF0000000000405940");
+}
@@ -12,31 +12,31 @@ pub trait Point: Send + Sync {
 }
 
 pub trait LineString<'a>: Send + Sync {
-    type ItemType: 'a + Point;
+    type ItemType: 'a + Point + ?Sized;
     type Iter: Iterator<Item = &'a Self::ItemType>;
     fn points(&'a self) -> Self::Iter;
 }
 
 pub trait Polygon<'a>: Send + Sync {
-    type ItemType: 'a + LineString<'a>;
+    type ItemType: 'a + LineString<'a> + ?Sized;
     type Iter: Iterator<Item = &'a Self::ItemType>;
     fn rings(&'a self) -> Self::Iter;
 }
 
 pub trait MultiPoint<'a>: Send + Sync {
-    type ItemType: 'a + Point;
+    type ItemType: 'a + Point + ?Sized;
     type Iter: Iterator<Item = &'a Self::ItemType>;
     fn points(&'a self) -> Self::Iter;
 }
 
 pub trait MultiLineString<'a>: Send + Sync {
-    type ItemType: 'a + LineString<'a>;
+    type ItemType: 'a + LineString<'a> + ?Sized;
     type Iter: Iterator<Item = &'a Self::ItemType>;
     fn lines(&'a self) -> Self::Iter;
 }
 
 pub trait MultiPolygon<'a>: Send + Sync {
-    type ItemType: 'a + Polygon<'a>;
+    type ItemType: 'a + Polygon<'a> + ?Sized;
     type Iter: Iterator<Item = &'a Self::ItemType>;
     fn polygons(&'a self) -> Self::Iter;
 }
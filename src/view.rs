@@ -0,0 +1,887 @@
+// Borrowed views of EWKB
+//
+// An `EwkbView` checks the structure of an EWKB geometry once, which only reads its headers and
+// counts, and then reads types, counts and coordinates from the bytes on demand without
+// allocating. Rings, parts and coordinates are random access through the typed views, which
+// implement the `types` traits. As the traits return references to their items, the typed views
+// are unsized wrappers of the bytes, and the coordinate layout and byte order are type
+// parameters instead of values.
+extern crate byteorder;
+
+pub use self::byteorder::{BigEndian, LittleEndian};
+
+use self::byteorder::ByteOrder;
+use std::f64;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+
+use crate::error::Error;
+use crate::ewkb::{has_m, has_z, Endianness};
+use crate::flat::CoordType;
+use crate::geojson::{Dims, GeometryType};
+use crate::limits::{exceeded, ParseBudget, ParseLimits};
+use crate::types as postgis;
+use crate::visit::{Bbox, Visitor};
+
+const CHECKED: &str = "EwkbView is checked when created";
+
+/// Limits for reading a view that has already been checked.
+const UNLIMITED: ParseLimits = ParseLimits {
+    max_vertices: usize::MAX,
+    max_parts: usize::MAX,
+    max_depth: usize::MAX,
+    max_bytes: usize::MAX,
+};
+
+fn truncated() -> Error {
+    Error::Read("EWKB ends before the end of the geometry".to_owned())
+}
+
+fn read_u32(bytes: &[u8], is_be: bool) -> u32 {
+    if is_be {
+        BigEndian::read_u32(bytes)
+    } else {
+        LittleEndian::read_u32(bytes)
+    }
+}
+
+fn read_f64(bytes: &[u8], is_be: bool) -> f64 {
+    if is_be {
+        BigEndian::read_f64(bytes)
+    } else {
+        LittleEndian::read_f64(bytes)
+    }
+}
+
+/// Size of a coordinate of a geometry with type id `type_id` in bytes.
+fn coord_size(type_id: u32) -> usize {
+    8 * (2 + has_z(type_id) as usize + has_m(type_id) as usize)
+}
+
+/// Walk over the structure of EWKB, checking sizes and limits and passing every run of
+/// consecutive coordinates to `coords`.
+struct Scan<'a, F> {
+    data: &'a [u8],
+    is_be: bool,
+    budget: ParseBudget,
+    coords: F,
+}
+
+impl<'a, F: FnMut(&'a [u8])> Scan<'a, F> {
+    fn take(&self, pos: usize, len: usize) -> Result<&'a [u8], Error> {
+        pos.checked_add(len)
+            .and_then(|end| self.data.get(pos..end))
+            .ok_or_else(truncated)
+    }
+
+    fn count(&self, pos: usize) -> Result<u64, Error> {
+        Ok(read_u32(self.take(pos, 4)?, self.is_be).into())
+    }
+
+    /// Header at `pos` of a geometry with byte order `is_be`, returning its type id, SRID and
+    /// the start of its body.
+    fn header(&self, pos: usize) -> Result<(u32, Option<i32>, usize), Error> {
+        let type_id = read_u32(self.take(pos + 1, 4)?, self.is_be);
+        if type_id & 0x20000000 == 0 {
+            return Ok((type_id, None, pos + 5));
+        }
+        let srid = read_u32(self.take(pos + 5, 4)?, self.is_be) as i32;
+        Ok((type_id, Some(srid), pos + 9))
+    }
+
+    /// Body of a geometry with type id `type_id` at `pos`, returning its end.
+    fn geometry(&mut self, pos: usize, type_id: u32) -> Result<usize, Error> {
+        match type_id & 0xff {
+            0x01 => {
+                self.budget.add_vertices(1)?;
+                self.coords(pos, 1, type_id)
+            }
+            0x02 => self.line(pos, type_id),
+            0x03 => self.polygon(pos, type_id),
+            code @ 0x04..=0x07 => {
+                if code == 0x07 {
+                    self.budget.enter()?;
+                }
+                let size = self.budget.add_parts(self.count(pos)?)?;
+                if code == 0x04 {
+                    self.budget.add_vertices(size as u64)?;
+                }
+                let mut pos = pos + 4;
+                for _ in 0..size {
+                    pos = self.member(pos, type_id)?.3;
+                }
+                if code == 0x07 {
+                    self.budget.leave();
+                }
+                Ok(pos)
+            }
+            _ => Err(Error::Read(format!(
+                "Error reading generic geometry type - unsupported type id {}.",
+                type_id
+            ))),
+        }
+    }
+
+    /// Member at `pos` of a geometry with type id `type_id`, returning the type id it is read
+    /// as, its SRID, and the start and end of its body.
+    fn member(
+        &mut self,
+        pos: usize,
+        type_id: u32,
+    ) -> Result<(u32, Option<i32>, usize, usize), Error> {
+        let is_be = *self.take(pos, 1)? == [0];
+        if is_be != self.is_be {
+            return Err(Error::Read(
+                "geometry has members with different byte orders".to_owned(),
+            ));
+        }
+        let (member_type_id, srid, body) = self.header(pos)?;
+        if has_z(member_type_id) != has_z(type_id) || has_m(member_type_id) != has_m(type_id) {
+            return Err(Error::Read(
+                "geometry has members with different dimensions".to_owned(),
+            ));
+        }
+        // Members of multi-geometries are read as their parts whatever their type says.
+        let (member_type_id, end) = match type_id & 0xff {
+            0x04 => (
+                (member_type_id & !0xff) | 0x01,
+                self.coords(body, 1, type_id)?,
+            ),
+            0x05 => ((member_type_id & !0xff) | 0x02, self.line(body, type_id)?),
+            0x06 => (
+                (member_type_id & !0xff) | 0x03,
+                self.polygon(body, type_id)?,
+            ),
+            _ => (member_type_id, self.geometry(body, member_type_id)?),
+        };
+        Ok((member_type_id, srid, body, end))
+    }
+
+    fn line(&mut self, pos: usize, type_id: u32) -> Result<usize, Error> {
+        let size = self.budget.add_vertices(self.count(pos)?)?;
+        self.coords(pos + 4, size, type_id)
+    }
+
+    fn polygon(&mut self, pos: usize, type_id: u32) -> Result<usize, Error> {
+        let size = self.budget.add_parts(self.count(pos)?)?;
+        let mut pos = pos + 4;
+        for _ in 0..size {
+            pos = self.line(pos, type_id)?;
+        }
+        Ok(pos)
+    }
+
+    fn coords(&mut self, pos: usize, count: usize, type_id: u32) -> Result<usize, Error> {
+        let len = count
+            .checked_mul(coord_size(type_id))
+            .ok_or_else(truncated)?;
+        let coords = self.take(pos, len)?;
+        (self.coords)(coords);
+        Ok(pos + len)
+    }
+}
+
+/// Borrowed EWKB geometry, checked once and read on demand.
+///
+/// Creating a view checks the headers, counts and sizes of the geometry and its members against
+/// the input and the limits, without reading coordinates. Members must have the byte order and
+/// dimensions of the geometry. Counts, the bounding box and coordinates are then read from the
+/// bytes when asked for, and [`EwkbView::as_polygon`] and the like give typed views for random
+/// access to rings, members and coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct EwkbView<'a> {
+    data: &'a [u8],
+    is_be: bool,
+    type_id: u32,
+    srid: Option<i32>,
+    body: usize,
+}
+
+impl<'a> EwkbView<'a> {
+    /// View of the geometry in `data`, which must hold exactly one geometry.
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        Self::new_limited(data, &ParseLimits::default())
+    }
+
+    /// View of the geometry in `data` like [`EwkbView::new`], failing when the input exceeds
+    /// `limits`.
+    pub fn new_limited(data: &'a [u8], limits: &ParseLimits) -> Result<Self, Error> {
+        if data.len() > limits.max_bytes {
+            return Err(exceeded("max_bytes", limits.max_bytes));
+        }
+        let view = Self::prefix_limited(data, limits)?;
+        if view.data.len() < data.len() {
+            return Err(Error::Read(format!(
+                "{} trailing bytes after geometry",
+                data.len() - view.data.len()
+            )));
+        }
+        Ok(view)
+    }
+
+    /// View of the geometry at the start of `data`, which may be followed by other bytes.
+    pub(crate) fn prefix_limited(data: &'a [u8], limits: &ParseLimits) -> Result<Self, Error> {
+        let mut scan = Scan {
+            data,
+            is_be: data.first() == Some(&0),
+            budget: ParseBudget::new(limits),
+            coords: |_| (),
+        };
+        let (type_id, srid, body) = scan.header(0)?;
+        let end = scan.geometry(body, type_id)?;
+        if end > limits.max_bytes {
+            return Err(exceeded("max_bytes", limits.max_bytes));
+        }
+        Ok(EwkbView {
+            data: &data[..end],
+            is_be: scan.is_be,
+            type_id,
+            srid,
+            body,
+        })
+    }
+
+    /// Walk over the geometry again, passing every run of consecutive coordinates to `coords`.
+    fn scan<F: FnMut(&'a [u8])>(&self, coords: F) {
+        let mut scan = Scan {
+            data: self.data,
+            is_be: self.is_be,
+            budget: ParseBudget::new(&UNLIMITED),
+            coords,
+        };
+        scan.geometry(self.body, self.type_id).expect(CHECKED);
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn geometry_type(&self) -> GeometryType {
+        match self.type_id & 0xff {
+            0x01 => GeometryType::Point,
+            0x02 => GeometryType::LineString,
+            0x03 => GeometryType::Polygon,
+            0x04 => GeometryType::MultiPoint,
+            0x05 => GeometryType::MultiLineString,
+            0x06 => GeometryType::MultiPolygon,
+            _ => GeometryType::GeometryCollection,
+        }
+    }
+
+    pub fn dims(&self) -> Dims {
+        Dims::new(has_z(self.type_id), has_m(self.type_id))
+    }
+
+    pub fn srid(&self) -> Option<i32> {
+        self.srid
+    }
+
+    pub fn byte_order(&self) -> Endianness {
+        if self.is_be {
+            Endianness::Big
+        } else {
+            Endianness::Little
+        }
+    }
+
+    /// Number of vertices, read from the counts of the geometry.
+    pub fn num_vertices(&self) -> usize {
+        let size = coord_size(self.type_id);
+        let mut count = 0;
+        self.scan(|coords| count += coords.len() / size);
+        count
+    }
+
+    /// Bounding box of x and y, skipping NaN values.
+    pub fn bbox(&self) -> Bbox {
+        let size = coord_size(self.type_id);
+        let is_be = self.is_be;
+        let mut bbox = Bbox::default();
+        self.scan(|coords| {
+            for coord in coords.chunks_exact(size) {
+                let x = read_f64(&coord[..8], is_be);
+                let y = read_f64(&coord[8..16], is_be);
+                // Bbox never fails
+                let _ = bbox.coord(x, y, None, None);
+            }
+        });
+        bbox
+    }
+
+    /// First vertex as `C`, or `None` if the geometry has none. Values missing from the geometry
+    /// are NaN, and empty points have NaN coordinates.
+    pub fn first_coord<C: CoordType>(&self) -> Option<C> {
+        let count = |pos: usize| read_u32(&self.data[pos..pos + 4], self.is_be) as usize;
+        match self.type_id & 0xff {
+            0x01 => Some(self.coord_at(self.body)),
+            0x02 if count(self.body) > 0 => Some(self.coord_at(self.body + 4)),
+            0x02 => None,
+            0x03 => {
+                let mut pos = self.body + 4;
+                for _ in 0..count(self.body) {
+                    let size = count(pos);
+                    if size > 0 {
+                        return Some(self.coord_at(pos + 4));
+                    }
+                    pos += 4 + size * coord_size(self.type_id);
+                }
+                None
+            }
+            _ => self.members().find_map(|member| member.first_coord()),
+        }
+    }
+
+    fn coord_at<C: CoordType>(&self, pos: usize) -> C {
+        let value = |i: usize| read_f64(&self.data[pos + 8 * i..pos + 8 * i + 8], self.is_be);
+        let z = if has_z(self.type_id) {
+            Some(value(2))
+        } else {
+            None
+        };
+        let m = if has_m(self.type_id) {
+            Some(value(2 + has_z(self.type_id) as usize))
+        } else {
+            None
+        };
+        C::new(value(0), value(1), z, m)
+    }
+
+    /// Number of members of a multi-geometry or geometry collection, zero for other geometries.
+    pub fn num_members(&self) -> usize {
+        match self.type_id & 0xff {
+            0x04..=0x07 => read_u32(&self.data[self.body..self.body + 4], self.is_be) as usize,
+            _ => 0,
+        }
+    }
+
+    /// Members of a multi-geometry or geometry collection as views. Members of multi-geometries
+    /// are viewed as points, linestrings or polygons whatever their headers say, like readers
+    /// read them.
+    pub fn members(&self) -> Members<'a> {
+        Members {
+            parent: *self,
+            pos: self.body + 4,
+            remaining: self.num_members(),
+        }
+    }
+
+    /// Member at `index` of a multi-geometry or geometry collection.
+    pub fn member(&self, index: usize) -> Option<EwkbView<'a>> {
+        self.members().nth(index)
+    }
+
+    /// Body of the geometry, failing unless it is a `expected` with the layout of `C` and the
+    /// byte order of `B`.
+    fn body_as<C: CoordType, B: ByteOrder>(
+        &self,
+        expected: GeometryType,
+    ) -> Result<&'a [u8], Error> {
+        let found = self.geometry_type();
+        if found != expected {
+            return Err(Error::Read(format!(
+                "expected {}, found {}",
+                expected, found
+            )));
+        }
+        if self.dims() != C::DIMS {
+            return Err(Error::Read(format!(
+                "geometry has dimensions {}, expected {}",
+                self.dims(),
+                C::DIMS
+            )));
+        }
+        let byte_order = if B::read_u16(&[0, 1]) == 1 {
+            Endianness::Big
+        } else {
+            Endianness::Little
+        };
+        if self.byte_order() != byte_order {
+            return Err(Error::Read(format!(
+                "geometry has byte order {:?}, expected {:?}",
+                self.byte_order(),
+                byte_order
+            )));
+        }
+        Ok(&self.data[self.body..])
+    }
+
+    pub fn as_point<C: CoordType, B: ByteOrder>(&self) -> Result<&'a PointView<C, B>, Error> {
+        self.body_as::<C, B>(GeometryType::Point)
+            .map(PointView::cast)
+    }
+
+    pub fn as_linestring<C: CoordType, B: ByteOrder>(
+        &self,
+    ) -> Result<&'a LineStringView<C, B>, Error> {
+        self.body_as::<C, B>(GeometryType::LineString)
+            .map(LineStringView::cast)
+    }
+
+    pub fn as_polygon<C: CoordType, B: ByteOrder>(&self) -> Result<&'a PolygonView<C, B>, Error> {
+        self.body_as::<C, B>(GeometryType::Polygon)
+            .map(PolygonView::cast)
+    }
+
+    pub fn as_multipoint<C: CoordType, B: ByteOrder>(
+        &self,
+    ) -> Result<&'a MultiPointView<C, B>, Error> {
+        self.body_as::<C, B>(GeometryType::MultiPoint)
+            .map(MultiPointView::cast)
+    }
+
+    pub fn as_multilinestring<C: CoordType, B: ByteOrder>(
+        &self,
+    ) -> Result<&'a MultiLineStringView<C, B>, Error> {
+        self.body_as::<C, B>(GeometryType::MultiLineString)
+            .map(MultiLineStringView::cast)
+    }
+
+    pub fn as_multipolygon<C: CoordType, B: ByteOrder>(
+        &self,
+    ) -> Result<&'a MultiPolygonView<C, B>, Error> {
+        self.body_as::<C, B>(GeometryType::MultiPolygon)
+            .map(MultiPolygonView::cast)
+    }
+}
+
+/// Iterator over the members of an [`EwkbView`].
+pub struct Members<'a> {
+    parent: EwkbView<'a>,
+    pos: usize,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Members<'a> {
+    type Item = EwkbView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let mut scan = Scan {
+            data: self.parent.data,
+            is_be: self.parent.is_be,
+            budget: ParseBudget::new(&UNLIMITED),
+            coords: |_| (),
+        };
+        let start = self.pos;
+        let (type_id, srid, body, end) = scan.member(start, self.parent.type_id).expect(CHECKED);
+        self.pos = end;
+        Some(EwkbView {
+            data: &self.parent.data[start..end],
+            is_be: self.parent.is_be,
+            type_id,
+            srid,
+            body: body - start,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Members<'a> {}
+
+// --- Typed views
+
+mod private {
+    /// View of a part of an EWKB geometry.
+    pub trait Part {
+        /// Size of every part, if the same for all.
+        const SIZE: Option<usize>;
+
+        /// Part starting at `bytes` and the bytes after it.
+        fn split(bytes: &[u8]) -> (&Self, &[u8]);
+
+        /// Size of the geometry header starting at `bytes`.
+        fn header_size(bytes: &[u8]) -> usize;
+    }
+}
+
+use self::private::Part;
+
+macro_rules! view_type {
+    ($(#[$attr:meta])* $view:ident) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        pub struct $view<C: CoordType, B: ByteOrder>(PhantomData<fn() -> (C, B)>, [u8]);
+
+        impl<C: CoordType, B: ByteOrder> $view<C, B> {
+            fn cast(bytes: &[u8]) -> &Self {
+                // The view is a transparent wrapper of the bytes.
+                unsafe { &*(bytes as *const [u8] as *const Self) }
+            }
+        }
+
+        impl<C: CoordType, B: ByteOrder> fmt::Debug for $view<C, B> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}({} bytes)", stringify!($view), self.1.len())
+            }
+        }
+    };
+}
+
+macro_rules! view_part {
+    ($view:ident, size: $fixed:expr, |$bytes:ident| $size:expr) => {
+        impl<C: CoordType, B: ByteOrder> Part for $view<C, B> {
+            const SIZE: Option<usize> = $fixed;
+
+            fn split($bytes: &[u8]) -> (&Self, &[u8]) {
+                let (part, rest) = $bytes.split_at($size);
+                (Self::cast(part), rest)
+            }
+
+            fn header_size(bytes: &[u8]) -> usize {
+                if B::read_u32(&bytes[1..5]) & 0x20000000 == 0 {
+                    5
+                } else {
+                    9
+                }
+            }
+        }
+    };
+}
+
+view_type!(
+    /// Coordinate of an EWKB geometry with the layout of `C` and the byte order of `B`.
+    PointView
+);
+view_type!(
+    /// Linestring or ring of an EWKB geometry.
+    LineStringView
+);
+view_type!(
+    /// Polygon of an EWKB geometry.
+    PolygonView
+);
+view_type!(
+    /// Multipoint of an EWKB geometry.
+    MultiPointView
+);
+view_type!(
+    /// Multilinestring of an EWKB geometry.
+    MultiLineStringView
+);
+view_type!(
+    /// Multipolygon of an EWKB geometry.
+    MultiPolygonView
+);
+
+/// Size of a ring or linestring body at the start of `bytes`.
+fn line_size<C: CoordType, B: ByteOrder>(bytes: &[u8]) -> usize {
+    4 + B::read_u32(bytes) as usize * mem::size_of::<C>()
+}
+
+/// Size of a polygon body at the start of `bytes`.
+fn polygon_size<C: CoordType, B: ByteOrder>(bytes: &[u8]) -> usize {
+    let mut size = 4;
+    for _ in 0..B::read_u32(bytes) {
+        size += line_size::<C, B>(&bytes[size..]);
+    }
+    size
+}
+
+view_part!(PointView, size: Some(mem::size_of::<C>()), |bytes| mem::size_of::<C>());
+view_part!(LineStringView, size: None, |bytes| line_size::<C, B>(bytes));
+view_part!(PolygonView, size: None, |bytes| polygon_size::<C, B>(bytes));
+
+/// Iterator over the coordinates, rings or members of a typed view.
+pub struct Parts<'a, T: ?Sized> {
+    bytes: &'a [u8],
+    remaining: usize,
+    headers: bool,
+    marker: PhantomData<&'a T>,
+}
+
+impl<'a, T: ?Sized> Parts<'a, T> {
+    /// Parts following a count at the start of `bytes`, with a geometry header before each if
+    /// `headers`.
+    fn new<B: ByteOrder>(bytes: &'a [u8], headers: bool) -> Self {
+        Parts {
+            bytes: &bytes[4..],
+            remaining: B::read_u32(bytes) as usize,
+            headers,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized + Part> Iterator for Parts<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let mut bytes = self.bytes;
+        if self.headers {
+            bytes = &bytes[T::header_size(bytes)..];
+        }
+        let (part, rest) = T::split(bytes);
+        self.bytes = rest;
+        Some(part)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match T::SIZE {
+            // Skip parts of the same size without reading them.
+            Some(size) if !self.headers => {
+                let skipped = n.min(self.remaining);
+                self.bytes = &self.bytes[skipped * size..];
+                self.remaining -= skipped;
+            }
+            _ => {
+                for _ in 0..n {
+                    self.next()?;
+                }
+            }
+        }
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: ?Sized + Part> ExactSizeIterator for Parts<'a, T> {}
+
+impl<C: CoordType, B: ByteOrder> PointView<C, B> {
+    fn value(&self, index: usize) -> f64 {
+        B::read_f64(&self.1[8 * index..8 * index + 8])
+    }
+
+    /// The coordinate as `C`.
+    pub fn coord(&self) -> C {
+        use crate::types::Point;
+        C::new(self.x(), self.y(), self.opt_z(), self.opt_m())
+    }
+}
+
+impl<C: CoordType, B: ByteOrder> postgis::Point for PointView<C, B> {
+    fn x(&self) -> f64 {
+        self.value(0)
+    }
+    fn y(&self) -> f64 {
+        self.value(1)
+    }
+    fn opt_z(&self) -> Option<f64> {
+        match C::DIMS {
+            Dims::Xyz | Dims::Xyzm => Some(self.value(2)),
+            _ => None,
+        }
+    }
+    fn opt_m(&self) -> Option<f64> {
+        match C::DIMS {
+            Dims::Xym => Some(self.value(2)),
+            Dims::Xyzm => Some(self.value(3)),
+            _ => None,
+        }
+    }
+    fn crds(&self) -> Vec<f64> {
+        (0..self.1.len() / 8).map(|i| self.value(i)).collect()
+    }
+}
+
+macro_rules! view_container {
+    ($geotypetrait:ident for $view:ident contains $item:ident named $itemname:ident, $getter:ident, headers: $headers:expr) => {
+        impl<C: CoordType, B: ByteOrder> $view<C, B> {
+            pub fn len(&self) -> usize {
+                B::read_u32(&self.1) as usize
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            pub fn $getter(&self, index: usize) -> Option<&$item<C, B>> {
+                Parts::new::<B>(&self.1, $headers).nth(index)
+            }
+        }
+
+        impl<'a, C: CoordType, B: 'a + ByteOrder> postgis::$geotypetrait<'a> for $view<C, B> {
+            type ItemType = $item<C, B>;
+            type Iter = Parts<'a, $item<C, B>>;
+            fn $itemname(&'a self) -> Self::Iter {
+                Parts::new::<B>(&self.1, $headers)
+            }
+        }
+    };
+}
+
+view_container!(LineString for LineStringView contains PointView named points, point, headers: false);
+view_container!(Polygon for PolygonView contains LineStringView named rings, ring, headers: false);
+view_container!(MultiPoint for MultiPointView contains PointView named points, point, headers: true);
+view_container!(MultiLineString for MultiLineStringView contains LineStringView named lines, line, headers: true);
+view_container!(MultiPolygon for MultiPolygonView contains PolygonView named polygons, polygon, headers: true);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::decode_hex;
+    use crate::ewkb::{self, EwkbRead};
+    use crate::flat::{Coord, CoordZ};
+    use crate::types::{LineString as _, MultiPolygon as _, Point as _, Polygon as _};
+    use crate::visit::VertexCount;
+
+    const EWKB: [&str; 6] = [
+        // SRID=4326;LINESTRING Z(10 -20 100,0 -0.5 101)
+        "01020000A0E610000002000000000000000000244000000000000034C000000000000059400000000000000000000000000000E0BF0000000000405940",
+        // SRID=4326;MULTIPOINT Z(10 -20 100,0 -0.5 101)
+        "01040000A0E6100000020000000101000080000000000000244000000000000034C0000000000000594001010000800000000000000000000000000000E0BF0000000000405940",
+        // SRID=4326;MULTILINESTRING((10 -20,0 -0.5),(0 0,2 0))
+        "0105000020E610000002000000010200000002000000000000000000244000000000000034C00000000000000000000000000000E0BF0102000000020000000000000000000000000000000000000000000000000000400000000000000000",
+        // SRID=4326;MULTIPOLYGON(((0 0,2 0,2 2,0 2,0 0)),((10 10,-2 10,-2 -2,10 -2,10 10)))
+        "0106000020E610000002000000010300000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000010300000001000000050000000000000000002440000000000000244000000000000000C0000000000000244000000000000000C000000000000000C0000000000000244000000000000000C000000000000024400000000000002440",
+        // GEOMETRYCOLLECTION(POINT(10 10),POINT(30 30),LINESTRING(15 15,20 20))
+        "01070000000300000001010000000000000000002440000000000000244001010000000000000000003E400000000000003E400102000000020000000000000000002E400000000000002E4000000000000034400000000000003440",
+        // POINT(10 -20), big-endian
+        "00000000014024000000000000C034000000000000",
+    ];
+
+    #[test]
+    fn test_view() {
+        let limits = ParseLimits::default();
+        for hex in EWKB.iter() {
+            let data = decode_hex(hex.as_bytes()).unwrap();
+            let view = EwkbView::new(&data).unwrap();
+            assert_eq!(view.as_bytes(), &data[..]);
+
+            let mut count = VertexCount::default();
+            ewkb::visit_ewkb(&mut data.as_slice(), &mut count, &limits).unwrap();
+            assert_eq!(view.num_vertices(), count.0);
+            let mut bbox = Bbox::default();
+            ewkb::visit_ewkb(&mut data.as_slice(), &mut bbox, &limits).unwrap();
+            assert_eq!(view.bbox(), bbox);
+        }
+
+        let data = decode_hex(EWKB[0].as_bytes()).unwrap();
+        let view = EwkbView::new(&data).unwrap();
+        assert!(view.geometry_type() == GeometryType::LineString);
+        assert_eq!(view.dims(), Dims::Xyz);
+        assert_eq!(view.srid(), Some(4326));
+        assert_eq!(view.first_coord(), Some(CoordZ([10.0, -20.0, 100.0])));
+        assert_eq!(view.num_members(), 0);
+
+        let data = decode_hex(EWKB[5].as_bytes()).unwrap();
+        let view = EwkbView::new(&data).unwrap();
+        assert_eq!(view.byte_order(), Endianness::Big);
+        let point = view.as_point::<Coord, BigEndian>().unwrap();
+        assert_eq!(point.coord(), Coord([10.0, -20.0]));
+
+        // LINESTRING EMPTY
+        let data = decode_hex(b"010200000000000000").unwrap();
+        let view = EwkbView::new(&data).unwrap();
+        assert_eq!(view.first_coord::<Coord>(), None);
+        assert!(view.bbox().is_empty());
+    }
+
+    #[test]
+    fn test_members() {
+        let data = decode_hex(EWKB[4].as_bytes()).unwrap();
+        let view = EwkbView::new(&data).unwrap();
+        assert_eq!(view.num_members(), 3);
+        assert_eq!(view.first_coord(), Some(Coord([10.0, 10.0])));
+        let types: Vec<String> = view
+            .members()
+            .map(|member| member.geometry_type().to_string())
+            .collect();
+        assert_eq!(types, ["Point", "Point", "LineString"]);
+
+        let line = view.member(2).unwrap();
+        assert_eq!(line.srid(), None);
+        assert_eq!(line.num_vertices(), 2);
+        let expected = ewkb::LineString::read_ewkb(&mut line.as_bytes()).unwrap();
+        let line = line.as_linestring::<Coord, LittleEndian>().unwrap();
+        assert_eq!(line.len(), 2);
+        assert_eq!(line.point(1).unwrap().coord(), Coord([20.0, 20.0]));
+        assert!(line.point(2).is_none());
+        let crds: Vec<Vec<f64>> = line.points().map(|p| p.crds()).collect();
+        let expected: Vec<Vec<f64>> = expected.points().map(|p| p.crds()).collect();
+        assert_eq!(crds, expected);
+        assert!(view.member(3).is_none());
+    }
+
+    #[test]
+    fn test_typed() {
+        let data = decode_hex(EWKB[3].as_bytes()).unwrap();
+        let view = EwkbView::new(&data).unwrap();
+        let polygons = view.as_multipolygon::<Coord, LittleEndian>().unwrap();
+        assert_eq!(polygons.len(), 2);
+        let ring = polygons.polygon(1).unwrap().ring(0).unwrap();
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring.point(3).unwrap().coord(), Coord([10.0, -2.0]));
+
+        let expected = ewkb::MultiPolygon::read_ewkb(&mut data.as_slice()).unwrap();
+        let viewed: Vec<Vec<f64>> = polygons
+            .polygons()
+            .flat_map(|poly| poly.rings())
+            .flat_map(|ring| ring.points())
+            .map(|p| p.crds())
+            .collect();
+        let read: Vec<Vec<f64>> = expected
+            .polygons()
+            .flat_map(|poly| poly.rings())
+            .flat_map(|ring| ring.points())
+            .map(|p| p.crds())
+            .collect();
+        assert_eq!(viewed, read);
+        assert_eq!(polygons.polygons().len(), 2);
+
+        let err = view.as_polygon::<Coord, LittleEndian>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Read(\"expected Polygon, found MultiPolygon\")"
+        );
+        let err = view.as_multipolygon::<CoordZ, LittleEndian>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Read(\"geometry has dimensions XY, expected XYZ\")"
+        );
+        let err = view.as_multipolygon::<Coord, BigEndian>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Read(\"geometry has byte order Little, expected Big\")"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let data = decode_hex(EWKB[3].as_bytes()).unwrap();
+        for len in 0..data.len() {
+            assert!(EwkbView::new(&data[..len]).is_err());
+        }
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(
+            EwkbView::new(&trailing).unwrap_err().to_string(),
+            "Read(\"1 trailing bytes after geometry\")"
+        );
+
+        let limits = ParseLimits {
+            max_vertices: 9,
+            ..Default::default()
+        };
+        assert!(EwkbView::new_limited(&data, &limits).is_err());
+        let limits = ParseLimits {
+            max_bytes: data.len() - 1,
+            ..Default::default()
+        };
+        assert!(EwkbView::new_limited(&data, &limits).is_err());
+
+        // MULTIPOINT(10 -20) with a big-endian member
+        let data =
+            decode_hex(b"01040000000100000000000000014024000000000000C034000000000000").unwrap();
+        assert_eq!(
+            EwkbView::new(&data).unwrap_err().to_string(),
+            "Read(\"geometry has members with different byte orders\")"
+        );
+
+        let data = decode_hex(b"0102000000FFFFFFFF").unwrap();
+        assert!(EwkbView::new(&data).is_err());
+    }
+}
@@ -0,0 +1,695 @@
+// Visitors of geometries being read
+//
+// `ewkb::visit_ewkb` and `twkb::visit_twkb` pass the parts of a geometry to a `Visitor` as
+// they are read, so that it can be converted, measured or re-encoded in a single pass without
+// building geometry structs and per-vertex vectors first.
+extern crate byteorder;
+extern crate ryu;
+
+use self::byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+use std::convert::TryFrom;
+use std::f64;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::ewkb::Endianness;
+use crate::geojson::Dims;
+
+/// Receiver of the parts of a geometry while it is read.
+///
+/// Events nest like the geometry. A point is `begin_point`, `coord` and `end_point`, and a
+/// polygon is `begin_polygon`, then `begin_ring`, a `coord` per vertex and `end_ring` for each
+/// ring, then `end_polygon`. Members of multipoints are passed as coordinates only, members of
+/// multilinestrings and multipolygons as linestrings and polygons, and members of collections
+/// as whole geometries. Sizes are those given by the input, and empty points have NaN
+/// coordinates. The SRID, if any, comes before the geometry.
+///
+/// All methods do nothing by default, and an error stops reading.
+#[allow(unused_variables)]
+pub trait Visitor {
+    fn srid(&mut self, srid: Option<i32>) -> Result<(), Error> {
+        Ok(())
+    }
+    fn coord(&mut self, x: f64, y: f64, z: Option<f64>, m: Option<f64>) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_point(&mut self, dims: Dims) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_point(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_linestring(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_linestring(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_polygon(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_ring(&mut self, size: usize) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_ring(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_polygon(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_multipoint(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_multipoint(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_multilinestring(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_multilinestring(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_multipolygon(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_multipolygon(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn begin_collection(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        Ok(())
+    }
+    fn end_collection(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Visitor counting vertices.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VertexCount(pub usize);
+
+impl Visitor for VertexCount {
+    fn coord(&mut self, _x: f64, _y: f64, _z: Option<f64>, _m: Option<f64>) -> Result<(), Error> {
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+/// Visitor accumulating the bounding box of x and y, skipping NaN values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bbox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Bbox {
+    /// Whether no coordinates have been added.
+    pub fn is_empty(&self) -> bool {
+        self.min_x > self.max_x || self.min_y > self.max_y
+    }
+}
+
+impl Default for Bbox {
+    fn default() -> Self {
+        Bbox {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Visitor for Bbox {
+    fn coord(&mut self, x: f64, y: f64, _z: Option<f64>, _m: Option<f64>) -> Result<(), Error> {
+        // min and max ignore NaN
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Point,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+    Collection,
+}
+
+/// How [`GeoJsonWriter`] writes the CRS of a geometry.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum CrsFormat {
+    /// Named CRS object with the EPSG code, `null` without an SRID.
+    #[default]
+    Name,
+    /// SRID as an integer like in the Python dictionaries, `null` without an SRID.
+    Srid,
+    /// No `crs` member.
+    Omit,
+}
+
+impl FromStr for CrsFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<CrsFormat, Error> {
+        match s {
+            "name" => Ok(CrsFormat::Name),
+            "srid" => Ok(CrsFormat::Srid),
+            "none" => Ok(CrsFormat::Omit),
+            _ => Err(Error::Other(format!(
+                "invalid crs format '{}' - expected one of name, srid, none",
+                s
+            ))),
+        }
+    }
+}
+
+/// Visitor writing GeoJSON into one buffer.
+///
+/// By default the output matches
+/// [`GeoJSONEncode::as_str`](crate::geojson::GeoJSONEncode::as_str) of the same geometry read
+/// into [`GeoJSONGeometry`](crate::geojson::GeoJSONGeometry). Geometry collections are written
+/// with their members in `geometries`. Numbers are written in their shortest form that reads
+/// back to the same value, and non-finite numbers as `null`.
+#[derive(Default)]
+pub struct GeoJsonWriter {
+    out: String,
+    precision: Option<u8>,
+    crs: CrsFormat,
+    srid: Option<i32>,
+    /// Kinds of the GeoJSON objects being written.
+    objects: Vec<Kind>,
+    /// Whether a comma must precede the next value.
+    comma: bool,
+    numbers: ryu::Buffer,
+}
+
+impl GeoJsonWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writer reserving `capacity` bytes for the output.
+    pub fn with_capacity(capacity: usize) -> Self {
+        GeoJsonWriter {
+            out: String::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Round coordinates to `decimals` decimals.
+    pub fn precision(mut self, decimals: u8) -> Self {
+        self.precision = Some(decimals);
+        self
+    }
+
+    /// Write the CRS as `crs` says.
+    pub fn crs(mut self, crs: CrsFormat) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    /// Return the GeoJSON written.
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn separate(&mut self) {
+        if self.comma {
+            self.out.push(',');
+        }
+        self.comma = true;
+    }
+
+    fn open_array(&mut self) {
+        self.separate();
+        self.out.push('[');
+        self.comma = false;
+    }
+
+    fn close_array(&mut self) {
+        self.out.push(']');
+        self.comma = true;
+    }
+
+    fn open_object(&mut self, kind: Kind) {
+        let (name, key) = match kind {
+            Kind::Point => ("Point", "coordinates"),
+            Kind::LineString => ("LineString", "coordinates"),
+            Kind::Polygon => ("Polygon", "coordinates"),
+            Kind::MultiPoint => ("MultiPoint", "coordinates"),
+            Kind::MultiLineString => ("MultiLineString", "coordinates"),
+            Kind::MultiPolygon => ("MultiPolygon", "coordinates"),
+            Kind::Collection => ("GeometryCollection", "geometries"),
+        };
+        self.separate();
+        self.out.push_str("{\"type\":\"");
+        self.out.push_str(name);
+        self.out.push('"');
+        let srid = self.srid.take();
+        match (self.crs, srid) {
+            (CrsFormat::Omit, _) => {}
+            (_, None) => self.out.push_str(",\"crs\":null"),
+            (CrsFormat::Name, Some(srid)) => {
+                let _ = write!(
+                    self.out,
+                    ",\"crs\":{{\"type\":\"name\",\"properties\":{{\"name\":\"EPSG:{}\"}}}}",
+                    srid
+                );
+            }
+            (CrsFormat::Srid, Some(srid)) => {
+                let _ = write!(self.out, ",\"crs\":{}", srid);
+            }
+        }
+        self.out.push_str(",\"");
+        self.out.push_str(key);
+        self.out.push_str("\":");
+        self.objects.push(kind);
+        self.comma = false;
+    }
+
+    fn close_object(&mut self) {
+        self.objects.pop().expect("GeoJSON object to close");
+        self.out.push('}');
+        self.comma = true;
+    }
+
+    /// Whether the innermost open object is of `kind`.
+    fn inside(&self, kind: Kind) -> bool {
+        self.objects.last() == Some(&kind)
+    }
+
+    fn write_number(&mut self, value: f64) {
+        let value = match self.precision {
+            Some(decimals) => {
+                let scale = 10f64.powi(decimals.into());
+                (value * scale).round() / scale
+            }
+            None => value,
+        };
+        if value.is_finite() {
+            self.out.push_str(self.numbers.format_finite(value));
+        } else {
+            self.out.push_str("null");
+        }
+    }
+}
+
+impl Visitor for GeoJsonWriter {
+    fn srid(&mut self, srid: Option<i32>) -> Result<(), Error> {
+        self.srid = srid;
+        Ok(())
+    }
+
+    fn coord(&mut self, x: f64, y: f64, z: Option<f64>, m: Option<f64>) -> Result<(), Error> {
+        self.open_array();
+        self.write_number(x);
+        self.out.push(',');
+        self.write_number(y);
+        for value in z.into_iter().chain(m) {
+            self.out.push(',');
+            self.write_number(value);
+        }
+        self.close_array();
+        Ok(())
+    }
+
+    fn begin_point(&mut self, _dims: Dims) -> Result<(), Error> {
+        self.open_object(Kind::Point);
+        Ok(())
+    }
+
+    fn end_point(&mut self) -> Result<(), Error> {
+        self.close_object();
+        Ok(())
+    }
+
+    fn begin_linestring(&mut self, _dims: Dims, _size: usize) -> Result<(), Error> {
+        if !self.inside(Kind::MultiLineString) {
+            self.open_object(Kind::LineString);
+        }
+        self.open_array();
+        Ok(())
+    }
+
+    fn end_linestring(&mut self) -> Result<(), Error> {
+        self.close_array();
+        if self.inside(Kind::LineString) {
+            self.close_object();
+        }
+        Ok(())
+    }
+
+    fn begin_polygon(&mut self, _dims: Dims, _size: usize) -> Result<(), Error> {
+        if !self.inside(Kind::MultiPolygon) {
+            self.open_object(Kind::Polygon);
+        }
+        self.open_array();
+        Ok(())
+    }
+
+    fn begin_ring(&mut self, _size: usize) -> Result<(), Error> {
+        self.open_array();
+        Ok(())
+    }
+
+    fn end_ring(&mut self) -> Result<(), Error> {
+        self.close_array();
+        Ok(())
+    }
+
+    fn end_polygon(&mut self) -> Result<(), Error> {
+        self.close_array();
+        if self.inside(Kind::Polygon) {
+            self.close_object();
+        }
+        Ok(())
+    }
+
+    fn begin_multipoint(&mut self, _dims: Dims, _size: usize) -> Result<(), Error> {
+        self.open_object(Kind::MultiPoint);
+        self.open_array();
+        Ok(())
+    }
+
+    fn end_multipoint(&mut self) -> Result<(), Error> {
+        self.close_array();
+        self.close_object();
+        Ok(())
+    }
+
+    fn begin_multilinestring(&mut self, _dims: Dims, _size: usize) -> Result<(), Error> {
+        self.open_object(Kind::MultiLineString);
+        self.open_array();
+        Ok(())
+    }
+
+    fn end_multilinestring(&mut self) -> Result<(), Error> {
+        self.close_array();
+        self.close_object();
+        Ok(())
+    }
+
+    fn begin_multipolygon(&mut self, _dims: Dims, _size: usize) -> Result<(), Error> {
+        self.open_object(Kind::MultiPolygon);
+        self.open_array();
+        Ok(())
+    }
+
+    fn end_multipolygon(&mut self) -> Result<(), Error> {
+        self.close_array();
+        self.close_object();
+        Ok(())
+    }
+
+    fn begin_collection(&mut self, _dims: Dims, _size: usize) -> Result<(), Error> {
+        self.open_object(Kind::Collection);
+        self.open_array();
+        Ok(())
+    }
+
+    fn end_collection(&mut self) -> Result<(), Error> {
+        self.close_array();
+        self.close_object();
+        Ok(())
+    }
+}
+
+/// Visitor writing EWKB into `out`.
+///
+/// The SRID is written into the header of the outermost geometry, and coordinates with the
+/// layout given by the geometry, missing Z and M values as 0.
+pub struct EwkbWriter<W: Write> {
+    out: W,
+    byte_order: Endianness,
+    srid: Option<i32>,
+    dims: Dims,
+    in_multipoint: bool,
+}
+
+impl<W: Write> EwkbWriter<W> {
+    pub fn new(out: W, byte_order: Endianness) -> Self {
+        EwkbWriter {
+            out,
+            byte_order,
+            srid: None,
+            dims: Dims::Xy,
+            in_multipoint: false,
+        }
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn write_header(&mut self, base_type: u32, dims: Dims) -> Result<(), Error> {
+        self.dims = dims;
+        let srid = self.srid.take();
+        let mut type_id = base_type;
+        if dims.has_z() {
+            type_id |= 0x80000000;
+        }
+        if dims.has_m() {
+            type_id |= 0x40000000;
+        }
+        if srid.is_some() {
+            type_id |= 0x20000000;
+        }
+        self.out.write_u8(match self.byte_order {
+            Endianness::Little => 0x01,
+            Endianness::Big => 0x00,
+        })?;
+        self.write_u32(type_id)?;
+        if let Some(srid) = srid {
+            self.write_u32(srid as u32)?;
+        }
+        Ok(())
+    }
+
+    fn write_u32(&mut self, v: u32) -> Result<(), Error> {
+        match self.byte_order {
+            Endianness::Little => self.out.write_u32::<LittleEndian>(v)?,
+            Endianness::Big => self.out.write_u32::<BigEndian>(v)?,
+        }
+        Ok(())
+    }
+
+    fn write_size(&mut self, size: usize) -> Result<(), Error> {
+        let size = u32::try_from(size)
+            .map_err(|_| Error::Write(format!("too many parts for EWKB {}", size)))?;
+        self.write_u32(size)
+    }
+
+    fn write_f64(&mut self, v: f64) -> Result<(), Error> {
+        match self.byte_order {
+            Endianness::Little => self.out.write_f64::<LittleEndian>(v)?,
+            Endianness::Big => self.out.write_f64::<BigEndian>(v)?,
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self, base_type: u32, dims: Dims, size: usize) -> Result<(), Error> {
+        self.write_header(base_type, dims)?;
+        self.write_size(size)
+    }
+}
+
+impl<W: Write> Visitor for EwkbWriter<W> {
+    fn srid(&mut self, srid: Option<i32>) -> Result<(), Error> {
+        self.srid = srid;
+        Ok(())
+    }
+
+    fn coord(&mut self, x: f64, y: f64, z: Option<f64>, m: Option<f64>) -> Result<(), Error> {
+        let dims = self.dims;
+        if self.in_multipoint {
+            self.write_header(0x01, dims)?;
+        }
+        self.write_f64(x)?;
+        self.write_f64(y)?;
+        if dims.has_z() {
+            self.write_f64(z.unwrap_or(0.0))?;
+        }
+        if dims.has_m() {
+            self.write_f64(m.unwrap_or(0.0))?;
+        }
+        Ok(())
+    }
+
+    fn begin_point(&mut self, dims: Dims) -> Result<(), Error> {
+        self.write_header(0x01, dims)
+    }
+
+    fn begin_linestring(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.begin(0x02, dims, size)
+    }
+
+    fn begin_polygon(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.begin(0x03, dims, size)
+    }
+
+    fn begin_ring(&mut self, size: usize) -> Result<(), Error> {
+        self.write_size(size)
+    }
+
+    fn begin_multipoint(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.begin(0x04, dims, size)?;
+        self.in_multipoint = true;
+        Ok(())
+    }
+
+    fn end_multipoint(&mut self) -> Result<(), Error> {
+        self.in_multipoint = false;
+        Ok(())
+    }
+
+    fn begin_multilinestring(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.begin(0x05, dims, size)
+    }
+
+    fn begin_multipolygon(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.begin(0x06, dims, size)
+    }
+
+    fn begin_collection(&mut self, dims: Dims, size: usize) -> Result<(), Error> {
+        self.begin(0x07, dims, size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bbox, CrsFormat, EwkbWriter, GeoJsonWriter, VertexCount};
+    use crate::encoding::decode_hex;
+    use crate::ewkb::{self, Endianness};
+    use crate::geojson::{self, BinaryFormat, GeoJSONEncode};
+    use crate::limits::ParseLimits;
+    use crate::twkb::{self, TwkbGeom};
+
+    const EWKB: [&str; 4] = [
+        // SRID=4326;LINESTRING(10 -20,0 -0.5)
+        "0102000020E610000002000000000000000000244000000000000034C00000000000000000000000000000E0BF",
+        // SRID=4326;MULTIPOINT Z(0 1 2,3 4 -5)
+        "01040000A0E61000000200000001010000800000000000000000000000000000F03F000000000000004001010000800000000000000840000000000000104000000000000014C0",
+        // SRID=4326;POLYGON((0 0,2 0,2 2,0 2,0 0))
+        "0103000020E610000001000000050000000000000000000000000000000000000000000000000000400000000000000000000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000",
+        // POINT EMPTY
+        "0101000000000000000000F87F000000000000F87F",
+    ];
+
+    const TWKB: [&str; 3] = [
+        // POINT(10 -20)
+        "01001427",
+        // POLYGON((0 0,2 0,2 2,0 2,0 0))
+        "0300010500000400000403000003",
+        // MULTILINESTRING((0 0,1 1),(2 2,3 3))
+        "05000202000002020202020202",
+    ];
+
+    #[test]
+    fn test_geojson_writer() {
+        let limits = ParseLimits::default();
+        for hex in EWKB.iter() {
+            let ewkb = decode_hex(hex.as_bytes()).unwrap();
+            let mut writer = GeoJsonWriter::new();
+            ewkb::visit_ewkb(&mut ewkb.as_slice(), &mut writer, &limits).unwrap();
+            let geom = geojson::read_binary(&ewkb, BinaryFormat::Ewkb, &limits).unwrap();
+            assert_eq!(writer.finish(), geom.as_str());
+        }
+        for hex in TWKB.iter() {
+            let twkb = decode_hex(hex.as_bytes()).unwrap();
+            let mut writer = GeoJsonWriter::new();
+            twkb::visit_twkb(&mut twkb.as_slice(), &mut writer, &limits).unwrap();
+            let geom = geojson::read_binary(&twkb, BinaryFormat::Twkb, &limits).unwrap();
+            assert_eq!(writer.finish(), geom.as_str());
+        }
+
+        // SRID=4326;GEOMETRYCOLLECTION(POINT(10 -20),LINESTRING EMPTY)
+        let ewkb = decode_hex(
+            b"0107000020E6100000020000000101000000000000000000244000000000000034C0010200000000000000",
+        )
+        .unwrap();
+        let mut writer = GeoJsonWriter::new();
+        ewkb::visit_ewkb(&mut ewkb.as_slice(), &mut writer, &limits).unwrap();
+        assert_eq!(
+            writer.finish(),
+            r#"{"type":"GeometryCollection","crs":{"type":"name","properties":{"name":"EPSG:4326"}},"geometries":[{"type":"Point","crs":null,"coordinates":[10.0,-20.0]},{"type":"LineString","crs":null,"coordinates":[]}]}"#
+        );
+    }
+
+    #[test]
+    fn test_geojson_options() {
+        let limits = ParseLimits::default();
+        let ewkb = decode_hex(EWKB[0].as_bytes()).unwrap();
+        let mut writer = GeoJsonWriter::new().crs("srid".parse().unwrap());
+        ewkb::visit_ewkb(&mut ewkb.as_slice(), &mut writer, &limits).unwrap();
+        assert_eq!(
+            writer.finish(),
+            r#"{"type":"LineString","crs":4326,"coordinates":[[10.0,-20.0],[0.0,-0.5]]}"#
+        );
+
+        let mut writer = GeoJsonWriter::with_capacity(64)
+            .precision(0)
+            .crs(CrsFormat::Omit);
+        ewkb::visit_ewkb(&mut ewkb.as_slice(), &mut writer, &limits).unwrap();
+        assert_eq!(
+            writer.finish(),
+            r#"{"type":"LineString","coordinates":[[10.0,-20.0],[0.0,-1.0]]}"#
+        );
+        assert!("wkt".parse::<CrsFormat>().is_err());
+    }
+
+    #[test]
+    fn test_measure() {
+        let limits = ParseLimits::default();
+        let twkb = decode_hex(TWKB[1].as_bytes()).unwrap();
+        let mut count = VertexCount::default();
+        let mut bbox = Bbox::default();
+        assert!(bbox.is_empty());
+        twkb::visit_twkb(&mut twkb.as_slice(), &mut count, &limits).unwrap();
+        twkb::visit_twkb(&mut twkb.as_slice(), &mut bbox, &limits).unwrap();
+        assert_eq!(count, VertexCount(5));
+        assert_eq!(
+            (bbox.min_x, bbox.min_y, bbox.max_x, bbox.max_y),
+            (0., 0., 2., 2.)
+        );
+
+        let mut bbox = Bbox::default();
+        let ewkb = decode_hex(EWKB[3].as_bytes()).unwrap();
+        ewkb::visit_ewkb(&mut ewkb.as_slice(), &mut bbox, &limits).unwrap();
+        assert!(bbox.is_empty());
+
+        let limits = ParseLimits {
+            max_vertices: 4,
+            ..ParseLimits::default()
+        };
+        assert!(twkb::visit_twkb(&mut twkb.as_slice(), &mut count, &limits).is_err());
+    }
+
+    #[test]
+    fn test_ewkb_writer() {
+        let limits = ParseLimits::default();
+        for hex in EWKB.iter() {
+            let ewkb = decode_hex(hex.as_bytes()).unwrap();
+            let mut writer = EwkbWriter::new(Vec::new(), Endianness::Little);
+            ewkb::visit_ewkb(&mut ewkb.as_slice(), &mut writer, &limits).unwrap();
+            assert_eq!(writer.into_inner(), ewkb);
+        }
+
+        let twkb = decode_hex(TWKB[2].as_bytes()).unwrap();
+        let mut writer = EwkbWriter::new(Vec::new(), Endianness::Big);
+        twkb::visit_twkb(&mut twkb.as_slice(), &mut writer, &limits).unwrap();
+        let geom = twkb::Geometry::read_twkb(&mut twkb.as_slice()).unwrap();
+        let expected = geojson::GeoJSONGeometry::from(&geom).to_ewkb_with(Endianness::Big);
+        assert_eq!(writer.into_inner(), expected.unwrap());
+    }
+}
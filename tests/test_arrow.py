@@ -0,0 +1,49 @@
+"""Tests for python side GeoArrow conversions"""
+
+import pytest
+import wkbparse
+
+if not hasattr(wkbparse, "ewkb_to_geoarrow"):
+    pytest.skip("built without the arrow feature", allow_module_level=True)
+
+TWKB_POINT = bytes.fromhex("610805d00fa01f50")
+EWKB_POLYGON = bytes.fromhex(
+    "0103000020E610000001000000050000000000000000000000000000000000000000000000000000400000000000000000"
+    "000000000000004000000000000000400000000000000000000000000000004000000000000000000000000000000000"
+)
+
+
+def test_arrow_capsules():
+    """Test exporting and importing arrays through the Arrow PyCapsule interface"""
+    points = wkbparse.twkb_to_geoarrow([TWKB_POINT, None, TWKB_POINT])
+    assert len(points) == 3
+    schema, array = points.__arrow_c_array__()
+    assert "arrow_schema" in repr(schema)
+    assert "arrow_array" in repr(array)
+
+    ewkb = wkbparse.geoarrow_to_ewkb(points, byte_order="big")
+    assert len(ewkb) == 3
+    assert len(wkbparse.ewkb_to_geoarrow(ewkb)) == 3
+    with pytest.raises(ValueError, match="missing GeoArrow extension name"):
+        wkbparse.geoarrow_to_ewkb(ewkb)
+    with pytest.raises(ValueError, match="expected a binary array"):
+        wkbparse.ewkb_to_geoarrow(points)
+    with pytest.raises(ValueError, match="cannot store point and polygon geometries"):
+        wkbparse.ewkb_to_geoarrow([wkbparse.twkb_to_ewkb(TWKB_POINT), EWKB_POLYGON])
+
+
+def test_pyarrow():
+    """Test conversions of pyarrow arrays"""
+    pa = pytest.importorskip("pyarrow")
+    binary = pa.array([EWKB_POLYGON, None, EWKB_POLYGON], type=pa.binary())
+    polygons = wkbparse.ewkb_to_geoarrow(binary)
+    field = pa.field(polygons)
+    assert field.metadata[b"ARROW:extension:name"] == b"geoarrow.polygon"
+    assert field.metadata[b"ARROW:extension:metadata"] == b'{"crs":"EPSG:4326","crs_type":"authority_code"}'
+    rings = pa.array(polygons).to_pylist()
+    assert rings[0] == [[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0], [0.0, 0.0]]]
+    assert rings[1] is None
+
+    ewkb = pa.array(wkbparse.geoarrow_to_ewkb(polygons))
+    assert ewkb.to_pylist() == binary.to_pylist()
+    assert pa.array(wkbparse.twkb_to_geoarrow(pa.array([TWKB_POINT]))).to_pylist() == [[1.0, 2.0, 4.0]]
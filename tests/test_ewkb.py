@@ -1,8 +1,11 @@
 """Tests for python side EWKB-parsing"""
 
+import base64
 import json
+import struct
 from pathlib import Path
 from time import time
+import pytest
 import wkbparse
 
 
@@ -22,6 +25,20 @@ def test_parse_ewkb_point():
     assert hex_string.lower() == hex_result.lower()
 
 
+def test_parse_ewkb_point_m():
+    """Test that measured points keep their M value instead of becoming Z"""
+    hex_string = "0101000040000000000000F03F00000000000000400000000000001040"
+    result = wkbparse.ewkb_to_geojson(bytes.fromhex(hex_string))
+    assert result["coordinates"] == [1.0, 2.0, 4.0]
+    assert result["dims"] == "XYM"
+    assert wkbparse.geojson_to_ewkb(result).hex().upper() == hex_string
+
+    del result["dims"]
+    assert wkbparse.ewkb_to_geojson(wkbparse.geojson_to_ewkb(result))["dims"] == "XYZ"
+    with pytest.raises(ValueError, match="invalid dims"):
+        wkbparse.geojson_to_ewkb({**result, "dims": "XZ"})
+
+
 def test_parse_ewkb_line():
     """Test linestring parsing"""
     hex_string = "010200008002000000000000000000f03f0000000000000040000000000000144000000000000024400000000000002e400000000000003640"
@@ -155,5 +172,178 @@ def test_parse_large_multipolygon():
     assert len(crds) > 300000
 
 
+def test_parse_invalid_ewkb():
+    """Test that malformed input raises instead of crashing"""
+    nested = bytes.fromhex("010700000001000000") * 100000
+    for data in [b"", bytes.fromhex("0102000000ffffffff")]:
+        with pytest.raises(ValueError):
+            wkbparse.ewkb_to_geojson(data)
+    with pytest.raises(ValueError, match="max_depth"):
+        wkbparse.reproject_ewkb(nested, 3857, 4326)
+    with pytest.raises(ValueError, match="invalid coordinate"):
+        wkbparse.geojson_to_ewkb({"type": "LineString", "coordinates": [[1, 2, 3], [4, 5]]})
+
+
+
+def test_parse_ewkb_many():
+    """Test parsing geometries stored back to back and rejecting trailing bytes"""
+    point = bytes.fromhex("0101000020E6100000000000000000244000000000000034C0")
+    line = bytes.fromhex(
+        "010200000002000000000000000000244000000000000034C00000000000000000000000000000E0BF"
+    )
+    result = wkbparse.ewkb_to_geojson_many(point + line)
+    assert [(geom["type"], geom["crs"]) for geom in result] == [("Point", 4326), ("LineString", None)]
+    assert result[1]["coordinates"] == [[10.0, -20.0], [0.0, -0.5]]
+    with pytest.raises(ValueError):
+        wkbparse.ewkb_to_geojson_many(point + line[:-1])
+
+    assert wkbparse.ewkb_to_geojson(point + line)["type"] == "Point"
+    with pytest.raises(ValueError, match="41 trailing bytes"):
+        wkbparse.ewkb_to_geojson(point + line, strict=True)
+
+
+def test_parse_many_large_multilinestrings():
+    """Test parsing large multi-part geometries, read in parallel with the rayon feature"""
+    lines = [[[part + i * 1e-5, i * 1e-5] for i in range(8200)] for part in range(8)]
+    data = struct.pack("<BIiI", 1, 0x20000005, 3067, len(lines))
+    for line in lines:
+        data += struct.pack("<BII", 1, 2, len(line))
+        data += b"".join(struct.pack("<dd", *crd) for crd in line)
+    expected = {"type": "MultiLineString", "crs": 3067, "coordinates": lines, "dims": "XY"}
+    assert wkbparse.ewkb_to_geojson(data) == expected
+    assert wkbparse.ewkb_to_geojson(data + data[:5]) == expected
+    with pytest.raises(ValueError, match="5 trailing bytes"):
+        wkbparse.ewkb_to_geojson(data + data[:5], strict=True)
+    assert wkbparse.ewkb_to_geojson_many(data * 2) == [expected, expected]
+    assert wkbparse.ewkb_to_geojson_batch(data * 2, [0, len(data), 2 * len(data)]) == [
+        expected,
+        expected,
+    ]
+    with pytest.raises(ValueError):
+        wkbparse.ewkb_to_geojson_many(data + data[:-1])
+
+
+def test_write_ewkb_byte_order():
+    """Test writing little and big-endian EWKB"""
+    geom = {"type": "LineString", "crs": 4326, "coordinates": [[10.0, -20.0], [0.0, -0.5]]}
+    ndr = wkbparse.geojson_to_ewkb(geom)
+    assert ndr == wkbparse.geojson_to_ewkb(geom, byte_order="little")
+    assert ndr.hex().upper() == (
+        "0102000020E610000002000000000000000000244000000000000034C00000000000000000000000000000E0BF"
+    )
+    xdr = wkbparse.geojson_to_ewkb(geom, byte_order="big")
+    assert xdr.hex().upper() == (
+        "0020000002000010E6000000024024000000000000C0340000000000000000000000000000BFE0000000000000"
+    )
+    assert wkbparse.ewkb_to_geojson(xdr) == wkbparse.ewkb_to_geojson(ndr)
+
+    twkb = bytes.fromhex("610805d00fa01f50")
+    xdr = wkbparse.twkb_to_ewkb(twkb, byte_order="big")
+    assert xdr[0] == 0
+    assert wkbparse.ewkb_to_geojson(xdr) == wkbparse.twkb_to_geojson(twkb)
+    with pytest.raises(ValueError, match="invalid byte order"):
+        wkbparse.geojson_to_ewkb(geom, byte_order="XDR")
+
+
+def test_ewkb_encodings():
+    """Test hex and base64 EWKB input and output"""
+    geom = {"type": "LineString", "crs": 4326, "coordinates": [[10.0, -20.0], [0.0, -0.5]]}
+    ewkb = wkbparse.geojson_to_ewkb(geom)
+    ewkb_hex = wkbparse.geojson_to_ewkb(geom, output_encoding="hex")
+    assert ewkb_hex == ewkb.hex().upper()
+    ewkb_base64 = wkbparse.geojson_to_ewkb(geom, output_encoding="base64")
+    assert ewkb_base64 == base64.b64encode(ewkb).decode()
+    expected = wkbparse.ewkb_to_geojson(ewkb)
+    assert wkbparse.ewkb_to_geojson(ewkb_hex) == expected
+    assert wkbparse.ewkb_to_geojson(ewkb.hex()) == expected
+    assert wkbparse.ewkb_to_geojson("\\x" + ewkb_hex) == expected
+    assert wkbparse.ewkb_to_geojson(ewkb_base64, input_encoding="base64") == expected
+    assert wkbparse.ewkb_to_geojson(ewkb_base64.encode(), input_encoding="base64") == expected
+    assert wkbparse.ewkb_to_geojson_many(ewkb_hex * 2) == [expected, expected]
+    reprojected = wkbparse.reproject_ewkb(ewkb_hex, 3857, output_encoding="hex")
+    assert reprojected == wkbparse.reproject_ewkb(ewkb, 3857).hex().upper()
+    with pytest.raises(ValueError, match="invalid hex digit"):
+        wkbparse.ewkb_to_geojson(ewkb_base64)
+    with pytest.raises(ValueError, match="invalid encoding"):
+        wkbparse.geojson_to_ewkb(geom, output_encoding="utf8")
+
+def test_ewkb_buffer_input():
+    """Test EWKB input from buffers and batches delimited by offsets"""
+    geom = {"type": "LineString", "crs": 4326, "coordinates": [[10.0, -20.0], [0.0, -0.5]]}
+    ewkb = wkbparse.geojson_to_ewkb(geom)
+    expected = wkbparse.ewkb_to_geojson(ewkb)
+    assert wkbparse.ewkb_to_geojson(bytearray(ewkb)) == expected
+    assert wkbparse.ewkb_to_geojson_many(memoryview(ewkb * 2)) == [expected, expected]
+    assert wkbparse.reproject_ewkb(bytearray(ewkb), 3857) == wkbparse.reproject_ewkb(ewkb, 3857)
+    offsets = [0, len(ewkb), len(ewkb), 2 * len(ewkb)]
+    assert wkbparse.ewkb_to_geojson_batch(bytearray(ewkb * 2), offsets) == [expected, None, expected]
+    reprojected = wkbparse.ewkb_to_geojson_batch(ewkb, [0, len(ewkb)], to_srid=3857)
+    assert reprojected == [wkbparse.ewkb_to_geojson(ewkb, to_srid=3857)]
+
+
+class GeoInterface:
+    def __init__(self, geo_interface):
+        self.__geo_interface__ = geo_interface
+
+
+def test_geo_interface_input():
+    """Test converting Features and objects implementing __geo_interface__"""
+    geom = {"type": "LineString", "crs": 4326, "coordinates": [[10.0, -20.0], [0.0, -0.5]]}
+    ewkb = wkbparse.geojson_to_ewkb(geom)
+    line = GeoInterface({"type": "LineString", "coordinates": ((10.0, -20.0), (0.0, -0.5))})
+    assert wkbparse.geojson_to_ewkb(line) == wkbparse.geojson_to_ewkb({**geom, "crs": None})
+    feature = {"type": "Feature", "geometry": geom, "properties": {}}
+    assert wkbparse.geojson_to_ewkb(feature) == ewkb
+    assert wkbparse.geojson_to_ewkb(GeoInterface(feature)) == ewkb
+    reprojected = wkbparse.reproject_geojson(line, to_srid=3857, from_srid=4326)
+    assert reprojected == wkbparse.ewkb_to_geojson(ewkb, to_srid=3857)
+    with pytest.raises(ValueError, match="__geo_interface__"):
+        wkbparse.geojson_to_ewkb([1.0, 2.0])
+
+
+def test_shapely_output():
+    """Test returning Shapely geometries"""
+    shapely = pytest.importorskip("shapely")
+    point = bytes.fromhex("0101000020E6100000000000000000244000000000000034C0")
+    line = bytes.fromhex(
+        "010200000002000000000000000000244000000000000034C00000000000000000000000000000E0BF"
+    )
+    geom = wkbparse.ewkb_to_geojson(point, as_shapely=True)
+    assert geom.equals(shapely.Point(10.0, -20.0))
+    assert shapely.get_srid(geom) == 4326
+    geoms = wkbparse.ewkb_to_geojson_many(point + line + point, as_shapely=True)
+    assert [g.geom_type for g in geoms] == ["Point", "LineString", "Point"]
+    assert shapely.get_srid(geoms[1]) == 0
+    assert list(geoms[1].coords) == [(10.0, -20.0), (0.0, -0.5)]
+    offsets = [0, len(point), len(point), len(point + line)]
+    batch = wkbparse.ewkb_to_geojson_batch(point + line, offsets, as_shapely=True)
+    assert batch[1] is None
+    assert batch[2].equals(geoms[1])
+    assert wkbparse.geojson_to_ewkb(geoms[1]) == line
+    measured = {"type": "Point", "coordinates": [1.0, 2.0, 3.0], "dims": "XYM"}
+    with pytest.raises(ValueError, match="M values"):
+        wkbparse.ewkb_to_geojson(wkbparse.geojson_to_ewkb(measured), as_shapely=True)
+
+
+def test_ewkb_to_geojson_str():
+    """Test writing EWKB straight into a GeoJSON string"""
+    line = "0102000020E610000002000000000000000000244000000000000034C00000000000000000000000000000E0BF"
+    result = json.loads(wkbparse.ewkb_to_geojson_str(line))
+    assert result["crs"] == {"type": "name", "properties": {"name": "EPSG:4326"}}
+    assert result["coordinates"] == wkbparse.ewkb_to_geojson(line)["coordinates"]
+
+    result = json.loads(wkbparse.ewkb_to_geojson_str(bytes.fromhex(line), precision=0, crs="srid"))
+    assert result == {
+        "type": "LineString",
+        "crs": 4326,
+        "coordinates": [[10.0, -20.0], [0.0, -1.0]],
+    }
+    measured = "0101000040000000000000F03F00000000000000400000000000001040"
+    result = json.loads(wkbparse.ewkb_to_geojson_str(measured, crs="none"))
+    assert result == {"type": "Point", "coordinates": [1.0, 2.0, 4.0]}
+    with pytest.raises(ValueError, match="trailing bytes"):
+        wkbparse.ewkb_to_geojson_str(line + "00", strict=True)
+
+
 if __name__ == "__main__":
     test_parse_large_multipolygon()
@@ -0,0 +1,50 @@
+"""Tests for python side PostgreSQL binary COPY conversions"""
+
+from pathlib import Path
+import pytest
+import wkbparse
+
+# Binary COPY stream of an int4, a geometry and a bytea column holding TWKB, with rows of a point,
+# a line without TWKB and NULL geometries
+EXAMPLE = Path(__file__).parent / "data" / "example.pgcopy"
+POINT = bytes.fromhex("0101000020E6100000000000000000244000000000000034C0")
+TWKB_POINT = bytes.fromhex("610805d00fa01f50")
+
+
+def test_read_pgcopy():
+    """Test reading geometry columns of a binary COPY stream"""
+    data = EXAMPLE.read_bytes()
+    rows = wkbparse.read_pgcopy(data, {1: "ewkb", 2: "twkb"})
+    assert len(rows) == 3
+    assert rows[0][0] == (1).to_bytes(4, "big")
+    assert rows[0][1] == wkbparse.ewkb_to_geojson(POINT)
+    assert rows[0][2] == wkbparse.twkb_to_geojson(TWKB_POINT)
+    assert rows[1][1]["type"] == "LineString"
+    assert rows[1][2] is None
+    assert rows[2][1:] == [None, None]
+
+    rows = wkbparse.read_pgcopy(memoryview(data), {1: "ewkb", 2: "twkb"}, as_ewkb=True)
+    assert rows[0][1] == POINT
+    assert rows[0][2] == wkbparse.twkb_to_ewkb(TWKB_POINT)
+
+    with pytest.raises(ValueError, match="invalid COPY signature"):
+        wkbparse.read_pgcopy(data[1:], {})
+    with pytest.raises(ValueError, match="unexpected end of COPY data"):
+        wkbparse.read_pgcopy(data[:-10], {})
+    with pytest.raises(ValueError, match="missing geometry column 3"):
+        wkbparse.read_pgcopy(data, {3: "ewkb"})
+    with pytest.raises(ValueError, match="invalid format"):
+        wkbparse.read_pgcopy(data, {1: "wkt"})
+
+
+def test_write_pgcopy():
+    """Test writing a binary COPY stream"""
+    data = EXAMPLE.read_bytes()
+    rows = wkbparse.read_pgcopy(data, {1: "ewkb"})
+    assert wkbparse.write_pgcopy(rows) == data
+    assert wkbparse.write_pgcopy(wkbparse.read_pgcopy(data, {})) == data
+
+    geom = {"type": "Point", "crs": 4326, "coordinates": [10.0, -20.0]}
+    written = wkbparse.write_pgcopy([[geom, bytearray(b"a"), None]], byte_order="big")
+    assert wkbparse.read_pgcopy(written, {0: "ewkb"}) == [[wkbparse.ewkb_to_geojson(POINT), b"a", None]]
+    assert wkbparse.read_pgcopy(written, {0: "ewkb"}, as_ewkb=True)[0][0][0] == 0
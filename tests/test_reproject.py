@@ -1,5 +1,6 @@
 """Reprojection tests for wkbparse-proj"""
 
+import math
 from typing import Optional
 import pytest
 import wkbparse
@@ -384,3 +385,129 @@ def test_reproject_geojson_linestring():
         assert vtx[1] == pytest.approx(expected_crd[1], abs=0.001)
         assert vtx[2] == pytest.approx(expected_crd[2], abs=0.001)
         assert vtx[3] == pytest.approx(expected_crd[3], abs=0.001)
+
+
+def test_transform_cache():
+    """Test transform cache warming, statistics and eviction"""
+    wkbparse.clear_transform_cache()
+    wkbparse.set_transform_cache_size(2)
+    wkbparse.warm_transforms([(4326, 3857), (3857, 4326)])
+    info = wkbparse.transform_cache_info()
+    assert info == {"hits": 0, "misses": 0, "evictions": 0, "size": 2, "max_size": 2}
+
+    hex_string = "0101000080000000000000F03F00000000000000400000000000001040"
+    wkbparse.ewkb_to_geojson(bytes.fromhex(hex_string), from_srid=4326, to_srid=3857)
+    wkbparse.ewkb_to_geojson(bytes.fromhex(hex_string), from_srid=4326, to_srid=4087)
+    info = wkbparse.transform_cache_info()
+    assert info["hits"] == 1
+    assert info["misses"] == 1
+    assert info["evictions"] == 1
+    assert info["size"] == 2
+
+    wkbparse.set_transform_cache_size(64)
+    wkbparse.clear_transform_cache()
+    assert wkbparse.transform_cache_info()["size"] == 0
+
+
+# EWKB-EWKB reprojection
+def test_reproject_ewkb_to_ewkb():
+    """Test EWKB linestring reprojection from WGS84 latlng to Webmercator without GeoJSON"""
+    hex_string = "01020000a0e610000002000000000000000000f03f0000000000000040000000000000144000000000000024400000000000002e400000000000003640"
+    data = wkbparse.reproject_ewkb(bytes.fromhex(hex_string), 3857)
+    assert isinstance(data, bytes)
+    result = wkbparse.ewkb_to_geojson(data)
+    assert result.get("type") == "LineString"
+    assert result.get("crs") == 3857
+    crds = result.get("coordinates", [])
+    assert crds[0][0] == pytest.approx(111319.491, abs=0.001)
+    assert crds[0][1] == pytest.approx(222684.209, abs=0.001)
+    assert crds[0][2] == pytest.approx(5.0, abs=0.001)
+    assert crds[1][0] == pytest.approx(1113194.908, abs=0.001)
+    assert crds[1][1] == pytest.approx(1689200.14, abs=0.001)
+    assert crds[1][2] == pytest.approx(22.0, abs=0.001)
+
+
+def test_reproject_twkb_to_ewkb():
+    """Test TWKB point reprojection into EWKB carrying the target SRID"""
+    hex_string = "610805d00fa01f50"
+    data = wkbparse.twkb_to_ewkb(bytes.fromhex(hex_string), from_srid=4326, to_srid=3857)
+    result = wkbparse.ewkb_to_geojson(data)
+    assert result.get("type") == "Point"
+    assert result.get("crs") == 3857
+    crds = result.get("coordinates", [])
+    assert crds[0] == pytest.approx(111319.491, abs=0.001)
+    assert crds[1] == pytest.approx(222684.209, abs=0.001)
+    assert crds[2] == pytest.approx(4.0, abs=0.001)
+
+
+def test_reproject_error_policy():
+    """Test handling of vertices that cannot be projected into Webmercator"""
+    geom = {"type": "LineString", "coordinates": [[24.9, 60.1], [25.0, 90.0], [25.1, 60.2]]}
+    with pytest.raises(ValueError, match="vertex 1"):
+        wkbparse.reproject_geojson(geom, 3857, 4326)
+
+    result = wkbparse.reproject_geojson(geom, 3857, 4326, on_error="drop_vertex")
+    assert len(result["coordinates"]) == 2
+
+    result = wkbparse.reproject_geojson(geom, 3857, 4326, on_error="nan")
+    assert math.isnan(result["coordinates"][1][0])
+
+    result = wkbparse.reproject_geojson(geom, 3857, 4326, on_error="clamp")
+    assert result["coordinates"][1][1] == pytest.approx(20037508.34, rel=0.01)
+
+    with pytest.raises(ValueError, match="invalid error policy"):
+        wkbparse.reproject_geojson(geom, 3857, 4326, on_error="ignore")
+
+
+def test_reproject_drop_vertex_polygon():
+    """Test that dropping the first vertex of a ring keeps the polygon valid"""
+    ring = [[25.0, 95.0], [26.0, 60.0], [27.0, 61.0], [28.0, 60.0], [25.0, 95.0]]
+    geom = {"type": "Polygon", "coordinates": [ring]}
+    result = wkbparse.reproject_geojson(geom, 3857, 4326, on_error="drop_vertex")
+    crds = result["coordinates"][0]
+    assert len(crds) == 4
+    assert crds[0] == crds[-1]
+    wkbparse.geojson_to_ewkb(result)
+
+    # Only two vertices remain, which is not a ring
+    geom = {"type": "Polygon", "coordinates": [ring[:2] + ring[-2:]]}
+    result = wkbparse.reproject_geojson(geom, 3857, 4326, on_error="drop_vertex")
+    assert result["coordinates"] == []
+
+
+def test_reproject_check_area_of_use():
+    """Test rejecting vertices outside the area of use of UTM zone 35N"""
+    geom = {"type": "LineString", "coordinates": [[24.9, 60.1], [40.0, 60.1]]}
+    assert wkbparse.reproject_geojson(geom, 32635, 4326)
+    with pytest.raises(ValueError, match="outside the area of use"):
+        wkbparse.reproject_geojson(geom, 32635, 4326, check_area_of_use=True)
+
+
+def test_configure_proj():
+    """Test loading an NTv2 grid from a local directory with network access disabled"""
+    pipeline = (
+        "+proj=pipeline +step +proj=unitconvert +xy_in=deg +xy_out=rad "
+        "+step +proj=hgridshift +grids=test_hgrid.gsb "
+        "+step +proj=unitconvert +xy_in=rad +xy_out=deg"
+    )
+    geom = {"type": "Point", "coordinates": [4.5, 52.5]}
+    try:
+        wkbparse.configure_proj(
+            search_paths=["./tests/data"],
+            network=False,
+            operations={(4326, 4230): pipeline, (4326, 3067): (19.0, 59.0, 32.0, 71.0)},
+        )
+        result = wkbparse.reproject_geojson(geom, 4230, 4326)
+        assert result["coordinates"][0] == pytest.approx(5.875, abs=1e-6)
+        assert result["coordinates"][1] == pytest.approx(55.375, abs=1e-6)
+
+        line = {"type": "LineString", "coordinates": [[24.9, 60.1], [25.0, 60.2]]}
+        result = wkbparse.reproject_geojson(line, 3067, 4326)
+        assert result["coordinates"][0][0] == pytest.approx(383060.0, abs=1000.0)
+
+        with pytest.raises(ValueError, match="must be a PROJ pipeline"):
+            wkbparse.configure_proj(operations={(4326, 4230): 1})
+    finally:
+        wkbparse.configure_proj()
+    result = wkbparse.reproject_geojson(geom, 4230, 4326)
+    assert result["coordinates"][0] != pytest.approx(5.875, abs=0.1)
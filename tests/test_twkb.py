@@ -1,8 +1,11 @@
 """Tests for python side TWKB-parsing"""
 
+import array
+import base64
 import json
 from pathlib import Path
 from time import time
+import pytest
 import wkbparse
 
 
@@ -78,7 +81,7 @@ def test_parse_twkb_multipolygon():
     assert isinstance(result, dict)
     assert result.get("type") == "MultiPolygon"
     crds = result.get("coordinates", [])
-    expected_crds = "[[[[285127.716, 6700175.992, 0.0], [285125.755, 6700171.219, 0.0], [285120.922, 6700172.494999999, 0.0], [285127.716, 6700175.992, 0.0]]]]"
+    expected_crds = "[[[[285127.716, 6700175.992, 0.0], [285125.755, 6700171.219, 0.0], [285120.922, 6700172.495, 0.0], [285127.716, 6700175.992, 0.0]]]]"
     assert json.dumps(crds) == expected_crds
 
 
@@ -127,5 +130,138 @@ def test_parse_large_multipolygon():
     assert len(crds) > 300000
 
 
+def test_parse_invalid_twkb():
+    """Test that malformed input raises instead of crashing"""
+    for data in [b"", bytes.fromhex("0200ffffffffffffffff7f"), bytes.fromhex("0300ff")]:
+        with pytest.raises(ValueError):
+            wkbparse.twkb_to_geojson(data)
+        with pytest.raises(ValueError):
+            wkbparse.twkb_to_ewkb(data)
+
+
+def test_parse_limits():
+    """Test rejecting input exceeding the parse limits"""
+    data = bytes.fromhex("03000205000004000004030000030514141700001718000018")
+    try:
+        wkbparse.set_parse_limits(max_vertices=10, max_parts=2, max_bytes=len(data))
+        assert wkbparse.get_parse_limits() == {
+            "max_vertices": 10,
+            "max_parts": 2,
+            "max_depth": 64,
+            "max_bytes": len(data),
+        }
+        assert wkbparse.twkb_to_geojson(data).get("type") == "Polygon"
+        # max_bytes applies to the TWKB input, not to the EWKB reprojected from it
+        assert wkbparse.twkb_to_ewkb(data, from_srid=3857, to_srid=4326)
+
+        wkbparse.set_parse_limits(max_vertices=9)
+        with pytest.raises(ValueError, match="max_vertices"):
+            wkbparse.twkb_to_geojson(data)
+        wkbparse.set_parse_limits(max_parts=1)
+        with pytest.raises(ValueError, match="max_parts"):
+            wkbparse.twkb_to_ewkb(data)
+        wkbparse.set_parse_limits(max_bytes=len(data) - 1)
+        with pytest.raises(ValueError, match="max_bytes"):
+            wkbparse.twkb_to_geojson(data)
+    finally:
+        wkbparse.set_parse_limits()
+    assert wkbparse.get_parse_limits()["max_vertices"] is None
+
+
+
+def test_parse_twkb_many():
+    """Test parsing geometries stored back to back and rejecting trailing bytes"""
+    point = bytes.fromhex("01001427")
+    line = bytes.fromhex("0202050214271326")
+    result = wkbparse.twkb_to_geojson_many(point + line + point)
+    assert [geom["type"] for geom in result] == ["Point", "LineString", "Point"]
+    assert result[1]["coordinates"] == [[10.0, -20.0], [0.0, -1.0]]
+    assert wkbparse.twkb_to_geojson_many(b"") == []
+    with pytest.raises(ValueError, match="size header"):
+        wkbparse.twkb_to_geojson_many(point + bytes.fromhex("0202060214271326"))
+
+    assert wkbparse.twkb_to_geojson(point + line)["type"] == "Point"
+    with pytest.raises(ValueError, match="8 trailing bytes"):
+        wkbparse.twkb_to_geojson(point + line, strict=True)
+    with pytest.raises(ValueError, match="trailing bytes"):
+        wkbparse.twkb_to_ewkb(point + line, strict=True)
+
+
+def test_twkb_encodings():
+    """Test hex and base64 TWKB input and EWKB output"""
+    expected = wkbparse.twkb_to_geojson(bytes.fromhex("610805d00fa01f50"))
+    assert wkbparse.twkb_to_geojson("610805D00FA01F50") == expected
+    assert wkbparse.twkb_to_geojson("YQgF0A+gH1A=", input_encoding="base64") == expected
+    assert wkbparse.twkb_to_geojson_many("YQgF0A+gH1A", input_encoding="base64") == [expected]
+    ewkb = wkbparse.twkb_to_ewkb("610805d00fa01f50", output_encoding="base64")
+    assert wkbparse.ewkb_to_geojson(ewkb, input_encoding="base64") == expected
+    with pytest.raises(ValueError, match="odd number of digits"):
+        wkbparse.twkb_to_geojson("610805d00fa01f5")
+    with pytest.raises(ValueError, match="invalid base64"):
+        wkbparse.twkb_to_geojson("610805d00fa01f50!", input_encoding="base64")
+
+
+def test_twkb_buffer_input():
+    """Test TWKB input from buffers and batches delimited by offsets"""
+    data = bytes.fromhex("610805d00fa01f50")
+    expected = wkbparse.twkb_to_geojson(data)
+    assert wkbparse.twkb_to_geojson(bytearray(data)) == expected
+    assert wkbparse.twkb_to_geojson(memoryview(data * 2)[8:]) == expected
+    assert wkbparse.twkb_to_geojson(array.array("B", data)) == expected
+    # pyarrow buffers export signed bytes
+    assert wkbparse.twkb_to_geojson(memoryview(data).cast("b")) == expected
+    assert wkbparse.twkb_to_geojson(array.array("b", memoryview(data).cast("b"))) == expected
+    doubled = memoryview(bytes(b for b in data for _ in range(2))).cast("b")
+    assert wkbparse.twkb_to_geojson(doubled[::2]) == expected
+    assert wkbparse.twkb_to_geojson(memoryview(bytes(b for b in data for _ in range(2)))[::2]) == expected
+    with pytest.raises(TypeError):
+        wkbparse.twkb_to_geojson(array.array("d", [1.0]))
+
+    batch = memoryview(data * 2)
+    assert wkbparse.twkb_to_geojson_batch(batch, [0, 8, 16]) == [expected, expected]
+    assert wkbparse.twkb_to_geojson_batch(batch, array.array("i", [0, 8, 8, 16])) == [
+        expected,
+        None,
+        expected,
+    ]
+    assert wkbparse.twkb_to_geojson_batch(batch, array.array("q", [8, 16])) == [expected]
+    with pytest.raises(ValueError, match="trailing bytes"):
+        wkbparse.twkb_to_geojson_batch(batch, [0, 9])
+    with pytest.raises(ValueError, match="invalid offsets"):
+        wkbparse.twkb_to_geojson_batch(batch, [8, 0])
+    with pytest.raises(ValueError, match="invalid offsets"):
+        wkbparse.twkb_to_geojson_batch(batch, [0, 17])
+    # offsets index the encoded text, as in Arrow string arrays
+    assert wkbparse.twkb_to_geojson_batch(data.hex() * 2, [0, 16, 16, 32]) == [expected, None, expected]
+    encoded = base64.b64encode(data) * 2
+    assert wkbparse.twkb_to_geojson_batch(encoded, [0, 12, 24], input_encoding="base64") == [
+        expected,
+        expected,
+    ]
+
+
+def test_twkb_to_geojson_str():
+    """Test writing TWKB straight into a GeoJSON string"""
+    polygon = bytes.fromhex("03000205000004000004030000030514141700001718000018")
+    for data in [bytes.fromhex("610805d00fa01f50"), bytes.fromhex("0202050214271326"), polygon]:
+        result = json.loads(wkbparse.twkb_to_geojson_str(data))
+        expected = wkbparse.twkb_to_geojson(data)
+        # the layout is only in the dictionaries
+        del expected["dims"]
+        assert result == expected
+
+    line = bytes.fromhex("0202050214271326")
+    assert json.loads(wkbparse.twkb_to_geojson_str(line.hex(), crs="none")) == {
+        "type": "LineString",
+        "coordinates": [[10.0, -20.0], [0.0, -1.0]],
+    }
+    with pytest.raises(ValueError, match="invalid crs format"):
+        wkbparse.twkb_to_geojson_str(line, crs="epsg")
+    with pytest.raises(ValueError, match="trailing bytes"):
+        wkbparse.twkb_to_geojson_str(line + line, strict=True)
+    with pytest.raises(ValueError):
+        wkbparse.twkb_to_geojson_str(line[:-1])
+
+
 if __name__ == "__main__":
     test_parse_twkb_multipolygon()
@@ -1,37 +1,365 @@
-from typing import Any, Dict, Optional
+from typing import Any, Dict, Iterable, List, Literal, Optional, Sequence, Tuple, Union
+
+ErrorPolicy = Literal["raise", "drop_vertex", "nan", "clamp"]
+ByteOrder = Literal["little", "big"]
+Encoding = Literal["bytes", "hex", "base64"]
+CrsFormat = Literal["name", "srid", "none"]
+# bytes or any object supporting the buffer protocol with unsigned bytes, e.g. NumPy uint8 arrays
+Binary = Union[bytes, bytearray, memoryview]
+Geometry = Any
 
 def twkb_to_geojson(
-    data: bytes, from_srid: Optional[int] = None, to_srid: Optional[int] = None
-) -> Dict[str, Any]:
+    data: Union[Binary, str],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    strict: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    as_shapely: Optional[bool] = None,
+) -> Union[Dict[str, Any], Geometry]:
     """Convert data containing TWKB-bytes into a GeoJSON-like dictionary.
+    With `strict` input with bytes left after the geometry raises a `ValueError`.
+    `data` may be bytes, a buffer or a hex string, optionally prefixed with `\\x`. Buffers are
+    read without copying when contiguous. `input_encoding`
+    ("bytes", "hex" or "base64") overrides how `data` is decoded.
     Optionally reproject from `from_srid` to `to_srid` by providing integers matching EPSG codes.
-    NOTE: Reprojection requires using package `wkbparse-proj` and a recent Proj installed on the system.
+    `on_error` decides what happens to vertices that cannot be transformed: "raise" (default),
+    "drop_vertex", "nan" or "clamp" onto the area of use. With `check_area_of_use` vertices outside
+    the area of use of the transform count as failed.
+    NOTE: EPSG:4326, 3857, 4087 and the WGS84 UTM zones (326xx, 327xx) are supported out of the box.
+    Other codes require using package `wkbparse-proj` and a recent Proj installed on the system.
+    With `as_shapely` Shapely 2 geometries are returned instead, keeping the SRID. Requires
+    `shapely` and `numpy` to be installed. Geometries with M values cannot be returned as Shapely
+    geometries.
     """
 
 def ewkb_to_geojson(
-    data: bytes, from_srid: Optional[int] = None, to_srid: Optional[int] = None
-) -> Dict[str, Any]:
+    data: Union[Binary, str],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    strict: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    as_shapely: Optional[bool] = None,
+) -> Union[Dict[str, Any], Geometry]:
     """Convert data containing EWKB-bytes into a GeoJSON-like dictionary.
+    With `strict` input with bytes left after the geometry raises a `ValueError`.
+    `data` may be bytes, a buffer or a hex string, optionally prefixed with `\\x`. Buffers are
+    read without copying when contiguous. `input_encoding`
+    ("bytes", "hex" or "base64") overrides how `data` is decoded.
     Optionally reproject from `from_srid` to `to_srid` by providing integers matching EPSG codes.
-    NOTE: Reprojection requires using package `wkbparse-proj` and a recent Proj installed on the system.
+    `on_error` decides what happens to vertices that cannot be transformed: "raise" (default),
+    "drop_vertex", "nan" or "clamp" onto the area of use. With `check_area_of_use` vertices outside
+    the area of use of the transform count as failed.
+    NOTE: EPSG:4326, 3857, 4087 and the WGS84 UTM zones (326xx, 327xx) are supported out of the box.
+    Other codes require using package `wkbparse-proj` and a recent Proj installed on the system.
+    With `as_shapely` Shapely 2 geometries are returned instead, keeping the SRID. Requires
+    `shapely` and `numpy` to be installed. Geometries with M values cannot be returned as Shapely
+    geometries.
+    """
+
+def twkb_to_geojson_str(
+    data: Union[Binary, str],
+    strict: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    precision: Optional[int] = None,
+    crs: Optional[CrsFormat] = None,
+) -> str:
+    """Convert data containing TWKB-bytes into a GeoJSON string without building a dictionary.
+    Takes the same input encodings and `strict` option as `twkb_to_geojson`.
+    `precision` rounds coordinates to the given number of decimals. `crs` writes the SRID as a
+    named CRS object (default, "name"), as a plain integer ("srid") or leaves it out ("none").
+    """
+
+def ewkb_to_geojson_str(
+    data: Union[Binary, str],
+    strict: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    precision: Optional[int] = None,
+    crs: Optional[CrsFormat] = None,
+) -> str:
+    """Convert data containing EWKB-bytes into a GeoJSON string without building a dictionary.
+    Takes the same options as `twkb_to_geojson_str`.
+    """
+
+def twkb_to_geojson_many(
+    data: Union[Binary, str],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    as_shapely: Optional[bool] = None,
+) -> List[Union[Dict[str, Any], Geometry]]:
+    """Convert data containing TWKB geometries stored back to back into GeoJSON-like dictionaries.
+    Takes the same options and input encodings as `twkb_to_geojson`, and raises a `ValueError` on the first invalid
+    geometry.
+    Returns Shapely geometries with `as_shapely`.
+    """
+
+def ewkb_to_geojson_many(
+    data: Union[Binary, str],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    as_shapely: Optional[bool] = None,
+) -> List[Union[Dict[str, Any], Geometry]]:
+    """Convert data containing EWKB geometries stored back to back into GeoJSON-like dictionaries.
+    Takes the same options and input encodings as `ewkb_to_geojson`, and raises a `ValueError` on the first invalid
+    geometry.
+    Returns Shapely geometries with `as_shapely`.
+    """
+
+def twkb_to_geojson_batch(
+    data: Union[Binary, str],
+    offsets: Union[Binary, List[int]],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    as_shapely: Optional[bool] = None,
+) -> List[Optional[Union[Dict[str, Any], Geometry]]]:
+    """Convert TWKB geometries delimited by `offsets` into GeoJSON-like dictionaries.
+    Geometry `i` spans `data[offsets[i]:offsets[i + 1]]`, as in Arrow binary arrays. `offsets` is
+    a list or a buffer of 32 or 64-bit integers. Empty entries, such as nulls, map to `None`.
+    With `input_encoding` or string input each entry is decoded on its own, so `offsets` index
+    into the hex or base64 text, as in Arrow string arrays.
+    Takes the same options as `twkb_to_geojson`, and raises a `ValueError` on the first invalid
+    geometry or offset.
+    Returns Shapely geometries with `as_shapely`.
+    """
+
+def ewkb_to_geojson_batch(
+    data: Union[Binary, str],
+    offsets: Union[Binary, List[int]],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    as_shapely: Optional[bool] = None,
+) -> List[Optional[Union[Dict[str, Any], Geometry]]]:
+    """Convert EWKB geometries delimited by `offsets` into GeoJSON-like dictionaries.
+    Geometry `i` spans `data[offsets[i]:offsets[i + 1]]`, as in Arrow binary arrays. `offsets` is
+    a list or a buffer of 32 or 64-bit integers. Empty entries, such as nulls, map to `None`.
+    With `input_encoding` or string input each entry is decoded on its own, so `offsets` index
+    into the hex or base64 text, as in Arrow string arrays.
+    Takes the same options as `ewkb_to_geojson`, and raises a `ValueError` on the first invalid
+    geometry or offset.
+    Returns Shapely geometries with `as_shapely`.
     """
 
 def geojson_to_ewkb(
-    data: Dict[str, Any], from_srid: Optional[int] = None, to_srid: Optional[int] = None
-) -> bytes:
+    data: Union[Dict[str, Any], Any],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    byte_order: Optional[ByteOrder] = None,
+    output_encoding: Optional[Encoding] = None,
+) -> Union[bytes, str]:
     """Convert GeoJSON-like dictionary into EWKB-bytes.
+    `data` may also be a GeoJSON Feature or any object implementing `__geo_interface__`, such as
+    a Shapely geometry.
+    The optional "dims" key ("XY", "XYZ", "XYM" or "XYZM") sets the coordinate layout. Without it
+    the layout is inferred from the number of values in the first vertex.
+    `byte_order` is "little" (NDR, default) or "big" (XDR).
+    `output_encoding` returns the EWKB as "bytes" (default), an upper case "hex" string or a
+    "base64" string.
+    Optionally reproject from `from_srid` to `to_srid` by providing integers matching EPSG codes.
+    `on_error` decides what happens to vertices that cannot be transformed: "raise" (default),
+    "drop_vertex", "nan" or "clamp" onto the area of use. With `check_area_of_use` vertices outside
+    the area of use of the transform count as failed.
+    NOTE: EPSG:4326, 3857, 4087 and the WGS84 UTM zones (326xx, 327xx) are supported out of the box.
+    Other codes require using package `wkbparse-proj` and a recent Proj installed on the system.
+    """
+
+def twkb_to_ewkb(
+    data: Union[Binary, str],
+    from_srid: Optional[int] = None,
+    to_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    strict: Optional[bool] = None,
+    byte_order: Optional[ByteOrder] = None,
+    input_encoding: Optional[Encoding] = None,
+    output_encoding: Optional[Encoding] = None,
+) -> Union[bytes, str]:
+    """Convert TWKB-bytes into EWKB-bytes.
+    With `strict` input with bytes left after the geometry raises a `ValueError`.
+    `data` may be bytes, a buffer or a hex string, optionally prefixed with `\\x`. Buffers are
+    read without copying when contiguous. `input_encoding`
+    ("bytes", "hex" or "base64") overrides how `data` is decoded.
+    `byte_order` is "little" (NDR, default) or "big" (XDR).
+    `output_encoding` returns the EWKB as "bytes" (default), an upper case "hex" string or a
+    "base64" string.
     Optionally reproject from `from_srid` to `to_srid` by providing integers matching EPSG codes.
-    NOTE: Reprojection requires using package `wkbparse-proj` and a recent Proj installed on the system.
+    The resulting EWKB contains `to_srid` as its SRID.
+    `on_error` decides what happens to vertices that cannot be transformed: "raise" (default),
+    "drop_vertex", "nan" or "clamp" onto the area of use. With `check_area_of_use` vertices outside
+    the area of use of the transform count as failed.
+    NOTE: EPSG:4326, 3857, 4087 and the WGS84 UTM zones (326xx, 327xx) are supported out of the box.
+    Other codes require using package `wkbparse-proj` and a recent Proj installed on the system.
+    """
+
+def read_pgcopy(
+    data: Binary,
+    geometry_columns: Dict[int, Literal["ewkb", "wkb", "twkb"]],
+    as_ewkb: Optional[bool] = None,
+) -> List[List[Optional[Any]]]:
+    """Read the rows of a PostgreSQL binary COPY stream, `COPY ... TO STDOUT (FORMAT binary)`.
+    `geometry_columns` maps column indices to their format: "ewkb" for geometry columns, or
+    "twkb" for bytea columns holding TWKB. Geometry columns are decoded into GeoJSON-like
+    dictionaries, or into EWKB bytes with `as_ewkb`. Other columns are returned as the bytes of
+    their binary representation. NULLs map to `None`.
+    """
+
+def write_pgcopy(
+    rows: Iterable[Sequence[Optional[Any]]],
+    byte_order: Optional[ByteOrder] = None,
+) -> bytes:
+    """Write rows into a PostgreSQL binary COPY stream for `COPY ... FROM STDIN (FORMAT binary)`.
+    Bytes and buffers are written as they are and must hold the binary representation of their
+    column. GeoJSON-like dictionaries and objects implementing `__geo_interface__` are written as
+    EWKB with `byte_order` "little" (NDR, default) or "big" (XDR). `None` is written as NULL.
+    """
+
+class ArrowArray:
+    """Arrow array implementing the Arrow PyCapsule interface. Pass it to e.g. `pyarrow.array`,
+    `pyarrow.field` or `polars.from_arrow` to use it without copying.
     """
 
-def twkb_to_ewkb(data: bytes) -> bytes:
-    """Convert TWKB-bytes into EWKB-bytes"""
+    def __len__(self) -> int: ...
+    def __arrow_c_schema__(self) -> Any: ...
+    def __arrow_c_array__(self, requested_schema: Optional[Any] = None) -> Tuple[Any, Any]: ...
+
+def ewkb_to_geoarrow(data: Union[Any, Sequence[Optional[bytes]]]) -> ArrowArray:
+    """Convert an Arrow binary array of EWKB or WKB into a GeoArrow native array.
+    `data` is an object implementing `__arrow_c_array__`, such as a `pyarrow.BinaryArray`, or a
+    list of bytes. Geometries must be of one type, coordinate layout and SRID, except that single
+    geometries mixed with multi geometries are stored as multi geometries. Nulls stay null.
+    The field carries the GeoArrow extension name, and the CRS if the geometries have an SRID.
+    Requires the `arrow` feature, which pre-built wheels include.
+    """
+
+def twkb_to_geoarrow(data: Union[Any, Sequence[Optional[bytes]]]) -> ArrowArray:
+    """Convert an Arrow binary array of TWKB into a GeoArrow native array.
+    Works like `ewkb_to_geoarrow`.
+    """
+
+def geoarrow_to_ewkb(data: Any, byte_order: Optional[ByteOrder] = None) -> ArrowArray:
+    """Convert a GeoArrow native array with interleaved or separated coordinates into an Arrow
+    binary array of EWKB. `data` is an object implementing `__arrow_c_array__` whose field
+    carries the GeoArrow extension name. An EPSG code in the CRS metadata is written as the SRID.
+    `byte_order` is "little" (NDR, default) or "big" (XDR).
+    """
+
+def set_parse_limits(
+    max_vertices: Optional[int] = None,
+    max_parts: Optional[int] = None,
+    max_depth: Optional[int] = None,
+    max_bytes: Optional[int] = None,
+) -> None:
+    """Set limits for all EWKB and TWKB input. Input exceeding a limit raises a `ValueError`.
+
+    Replaces any previous limits. Omitted limits are unlimited, except `max_depth` which
+    defaults to 64.
+
+    Args:
+        max_vertices: Maximum number of vertices in a geometry.
+        max_parts: Maximum number of parts in a geometry: polygon rings and members of
+            multi-geometries and geometry collections.
+        max_depth: Maximum nesting depth of geometry collections.
+        max_bytes: Maximum size of the input in bytes.
+    """
+
+def get_parse_limits() -> Dict[str, Optional[int]]:
+    """Get the current parse limits. Unlimited ones are `None`."""
 
 def reproject_geojson(
-    data: Dict[str, Any], to_srid: int, from_srid: Optional[int] = None
-) -> Dict[str, Any]:
+    data: Union[Dict[str, Any], Any],
+    to_srid: int,
+    from_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    as_shapely: Optional[bool] = None,
+) -> Union[Dict[str, Any], Geometry]:
     """Transform a GeoJSON geometry into another coordinate system.
+    `data` may also be a GeoJSON Feature or any object implementing `__geo_interface__`.
+    Returns a Shapely geometry with `as_shapely`.
     Provide from_srid and to_srid as integers that match EPSG-codes.
     `from_srid` may be omitted if the input geometry already contains SRID definition.
-    NOTE: Reprojection requires using package `wkbparse-proj` and a recent Proj installed on the system.
+    `on_error` decides what happens to vertices that cannot be transformed: "raise" (default),
+    "drop_vertex", "nan" or "clamp" onto the area of use. With `check_area_of_use` vertices outside
+    the area of use of the transform count as failed.
+    NOTE: EPSG:4326, 3857, 4087 and the WGS84 UTM zones (326xx, 327xx) are supported out of the box.
+    Other codes require using package `wkbparse-proj` and a recent Proj installed on the system.
+    """
+
+def reproject_ewkb(
+    data: Union[Binary, str],
+    to_srid: int,
+    from_srid: Optional[int] = None,
+    on_error: Optional[ErrorPolicy] = None,
+    check_area_of_use: Optional[bool] = None,
+    input_encoding: Optional[Encoding] = None,
+    output_encoding: Optional[Encoding] = None,
+) -> Union[bytes, str]:
+    """Transform EWKB-bytes into another coordinate system without converting to GeoJSON.
+    `data` may be bytes, a buffer or a hex string, optionally prefixed with `\\x`. Buffers are
+    read without copying when contiguous. `input_encoding`
+    ("bytes", "hex" or "base64") overrides how `data` is decoded.
+    `output_encoding` returns the EWKB as "bytes" (default), an upper case "hex" string or a
+    "base64" string.
+    Provide from_srid and to_srid as integers that match EPSG-codes.
+    `from_srid` may be omitted if the input EWKB already contains SRID.
+    The resulting EWKB contains `to_srid` as its SRID and keeps the byte order of the input.
+    `on_error` decides what happens to vertices that cannot be transformed: "raise" (default),
+    "drop_vertex", "nan" or "clamp" onto the area of use. With `check_area_of_use` vertices outside
+    the area of use of the transform count as failed.
+    NOTE: EPSG:4326, 3857, 4087 and the WGS84 UTM zones (326xx, 327xx) are supported out of the box.
+    Other codes require using package `wkbparse-proj` and a recent Proj installed on the system.
+    """
+
+def warm_transforms(pairs: List[Tuple[int, int]]) -> None:
+    """Create transforms for the given (from_srid, to_srid) pairs ahead of time.
+    Transforms are cached process-wide and shared between threads.
+    """
+
+def clear_transform_cache() -> None:
+    """Remove all cached transforms and reset the cache statistics."""
+
+def set_transform_cache_size(max_size: int) -> None:
+    """Set the maximum number of cached transforms. Least recently used transforms are evicted first.
+    Zero disables caching. Defaults to 64.
+    """
+
+def transform_cache_info() -> Dict[str, int]:
+    """Get transform cache statistics: `hits`, `misses`, `evictions`, `size` and `max_size`."""
+
+def configure_proj(
+    search_paths: Optional[List[str]] = None,
+    network: Optional[bool] = None,
+    url_endpoint: Optional[str] = None,
+    operations: Optional[
+        Dict[Tuple[int, int], Union[str, Tuple[float, float, float, float]]]
+    ] = None,
+) -> None:
+    """Configure PROJ for transforms created from now on. Requires `wkbparse-proj`.
+
+    Replaces any previous configuration. Calling without arguments restores the PROJ defaults.
+
+    Args:
+        search_paths: Directories searched for grid files before the default PROJ data directories.
+        network: Whether PROJ may download missing grids. Defaults to the PROJ default.
+        url_endpoint: Endpoint grids are downloaded from. Defaults to the PROJ default.
+        operations: Operation to use for `(from_srid, to_srid)` pairs. Either an area of interest
+            `(west, south, east, north)` in degrees, for which PROJ picks the most accurate
+            operation, or a PROJ pipeline used as is. Pipelines get longitude and latitude in
+            degrees or easting and northing.
     """